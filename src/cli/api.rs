@@ -0,0 +1,103 @@
+use crate::cli::output;
+use crate::lib::client::EagleClient;
+use clap::builder::PossibleValuesParser;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use hyper::{Body, Method};
+use percent_encoding::{percent_encode, NON_ALPHANUMERIC};
+use serde_json::Value;
+use std::io::Read;
+
+pub fn build() -> Command {
+    Command::new("api")
+        .about("Call any Eagle endpoint, including ones eagle-eye has no command for yet")
+        .long_about(
+            "Call any Eagle endpoint, including ones eagle-eye has no command for yet.\n\n\
+             The response goes through the usual output flags, e.g.\n\
+             \x20 eagle-eye api GET /api/item/info --query id=KBHG6KA0Y5S9W --json\n\
+             \x20 eagle-eye api POST /api/folder/rename --body '{\"folderId\":\"X\",\"newName\":\"Y\"}'",
+        )
+        .arg(
+            Arg::new("method")
+                .value_name("METHOD")
+                .help("HTTP method")
+                .required(true)
+                .value_parser(PossibleValuesParser::new(["GET", "POST"]))
+                .ignore_case(true),
+        )
+        .arg(
+            Arg::new("path")
+                .value_name("PATH")
+                .help("Endpoint, e.g. /api/item/info; the /api/ prefix is optional")
+                .required(true),
+        )
+        .arg(
+            Arg::new("body")
+                .long("body")
+                .value_name("JSON")
+                .help("JSON body to send, or - to read it from stdin")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("query")
+                .long("query")
+                .value_name("KEY=VALUE")
+                .help("Query parameter to send; repeatable")
+                .action(ArgAction::Append)
+                .value_parser(|pair: &str| -> Result<(String, String), String> {
+                    match pair.split_once('=') {
+                        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+                        _ => Err(format!("expected KEY=VALUE, got {}", pair)),
+                    }
+                }),
+        )
+        .args(output::args())
+}
+
+/// The resource and action of an endpoint path like `/api/item/info`
+fn split_path(path: &str) -> Result<(&str, &str), String> {
+    let trimmed = path.trim_matches('/');
+    match trimmed.strip_prefix("api/").unwrap_or(trimmed).split_once('/') {
+        Some((resource, action)) if !resource.is_empty() && !action.is_empty() => Ok((resource, action)),
+        _ => Err(format!("Expected an endpoint like /api/item/info, got {}", path)),
+    }
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let method = matches.get_one::<String>("method").unwrap().to_uppercase();
+    let (resource, action) = split_path(matches.get_one::<String>("path").unwrap())?;
+    let query: Vec<String> = matches
+        .get_many::<(String, String)>("query")
+        .unwrap_or_default()
+        .map(|(key, value)| {
+            format!(
+                "{}={}",
+                percent_encode(key.as_bytes(), NON_ALPHANUMERIC),
+                percent_encode(value.as_bytes(), NON_ALPHANUMERIC)
+            )
+        })
+        .collect();
+    let body = match matches.get_one::<String>("body").map(String::as_str) {
+        Some("-") => {
+            let mut body = String::new();
+            std::io::stdin().read_to_string(&mut body)?;
+            Some(body)
+        }
+        body => body.map(str::to_string),
+    };
+    let body = match body {
+        Some(body) => {
+            // Catch typos here rather than as a vague error from Eagle
+            serde_json::from_str::<Value>(&body).map_err(|e| format!("--body isn't JSON: {}", e))?;
+            Body::from(body)
+        }
+        None => Body::empty(),
+    };
+
+    let uri = client.endpoint(resource, action, Some(query.join("&")))?;
+    let method = Method::from_bytes(method.as_bytes())?;
+    let response: Value = client.execute_request(uri, method, body).await?;
+    output::output(&response, matches)
+}
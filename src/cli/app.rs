@@ -1,7 +1,16 @@
+use crate::cli::library::select;
+use crate::cli::{output, stats, system};
 use crate::lib::client::EagleClient;
 use clap::ArgMatches;
 use clap::{Arg, Command};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
+/// File in the data directory remembering where Eagle was last seen running from
+const EXEC_PATH_FILE_NAME: &str = "eagle-exec-path";
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Default)]
 pub struct App;
 
 impl App {
@@ -30,16 +39,173 @@ pub fn build() -> Command {
             .required(false)
             .num_args(0)
             )
+        .subcommand(
+            Command::new("launch")
+                .about("Start Eagle and wait until its API answers; --library opens that library once it does")
+                .args(lifecycle_args()),
+        )
+        .subcommand(
+            Command::new("quit")
+                .about("Quit Eagle and wait until its API stops answering")
+                .args(lifecycle_args()),
+        )
+        .subcommand(
+            Command::new("restart")
+                .about("Quit and start Eagle again, reopening the library it had open unless --library is given")
+                .args(lifecycle_args()),
+        )
+}
+
+fn lifecycle_args() -> Vec<Arg> {
+    vec![
+        Arg::new("exec")
+            .long("exec")
+            .value_name("PATH")
+            .help("Eagle executable; defaults to the execPath Eagle last reported")
+            .env("EAGLE_EYE_EAGLE_PATH")
+            .num_args(1),
+        Arg::new("timeout")
+            .long("timeout")
+            .value_name("SECONDS")
+            .help("How long to wait for Eagle to start or quit")
+            .num_args(1)
+            .default_value("60")
+            .value_parser(clap::value_parser!(u64)),
+    ]
+}
+
+/// Whether `matches` are for a subcommand that starts Eagle, so `--library` can only be
+/// applied once it runs
+pub fn is_launching(matches: &ArgMatches) -> bool {
+    match matches.subcommand() {
+        Some(("app", app_matches)) => matches!(app_matches.subcommand_name(), Some("launch" | "restart")),
+        _ => false,
+    }
+}
+
+fn exec_path_file() -> Option<PathBuf> {
+    stats::data_dir().map(|dir| dir.join(EXEC_PATH_FILE_NAME))
+}
+
+/// Remember where Eagle runs from, for launching it when it isn't running
+fn remember_exec_path(exec_path: &str) {
+    if let Some(file) = exec_path_file() {
+        // Best effort; --exec still works without it
+        let _ = std::fs::create_dir_all(file.parent().unwrap());
+        let _ = std::fs::write(file, exec_path);
+    }
+}
+
+/// The Eagle executable: `--exec`, else the one Eagle last reported, else the default install
+/// location on macOS
+fn exec_path(matches: &ArgMatches) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    if let Some(path) = matches.get_one::<String>("exec") {
+        return Ok(PathBuf::from(path));
+    }
+    let remembered = exec_path_file()
+        .and_then(|file| std::fs::read_to_string(file).ok())
+        .map(|path| PathBuf::from(path.trim()))
+        .filter(|path| path.exists());
+    let default = Some(PathBuf::from("/Applications/Eagle.app/Contents/MacOS/Eagle"))
+        .filter(|path| cfg!(target_os = "macos") && path.exists());
+    remembered
+        .or(default)
+        .ok_or_else(|| "Don't know where Eagle is installed; pass --exec PATH or run Eagle once while eagle-eye can reach it".into())
+}
+
+/// Wait until Eagle's API answers, or stops answering when `running` is false
+async fn wait_for(client: &EagleClient, running: bool, matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let timeout = Duration::from_secs(*matches.get_one::<u64>("timeout").unwrap());
+    let started = Instant::now();
+    while client.application().info().await.is_ok() != running {
+        if started.elapsed() > timeout {
+            let state = if running { "start" } else { "quit" };
+            return Err(format!("Eagle didn't {} within {}s", state, timeout.as_secs()).into());
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+    Ok(())
+}
+
+async fn launch(
+    client: &EagleClient,
+    matches: &ArgMatches,
+    library: Option<PathBuf>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match client.application().info().await {
+        Ok(info) => {
+            eprintln!("Eagle is already running");
+            if let Some(exec_path) = &info.data.exec_path {
+                remember_exec_path(exec_path);
+            }
+        }
+        Err(_) => {
+            let exec = exec_path(matches)?;
+            eprintln!("Starting {}", exec.display());
+            system::spawn_detached(&exec)?;
+            wait_for(client, true, matches).await?;
+        }
+    }
+    let library = match matches.get_one::<String>("library_select") {
+        Some(value) => Some(select::resolve(client, value).await?),
+        None => library,
+    };
+    if let Some(library) = library {
+        let current = client.library().info().await?.data.library.path;
+        if !select::same_path(Path::new(&current), &library) {
+            eprintln!("Switching Eagle to {}", library.display());
+            select::switch(client, &library).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Quit Eagle, returning the library it had open
+async fn quit(client: &EagleClient, matches: &ArgMatches) -> Result<Option<PathBuf>, Box<dyn std::error::Error>> {
+    let Ok(info) = client.application().info().await else {
+        eprintln!("Eagle isn't running");
+        return Ok(None);
+    };
+    let library = client.library().info().await.ok().map(|info| PathBuf::from(info.data.library.path));
+    let exec = match (matches.get_one::<String>("exec"), info.data.exec_path) {
+        (Some(path), _) => PathBuf::from(path),
+        (None, Some(path)) => {
+            remember_exec_path(&path);
+            PathBuf::from(path)
+        }
+        (None, None) => exec_path(matches)?,
+    };
+    eprintln!("Quitting Eagle");
+    system::quit_app(&exec)?;
+    wait_for(client, false, matches).await?;
+    Ok(library)
 }
 
 pub async fn execute(
     client: &EagleClient,
     matches: &ArgMatches,
     ) -> Result<(), Box<dyn std::error::Error>> {
+    match matches.subcommand() {
+        Some(("launch", launch_matches)) => {
+            return launch(client, launch_matches, None).await;
+        },
+        Some(("quit", quit_matches)) => {
+            return quit(client, quit_matches).await.map(|_| ());
+        },
+        Some(("restart", restart_matches)) => {
+            let library = quit(client, restart_matches).await?;
+            return launch(client, restart_matches, library).await;
+        },
+        _ => {},
+    }
     let data = client.application().info().await?.data;
+    if let Some(exec_path) = &data.exec_path {
+        remember_exec_path(exec_path);
+    }
 
     if matches.get_flag("version") {
         println!("{}", data.version);
+        return Ok(());
     }
-    Ok(())
+    output::output(&serde_json::to_value(data)?, matches)
 }
@@ -0,0 +1,82 @@
+use crate::cli::{confirm, exit};
+use crate::cli::plan::{self, Step};
+use crate::cli::progress::Progress;
+use crate::lib::client::EagleClient;
+use crate::lib::types::UpdateItemParams;
+use clap::{ArgMatches, Command};
+use std::collections::HashMap;
+
+pub fn build() -> Command {
+    Command::new("apply")
+        .about("Bring the library to a changes file, printing the plan first (see `plan --help`)")
+        .arg(plan::file_arg())
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let changes = plan::load(matches.get_one::<String>("file").unwrap())?;
+    let steps = plan::compute(client, &changes).await?;
+    plan::print(&steps);
+    if steps.is_empty() {
+        return Ok(());
+    }
+    println!();
+    confirm::confirm(matches, &format!("{} changes will be made", steps.len()))?;
+
+    let mut progress = Progress::new(steps.len(), "applying", matches);
+    let (mut done, mut failed) = (0, 0);
+    let mut folder_ids = None;
+    for step in &steps {
+        progress.inc();
+        let result = match step {
+            Step::RenameFolder { id, name, .. } => client.folder().rename(id, name).await.map(|_| ()),
+            Step::CreateFolder { path, name, parent } => {
+                // Folders are looked up by path once every rename went through
+                if folder_ids.is_none() {
+                    folder_ids = Some(plan::path_ids(&client.folder().list().await?.data));
+                }
+                create_folder(client, folder_ids.as_mut().unwrap(), path, name, parent.as_deref()).await
+            }
+            Step::UpdateItem { id, new_name, tags, .. } => {
+                let params = UpdateItemParams {
+                    id: id.clone(),
+                    name: new_name.clone(),
+                    tags: tags.clone(),
+                    ..Default::default()
+                };
+                client.item().update(&params).await.map(|_| ())
+            }
+        };
+        match result {
+            Ok(()) => done += 1,
+            Err(error) => {
+                progress.finish();
+                eprintln!("Failed: {}: {}", step.describe(), error);
+                failed += 1;
+            }
+        }
+    }
+    progress.finish();
+
+    println!("Applied {} of {} changes ({} failed)", done, steps.len(), failed);
+    exit::outcome(done, failed)
+}
+
+/// Create a folder below its parent, found by path, and record its id under `path`
+async fn create_folder(
+    client: &EagleClient,
+    ids: &mut HashMap<String, String>,
+    path: &str,
+    name: &str,
+    parent: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let parent_id = match parent {
+        Some(parent) => Some(ids.get(parent).cloned().ok_or_else(|| format!("parent folder {} is missing", parent))?),
+        None => None,
+    };
+    let created = client.folder().create(name, parent_id.as_deref()).await?;
+    ids.insert(path.to_string(), created.data.id);
+    Ok(())
+}
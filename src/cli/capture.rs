@@ -0,0 +1,183 @@
+use crate::cli::folder::resolve_folder;
+use crate::cli::item::list::filter::split_list;
+use crate::cli::system::{self, Clipboard};
+use crate::lib::client::EagleClient;
+use crate::lib::types::{AddBookmarkParams, AddFromPathParams, AddFromUrlParams};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::time::Duration;
+
+/// URL endings imported as images rather than bookmarked
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp", "avif", "svg", "bmp", "tif", "tiff", "heic"];
+
+pub fn build() -> Command {
+    Command::new("capture")
+        .about("Import what gets copied to the clipboard until interrupted")
+        .arg_required_else_help(true)
+        .arg(
+            Arg::new("watch_clipboard")
+                .long("watch-clipboard")
+                .help("Watch the clipboard and import copied images, image URLs (as images) and page URLs (as bookmarks)")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("folder")
+                .short('f')
+                .long("folder")
+                .value_name("FOLDER")
+                .help("Inbox folder for captured items, by id, path (Brand/Logos) or unique name")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("tags")
+                .short('t')
+                .long("tags")
+                .value_name("TAG")
+                .help("Tags for captured items. Comma separated")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("interval")
+                .long("interval")
+                .value_name("MS")
+                .help("How often to check the clipboard, in milliseconds")
+                .num_args(1)
+                .default_value("1000")
+                .value_parser(clap::value_parser!(u64).range(100..)),
+        )
+        .arg(
+            Arg::new("include_current")
+                .long("include-current")
+                .help("Also import what is on the clipboard when capture starts")
+                .action(ArgAction::SetTrue),
+        )
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !matches.get_flag("watch_clipboard") {
+        return Err("Nothing to capture; pass --watch-clipboard".into());
+    }
+    let interval = Duration::from_millis(*matches.get_one::<u64>("interval").unwrap());
+    let tags = Some(split_list(matches, "tags")).filter(|tags| !tags.is_empty());
+    let folder_id = match matches.get_one::<String>("folder") {
+        Some(folder) => Some(resolve_folder(&client.folder().list().await?.data, folder)?),
+        None => None,
+    };
+
+    // Fail now, not on the first copy, when the clipboard tools are missing
+    let mut last = fingerprint(&system::read_clipboard()?);
+    if matches.get_flag("include_current") {
+        last = None;
+    }
+    eprintln!("Watching the clipboard, press Ctrl-C to stop");
+
+    let mut captured = 0;
+    loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => break,
+            _ = tokio::time::sleep(interval) => {}
+        }
+        let clipboard = match system::read_clipboard() {
+            Ok(clipboard) => clipboard,
+            Err(error) => {
+                eprintln!("Failed to read the clipboard: {}", error);
+                continue;
+            }
+        };
+        let current = fingerprint(&clipboard);
+        if current == last {
+            continue;
+        }
+        last = current;
+
+        match capture(client, &clipboard, tags.clone(), folder_id.clone()).await {
+            Ok(Some(description)) => {
+                captured += 1;
+                println!("Captured {}", description);
+            }
+            Ok(None) => {}
+            Err(error) => eprintln!("Failed to capture the clipboard: {}", error),
+        }
+    }
+
+    eprintln!("Captured {} items", captured);
+    Ok(())
+}
+
+/// Hash of the clipboard content, to notice when it changes; `None` when empty
+fn fingerprint(clipboard: &Clipboard) -> Option<u64> {
+    let mut hasher = DefaultHasher::new();
+    match clipboard {
+        Clipboard::Image(data) => data.hash(&mut hasher),
+        Clipboard::Text(text) => text.hash(&mut hasher),
+        Clipboard::Empty => return None,
+    }
+    Some(hasher.finish())
+}
+
+/// Import the clipboard content; returns what was captured, or `None` for text that isn't a URL
+async fn capture(
+    client: &EagleClient,
+    clipboard: &Clipboard,
+    tags: Option<Vec<String>>,
+    folder_id: Option<String>,
+) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let now = chrono::Local::now();
+    match clipboard {
+        Clipboard::Image(data) => {
+            let name = format!("Clipboard {}", now.format("%Y-%m-%d %H.%M.%S"));
+            let path = std::env::temp_dir().join(format!("eagle-eye-capture-{}.png", now.timestamp_millis()));
+            std::fs::write(&path, data)?;
+            let params = AddFromPathParams {
+                path: path.to_string_lossy().into_owned(),
+                name: name.clone(),
+                tags,
+                folder_id,
+                ..Default::default()
+            };
+            let result = client.item().add_from_path(&params).await;
+            let _ = std::fs::remove_file(&path);
+            result?;
+            Ok(Some(format!("image {}", name)))
+        }
+        Clipboard::Text(text) => {
+            let Some(url) = as_url(text) else {
+                return Ok(None);
+            };
+            let file_name = url.split(['?', '#']).next().unwrap_or(url).rsplit('/').next().unwrap_or_default();
+            let (stem, ext) = file_name.rsplit_once('.').unwrap_or((file_name, ""));
+            if IMAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str()) {
+                let params = AddFromUrlParams {
+                    url: url.to_string(),
+                    name: if stem.is_empty() { url.to_string() } else { stem.to_string() },
+                    tags,
+                    folder_id,
+                    ..Default::default()
+                };
+                client.item().add_from_url(&params).await?;
+                return Ok(Some(format!("image {}", url)));
+            }
+            let params = AddBookmarkParams {
+                url: url.to_string(),
+                name: url.split_once("://").map_or(url, |(_, rest)| rest).to_string(),
+                tags,
+                folder_id,
+                ..Default::default()
+            };
+            client.item().add_bookmark(&params).await?;
+            Ok(Some(format!("bookmark {}", url)))
+        }
+        Clipboard::Empty => Ok(None),
+    }
+}
+
+/// The text when it is a single http(s) URL
+fn as_url(text: &str) -> Option<&str> {
+    let text = text.trim();
+    let is_url = (text.starts_with("http://") || text.starts_with("https://")) && !text.contains(char::is_whitespace);
+    is_url.then_some(text)
+}
@@ -0,0 +1,427 @@
+use crate::cli::folder::folder_paths;
+use crate::cli::item::list;
+use crate::lib::client::EagleClient;
+use crate::lib::library::LibraryDir;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use clap_complete::Shell;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+const BIN_NAME: &str = "eagle-eye";
+
+pub fn build() -> Command {
+    Command::new("completions")
+        .about("Print a shell completion script, man pages, or a description of every command")
+        .long_about(
+            "Print a shell completion script that also completes folders, tags, item ids, and libraries, \
+             man pages, or a description of every command.\n\n\
+             In bash, zsh, and fish, values of options like --folders and --tags, and item ids, are \
+             asked from Eagle when completing. Load the script from your shell's startup file, e.g. \
+             `source <(eagle-eye completions bash)` or \
+             `eagle-eye completions fish | source`.",
+        )
+        .arg(
+            Arg::new("shell")
+                .value_name("SHELL")
+                .help("Shell to print the script for")
+                .required_unless_present_any(["values", "man", "manifest"])
+                .value_parser(clap::value_parser!(Shell)),
+        )
+        .arg(
+            Arg::new("man")
+                .long("man")
+                .help("Print the man page instead, or with --out write one page per command")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["shell", "values", "manifest"]),
+        )
+        .arg(
+            Arg::new("out")
+                .long("out")
+                .value_name("DIR")
+                .help("Directory to write the man pages to, e.g. /usr/local/share/man/man1")
+                .num_args(1)
+                .requires("man"),
+        )
+        .arg(
+            Arg::new("manifest")
+                .long("manifest")
+                .value_name("FORMAT")
+                .help("Print every command with its arguments and descriptions instead, for wrappers and docs")
+                .num_args(1)
+                .conflicts_with_all(["shell", "values"])
+                .value_parser(["json"]),
+        )
+        .arg(
+            Arg::new("values")
+                .long("values")
+                .value_name("KIND")
+                .help("Print the values to complete instead, one per line with a tab before the description; used by the scripts")
+                .num_args(1)
+                .conflicts_with("shell")
+                .value_parser(Kind::VALUES),
+        )
+}
+
+/// What the value of an argument is, when it can be completed from Eagle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Kind {
+    Folders,
+    Tags,
+    Items,
+    Libraries,
+}
+
+impl Kind {
+    const VALUES: [&'static str; 4] = ["folders", "tags", "items", "libraries"];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Kind::Folders => "folders",
+            Kind::Tags => "tags",
+            Kind::Items => "items",
+            Kind::Libraries => "libraries",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Kind> {
+        match name {
+            "folders" => Some(Kind::Folders),
+            "tags" => Some(Kind::Tags),
+            "items" => Some(Kind::Items),
+            "libraries" => Some(Kind::Libraries),
+            _ => None,
+        }
+    }
+
+    /// Kind of an argument from its value name, `path` being the subcommands it belongs to
+    fn of(path: &[String], arg: &Arg) -> Option<Kind> {
+        let value_name = arg.get_value_names()?.first()?.as_str();
+        match value_name {
+            "FOLDER" | "FOLDER-ID" | "FOLDER_ID" | "PARENT_FOLDER_ID" => Some(Kind::Folders),
+            "TAG" => Some(Kind::Tags),
+            "ITEM_ID" => Some(Kind::Items),
+            // Other ids, like smart folder ones, are outside `item`
+            "ID" if path.first().map(String::as_str) == Some("item") => Some(Kind::Items),
+            "LIBRARY" => Some(Kind::Libraries),
+            "NAME|PATH" if arg.get_id() == "library_select" => Some(Kind::Libraries),
+            _ => None,
+        }
+    }
+}
+
+/// An option completed from Eagle, in the subcommand `path` it belongs to
+struct ScopedOption {
+    path: Vec<String>,
+    short: Option<char>,
+    long: Option<String>,
+    kind: Kind,
+}
+
+/// Options and positional arguments whose values are completed from Eagle
+#[derive(Default)]
+struct Hooks {
+    /// Option names like `--folders` or `-f`, left out when they take other values elsewhere
+    options: BTreeMap<String, Option<Kind>>,
+    /// Subcommand paths like `item info` by the kind of their positional arguments
+    positionals: BTreeMap<Kind, BTreeSet<Vec<String>>>,
+    /// Every completed option with the subcommand path it belongs to, for fish
+    scoped: Vec<ScopedOption>,
+}
+
+impl Hooks {
+    fn collect(&mut self, command: &Command, path: &mut Vec<String>) {
+        for arg in command.get_arguments().filter(|arg| arg.get_num_args().is_none_or(|num| num.takes_values())) {
+            let kind = Kind::of(path, arg);
+            if arg.is_positional() {
+                if let Some(kind) = kind {
+                    self.positionals.entry(kind).or_default().insert(path.clone());
+                }
+                continue;
+            }
+            let names = arg
+                .get_long()
+                .map(|long| format!("--{}", long))
+                .into_iter()
+                .chain(arg.get_short().map(|short| format!("-{}", short)));
+            for name in names {
+                let entry = self.options.entry(name).or_insert(kind);
+                if *entry != kind {
+                    *entry = None;
+                }
+            }
+            if let Some(kind) = kind {
+                self.scoped.push(ScopedOption {
+                    path: path.clone(),
+                    short: arg.get_short(),
+                    long: arg.get_long().map(str::to_string),
+                    kind,
+                });
+            }
+        }
+        for subcommand in command.get_subcommands() {
+            path.push(subcommand.get_name().to_string());
+            self.collect(subcommand, path);
+            path.pop();
+        }
+    }
+
+    /// Option names completed with `kind`, as a `case` pattern like `--folders|-f`
+    fn options_of(&self, kind: Kind) -> String {
+        let names: Vec<&str> = self
+            .options
+            .iter()
+            .filter(|(_, option_kind)| **option_kind == Some(kind))
+            .map(|(name, _)| name.as_str())
+            .collect();
+        names.join("|")
+    }
+
+    /// Subcommand paths whose positional arguments are `kind`, as a `case` pattern like `"item info "*|"item open "*`
+    fn positionals_of(&self, kind: Kind) -> String {
+        let paths: Vec<String> = self
+            .positionals
+            .get(&kind)
+            .into_iter()
+            .flatten()
+            .map(|path| format!("\"{} \"*", path.join(" ")))
+            .collect();
+        paths.join("|")
+    }
+
+    fn kinds(&self) -> BTreeSet<Kind> {
+        self.options.values().flatten().copied().chain(self.positionals.keys().copied()).collect()
+    }
+}
+
+/// Bash and zsh: a function that completes values from Eagle when the word before the cursor
+/// is one of the hooked options, or the command takes item ids, and otherwise hands over to
+/// the generated completion function.
+fn dispatch_function(hooks: &Hooks, previous: &str, current: &str, command_words: &str) -> String {
+    let mut options = String::new();
+    let mut positionals = String::new();
+    for kind in hooks.kinds() {
+        let names = hooks.options_of(kind);
+        if !names.is_empty() {
+            options.push_str(&format!("        {}) _eagle_eye_values {}; return ;;\n", names, kind.name()));
+        }
+        let paths = hooks.positionals_of(kind);
+        if !paths.is_empty() {
+            positionals.push_str(&format!("            {}) _eagle_eye_values {}; return ;;\n", paths, kind.name()));
+        }
+    }
+    format!(
+        "_eagle_eye_dynamic() {{\n    \
+             case \"{previous}\" in\n{options}    esac\n    \
+             if [[ \"{current}\" != -* && \"{previous}\" != -* ]]; then\n        \
+                 case \"{command_words}\" in\n{positionals}        esac\n    \
+             fi\n    \
+             _eagle-eye \"$@\"\n\
+         }}\n",
+    )
+}
+
+fn bash_hooks(hooks: &Hooks) -> String {
+    format!(
+        "\n_eagle_eye_values() {{\n    \
+             local IFS=$'\\n'\n    \
+             COMPREPLY=($(compgen -W \"$({BIN_NAME} completions --values \"$1\" 2>/dev/null | cut -f1)\" -- \"${{COMP_WORDS[COMP_CWORD]}}\"))\n\
+         }}\n\n{}\
+         complete -F _eagle_eye_dynamic -o bashdefault -o default {BIN_NAME}\n",
+        dispatch_function(
+            hooks,
+            "${COMP_WORDS[COMP_CWORD-1]}",
+            "${COMP_WORDS[COMP_CWORD]}",
+            "${COMP_WORDS[*]:1}",
+        ),
+    )
+}
+
+fn zsh_hooks(hooks: &Hooks) -> String {
+    format!(
+        "\n_eagle_eye_values() {{\n    \
+             local line\n    \
+             local -a values\n    \
+             for line in \"${{(@f)$({BIN_NAME} completions --values $1 2>/dev/null)}}\"; do\n        \
+                 [[ -n \"$line\" ]] && values+=(\"${{${{line%%$'\\t'*}}//:/\\\\:}}:${{line#*$'\\t'}}\")\n    \
+             done\n    \
+             _describe -t $1 $1 values\n\
+         }}\n\n{}\
+         compdef _eagle_eye_dynamic {BIN_NAME}\n",
+        dispatch_function(hooks, "${words[CURRENT-1]}", "${words[CURRENT]}", "${words[2,-1]}"),
+    )
+}
+
+/// Fish condition that holds inside the subcommand `path`
+fn fish_condition(path: &[String]) -> String {
+    match path {
+        [] => String::new(),
+        path => {
+            let seen: Vec<String> = path.iter().map(|name| format!("__fish_seen_subcommand_from {}", name)).collect();
+            format!(" -n \"{}\"", seen.join("; and "))
+        }
+    }
+}
+
+fn fish_hooks(hooks: &Hooks) -> String {
+    let mut script = String::from("\n");
+    for option in &hooks.scoped {
+        let short = option.short.map(|short| format!(" -s {}", short)).unwrap_or_default();
+        let long = option.long.as_ref().map(|long| format!(" -l {}", long)).unwrap_or_default();
+        script.push_str(&format!(
+            "complete -c {BIN_NAME}{}{}{} -x -a \"({BIN_NAME} completions --values {} 2>/dev/null)\"\n",
+            fish_condition(&option.path),
+            short,
+            long,
+            option.kind.name()
+        ));
+    }
+    for (kind, paths) in &hooks.positionals {
+        for path in paths {
+            script.push_str(&format!(
+                "complete -c {BIN_NAME}{} -f -a \"({BIN_NAME} completions --values {} 2>/dev/null)\"\n",
+                fish_condition(path),
+                kind.name()
+            ));
+        }
+    }
+    script
+}
+
+fn print_script(shell: Shell) {
+    let mut command = crate::cli::build();
+    let mut hooks = Hooks::default();
+    hooks.collect(&command, &mut Vec::new());
+
+    let mut script = Vec::new();
+    clap_complete::generate(shell, &mut command, BIN_NAME, &mut script);
+    print!("{}", String::from_utf8_lossy(&script));
+    match shell {
+        Shell::Bash => print!("{}", bash_hooks(&hooks)),
+        Shell::Zsh => print!("{}", zsh_hooks(&hooks)),
+        Shell::Fish => print!("{}", fish_hooks(&hooks)),
+        _ => eprintln!("Folders, tags, and item ids are only completed in bash, zsh, and fish"),
+    }
+}
+
+/// Values of `kind` with their descriptions, sorted by value
+async fn values(client: &EagleClient, kind: Kind) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
+    Ok(match kind {
+        Kind::Folders => {
+            let mut folders: Vec<(String, String)> = folder_paths(&client.folder().list().await?.data).into_iter().collect();
+            folders.sort_by(|a, b| a.1.cmp(&b.1));
+            folders
+        }
+        Kind::Tags => {
+            let library = LibraryDir::new(client.library().info().await?.data.library.path);
+            let mut tags: BTreeSet<String> = library.read_tags()?.history_tags.into_iter().collect();
+            for item in list::all_items(client).await? {
+                tags.extend(item.tags);
+            }
+            tags.into_iter().map(|tag| (tag, String::new())).collect()
+        }
+        Kind::Items => list::all_items(client)
+            .await?
+            .into_iter()
+            .map(|item| (item.id, format!("{}.{}", item.name, item.ext)))
+            .collect(),
+        Kind::Libraries => {
+            let mut libraries = client.library().history().await?.data;
+            libraries.dedup();
+            libraries
+                .into_iter()
+                .map(|library| {
+                    let name = Path::new(&library).file_stem().map(|name| name.to_string_lossy().into_owned());
+                    (library, name.unwrap_or_default())
+                })
+                .collect()
+        }
+    })
+}
+
+fn print_man(out: Option<&String>) -> Result<(), Box<dyn std::error::Error>> {
+    let command = crate::cli::build();
+    let Some(out) = out else {
+        let mut page = Vec::new();
+        clap_mangen::Man::new(command).render(&mut page)?;
+        print!("{}", String::from_utf8_lossy(&page));
+        return Ok(());
+    };
+    let out = PathBuf::from(out);
+    std::fs::create_dir_all(&out).map_err(|e| format!("Failed to create {}: {}", out.display(), e))?;
+    clap_mangen::generate_to(command, &out).map_err(|e| format!("Failed to write man pages to {}: {}", out.display(), e))?;
+    eprintln!("Wrote man pages to {}", out.display());
+    Ok(())
+}
+
+fn arg_manifest(arg: &Arg) -> Value {
+    let strings = |values: Option<&[clap::builder::Str]>| -> Vec<String> {
+        values.into_iter().flatten().map(|value| value.to_string()).collect()
+    };
+    let takes_value = arg.get_num_args().is_none_or(|num| num.takes_values());
+    json!({
+        "id": arg.get_id().as_str(),
+        "long": arg.get_long(),
+        "short": arg.get_short(),
+        "positional": arg.is_positional(),
+        "takesValue": takes_value,
+        "valueNames": strings(arg.get_value_names()),
+        "possibleValues": arg.get_possible_values().iter().map(|value| value.get_name().to_string()).collect::<Vec<_>>(),
+        "default": arg.get_default_values().iter().map(|value| value.to_string_lossy().into_owned()).collect::<Vec<_>>(),
+        "required": arg.is_required_set(),
+        "global": arg.is_global_set(),
+        "help": arg.get_help().map(|help| help.to_string()),
+    })
+}
+
+/// `command` and its subcommands as JSON, `path` being the words that run it
+fn command_manifest(command: &Command, path: &str) -> Value {
+    let path = match path {
+        "" => command.get_name().to_string(),
+        parent => format!("{} {}", parent, command.get_name()),
+    };
+    json!({
+        "name": command.get_name(),
+        "path": path,
+        "about": command.get_about().map(|about| about.to_string()),
+        "longAbout": command.get_long_about().map(|about| about.to_string()),
+        "args": command
+            .get_arguments()
+            .filter(|arg| !arg.is_hide_set())
+            .map(arg_manifest)
+            .collect::<Vec<_>>(),
+        "subcommands": command
+            .get_subcommands()
+            .filter(|subcommand| !subcommand.is_hide_set())
+            .map(|subcommand| command_manifest(subcommand, &path))
+            .collect::<Vec<_>>(),
+    })
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(&shell) = matches.get_one::<Shell>("shell") {
+        print_script(shell);
+        return Ok(());
+    }
+    if matches.get_flag("man") {
+        return print_man(matches.get_one::<String>("out"));
+    }
+    if matches.contains_id("manifest") {
+        let command = crate::cli::build();
+        let mut manifest = command_manifest(&command, "");
+        manifest["version"] = json!(command.get_version());
+        println!("{}", serde_json::to_string_pretty(&manifest)?);
+        return Ok(());
+    }
+    let kind = matches.get_one::<String>("values").and_then(|kind| Kind::parse(kind)).unwrap();
+    for (value, description) in values(client, kind).await? {
+        match description.is_empty() {
+            true => println!("{}", value),
+            false => println!("{}\t{}", value, description),
+        }
+    }
+    Ok(())
+}
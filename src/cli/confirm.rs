@@ -0,0 +1,39 @@
+use clap::{Arg, ArgAction, ArgMatches};
+use std::io::{IsTerminal, Write};
+
+pub fn arg() -> Arg {
+    Arg::new("yes")
+        .short('y')
+        .long("yes")
+        .help("Don't ask before destructive changes, for scripts")
+        .action(ArgAction::SetTrue)
+        .global(true)
+}
+
+pub fn is_assumed(matches: &ArgMatches) -> bool {
+    matches.try_get_one::<bool>("yes").ok().flatten().copied().unwrap_or(false)
+}
+
+/// Whether there is someone at a terminal to ask
+pub fn is_interactive() -> bool {
+    std::io::stdin().is_terminal() && std::io::stderr().is_terminal()
+}
+
+/// Ask "`message` — continue? [y/N]" on the terminal before a destructive change, and fail
+/// unless the answer is yes.
+///
+/// Nothing is asked with `--yes`, or when stdin or stderr isn't a terminal, so pipelines
+/// behave as before.
+pub fn confirm(matches: &ArgMatches, message: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if is_assumed(matches) || !is_interactive() {
+        return Ok(());
+    }
+    eprint!("{} — continue? [y/N] ", message);
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    match answer.trim().to_lowercase().as_str() {
+        "y" | "yes" => Ok(()),
+        _ => Err("Cancelled".into()),
+    }
+}
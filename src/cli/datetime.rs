@@ -0,0 +1,191 @@
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime};
+use clap::{Arg, ArgMatches};
+use std::fmt;
+use std::str::FromStr;
+
+/// Time zone used to interpret date arguments and to format Eagle timestamps.
+///
+/// Eagle stores every timestamp as epoch milliseconds (UTC), so a date such as
+/// `2024-06-01` only becomes meaningful once a zone is chosen for it.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum TimeZone {
+    #[default]
+    Local,
+    Named(chrono_tz::Tz),
+    Fixed(FixedOffset),
+}
+
+impl FromStr for TimeZone {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        if value.eq_ignore_ascii_case("local") {
+            return Ok(TimeZone::Local);
+        }
+        if value.eq_ignore_ascii_case("utc") || value == "Z" {
+            return Ok(TimeZone::Named(chrono_tz::UTC));
+        }
+        if value.starts_with('+') || value.starts_with('-') {
+            // Reuse chrono's offset parser, e.g. "+02:00" or "-0530"
+            return DateTime::parse_from_str(&format!("2000-01-01 00:00 {}", value), "%Y-%m-%d %H:%M %z")
+                .map(|date_time| TimeZone::Fixed(*date_time.offset()))
+                .map_err(|_| format!("invalid UTC offset: {}", value));
+        }
+        value
+            .parse::<chrono_tz::Tz>()
+            .map(TimeZone::Named)
+            .map_err(|_| format!("unknown time zone: {}", value))
+    }
+}
+
+impl fmt::Display for TimeZone {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TimeZone::Local => write!(f, "local"),
+            TimeZone::Named(tz) => write!(f, "{}", tz),
+            TimeZone::Fixed(offset) => write!(f, "{}", offset),
+        }
+    }
+}
+
+impl TimeZone {
+    /// Interpret a date or date-time string in this zone and return epoch milliseconds.
+    ///
+    /// Accepts `YYYY-MM-DD` (midnight), `YYYY-MM-DDTHH:MM[:SS]`, and RFC 3339
+    /// strings; the latter carry their own offset and ignore the zone.
+    pub fn parse_millis(&self, value: &str) -> Result<i64, String> {
+        let value = value.trim();
+        if let Ok(date_time) = DateTime::parse_from_rfc3339(value) {
+            return Ok(date_time.timestamp_millis());
+        }
+
+        let naive = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .map(|date| date.and_hms_opt(0, 0, 0).unwrap())
+            .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S"))
+            .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M"))
+            .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S"))
+            .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M"))
+            .map_err(|_| format!("invalid date: {} (expected YYYY-MM-DD[THH:MM[:SS]])", value))?;
+
+        self.localize(&naive)
+            .ok_or_else(|| format!("{} does not exist in time zone {}", value, self))
+    }
+
+    /// Format epoch milliseconds as an ISO 8601 date-time in this zone.
+    pub fn format_millis(&self, millis: i64) -> String {
+        self.format_millis_as(millis, "%Y-%m-%dT%H:%M:%S%:z")
+    }
+
+    /// Format epoch milliseconds with a strftime `format` in this zone; the format must be
+    /// valid, see [`is_valid_format`].
+    pub fn format_millis_as(&self, millis: i64, format: &str) -> String {
+        match self {
+            TimeZone::Local => format_in(&Local, millis, format),
+            TimeZone::Named(tz) => format_in(tz, millis, format),
+            TimeZone::Fixed(offset) => format_in(offset, millis, format),
+        }
+    }
+
+    fn localize(&self, naive: &NaiveDateTime) -> Option<i64> {
+        match self {
+            TimeZone::Local => localize_in(&Local, naive),
+            TimeZone::Named(tz) => localize_in(tz, naive),
+            TimeZone::Fixed(offset) => localize_in(offset, naive),
+        }
+    }
+}
+
+fn localize_in<Z: chrono::TimeZone>(tz: &Z, naive: &NaiveDateTime) -> Option<i64> {
+    // On DST transitions pick the earliest instant, so "midnight" never skips a day
+    tz.from_local_datetime(naive)
+        .earliest()
+        .map(|date_time| date_time.timestamp_millis())
+}
+
+fn format_in<Z: chrono::TimeZone>(tz: &Z, millis: i64, format: &str) -> String
+where
+    Z::Offset: fmt::Display,
+{
+    match tz.timestamp_millis_opt(millis).single() {
+        Some(date_time) => date_time.format(format).to_string(),
+        None => millis.to_string(),
+    }
+}
+
+pub fn arg() -> Arg {
+    Arg::new("tz")
+        .long("tz")
+        .value_name("TZ")
+        .help("Time zone for date arguments and timestamps: local, UTC, an IANA name, or an offset like +02:00")
+        .env("EAGLE_EYE_TZ")
+        .default_value("local")
+        .value_parser(|value: &str| value.parse::<TimeZone>())
+        .global(true)
+}
+
+/// Get the time zone selected with the global `--tz` argument.
+pub fn from_matches(matches: &ArgMatches) -> TimeZone {
+    matches
+        .try_get_one::<TimeZone>("tz")
+        .ok()
+        .flatten()
+        .copied()
+        .unwrap_or_default()
+}
+
+/// Whether `format` is a strftime format chrono can render; formatting with an invalid one panics.
+pub fn is_valid_format(format: &str) -> bool {
+    chrono::format::StrftimeItems::new(format).all(|item| !matches!(item, chrono::format::Item::Error))
+}
+
+/// A point in time given on the command line, either absolute or relative to now.
+#[derive(Debug, Clone)]
+pub enum DateBound {
+    /// A duration such as `7d` or `12h`, in milliseconds before now
+    Ago(i64),
+    /// A date or date-time resolved in the `--tz` zone, see [`TimeZone::parse_millis`]
+    Date(String),
+}
+
+impl FromStr for DateBound {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        if let Some(millis) = parse_duration_millis(value) {
+            return Ok(DateBound::Ago(millis));
+        }
+        // Only validate the format here; the zone is known once all arguments are parsed
+        TimeZone::Named(chrono_tz::UTC).parse_millis(value).map_err(|_| {
+            format!("invalid date: {} (expected YYYY-MM-DD[THH:MM[:SS]] or a duration like 7d, 12h)", value)
+        })?;
+        Ok(DateBound::Date(value.to_string()))
+    }
+}
+
+impl DateBound {
+    /// Resolve to epoch milliseconds in the given zone.
+    pub fn resolve(&self, tz: &TimeZone) -> Result<i64, String> {
+        match self {
+            DateBound::Ago(millis) => Ok(chrono::Utc::now().timestamp_millis() - millis),
+            DateBound::Date(value) => tz.parse_millis(value),
+        }
+    }
+}
+
+/// Parse a duration such as `90s`, `30m`, `12h`, `7d`, or `2w` into milliseconds.
+pub fn parse_duration_millis(value: &str) -> Option<i64> {
+    let split = value.find(|c: char| !c.is_ascii_digit())?;
+    let (number, unit) = value.split_at(split);
+    let number: i64 = number.parse().ok()?;
+    let unit_millis = match unit {
+        "s" => 1_000,
+        "m" | "min" => 60_000,
+        "h" => 3_600_000,
+        "d" => 86_400_000,
+        "w" => 7 * 86_400_000,
+        _ => return None,
+    };
+    number.checked_mul(unit_millis)
+}
@@ -0,0 +1,13 @@
+use clap::{Arg, ArgAction, ArgMatches};
+
+pub fn arg() -> Arg {
+    Arg::new("strict_decode")
+        .long("strict-decode")
+        .help("Fail when an Eagle response has fields eagle-eye doesn't model, or lacks ones it does, to spot API changes")
+        .action(ArgAction::SetTrue)
+        .global(true)
+}
+
+pub fn is_strict(matches: &ArgMatches) -> bool {
+    matches.try_get_one::<bool>("strict_decode").ok().flatten().copied().unwrap_or(false)
+}
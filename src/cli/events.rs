@@ -0,0 +1,301 @@
+use crate::cli::datetime::{self, TimeZone};
+use crate::cli::folder::folder_paths;
+use crate::cli::http::HttpClient;
+use crate::cli::item::list;
+use crate::lib::client::EagleClient;
+use crate::lib::types::{Child, ItemListData};
+use clap::{Arg, ArgMatches, Command};
+use ring::hmac;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Item fields that change on every save and say nothing on their own
+const VOLATILE_FIELDS: &[&str] = &["modificationTime"];
+
+pub fn build() -> Command {
+    Command::new("events")
+        .about("Watch the library and print each change as a line of JSON")
+        .long_about(
+            "Watch the library and print each change as a line of JSON (NDJSON).\n\n\
+             Events: item.added, item.updated (with the changed fields), item.deleted, \
+             folder.added, folder.renamed, folder.moved and folder.removed.\n\n\
+             With --post-to each event is also POSTed to a webhook, with the event name in \
+             X-Eagle-Eye-Event and, given --secret, an HMAC-SHA256 signature of the body.",
+        )
+        .arg(
+            Arg::new("interval")
+                .long("interval")
+                .value_name("DURATION")
+                .help("How often to poll Eagle, e.g. 5s or 1m")
+                .num_args(1)
+                .default_value("5s")
+                .value_parser(|value: &str| {
+                    datetime::parse_duration_millis(value)
+                        .filter(|millis| *millis > 0)
+                        .map(|millis| Duration::from_millis(millis as u64))
+                        .ok_or_else(|| format!("invalid interval: {} (use 30s, 5m, ...)", value))
+                }),
+        )
+        .arg(
+            Arg::new("post_to")
+                .long("post-to")
+                .value_name("URL")
+                .help("Also deliver every event to URL as a webhook (a JSON POST)")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("secret")
+                .long("secret")
+                .value_name("SECRET")
+                .help("Sign webhooks with HMAC-SHA256, sent as X-Eagle-Eye-Signature: sha256=<hex of the body>")
+                .env("EAGLE_EYE_WEBHOOK_SECRET")
+                .hide_env_values(true)
+                .num_args(1)
+                .requires("post_to"),
+        )
+        .arg(
+            Arg::new("retries")
+                .long("retries")
+                .value_name("N")
+                .help("Retry a failed delivery N times, waiting 1s, 2s, 4s, ... in between")
+                .num_args(1)
+                .default_value("3")
+                .value_parser(clap::value_parser!(u32).range(..=10)),
+        )
+}
+
+/// Delivers events to `--post-to`
+struct Webhook {
+    http: HttpClient,
+    url: String,
+    key: Option<hmac::Key>,
+    retries: u32,
+}
+
+impl Webhook {
+    fn from_matches(matches: &ArgMatches) -> Option<Self> {
+        Some(Webhook {
+            http: HttpClient::new(),
+            url: matches.get_one::<String>("post_to")?.clone(),
+            key: matches
+                .get_one::<String>("secret")
+                .map(|secret| hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes())),
+            retries: *matches.get_one::<u32>("retries").unwrap(),
+        })
+    }
+
+    /// POST the event, retrying connection errors, 5xx and 429 responses with exponential backoff
+    async fn deliver(&self, event: &Value) -> Result<(), Box<dyn std::error::Error>> {
+        let body = serde_json::to_vec(event)?;
+        let mut headers = vec![
+            ("content-type".to_string(), "application/json".to_string()),
+            ("user-agent".to_string(), format!("eagle-eye/{}", env!("CARGO_PKG_VERSION"))),
+            ("x-eagle-eye-event".to_string(), event["event"].as_str().unwrap_or_default().to_string()),
+        ];
+        if let Some(key) = &self.key {
+            let tag = hmac::sign(key, &body);
+            let hex: String = tag.as_ref().iter().map(|byte| format!("{:02x}", byte)).collect();
+            headers.push(("x-eagle-eye-signature".to_string(), format!("sha256={}", hex)));
+        }
+
+        let mut attempt = 0;
+        loop {
+            let error = match self.http.post(&self.url, &headers, body.clone()).await {
+                Ok(status) if status.is_success() => return Ok(()),
+                Ok(status) if status.is_server_error() || status.as_u16() == 429 => format!("{} returned {}", self.url, status),
+                Ok(status) => return Err(format!("{} returned {}", self.url, status).into()),
+                Err(error) => error.to_string(),
+            };
+            if attempt == self.retries {
+                return Err(format!("{} (gave up after {} attempts)", error, attempt + 1).into());
+            }
+            tokio::time::sleep(Duration::from_secs(1 << attempt)).await;
+            attempt += 1;
+        }
+    }
+}
+
+/// What the library looked like at the last poll
+#[derive(Default)]
+struct Snapshot {
+    /// False until the first poll, which only records the starting point
+    taken: bool,
+    /// Library `modificationTime`; folders are only listed again when it moves
+    modification_time: u64,
+    folders: HashMap<String, FolderState>,
+    items: HashMap<String, Value>,
+}
+
+#[derive(PartialEq)]
+struct FolderState {
+    name: String,
+    path: String,
+    parent: Option<String>,
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let interval = *matches.get_one::<Duration>("interval").unwrap();
+    let tz = datetime::from_matches(matches);
+    let webhook = Webhook::from_matches(matches);
+
+    let mut snapshot = Snapshot::default();
+    poll(client, &mut snapshot).await?;
+    eprintln!(
+        "Watching {} items and {} folders, press Ctrl-C to stop",
+        snapshot.items.len(),
+        snapshot.folders.len()
+    );
+
+    loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => break,
+            _ = tokio::time::sleep(interval) => {}
+        }
+        match poll(client, &mut snapshot).await {
+            Ok(events) => {
+                for event in events {
+                    let event = stamp(event, &tz);
+                    println!("{}", event);
+                    if let Some(webhook) = &webhook {
+                        if let Err(error) = webhook.deliver(&event).await {
+                            eprintln!("Failed to deliver {}: {}", event["event"], error);
+                        }
+                    }
+                }
+            }
+            Err(error) => eprintln!("Failed to poll Eagle: {}", error),
+        }
+    }
+    Ok(())
+}
+
+/// Take a new snapshot and return the events that lead to it from the previous one
+async fn poll(client: &EagleClient, snapshot: &mut Snapshot) -> Result<Vec<Value>, Box<dyn std::error::Error>> {
+    let mut events = Vec::new();
+
+    let modification_time = client.library().info().await?.data.modification_time;
+    if modification_time != snapshot.modification_time {
+        let folders = folder_states(&client.folder().list().await?.data);
+        if snapshot.taken {
+            events.extend(folder_events(&snapshot.folders, &folders));
+        }
+        snapshot.folders = folders;
+        snapshot.modification_time = modification_time;
+    }
+
+    let items: HashMap<String, Value> = list::all_items(client)
+        .await?
+        .into_iter()
+        .map(|item: ItemListData| (item.id.clone(), serde_json::to_value(item).unwrap_or_default()))
+        .collect();
+    if snapshot.taken {
+        events.extend(item_events(&snapshot.items, &items));
+    }
+    snapshot.items = items;
+    snapshot.taken = true;
+
+    Ok(events)
+}
+
+/// Add the time to an event, as its first field
+fn stamp(event: Value, tz: &TimeZone) -> Value {
+    let mut record = Map::new();
+    record.insert("time".to_string(), json!(tz.format_millis(chrono::Utc::now().timestamp_millis())));
+    if let Value::Object(fields) = event {
+        record.extend(fields);
+    }
+    Value::Object(record)
+}
+
+fn folder_states(folders: &[Child]) -> HashMap<String, FolderState> {
+    let paths = folder_paths(folders);
+    let mut states = HashMap::new();
+    let mut stack: Vec<(&Child, Option<&str>)> = folders.iter().map(|folder| (folder, None)).collect();
+    while let Some((folder, parent)) = stack.pop() {
+        stack.extend(folder.children.iter().map(|child| (child, Some(folder.id.as_str()))));
+        states.insert(
+            folder.id.clone(),
+            FolderState {
+                name: folder.name.clone(),
+                path: paths.get(&folder.id).cloned().unwrap_or_default(),
+                parent: parent.map(String::from),
+            },
+        );
+    }
+    states
+}
+
+fn folder_events(before: &HashMap<String, FolderState>, after: &HashMap<String, FolderState>) -> Vec<Value> {
+    let mut events = Vec::new();
+    for (id, folder) in after {
+        match before.get(id) {
+            None => events.push(json!({ "event": "folder.added", "id": id, "name": folder.name, "path": folder.path })),
+            Some(old) if old == folder => {}
+            Some(old) => {
+                if old.name != folder.name {
+                    events.push(json!({
+                        "event": "folder.renamed",
+                        "id": id,
+                        "from": old.name,
+                        "to": folder.name,
+                        "path": folder.path,
+                    }));
+                }
+                if old.parent != folder.parent {
+                    events.push(json!({ "event": "folder.moved", "id": id, "from": old.path, "to": folder.path }));
+                }
+            }
+        }
+    }
+    for (id, folder) in before {
+        if !after.contains_key(id) {
+            events.push(json!({ "event": "folder.removed", "id": id, "name": folder.name, "path": folder.path }));
+        }
+    }
+    events
+}
+
+fn item_events(before: &HashMap<String, Value>, after: &HashMap<String, Value>) -> Vec<Value> {
+    let is_deleted = |item: &Value| item["isDeleted"].as_bool().unwrap_or(false);
+    let mut events = Vec::new();
+    for (id, item) in after {
+        let event = match before.get(id) {
+            None if is_deleted(item) => continue,
+            None => json!({ "event": "item.added", "id": id, "name": item["name"], "item": item }),
+            Some(old) if !is_deleted(old) && is_deleted(item) => {
+                json!({ "event": "item.deleted", "id": id, "name": item["name"] })
+            }
+            Some(old) => {
+                let changes = changed_fields(old, item);
+                if changes.is_empty() {
+                    continue;
+                }
+                json!({ "event": "item.updated", "id": id, "name": item["name"], "changes": changes, "item": item })
+            }
+        };
+        events.push(event);
+    }
+    for (id, item) in before {
+        if !after.contains_key(id) && !is_deleted(item) {
+            events.push(json!({ "event": "item.deleted", "id": id, "name": item["name"] }));
+        }
+    }
+    events
+}
+
+fn changed_fields(old: &Value, new: &Value) -> Vec<String> {
+    let (Value::Object(old), Value::Object(new)) = (old, new) else {
+        return Vec::new();
+    };
+    let mut fields: Vec<String> = new
+        .iter()
+        .filter(|(key, value)| !VOLATILE_FIELDS.contains(&key.as_str()) && old.get(key.as_str()) != Some(value))
+        .map(|(key, _)| key.clone())
+        .collect();
+    fields.extend(old.keys().filter(|key| !new.contains_key(key.as_str())).cloned());
+    fields
+}
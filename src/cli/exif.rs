@@ -0,0 +1,204 @@
+use crate::cli::datetime::{self, DateBound, TimeZone};
+use crate::lib::library::LibraryDir;
+use crate::lib::types::ItemListData;
+use clap::{Arg, ArgMatches};
+use exif::{In, Tag, Value};
+use serde::Serialize;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+/// Capture metadata read from the EXIF block of an image file
+#[derive(Debug, Default, Serialize)]
+pub struct ExifInfo {
+    pub make: Option<String>,
+    pub model: Option<String>,
+    pub lens: Option<String>,
+    pub iso: Option<u32>,
+    /// Exposure time as written on cameras, e.g. `1/250`
+    pub exposure: Option<String>,
+    pub aperture: Option<f64>,
+    /// Focal length in millimeters
+    pub focal_length: Option<f64>,
+    /// Capture time as recorded by the camera, `YYYY-MM-DDTHH:MM:SS`, with the offset when known
+    pub taken: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+}
+
+impl ExifInfo {
+    /// Read the EXIF data of a JPEG, TIFF, HEIF, PNG, or WebP file.
+    pub fn read(path: &Path) -> Result<ExifInfo, Box<dyn std::error::Error>> {
+        let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        let exif = exif::Reader::new()
+            .read_from_container(&mut BufReader::new(file))
+            .map_err(|e| format!("No EXIF data in {}: {}", path.display(), e))?;
+        let field = |tag: Tag| exif.get_field(tag, In::PRIMARY).map(|field| &field.value);
+
+        Ok(ExifInfo {
+            make: field(Tag::Make).and_then(ascii),
+            model: field(Tag::Model).and_then(ascii),
+            lens: field(Tag::LensModel).and_then(ascii),
+            iso: field(Tag::PhotographicSensitivity).and_then(|value| value.get_uint(0)),
+            exposure: field(Tag::ExposureTime).and_then(|value| match value {
+                Value::Rational(rationals) => rationals.first().map(|time| match time.num {
+                    1 => format!("1/{}", time.denom),
+                    _ => format!("{}", time.to_f64()),
+                }),
+                _ => None,
+            }),
+            aperture: field(Tag::FNumber).and_then(rational),
+            focal_length: field(Tag::FocalLength).and_then(rational),
+            taken: field(Tag::DateTimeOriginal)
+                .or_else(|| field(Tag::DateTime))
+                .and_then(|value| match value {
+                    Value::Ascii(values) => values.first().and_then(|value| exif::DateTime::from_ascii(value).ok()),
+                    _ => None,
+                })
+                .map(|mut taken| {
+                    if let Some(Value::Ascii(offset)) = field(Tag::OffsetTimeOriginal) {
+                        offset.first().map(|offset| taken.parse_offset(offset));
+                    }
+                    format_date_time(&taken)
+                }),
+            latitude: coordinate(field(Tag::GPSLatitude), field(Tag::GPSLatitudeRef), "S"),
+            longitude: coordinate(field(Tag::GPSLongitude), field(Tag::GPSLongitudeRef), "W"),
+        })
+    }
+
+    /// Camera make and model as one string, without repeating the make
+    pub fn camera(&self) -> Option<String> {
+        match (&self.make, &self.model) {
+            (Some(make), Some(model)) if model.starts_with(make.as_str()) => Some(model.clone()),
+            (Some(make), Some(model)) => Some(format!("{} {}", make, model)),
+            (make, model) => make.clone().or_else(|| model.clone()),
+        }
+    }
+
+    /// Capture time in epoch milliseconds; times without an offset are taken to be in `tz`
+    pub fn taken_millis(&self, tz: &TimeZone) -> Option<i64> {
+        tz.parse_millis(self.taken.as_deref()?).ok()
+    }
+}
+
+fn ascii(value: &Value) -> Option<String> {
+    match value {
+        Value::Ascii(values) => values
+            .first()
+            .map(|value| String::from_utf8_lossy(value).trim_end_matches('\0').trim().to_string())
+            .filter(|value| !value.is_empty()),
+        _ => None,
+    }
+}
+
+fn rational(value: &Value) -> Option<f64> {
+    match value {
+        Value::Rational(rationals) => rationals.first().map(|value| (value.to_f64() * 10.0).round() / 10.0),
+        _ => None,
+    }
+}
+
+/// Degrees, minutes, and seconds to signed decimal degrees
+fn coordinate(value: Option<&Value>, reference: Option<&Value>, negative: &str) -> Option<f64> {
+    let Value::Rational(dms) = value? else {
+        return None;
+    };
+    let [degrees, minutes, seconds] = dms.as_slice() else {
+        return None;
+    };
+    let decimal = degrees.to_f64() + minutes.to_f64() / 60.0 + seconds.to_f64() / 3600.0;
+    let sign = if reference.and_then(ascii).as_deref() == Some(negative) { -1.0 } else { 1.0 };
+    Some((sign * decimal * 1e6).round() / 1e6)
+}
+
+fn format_date_time(taken: &exif::DateTime) -> String {
+    let date_time = format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        taken.year, taken.month, taken.day, taken.hour, taken.minute, taken.second
+    );
+    match taken.offset {
+        Some(offset) => format!("{}{}{:02}:{:02}", date_time, if offset < 0 { '-' } else { '+' }, offset.abs() / 60, offset.abs() % 60),
+        None => date_time,
+    }
+}
+
+/// `--taken-since`, `--taken-until`, and `--camera` arguments for filtering on EXIF data.
+pub fn filter_args() -> Vec<Arg> {
+    vec![
+        Arg::new("taken_since")
+            .long("taken-since")
+            .value_name("DATE")
+            .help("Only items captured at or after DATE (EXIF), or within a duration like 30d")
+            .num_args(1)
+            .value_parser(|value: &str| value.parse::<DateBound>()),
+        Arg::new("taken_until")
+            .long("taken-until")
+            .value_name("DATE")
+            .help("Only items captured before DATE (EXIF)")
+            .num_args(1)
+            .value_parser(|value: &str| value.parse::<DateBound>()),
+        Arg::new("camera")
+            .long("camera")
+            .value_name("TEXT")
+            .help("Only items shot with a camera whose make or model contains TEXT (EXIF)")
+            .num_args(1),
+    ]
+}
+
+/// Whether any of `filter_args()` is given.
+pub fn is_requested(matches: &ArgMatches) -> bool {
+    ["taken_since", "taken_until", "camera"].iter().any(|id| matches.contains_id(id))
+}
+
+/// Filters on EXIF data, which means opening every candidate file, so they run last.
+#[derive(Debug)]
+pub struct ExifFilter {
+    library: LibraryDir,
+    tz: TimeZone,
+    taken_since: Option<i64>,
+    taken_until: Option<i64>,
+    /// Lowercase
+    camera: Option<String>,
+}
+
+impl ExifFilter {
+    /// `None` when no EXIF filter is given.
+    pub fn from_matches(matches: &ArgMatches, library: &LibraryDir) -> Result<Option<Self>, Box<dyn std::error::Error>> {
+        let tz = datetime::from_matches(matches);
+        let resolve = |id: &str| matches.get_one::<DateBound>(id).map(|bound| bound.resolve(&tz)).transpose();
+        let filter = ExifFilter {
+            library: library.clone(),
+            tz,
+            taken_since: resolve("taken_since")?,
+            taken_until: resolve("taken_until")?,
+            camera: matches.get_one::<String>("camera").map(|camera| camera.to_lowercase()),
+        };
+        let active = filter.taken_since.is_some() || filter.taken_until.is_some() || filter.camera.is_some();
+        Ok(active.then_some(filter))
+    }
+
+    /// Items without a file or without EXIF data never match.
+    pub fn matches(&self, item: &ItemListData) -> bool {
+        let Some(info) = self
+            .library
+            .item_file(&item.id, &item.name, &item.ext)
+            .and_then(|path| ExifInfo::read(&path).ok())
+        else {
+            return false;
+        };
+        if let Some(camera) = &self.camera {
+            if !info.camera().is_some_and(|name| name.to_lowercase().contains(camera.as_str())) {
+                return false;
+            }
+        }
+        if self.taken_since.is_some() || self.taken_until.is_some() {
+            let Some(taken) = info.taken_millis(&self.tz) else {
+                return false;
+            };
+            if self.taken_since.is_some_and(|since| taken < since) || self.taken_until.is_some_and(|until| taken >= until) {
+                return false;
+            }
+        }
+        true
+    }
+}
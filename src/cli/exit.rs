@@ -0,0 +1,31 @@
+use std::fmt;
+
+/// Exit status when a bulk command changed some items but failed on others
+pub const PARTIAL: i32 = 3;
+
+/// Error for bulk commands where only some of the items could be processed.
+///
+/// `main` exits with [`PARTIAL`] instead of 1 so scripts can tell "nothing happened"
+/// from "rerun for the rest".
+#[derive(Debug)]
+pub struct Partial {
+    pub succeeded: usize,
+    pub failed: usize,
+}
+
+impl fmt::Display for Partial {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} of {} items failed", self.failed, self.succeeded + self.failed)
+    }
+}
+
+impl std::error::Error for Partial {}
+
+/// `Ok` when nothing failed, [`Partial`] when some items succeeded, a plain error otherwise.
+pub fn outcome(succeeded: usize, failed: usize) -> Result<(), Box<dyn std::error::Error>> {
+    match (succeeded, failed) {
+        (_, 0) => Ok(()),
+        (0, failed) => Err(format!("All {} items failed", failed).into()),
+        (succeeded, failed) => Err(Partial { succeeded, failed }.into()),
+    }
+}
@@ -0,0 +1,404 @@
+use crate::cli::folder::resolve_folder;
+use crate::cli::http::HttpClient;
+use crate::cli::item::import::{self, fallback_title, Entry};
+use crate::cli::item::list::filter::split_list;
+use crate::cli::progress::Progress;
+use crate::cli::{exit, output, stats};
+use crate::lib::client::EagleClient;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::PathBuf;
+
+const FEEDS_FILE_NAME: &str = "feeds.json";
+
+/// A subscribed feed and the entries already imported from it
+#[derive(Debug, Serialize, Deserialize)]
+struct Feed {
+    url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    folder_id: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    /// GUIDs of the entries imported (or skipped) so far
+    #[serde(default)]
+    seen: Vec<String>,
+}
+
+/// An entry of a fetched feed
+#[derive(Debug, Default)]
+struct FeedEntry {
+    guid: Option<String>,
+    title: String,
+    link: Option<String>,
+    image: Option<String>,
+    /// Description or content HTML, searched for an image when there is no enclosure
+    html: String,
+    date: Option<u64>,
+}
+
+impl FeedEntry {
+    /// Identity used for deduplication: the GUID, else the link, else the title
+    fn id(&self) -> String {
+        self.guid.clone().or_else(|| self.link.clone()).unwrap_or_else(|| self.title.clone())
+    }
+}
+
+pub fn build() -> Command {
+    Command::new("feed")
+        .about("Subscribe to RSS/Atom feeds and import their new entries")
+        .subcommand(
+            Command::new("add")
+                .about("Subscribe to a feed")
+                .arg(Arg::new("url").value_name("URL").help("Feed address").required(true).num_args(1))
+                .arg(
+                    Arg::new("folder")
+                        .short('f')
+                        .long("folder")
+                        .value_name("FOLDER")
+                        .help("Folder for imported entries, by id, path (Brand/Logos) or unique name")
+                        .num_args(1),
+                )
+                .arg(
+                    Arg::new("tags")
+                        .short('t')
+                        .long("tags")
+                        .value_name("TAG")
+                        .help("Tags for imported entries. Comma separated")
+                        .num_args(1),
+                )
+                .arg(
+                    Arg::new("only_new")
+                        .long("only-new")
+                        .help("Mark the entries currently in the feed as seen, so sync only imports later ones")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(Command::new("list").about("List subscribed feeds").args(output::args()))
+        .subcommand(
+            Command::new("remove")
+                .about("Unsubscribe from a feed")
+                .arg(Arg::new("url").value_name("URL").help("Feed address").required(true).num_args(1)),
+        )
+        .subcommand(
+            Command::new("sync")
+                .about("Fetch feeds and import entries not seen before")
+                .arg(
+                    Arg::new("url")
+                        .value_name("URL")
+                        .help("Only sync this feed")
+                        .num_args(1),
+                )
+                .arg(
+                    Arg::new("dry_run")
+                        .long("dry-run")
+                        .help("List the new entries without importing them")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut feeds = read_feeds()?;
+
+    match matches.subcommand() {
+        Some(("add", add_matches)) => {
+            let url = add_matches.get_one::<String>("url").unwrap();
+            if feeds.iter().any(|feed| &feed.url == url) {
+                return Err(format!("Already subscribed to {}", url).into());
+            }
+            // Fetch once up front so a wrong URL fails now rather than on every sync
+            let (title, entries) = fetch(&HttpClient::new(), url).await?;
+            let folder_id = match add_matches.get_one::<String>("folder") {
+                Some(folder) => Some(resolve_folder(&client.folder().list().await?.data, folder)?),
+                None => None,
+            };
+            let seen = match add_matches.get_flag("only_new") {
+                true => entries.iter().map(FeedEntry::id).collect(),
+                false => Vec::new(),
+            };
+            println!("Subscribed to {} ({} entries)", title.as_deref().unwrap_or(url), entries.len());
+            feeds.push(Feed {
+                url: url.clone(),
+                title,
+                folder_id,
+                tags: split_list(add_matches, "tags"),
+                seen,
+            });
+            write_feeds(&feeds)?;
+        }
+        Some(("list", list_matches)) => {
+            let rows: Vec<Value> = feeds
+                .iter()
+                .map(|feed| {
+                    json!({
+                        "url": feed.url,
+                        "title": feed.title,
+                        "folder": feed.folder_id,
+                        "tags": feed.tags,
+                        "seen": feed.seen.len(),
+                    })
+                })
+                .collect();
+            output::output(&Value::Array(rows), list_matches)?;
+        }
+        Some(("remove", remove_matches)) => {
+            let url = remove_matches.get_one::<String>("url").unwrap();
+            let count = feeds.len();
+            feeds.retain(|feed| &feed.url != url);
+            if feeds.len() == count {
+                return Err(format!("Not subscribed to {}", url).into());
+            }
+            write_feeds(&feeds)?;
+            println!("Unsubscribed from {}", url);
+        }
+        Some(("sync", sync_matches)) => {
+            let only = sync_matches.get_one::<String>("url");
+            if only.is_some_and(|url| !feeds.iter().any(|feed| &feed.url == url)) {
+                return Err(format!("Not subscribed to {}", only.unwrap()).into());
+            }
+            return sync(client, &mut feeds, only, sync_matches.get_flag("dry_run")).await;
+        }
+        _ => {
+            println!("No subcommand was used");
+        }
+    }
+    Ok(())
+}
+
+async fn sync(
+    client: &EagleClient,
+    feeds: &mut [Feed],
+    only: Option<&String>,
+    dry_run: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let http = HttpClient::new();
+    let (mut imported, mut failed) = (0, 0);
+    // Feeds are counted apart from items, which only the fetched feeds can have
+    let (mut fetched, mut unreachable) = (0, 0);
+    for feed in feeds.iter_mut().filter(|feed| only.is_none_or(|url| &feed.url == url)) {
+        let entries = match fetch(&http, &feed.url).await {
+            Ok((_, entries)) => entries,
+            Err(error) => {
+                eprintln!("Failed to fetch {}: {}", feed.url, error);
+                unreachable += 1;
+                continue;
+            }
+        };
+        fetched += 1;
+
+        let new_entries: Vec<&FeedEntry> = entries.iter().filter(|entry| !feed.seen.contains(&entry.id())).collect();
+        let mut added = 0;
+        for entry in new_entries {
+            if dry_run {
+                println!("{}  {}", entry.title, entry.link.as_deref().unwrap_or_default());
+                added += 1;
+                continue;
+            }
+            let Some(import_entry) = to_import_entry(entry, &feed.tags) else {
+                // Nothing to import; don't look at it again
+                feed.seen.push(entry.id());
+                continue;
+            };
+            let (ok, errors) = import::add_entries(client, vec![import_entry], feed.folder_id.as_deref(), 1, &mut Progress::hidden()).await;
+            if ok > 0 {
+                feed.seen.push(entry.id());
+                added += 1;
+            }
+            failed += errors.len();
+        }
+        imported += added;
+
+        // Entries that left the feed won't come back, so forget them to keep the file small
+        let current: Vec<String> = entries.iter().map(FeedEntry::id).collect();
+        feed.seen.retain(|id| current.contains(id));
+
+        let action = if dry_run { "new" } else { "imported" };
+        println!("{}: {} {}", feed.title.as_deref().unwrap_or(&feed.url), added, action);
+    }
+
+    if !dry_run {
+        write_feeds(feeds)?;
+    }
+    if unreachable > 0 && fetched == 0 {
+        return Err(format!("All {} feeds failed to fetch", unreachable).into());
+    }
+    exit::outcome(imported, failed)?;
+    if unreachable > 0 {
+        return Err(format!("{} of {} feeds failed to fetch", unreachable, fetched + unreachable).into());
+    }
+    Ok(())
+}
+
+/// Entries with an image become image items with the entry link as their source; others become
+/// bookmarks of their link. Entries with neither are skipped.
+fn to_import_entry(entry: &FeedEntry, tags: &[String]) -> Option<Entry> {
+    let image = entry.image.clone().or_else(|| first_image(&entry.html));
+    let url = entry.link.clone().or_else(|| image.clone())?;
+    let note = strip_html(&entry.html);
+    Some(Entry {
+        title: if entry.title.is_empty() { fallback_title(&url) } else { entry.title.clone() },
+        image,
+        tags: tags.to_vec(),
+        note: (!note.is_empty()).then_some(note),
+        created: entry.date,
+        url,
+    })
+}
+
+/// Fetch a feed and return its title and entries
+async fn fetch(http: &HttpClient, url: &str) -> Result<(Option<String>, Vec<FeedEntry>), Box<dyn std::error::Error>> {
+    let download = http.get(url, &[], None).await?;
+    parse(&String::from_utf8_lossy(&download.bytes))
+}
+
+/// Parse RSS 2.0, RSS 1.0 (RDF) or Atom.
+fn parse(xml: &str) -> Result<(Option<String>, Vec<FeedEntry>), Box<dyn std::error::Error>> {
+    let mut reader = Reader::from_str(xml);
+    let mut title = None;
+    let mut entries = Vec::new();
+    let mut entry: Option<FeedEntry> = None;
+    // Names of the open elements, innermost last
+    let mut open: Vec<String> = Vec::new();
+    let mut text = String::new();
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(element) => {
+                let name = element_name(&element);
+                if matches!(name.as_str(), "item" | "entry") {
+                    entry = Some(FeedEntry::default());
+                }
+                if let Some(entry) = entry.as_mut() {
+                    read_attributes(entry, &name, &element)?;
+                }
+                open.push(name);
+                text.clear();
+            }
+            Event::Empty(element) => {
+                if let Some(entry) = entry.as_mut() {
+                    read_attributes(entry, &element_name(&element), &element)?;
+                }
+            }
+            Event::Text(content) => text.push_str(&content.decode()?),
+            Event::CData(content) => text.push_str(&String::from_utf8_lossy(&content)),
+            Event::GeneralRef(reference) => {
+                if let Some(c) = reference.resolve_char_ref()? {
+                    text.push(c);
+                } else if let Some(resolved) = resolve_predefined_entity(&reference.decode()?) {
+                    text.push_str(resolved);
+                }
+            }
+            Event::End(_) => {
+                let name = open.pop().unwrap_or_default();
+                let value = std::mem::take(&mut text).trim().to_string();
+                match entry.as_mut() {
+                    Some(_) if matches!(name.as_str(), "item" | "entry") => entries.extend(entry.take()),
+                    Some(entry) => match name.as_str() {
+                        "title" => entry.title = value,
+                        "guid" | "id" if !value.is_empty() => entry.guid = Some(value),
+                        "link" if !value.is_empty() => entry.link = Some(value),
+                        "description" | "summary" | "content" | "content:encoded" if entry.html.len() < value.len() => {
+                            entry.html = value;
+                        }
+                        "pubDate" => entry.date = parse_date(&value),
+                        "published" | "updated" | "dc:date" if entry.date.is_none() => entry.date = parse_date(&value),
+                        _ => {}
+                    },
+                    None if name == "title" && title.is_none() => title = Some(value).filter(|title| !title.is_empty()),
+                    None => {}
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok((title, entries))
+}
+
+fn element_name(element: &BytesStart) -> String {
+    String::from_utf8_lossy(element.name().as_ref()).into_owned()
+}
+
+/// Links and images given as attributes: Atom `<link href>`, `<enclosure>` and Media RSS
+fn read_attributes(entry: &mut FeedEntry, name: &str, element: &BytesStart) -> Result<(), quick_xml::Error> {
+    let mut attributes = std::collections::HashMap::new();
+    for attribute in element.attributes().flatten() {
+        let key = String::from_utf8_lossy(attribute.key.as_ref()).into_owned();
+        attributes.insert(key, attribute.unescape_value()?.into_owned());
+    }
+    let get = |key: &str| attributes.get(key).map(String::as_str);
+    let is_image = get("type").is_some_and(|kind| kind.starts_with("image/")) || get("medium") == Some("image");
+
+    match name {
+        "link" => {
+            if let Some(href) = get("href") {
+                match get("rel").unwrap_or("alternate") {
+                    "alternate" if entry.link.is_none() => entry.link = Some(href.to_string()),
+                    "enclosure" if is_image && entry.image.is_none() => entry.image = Some(href.to_string()),
+                    _ => {}
+                }
+            }
+        }
+        "enclosure" | "media:content" if is_image && entry.image.is_none() => {
+            entry.image = get("url").map(String::from);
+        }
+        "media:thumbnail" if entry.image.is_none() => entry.image = get("url").map(String::from),
+        _ => {}
+    }
+    Ok(())
+}
+
+/// RSS dates are RFC 2822, Atom dates RFC 3339
+fn parse_date(value: &str) -> Option<u64> {
+    chrono::DateTime::parse_from_rfc2822(value.trim())
+        .ok()
+        .and_then(|date| u64::try_from(date.timestamp_millis()).ok())
+        .or_else(|| import::parse_date(value))
+}
+
+fn first_image(html: &str) -> Option<String> {
+    let img = Regex::new(r#"(?i)<img\s[^>]*?src\s*=\s*["']([^"']+)["']"#).ok()?;
+    img.captures(html).map(|captures| captures[1].replace("&amp;", "&"))
+}
+
+/// Plain text of a description, for the annotation
+fn strip_html(html: &str) -> String {
+    let tag = Regex::new(r"(?s)<[^>]*>").unwrap();
+    let text = tag.replace_all(html, " ");
+    let text = text.replace("&nbsp;", " ").replace("&amp;", "&").replace("&lt;", "<").replace("&gt;", ">");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn feeds_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(stats::data_dir()
+        .ok_or("Can't locate the data directory, set EAGLE_EYE_DATA_DIR")?
+        .join(FEEDS_FILE_NAME))
+}
+
+fn read_feeds() -> Result<Vec<Feed>, Box<dyn std::error::Error>> {
+    match fs::read(feeds_path()?) {
+        Ok(data) => Ok(serde_json::from_slice(&data)?),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(error) => Err(error.into()),
+    }
+}
+
+fn write_feeds(feeds: &[Feed]) -> Result<(), Box<dyn std::error::Error>> {
+    let path = feeds_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_vec_pretty(feeds)?)?;
+    Ok(())
+}
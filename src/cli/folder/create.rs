@@ -0,0 +1,18 @@
+use crate::cli::folder::resolve_folder;
+use crate::lib::client::EagleClient;
+use clap::ArgMatches;
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let name = matches.get_one::<String>("folder_name").unwrap();
+    let parent = match matches.get_one::<String>("parent_folder_id") {
+        Some(parent) => Some(resolve_folder(&client.folder().list().await?.data, parent)?),
+        None => None,
+    };
+
+    let created = client.folder().create(name, parent.as_deref()).await?;
+    println!("Created folder {} ({})", created.data.name, created.data.id);
+    Ok(())
+}
@@ -1,25 +1,15 @@
-use clap::builder::styling::AnsiColor;
 use crate::lib::types::*;
 use crate::cli::folder::list::ListOptions;
+use crate::cli::theme::Theme;
 
 pub fn print_folder_tree(
     folder: Option<&Child>,
     indent: &str,
     last: bool,
-    depth: usize
+    depth: usize,
+    theme: &Theme,
     ) {
 
-    let colors = [
-        (AnsiColor::Red, "31"),
-        (AnsiColor::Green, "32"),
-        (AnsiColor::Yellow, "33"),
-        (AnsiColor::Blue, "34"),
-        (AnsiColor::Magenta, "35"),
-        (AnsiColor::Cyan, "36"),
-    ];
-
-    let (_, color_code) = colors[depth % colors.len()];
-
     let (corner, vertical_line) = if last {
         ("╰── ", "    ")
     } else {
@@ -27,16 +17,8 @@ pub fn print_folder_tree(
     };
 
     if let Some(folder) = folder {
-        let formatted_name = format!(
-            "\x1b[{}m{}\x1b[0m",
-            color_code,
-            folder.name
-            );
-        let formatted_corner = format!(
-            "\x1b[{}m{}\x1b[0m",
-            color_code,
-            corner
-            );
+        let formatted_name = theme.depth(depth, &folder.name);
+        let formatted_corner = theme.depth(depth, corner);
 
         println!("{}{}{}", indent, formatted_corner, formatted_name);
 
@@ -55,30 +37,32 @@ pub fn print_folder_tree(
                     Some(child),
                 &new_indent,
                 i == child_count - 1,
-                new_depth
+                new_depth,
+                theme,
                 );
             }
         }
     } else {
-        println!("\x1b[31mNo folder was provided\x1b[0m");
+        println!("{}", theme.error("No folder was provided"));
     }
 }
 
 pub fn execute(
-    data: &Vec<Child>,
+    data: &[Child],
     options: &ListOptions,
     ) -> Result<(), Box<dyn std::error::Error>> {
     if options.recursive {
         for folder in data {
             println!("{}", folder.name);
             let initial_indent = "    ";
-            if folder.children.len() > 0 {
+            if !folder.children.is_empty() {
                 for (j, child) in folder.children.iter().enumerate() {
                     print_folder_tree(
                         Some(child),
                         initial_indent,
                         j == folder.children.len() - 1,
                         0,
+                        &options.theme,
                     );
                 }
             }
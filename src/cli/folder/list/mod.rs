@@ -1,27 +1,27 @@
+use crate::cli::folder::folder_paths;
+use crate::cli::output;
+use crate::cli::picker;
+use crate::cli::theme::Theme;
 use crate::lib::client::EagleClient;
 use clap::{Command, ArgMatches, Arg, ArgAction};
 use crate::lib::types::Child;
+use serde_json::{json, Value};
+use std::collections::HashMap;
 
 // Arguments
 pub mod args;
 
-pub struct ListCommand {
-    root: String,
-    options: ListOptions,
-}
-
+#[derive(Default)]
 pub struct ListOptions {
     recursive: bool,
-    tree: bool,
-    nesting_level: u8,
+    theme: Theme,
 }
 
 impl ListOptions {
     pub fn new() -> Self {
         ListOptions {
             recursive: false,
-            tree: false,
-            nesting_level: 0,
+            theme: Theme::default(),
         }
     }
 }
@@ -61,6 +61,8 @@ pub fn build() -> Command {
                     .help("Show folder tree recursively")
                     .action(ArgAction::SetTrue)
                     )
+
+                .arg(picker::arg())
 }
 
 pub async fn execute(
@@ -70,26 +72,36 @@ pub async fn execute(
 
     let data: Vec<Child> = client.folder().list().await?.data;
 
-    if matches.get_flag("tree") {
+    if picker::is_requested(matches) {
+        let mut entries: Vec<picker::Entry> = folder_paths(&data)
+            .into_iter()
+            .map(|(id, path)| picker::Entry { key: id, text: path })
+            .collect();
+        entries.sort_by(|a, b| a.text.cmp(&b.text));
+        return picker::pick_and_print(&entries);
+    }
+
+    // The tree is drawn for reading; asking for a format gets the rows instead
+    if matches.get_flag("tree") && !output::is_explicit(matches) {
         args::tree::execute(&data, &ListOptions {
             recursive: matches.get_flag("recursive"),
-            tree: matches.get_flag("tree"),
-            nesting_level: 0,
+            theme: Theme::from_matches(matches),
         })?;
         return Ok(());
     }
 
-    if matches.get_flag("recursive") {
+    if matches.get_flag("recursive") || matches.get_flag("tree") {
         // let nesting_level = matches.get_one::<u8>("nesting-level")?;
-        print_recursive(&data, 0);
-        return Ok(());
+        let paths = folder_paths(&data);
+        let mut rows = Vec::new();
+        push_rows(&data, &paths, true, &mut rows);
+        return output::output(&Value::Array(rows), matches);
     }
     match matches.subcommand() {
         Some(("tree", matches)) => {
             args::tree::execute(&data, &ListOptions {
                 recursive: matches.get_flag("recursive"),
-                tree: matches.get_flag("tree"),
-                nesting_level: 0,
+                theme: Theme::from_matches(matches),
             })?;
         }
         // Some(("recursive", matches)) => {
@@ -100,29 +112,29 @@ pub async fn execute(
         //     })?;
         // }
         _ => {
-            // print 'folders list' output
-            for child in data {
-                println!("{}", child.name);
-            }
+            let paths = folder_paths(&data);
+            let mut rows = Vec::new();
+            push_rows(&data, &paths, false, &mut rows);
+            output::output(&Value::Array(rows), matches)?;
         }
     }
 
     Ok(())
 }
 
-fn print_recursive(data: &Vec<Child>, mut nesting_level: u8) {
-    for child in data {
-        println!("{}", child.name);
-        if child.children.len() > 0 {
-            nesting_level += 1;
-            print_recursive(&child.children, nesting_level);
+/// Rows of `folders`, followed by those of their subfolders when `recursive`
+fn push_rows(folders: &[Child], paths: &HashMap<String, String>, recursive: bool, rows: &mut Vec<Value>) {
+    for folder in folders {
+        rows.push(json!({
+            "id": folder.id,
+            "name": folder.name,
+            "path": paths.get(&folder.id).cloned().unwrap_or_else(|| folder.name.clone()),
+            "items": folder.image_count,
+            "subfolders": folder.children.len(),
+        }));
+        if recursive {
+            push_rows(&folder.children, paths, recursive, rows);
         }
     }
 }
 
-
-// Recursive function to find duplicate folder names among siblings (having the same parent)
-fn find_duplicates(data: &Vec<Child>, duplicate_folder_names: &mut Vec<String>) {
-    todo!()
-}
-
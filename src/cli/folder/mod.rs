@@ -1,7 +1,11 @@
+pub mod create;
 pub mod list;
 pub mod rename;
+pub mod update;
 use crate::lib::client::EagleClient;
-use clap::{Arg, ArgMatches, ArgAction, Command};
+use crate::lib::types::Child;
+use clap::{Arg, ArgMatches, Command};
+use std::collections::HashMap;
 
 
 pub fn build() -> Command {
@@ -23,9 +27,8 @@ pub fn build() -> Command {
             .arg(
                 Arg::new("parent_folder_id")
                 .value_name("PARENT_FOLDER_ID")
-                .help("Specify parent folder")
+                .help("Specify parent folder, by id, path (Brand/Logos) or unique name")
                 .required(false)
-                .default_value("")
                 )
             )
 
@@ -35,7 +38,7 @@ pub fn build() -> Command {
                 .arg(
                     Arg::new("folder_id")
                     .value_name("FOLDER_ID")
-                    .help("Specify folder id")
+                    .help("Specify folder, by id, path (Brand/Logos) or unique name")
                     .required(true)
                     // Type: u64
                     )
@@ -56,7 +59,7 @@ pub fn build() -> Command {
                 .arg(
                     Arg::new("folder_id")
                     .value_name("FOLDER_ID")
-                    .help("Specify folder id")
+                    .help("Specify folder, by id, path (Brand/Logos) or unique name")
                     .required(true)
                     // Type: u64
                     )
@@ -66,7 +69,6 @@ pub fn build() -> Command {
                     .value_name("NEW_NAME")
                     .help("Specify new name")
                     .required(false)
-                    .default_value("")
                     // Type: String
                     )
 
@@ -75,7 +77,6 @@ pub fn build() -> Command {
                     .value_name("NEW_DESCRIPTION")
                     .help("Specify new description")
                     .required(false)
-                    .default_value("")
                     // Type: String
                     )
 
@@ -84,8 +85,7 @@ pub fn build() -> Command {
                     .value_name("NEW_COLOR")
                     .help("Specify new color")
                     .required(false)
-                    .default_value("")
-                    // Type: String
+                    .value_parser(update::COLORS)
                     )
                 )
 
@@ -102,13 +102,13 @@ pub async fn execute(
             list::execute(client, matches).await?;
         }
         Some(("create", matches)) => {
-            todo!();
+            create::execute(client, matches).await?;
         }
         Some(("rename", matches)) => {
-            todo!();
+            rename::execute(client, matches).await?;
         }
         Some(("update", matches)) => {
-            todo!();
+            update::execute(client, matches).await?;
         }
         _ => {}
     }
@@ -116,3 +116,51 @@ pub async fn execute(
 
     Ok(())
     }
+
+/// Map folder ids to their slash separated path from the root, e.g. `Brand/Logos`
+pub fn folder_paths(folders: &[Child]) -> HashMap<String, String> {
+    folder_chains(folders)
+        .into_iter()
+        .map(|(id, chain)| (id, chain.join("/")))
+        .collect()
+}
+
+/// Map folder ids to the names of the folders from the root down to them, which unlike
+/// [`folder_paths`] stays unambiguous when a name holds a `/`
+pub fn folder_chains(folders: &[Child]) -> HashMap<String, Vec<String>> {
+    let mut chains = HashMap::new();
+    let mut stack: Vec<(&Child, Vec<String>)> = folders
+        .iter()
+        .map(|folder| (folder, vec![folder.name.clone()]))
+        .collect();
+    while let Some((folder, chain)) = stack.pop() {
+        for child in &folder.children {
+            let mut child_chain = chain.clone();
+            child_chain.push(child.name.clone());
+            stack.push((child, child_chain));
+        }
+        chains.insert(folder.id.clone(), chain);
+    }
+    chains
+}
+
+/// Find a folder by id, by slash separated path (`Brand/Logos`), or by a name no other folder has.
+pub fn resolve_folder(folders: &[Child], value: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let paths = folder_paths(folders);
+    if paths.contains_key(value) {
+        return Ok(value.to_string());
+    }
+    if let Some((id, _)) = paths.iter().find(|(_, path)| path.as_str() == value) {
+        return Ok(id.clone());
+    }
+    let named: Vec<&String> = paths
+        .iter()
+        .filter(|(_, path)| path.rsplit('/').next() == Some(value))
+        .map(|(id, _)| id)
+        .collect();
+    match named.as_slice() {
+        [id] => Ok((*id).clone()),
+        [] => Err(format!("No folder matches {}", value).into()),
+        _ => Err(format!("Several folders are named {}; use the folder path or id", value).into()),
+    }
+}
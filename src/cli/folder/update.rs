@@ -0,0 +1,24 @@
+use crate::cli::folder::resolve_folder;
+use crate::lib::client::EagleClient;
+use clap::ArgMatches;
+
+/// Colors Eagle can mark a folder with
+pub const COLORS: [&str; 8] = ["red", "orange", "yellow", "green", "aqua", "blue", "purple", "pink"];
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let folder = matches.get_one::<String>("folder_id").unwrap();
+    // An empty value skips a positional, e.g. `update ID "" "New description"`
+    let value = |id: &str| matches.get_one::<String>(id).map(String::as_str).filter(|value| !value.is_empty());
+    let (new_name, new_description, new_color) = (value("new_name"), value("new_description"), value("new_color"));
+    if new_name.is_none() && new_description.is_none() && new_color.is_none() {
+        return Err("Nothing to update; give a new name, description, or color".into());
+    }
+    let id = resolve_folder(&client.folder().list().await?.data, folder)?;
+
+    client.folder().update(&id, new_name, new_description, new_color).await?;
+    println!("Updated folder {}", id);
+    Ok(())
+}
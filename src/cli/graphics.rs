@@ -0,0 +1,189 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use image::imageops::FilterType;
+use image::{DynamicImage, RgbImage};
+use std::fmt::Write;
+use std::io::Cursor;
+use std::str::FromStr;
+
+/// Approximate width of a terminal cell in pixels, used to size sixel images
+const CELL_WIDTH_PX: u32 = 8;
+
+/// How images are drawn in the terminal
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Protocol {
+    Kitty,
+    Iterm,
+    Sixel,
+    /// Half-block characters in 24-bit color, for terminals without a graphics protocol
+    Blocks,
+}
+
+impl FromStr for Protocol {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "kitty" => Ok(Protocol::Kitty),
+            "iterm" | "iterm2" => Ok(Protocol::Iterm),
+            "sixel" => Ok(Protocol::Sixel),
+            "blocks" => Ok(Protocol::Blocks),
+            _ => Err(format!("unknown graphics protocol: {} (use kitty, iterm, sixel, or blocks)", value)),
+        }
+    }
+}
+
+impl Protocol {
+    pub const VALUES: [&'static str; 4] = ["kitty", "iterm", "sixel", "blocks"];
+
+    /// Guess the protocol the terminal speaks from its environment variables.
+    pub fn detect() -> Protocol {
+        let env = |name: &str| std::env::var(name).unwrap_or_default();
+        let term = env("TERM");
+        let term_program = env("TERM_PROGRAM");
+
+        if std::env::var_os("KITTY_WINDOW_ID").is_some()
+            || term == "xterm-kitty"
+            || term == "xterm-ghostty"
+            || term_program == "ghostty"
+        {
+            Protocol::Kitty
+        } else if term_program == "iTerm.app" || term_program == "WezTerm" || std::env::var_os("ITERM_SESSION_ID").is_some() {
+            Protocol::Iterm
+        } else if term.contains("sixel") || term == "foot" || term_program == "mlterm" {
+            Protocol::Sixel
+        } else {
+            Protocol::Blocks
+        }
+    }
+}
+
+/// Render an image `columns` terminal cells wide, as bytes to write to stdout.
+pub fn render(image: &DynamicImage, protocol: Protocol, columns: u32) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let columns = columns.max(1);
+    Ok(match protocol {
+        Protocol::Kitty => kitty(image, columns)?.into_bytes(),
+        Protocol::Iterm => iterm(image, columns)?.into_bytes(),
+        Protocol::Sixel => {
+            let width = columns * CELL_WIDTH_PX;
+            sixel(&resize(image, width).to_rgb8()).into_bytes()
+        }
+        Protocol::Blocks => blocks(&resize(image, columns).to_rgb8()).into_bytes(),
+    })
+}
+
+fn resize(image: &DynamicImage, width: u32) -> DynamicImage {
+    let height = (image.height() as f64 * width as f64 / image.width().max(1) as f64).round().max(1.0) as u32;
+    image.resize_exact(width, height, FilterType::Triangle)
+}
+
+/// The image encoded as PNG, in base64
+pub fn png_base64(image: &DynamicImage) -> Result<String, Box<dyn std::error::Error>> {
+    let mut png = Vec::new();
+    image.write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)?;
+    Ok(BASE64.encode(png))
+}
+
+/// Kitty graphics protocol: PNG data sent in 4096 byte chunks
+fn kitty(image: &DynamicImage, columns: u32) -> Result<String, Box<dyn std::error::Error>> {
+    let data = png_base64(image)?;
+    let chunks: Vec<&[u8]> = data.as_bytes().chunks(4096).collect();
+    let mut out = String::new();
+    for (index, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(index + 1 < chunks.len());
+        let chunk = std::str::from_utf8(chunk)?;
+        if index == 0 {
+            write!(out, "\x1b_Ga=T,f=100,c={},m={};{}\x1b\\", columns, more, chunk)?;
+        } else {
+            write!(out, "\x1b_Gm={};{}\x1b\\", more, chunk)?;
+        }
+    }
+    out.push('\n');
+    Ok(out)
+}
+
+/// iTerm2 inline image protocol
+fn iterm(image: &DynamicImage, columns: u32) -> Result<String, Box<dyn std::error::Error>> {
+    let data = png_base64(image)?;
+    Ok(format!(
+        "\x1b]1337;File=inline=1;width={};preserveAspectRatio=1:{}\x07\n",
+        columns, data
+    ))
+}
+
+/// Sixel graphics quantized to a 6x6x6 color cube
+fn sixel(image: &RgbImage) -> String {
+    let (width, height) = image.dimensions();
+    let level = |value: u8| (value as u32 * 5 + 127) / 255;
+    let index_of = |x: u32, y: u32| {
+        let [r, g, b] = image.get_pixel(x, y).0;
+        (level(r) * 36 + level(g) * 6 + level(b)) as usize
+    };
+
+    let mut out = format!("\x1bPq\"1;1;{};{}", width, height);
+    for index in 0..216u32 {
+        let (r, g, b) = (index / 36, index / 6 % 6, index % 6);
+        write!(out, "#{};2;{};{};{}", index, r * 20, g * 20, b * 20).unwrap();
+    }
+
+    for band in (0..height).step_by(6) {
+        let rows = (height - band).min(6);
+        let mut used = [false; 216];
+        for y in band..band + rows {
+            for x in 0..width {
+                used[index_of(x, y)] = true;
+            }
+        }
+
+        for color in (0..216).filter(|&color| used[color]) {
+            write!(out, "#{}", color).unwrap();
+            let sixels: Vec<u8> = (0..width)
+                .map(|x| {
+                    let bits = (0..rows)
+                        .filter(|&row| index_of(x, band + row) == color)
+                        .fold(0u8, |bits, row| bits | (1 << row));
+                    63 + bits
+                })
+                .collect();
+            push_run_length(&mut out, &sixels);
+            out.push('$');
+        }
+        out.push('-');
+    }
+    out.push_str("\x1b\\\n");
+    out
+}
+
+/// Append sixel characters, collapsing runs with `!<count><char>`
+fn push_run_length(out: &mut String, sixels: &[u8]) {
+    let mut index = 0;
+    while index < sixels.len() {
+        let sixel = sixels[index];
+        let run = sixels[index..].iter().take_while(|&&other| other == sixel).count();
+        if run > 3 {
+            write!(out, "!{}{}", run, sixel as char).unwrap();
+        } else {
+            out.extend(std::iter::repeat_n(sixel as char, run));
+        }
+        index += run;
+    }
+}
+
+/// Upper half blocks: the foreground paints the top pixel, the background the bottom one
+fn blocks(image: &RgbImage) -> String {
+    let (width, height) = image.dimensions();
+    let mut out = String::new();
+    for y in (0..height).step_by(2) {
+        for x in 0..width {
+            let [r, g, b] = image.get_pixel(x, y).0;
+            write!(out, "\x1b[38;2;{};{};{}m", r, g, b).unwrap();
+            if y + 1 < height {
+                let [r, g, b] = image.get_pixel(x, y + 1).0;
+                write!(out, "\x1b[48;2;{};{};{}m", r, g, b).unwrap();
+            }
+            out.push('▀');
+        }
+        out.push_str("\x1b[0m\n");
+    }
+    out
+}
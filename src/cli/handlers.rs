@@ -0,0 +1,212 @@
+use crate::cli::folder::resolve_folder;
+use crate::cli::item::list::{self, expr::Expr};
+use crate::lib::client::EagleClient;
+use crate::lib::types::{AddBookmarkParams, AddFromPathParams, AddFromUrlParams, UpdateItemParams};
+use hyper::{Body, Method, StatusCode};
+use serde_json::{json, Value};
+
+/// Parameters of `item list` passed on to `/api/item/list`
+const LIST_PARAMS: &[&str] = &["limit", "offset", "orderBy", "keyword", "ext", "tags", "folders"];
+
+/// A failed operation, with the HTTP status `serve` answers it with
+#[derive(Debug)]
+pub struct HandlerError(pub StatusCode, pub String);
+
+impl HandlerError {
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        HandlerError(StatusCode::BAD_REQUEST, message.into())
+    }
+
+    fn upstream(error: Box<dyn std::error::Error>) -> Self {
+        HandlerError(StatusCode::BAD_GATEWAY, format!("Eagle: {}", error))
+    }
+}
+
+/// Result of an operation shared by `serve` and `rpc`; each takes its parameters as a JSON object
+pub type HandlerResult = Result<Value, HandlerError>;
+
+/// A parameter as text, whether it was sent as a string or a number
+fn param(params: &Value, name: &str) -> Option<String> {
+    match &params[name] {
+        Value::String(value) => Some(value.clone()),
+        Value::Number(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+fn strings(params: &Value, name: &str) -> Result<Option<Vec<String>>, HandlerError> {
+    let invalid = || HandlerError::bad_request(format!("{} must be a list of strings", name));
+    match &params[name] {
+        Value::Null => Ok(None),
+        Value::Array(values) => values
+            .iter()
+            .map(|value| value.as_str().map(String::from))
+            .collect::<Option<Vec<_>>>()
+            .map(Some)
+            .ok_or_else(invalid),
+        _ => Err(invalid()),
+    }
+}
+
+/// The `data` of a GET endpoint of Eagle, as Eagle returns it
+pub async fn passthrough(client: &EagleClient, resource: &str, action: &str, query: Option<String>) -> HandlerResult {
+    let uri = client.endpoint(resource, action, query).map_err(HandlerError::upstream)?;
+    let result: Value = client
+        .execute_request(uri, Method::GET, Body::empty())
+        .await
+        .map_err(HandlerError::upstream)?;
+    match result["status"].as_str() {
+        Some("success") => Ok(result["data"].clone()),
+        _ => Err(HandlerError(StatusCode::BAD_GATEWAY, format!("Eagle: {}", result))),
+    }
+}
+
+/// One page of `/api/item/list`
+pub async fn list_items(client: &EagleClient, params: &Value) -> HandlerResult {
+    let query: Vec<String> = LIST_PARAMS
+        .iter()
+        .filter_map(|name| param(params, name).map(|value| format!("{}={}", name, encode(&value))))
+        .collect();
+    passthrough(client, "item", "list", Some(query.join("&"))).await
+}
+
+pub async fn item_info(client: &EagleClient, params: &Value) -> HandlerResult {
+    let id = param(params, "id").ok_or_else(|| HandlerError::bad_request("id is required"))?;
+    passthrough(client, "item", "info", Some(format!("id={}", encode(&id)))).await
+}
+
+pub async fn folders(client: &EagleClient) -> HandlerResult {
+    passthrough(client, "folder", "list", None).await
+}
+
+/// Items outside the trash whose name, annotation, URL or tags contain `q`, and which
+/// satisfy the `where` expression, up to `limit`
+pub async fn search(client: &EagleClient, params: &Value) -> HandlerResult {
+    let text = param(params, "q").map(|q| q.to_lowercase()).unwrap_or_default();
+    let expr = match param(params, "where") {
+        Some(source) => Some(source.parse::<Expr>().map_err(HandlerError::bad_request)?),
+        None => None,
+    };
+    let limit = match param(params, "limit") {
+        Some(limit) => limit.parse::<usize>().map_err(|_| HandlerError::bad_request("limit must be a number"))?,
+        None => usize::MAX,
+    };
+
+    let items = list::all_items(client).await.map_err(HandlerError::upstream)?;
+    let found: Vec<Value> = items
+        .into_iter()
+        .filter(|item| !item.is_deleted)
+        .filter(|item| {
+            text.is_empty()
+                || [&item.name, &item.annotation, &item.url]
+                    .into_iter()
+                    .chain(&item.tags)
+                    .any(|field| field.to_lowercase().contains(&text))
+        })
+        .map(|item| serde_json::to_value(item).unwrap_or_default())
+        .filter(|item| expr.as_ref().is_none_or(|expr| expr.matches(item)))
+        .take(limit)
+        .collect();
+    Ok(Value::Array(found))
+}
+
+/// Update the fields given in `params` on item `id`
+pub async fn update_item(client: &EagleClient, params: &Value) -> HandlerResult {
+    let id = param(params, "id").ok_or_else(|| HandlerError::bad_request("id is required"))?;
+    let string = |field: &str| params[field].as_str().map(String::from);
+    let star = match &params["star"] {
+        Value::Null => None,
+        value => Some(
+            value
+                .as_u64()
+                .filter(|star| *star <= 5)
+                .ok_or_else(|| HandlerError::bad_request("star must be between 0 and 5"))? as u8,
+        ),
+    };
+    let update = UpdateItemParams {
+        id,
+        name: string("name"),
+        tags: strings(params, "tags")?,
+        folders: strings(params, "folders")?,
+        annotation: string("annotation"),
+        url: string("url"),
+        star,
+    };
+    let result = client.item().update(&update).await.map_err(HandlerError::upstream)?;
+    Ok(json!({ "id": result.data.id }))
+}
+
+/// Add a local file (`path`), an image URL (`url`) or, with `bookmark: true`, a bookmark
+pub async fn import(client: &EagleClient, params: &Value) -> HandlerResult {
+    let string = |field: &str| params[field].as_str().map(String::from);
+    let tags = strings(params, "tags")?;
+    let folder_id = match params["folder"].as_str() {
+        Some(folder) => {
+            let folders = client.folder().list().await.map_err(HandlerError::upstream)?.data;
+            Some(resolve_folder(&folders, folder).map_err(|e| HandlerError::bad_request(e.to_string()))?)
+        }
+        None => None,
+    };
+
+    if let Some(path) = string("path") {
+        let name = string("name").unwrap_or_else(|| {
+            std::path::Path::new(&path)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        });
+        let add = AddFromPathParams {
+            path,
+            name,
+            website: string("website"),
+            annotation: string("annotation"),
+            tags,
+            folder_id,
+        };
+        client.item().add_from_path(&add).await.map_err(HandlerError::upstream)?;
+        return Ok(json!({ "imported": add.path }));
+    }
+
+    let url = string("url").ok_or_else(|| HandlerError::bad_request("Send a url or a path"))?;
+    let name = string("name").unwrap_or_else(|| url.clone());
+    if params["bookmark"].as_bool().unwrap_or(false) {
+        let add = AddBookmarkParams {
+            url,
+            name,
+            tags,
+            folder_id,
+            ..Default::default()
+        };
+        client.item().add_bookmark(&add).await.map_err(HandlerError::upstream)?;
+        return Ok(json!({ "imported": add.url }));
+    }
+    let add = AddFromUrlParams {
+        url,
+        name,
+        website: string("website"),
+        annotation: string("annotation"),
+        tags,
+        folder_id,
+        ..Default::default()
+    };
+    client.item().add_from_url(&add).await.map_err(HandlerError::upstream)?;
+    Ok(json!({ "imported": add.url }))
+}
+
+/// Move the items in `ids` to the trash
+pub async fn trash(client: &EagleClient, params: &Value) -> HandlerResult {
+    let ids = strings(params, "ids")?.ok_or_else(|| HandlerError::bad_request("ids is required"))?;
+    client.item().move_to_trash(&ids).await.map_err(HandlerError::upstream)?;
+    Ok(json!({ "trashed": ids.len() }))
+}
+
+/// Percent-encode a query component
+pub fn encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b',' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
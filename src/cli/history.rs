@@ -0,0 +1,382 @@
+use crate::cli::datetime::{self, DateBound};
+use crate::cli::exit::Partial;
+use crate::cli::output::{self, OutputFormat, OutputOptions};
+use crate::cli::pager;
+use crate::cli::stats::data_dir;
+use crate::lib::client::EagleClient;
+use clap::parser::ValueSource;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+
+// Nothing is logged until `history enable` creates the marker file, or EAGLE_EYE_AUDIT_LOG
+// names a log file. Teams sharing a library point everyone's log to the same file on a
+// shared drive. The marker holds the log path, empty for the default in the data directory.
+const ENABLED_FILE_NAME: &str = "audit.enabled";
+const LOG_FILE_NAME: &str = "audit.jsonl";
+const LOG_ENV: &str = "EAGLE_EYE_AUDIT_LOG";
+
+/// Names of arguments, headers, query parameters and JSON fields whose values never go into
+/// the log
+const SECRET_WORDS: &[&str] = &["token", "secret", "password", "apikey", "api_key", "api-key", "cookie", "authorization"];
+
+/// Arguments logged as masked whole: cookie jars hold session cookies
+const SECRET_ARGS: &[&str] = &["cookie_jar"];
+
+/// Commands that run until stopped; their entries don't list what they changed
+const LONG_RUNNING: &[&str] = &["events", "rpc", "serve", "tui", "watch"];
+
+/// Flags that keep the command they're given to running until stopped
+const WATCH_FLAGS: &[&str] = &["watch", "watch_clipboard"];
+
+/// Logged in place of a secret
+const MASK: &str = "***";
+
+/// Columns of the table when no `--fields` are given
+const TABLE_FIELDS: &[&str] = &["time", "user", "command", "changed", "succeeded", "failed", "duration_ms"];
+
+/// One command run against the library
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Entry {
+    /// Start time in epoch milliseconds
+    pub time: i64,
+    pub user: String,
+    pub host: String,
+    /// Subcommand path, e.g. `item tag`
+    pub command: String,
+    /// Arguments given on the command line as `name=value`, secrets masked
+    pub args: Vec<String>,
+    /// Ids of changed items and folders, and the files and URLs imported; empty for commands
+    /// that run until stopped
+    pub changed: Vec<String>,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub duration_ms: u64,
+    pub success: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Whether the invoked command runs until stopped, so the client mustn't keep its changes
+pub fn is_long_running(matches: &ArgMatches) -> bool {
+    if matches.subcommand_name().is_some_and(|name| LONG_RUNNING.contains(&name)) {
+        return true;
+    }
+    let mut leaf = matches;
+    while let Some((_, sub_matches)) = leaf.subcommand() {
+        leaf = sub_matches;
+    }
+    WATCH_FLAGS
+        .iter()
+        .any(|id| leaf.try_get_one::<bool>(id).ok().flatten().copied().unwrap_or(false))
+}
+
+/// The audit log file, `None` while logging is off
+pub fn log_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os(LOG_ENV).filter(|path| !path.is_empty()) {
+        return Some(PathBuf::from(path));
+    }
+    let dir = data_dir()?;
+    let marker = fs::read_to_string(dir.join(ENABLED_FILE_NAME)).ok()?;
+    match marker.trim() {
+        "" => Some(dir.join(LOG_FILE_NAME)),
+        path => Some(PathBuf::from(path)),
+    }
+}
+
+/// Append an entry for this invocation when the audit log is on.
+pub fn record(
+    matches: &ArgMatches,
+    client: &EagleClient,
+    duration: Duration,
+    result: &Result<(), Box<dyn std::error::Error>>,
+) -> std::io::Result<()> {
+    let Some(path) = log_path() else {
+        return Ok(());
+    };
+
+    let mut command = Vec::new();
+    let mut args = Vec::new();
+    let mut current = matches;
+    loop {
+        for id in current.ids() {
+            if current.value_source(id.as_str()) != Some(ValueSource::CommandLine) {
+                continue;
+            }
+            let values: Vec<String> = current
+                .get_raw(id.as_str())
+                .into_iter()
+                .flatten()
+                .map(|value| masked(id.as_str(), &value.to_string_lossy()))
+                .collect();
+            args.push(format!("{}={}", id, values.join(",")));
+        }
+        match current.subcommand() {
+            Some((name, sub_matches)) => {
+                command.push(name);
+                current = sub_matches;
+            }
+            None => break,
+        }
+    }
+
+    let changed = client.changed();
+    let (succeeded, failed) = match result {
+        Ok(()) => (changed.len(), 0),
+        Err(error) => match error.downcast_ref::<Partial>() {
+            Some(partial) => (partial.succeeded, partial.failed),
+            None => (changed.len(), 0),
+        },
+    };
+    let now = chrono::Utc::now().timestamp_millis();
+    let entry = Entry {
+        time: now - duration.as_millis() as i64,
+        user: ["USER", "LOGNAME", "USERNAME"]
+            .iter()
+            .find_map(|name| std::env::var(name).ok())
+            .unwrap_or_default(),
+        host: host_name(),
+        command: command.join(" "),
+        args,
+        changed,
+        succeeded,
+        failed,
+        duration_ms: duration.as_millis() as u64,
+        success: result.is_ok(),
+        error: result.as_ref().err().map(|error| error.to_string()),
+    };
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(&entry)?)
+}
+
+fn is_secret(name: &str) -> bool {
+    let name = name.to_lowercase();
+    SECRET_WORDS.iter().any(|word| name.contains(word))
+}
+
+/// A value of argument `id` as it goes into the log, with secrets masked: whole for secret
+/// arguments, and by name inside `-H NAME: VALUE` headers, `K=V` pairs and JSON bodies
+fn masked(id: &str, value: &str) -> String {
+    if is_secret(id) || SECRET_ARGS.contains(&id) {
+        return MASK.to_string();
+    }
+    if id == "body" && value != "-" {
+        // A body that isn't JSON can't be searched for secrets
+        return match serde_json::from_str::<Value>(value) {
+            Ok(body) => mask_fields(body).to_string(),
+            Err(_) => MASK.to_string(),
+        };
+    }
+    match value.split_once(':').filter(|_| id == "header").or_else(|| value.split_once('=')) {
+        Some((name, _)) if is_secret(name) => {
+            let separator = if id == "header" { ": " } else { "=" };
+            format!("{}{}{}", name.trim(), separator, MASK)
+        }
+        _ => value.to_string(),
+    }
+}
+
+/// `value` with the fields named like secrets masked, at any depth
+fn mask_fields(value: Value) -> Value {
+    match value {
+        Value::Object(object) => Value::Object(
+            object
+                .into_iter()
+                .map(|(key, value)| match is_secret(&key) {
+                    true => (key, json!(MASK)),
+                    false => (key, mask_fields(value)),
+                })
+                .collect(),
+        ),
+        Value::Array(values) => Value::Array(values.into_iter().map(mask_fields).collect()),
+        value => value,
+    }
+}
+
+fn host_name() -> String {
+    ["HOSTNAME", "COMPUTERNAME"]
+        .iter()
+        .find_map(|name| std::env::var(name).ok())
+        .or_else(|| fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .unwrap_or_default()
+}
+
+fn read_entries(path: &PathBuf) -> Result<Vec<Entry>, Box<dyn std::error::Error>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    // Skip lines a crashed run may have left half-written
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+pub fn build() -> Command {
+    Command::new("history")
+        .about("Opt-in audit log of the commands run: who changed what, and how it went")
+        .args_conflicts_with_subcommands(true)
+        .subcommand(
+            Command::new("enable")
+                .about("Start logging every command with its arguments, changed ids, and result")
+                .arg(
+                    Arg::new("log")
+                        .long("log")
+                        .value_name("FILE")
+                        .help("Log to FILE, e.g. on a drive shared by everyone using the library")
+                        .num_args(1),
+                ),
+        )
+        .subcommand(Command::new("disable").about("Stop logging (keeps what was logged)"))
+        .subcommand(
+            Command::new("status")
+                .about("Show whether commands are logged and where")
+                .args(output::args()),
+        )
+        .arg(
+            Arg::new("user")
+                .long("user")
+                .value_name("USER")
+                .help("Only commands run by USER")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("command")
+                .long("command")
+                .value_name("COMMAND")
+                .help("Only this command and its subcommands, e.g. \"item\" or \"item tag\"")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("changed")
+                .long("changed")
+                .value_name("ID")
+                .help("Only commands that changed this item or folder id, file, or URL")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("since")
+                .long("since")
+                .value_name("DATE")
+                .help("Only commands run at or after DATE, e.g. 2024-01-01 or 7d (seven days ago)")
+                .num_args(1)
+                .value_parser(|value: &str| value.parse::<DateBound>()),
+        )
+        .arg(
+            Arg::new("failed")
+                .long("failed")
+                .help("Only commands that failed, fully or in part")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("limit")
+                .long("limit")
+                .value_name("N")
+                .help("Show the N most recent commands")
+                .num_args(1)
+                .default_value("50")
+                .value_parser(clap::value_parser!(usize)),
+        )
+        .args(output::args())
+}
+
+pub async fn execute(matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = data_dir().ok_or("Can't locate the data directory, set EAGLE_EYE_DATA_DIR")?;
+
+    match matches.subcommand() {
+        Some(("enable", enable_matches)) => {
+            let log = enable_matches.get_one::<String>("log").map(|log| {
+                std::path::absolute(log).unwrap_or_else(|_| PathBuf::from(log))
+            });
+            fs::create_dir_all(&dir)?;
+            let marker = log.as_ref().map(|log| log.display().to_string()).unwrap_or_default();
+            fs::write(dir.join(ENABLED_FILE_NAME), marker)?;
+            let path = log.unwrap_or_else(|| dir.join(LOG_FILE_NAME));
+            println!("Logging commands to {}", path.display());
+        }
+        Some(("disable", _)) => {
+            let enabled_file = dir.join(ENABLED_FILE_NAME);
+            if enabled_file.exists() {
+                fs::remove_file(enabled_file)?;
+            }
+            println!("Audit log disabled");
+            if std::env::var_os(LOG_ENV).is_some_and(|path| !path.is_empty()) {
+                println!("{} is still set and keeps logging on", LOG_ENV);
+            }
+        }
+        Some(("status", status_matches)) => {
+            let path = log_path();
+            let entries = path.as_ref().map(|path| read_entries(path)).transpose()?.map(|entries| entries.len());
+            if output::is_explicit(status_matches) {
+                let status = json!({ "enabled": path.is_some(), "entries": entries, "log": path });
+                return output::output(&status, status_matches);
+            }
+            match path {
+                Some(path) => println!("Audit log enabled ({} entries in {})", entries.unwrap_or(0), path.display()),
+                None => println!("Audit log disabled (run `history enable`)"),
+            }
+        }
+        _ => print_history(matches)?,
+    }
+    Ok(())
+}
+
+fn print_history(matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let path = log_path().unwrap_or_else(|| data_dir().unwrap_or_default().join(LOG_FILE_NAME));
+    let tz = datetime::from_matches(matches);
+    let since = matches.get_one::<DateBound>("since").map(|since| since.resolve(&tz)).transpose()?;
+    let user = matches.get_one::<String>("user");
+    let command = matches.get_one::<String>("command");
+    let changed = matches.get_one::<String>("changed");
+    let failed = matches.get_flag("failed");
+
+    let entries: Vec<Entry> = read_entries(&path)?
+        .into_iter()
+        .filter(|entry| user.is_none_or(|user| &entry.user == user))
+        .filter(|entry| {
+            command.is_none_or(|command| {
+                entry.command == *command || entry.command.starts_with(&format!("{} ", command))
+            })
+        })
+        .filter(|entry| changed.is_none_or(|changed| entry.changed.contains(changed)))
+        .filter(|entry| since.is_none_or(|since| entry.time >= since))
+        .filter(|entry| !failed || !entry.success || entry.failed > 0)
+        .collect();
+    if entries.is_empty() {
+        let hint = if log_path().is_some() { "" } else { " (run `history enable`)" };
+        eprintln!("No commands logged{}", hint);
+        return Ok(());
+    }
+
+    let mut options = OutputOptions::from_matches(matches);
+    let table = matches!(options.format, OutputFormat::Table);
+    if table && options.fields.is_empty() {
+        options.fields = TABLE_FIELDS.iter().map(|field| field.to_string()).collect();
+    }
+    let limit = *matches.get_one::<usize>("limit").unwrap();
+    let rows: Vec<Value> = entries[entries.len().saturating_sub(limit)..]
+        .iter()
+        .map(|entry| {
+            let mut row = serde_json::to_value(entry).unwrap_or_default();
+            row["time"] = json!(tz.format_millis(entry.time));
+            // A count keeps the table readable; --json has the ids
+            if table {
+                row["changed"] = json!(entry.changed.len());
+            }
+            row
+        })
+        .collect();
+    let rows = Value::Array(rows);
+    pager::print(&output::render(&rows, &options)?, matches)?;
+    output::summary(matches, output::count(&rows).0, None);
+    Ok(())
+}
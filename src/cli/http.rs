@@ -0,0 +1,231 @@
+use hyper::client::HttpConnector;
+use hyper::header::{AUTHORIZATION, CONTENT_DISPOSITION, CONTENT_TYPE, COOKIE, LOCATION, PROXY_AUTHORIZATION};
+use hyper::{Body, Client, Method, Request, StatusCode, Uri};
+use hyper_rustls::HttpsConnector;
+use serde_json::Value;
+use std::path::Path;
+
+/// Redirects followed before giving up
+const MAX_REDIRECTS: usize = 10;
+
+/// A response body fetched with `HttpClient::get`
+pub struct Download {
+    pub bytes: Vec<u8>,
+    /// URL the body came from, after redirects
+    pub url: Uri,
+    pub content_type: Option<String>,
+    /// File name suggested by `Content-Disposition`
+    pub file_name: Option<String>,
+}
+
+/// Client for services other than Eagle, over http or https.
+pub struct HttpClient {
+    client: Client<HttpsConnector<HttpConnector>>,
+}
+
+impl HttpClient {
+    pub fn new() -> Self {
+        let connector = hyper_rustls::HttpsConnectorBuilder::new()
+            .with_webpki_roots()
+            .https_or_http()
+            .enable_http1()
+            .build();
+        HttpClient {
+            client: Client::builder().build(connector),
+        }
+    }
+
+    /// POST `body` as JSON to `url` and decode the JSON response.
+    pub async fn post_json(&self, url: &str, body: &Value) -> Result<Value, Box<dyn std::error::Error>> {
+        let uri: Uri = url.parse()?;
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .header("accept", "application/json")
+            .body(Body::from(serde_json::to_vec(body)?))?;
+
+        let response = self.client.request(request).await?;
+        let status = response.status();
+        let bytes = hyper::body::to_bytes(response.into_body()).await?;
+        if !status.is_success() {
+            let text = String::from_utf8_lossy(&bytes);
+            return Err(format!("{} returned {}: {}", url, status, text.trim()).into());
+        }
+        serde_json::from_slice(&bytes).map_err(|e| format!("{} returned invalid JSON: {}", url, e).into())
+    }
+
+    /// POST `body` to `url` with `headers` and return the response status; the body is discarded.
+    pub async fn post(
+        &self,
+        url: &str,
+        headers: &[(String, String)],
+        body: Vec<u8>,
+    ) -> Result<StatusCode, Box<dyn std::error::Error>> {
+        let mut request = Request::builder().method(Method::POST).uri(url.parse::<Uri>()?);
+        for (name, value) in headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        let response = self.client.request(request.body(Body::from(body))?).await?;
+        let status = response.status();
+        hyper::body::to_bytes(response.into_body()).await?;
+        Ok(status)
+    }
+
+    /// GET `url` with extra `headers` and the cookies of `cookie_jar`, following redirects.
+    ///
+    /// Cookies are matched against every URL redirected to. Credentials in `headers` are only
+    /// sent to the origin of `url`, and redirects from https to http are refused.
+    pub async fn get(
+        &self,
+        url: &str,
+        headers: &[(String, String)],
+        cookie_jar: Option<&Path>,
+    ) -> Result<Download, Box<dyn std::error::Error>> {
+        let mut uri: Uri = url.parse()?;
+        let first_origin = origin(&uri);
+        for _ in 0..=MAX_REDIRECTS {
+            let cross_origin = origin(&uri) != first_origin;
+            let mut request = Request::builder().method(Method::GET).uri(uri.clone());
+            for (name, value) in headers {
+                if cross_origin && is_credential(name) {
+                    continue;
+                }
+                request = request.header(name.as_str(), value.as_str());
+            }
+            if let Some(jar) = cookie_jar {
+                let cookie = cookie_header(jar, &uri)
+                    .map_err(|e| format!("Failed to read cookie jar {}: {}", jar.display(), e))?;
+                if let Some(cookie) = cookie {
+                    request = request.header(COOKIE, cookie);
+                }
+            }
+            let response = self.client.request(request.body(Body::empty())?).await?;
+            let status = response.status();
+
+            if status.is_redirection() {
+                let location = response
+                    .headers()
+                    .get(LOCATION)
+                    .and_then(|value| value.to_str().ok())
+                    .ok_or_else(|| format!("{} redirected without a location", uri))?;
+                let next = resolve(&uri, location)?;
+                if uri.scheme_str() == Some("https") && next.scheme_str() != Some("https") {
+                    return Err(format!("{} redirected to {}, refusing to leave https", uri, next).into());
+                }
+                uri = next;
+                continue;
+            }
+            if !status.is_success() {
+                return Err(format!("{} returned {}", uri, status).into());
+            }
+
+            let header = |name| {
+                response
+                    .headers()
+                    .get(name)
+                    .and_then(|value: &hyper::header::HeaderValue| value.to_str().ok())
+                    .map(str::to_string)
+            };
+            let content_type = header(CONTENT_TYPE);
+            let file_name = header(CONTENT_DISPOSITION).and_then(|value| disposition_file_name(&value));
+            let bytes = hyper::body::to_bytes(response.into_body()).await?.to_vec();
+            return Ok(Download {
+                bytes,
+                url: uri,
+                content_type,
+                file_name,
+            });
+        }
+        Err(format!("{} redirected more than {} times", url, MAX_REDIRECTS).into())
+    }
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Scheme, host and port of `uri`, which must match for credentials to be sent along
+fn origin(uri: &Uri) -> (Option<String>, Option<String>, Option<u16>) {
+    let scheme = uri.scheme_str().map(str::to_lowercase);
+    let port = uri.port_u16().or(match scheme.as_deref() {
+        Some("https") => Some(443),
+        Some("http") => Some(80),
+        _ => None,
+    });
+    (scheme, uri.host().map(str::to_lowercase), port)
+}
+
+/// Whether a header carries credentials that mustn't follow a redirect to another origin
+fn is_credential(name: &str) -> bool {
+    [COOKIE, AUTHORIZATION, PROXY_AUTHORIZATION]
+        .iter()
+        .any(|header| name.eq_ignore_ascii_case(header.as_str()))
+}
+
+/// Resolve a `Location` header against the URL that sent it
+fn resolve(base: &Uri, location: &str) -> Result<Uri, Box<dyn std::error::Error>> {
+    if location.contains("://") {
+        return Ok(location.parse()?);
+    }
+    let scheme = base.scheme_str().unwrap_or("http");
+    let authority = base.authority().map(|authority| authority.as_str()).unwrap_or_default();
+    if let Some(rest) = location.strip_prefix("//") {
+        return Ok(format!("{}://{}", scheme, rest).parse()?);
+    }
+    let path = match location.starts_with('/') {
+        true => location.to_string(),
+        false => {
+            let dir = base.path().rsplit_once('/').map(|(dir, _)| dir).unwrap_or_default();
+            format!("{}/{}", dir, location)
+        }
+    };
+    Ok(format!("{}://{}{}", scheme, authority, path).parse()?)
+}
+
+/// `filename` of a `Content-Disposition: attachment; filename="..."` header
+fn disposition_file_name(value: &str) -> Option<String> {
+    value.split(';').map(str::trim).find_map(|part| {
+        let name = part.strip_prefix("filename=")?.trim_matches('"');
+        let name = name.rsplit(['/', '\\']).next().unwrap_or(name);
+        (!name.is_empty()).then(|| name.to_string())
+    })
+}
+
+/// `Cookie` header value for `url` from a Netscape `cookies.txt` jar, as written by curl and
+/// browser export extensions; `None` when no cookie applies.
+pub fn cookie_header(jar: &Path, url: &Uri) -> std::io::Result<Option<String>> {
+    let host = url.host().unwrap_or_default().to_lowercase();
+    let path = url.path();
+    let secure = url.scheme_str() == Some("https");
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+
+    let mut cookies = Vec::new();
+    for line in std::fs::read_to_string(jar)?.lines() {
+        // curl marks HttpOnly cookies with a prefix that otherwise looks like a comment
+        let line = line.strip_prefix("#HttpOnly_").unwrap_or(line);
+        if line.starts_with('#') || line.trim().is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = line.split('\t').collect();
+        let [domain, subdomains, cookie_path, cookie_secure, expires, name, value] = fields[..] else {
+            continue;
+        };
+        let domain = domain.trim_start_matches('.').to_lowercase();
+        let domain_matches = host == domain || (subdomains == "TRUE" && host.ends_with(&format!(".{}", domain)));
+        let expires: u64 = expires.parse().unwrap_or(0);
+        if domain_matches
+            && path.starts_with(cookie_path)
+            && (cookie_secure != "TRUE" || secure)
+            && (expires == 0 || expires > now)
+        {
+            cookies.push(format!("{}={}", name, value));
+        }
+    }
+    Ok((!cookies.is_empty()).then(|| cookies.join("; ")))
+}
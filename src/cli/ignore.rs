@@ -0,0 +1,174 @@
+use crate::cli::output;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use ::ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ::ignore::Match;
+use serde_json::{json, Value};
+use std::path::{Component, Path, PathBuf};
+
+/// Name of the ignore file looked up in the directory a command works on.
+pub const IGNORE_FILE_NAME: &str = ".eagleignore";
+
+/// Skip-list consulted by commands that ingest or export files.
+///
+/// Uses gitignore syntax: `*.tmp` and `.DS_Store` match names anywhere,
+/// `/drafts/` is anchored to the directory holding the ignore file, a trailing
+/// `/` only matches directories, and `!pattern` re-includes a path.
+pub struct IgnoreList {
+    root: PathBuf,
+    matcher: Gitignore,
+}
+
+impl IgnoreList {
+    pub fn empty() -> Self {
+        IgnoreList {
+            root: PathBuf::new(),
+            matcher: Gitignore::empty(),
+        }
+    }
+
+    /// Load `<root>/.eagleignore` (when present) plus any extra ignore files.
+    pub fn load(root: &Path, extra_files: &[PathBuf]) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut builder = GitignoreBuilder::new(root);
+        let default_file = root.join(IGNORE_FILE_NAME);
+        if default_file.is_file() {
+            if let Some(error) = builder.add(&default_file) {
+                return Err(error.into());
+            }
+        }
+        for file in extra_files {
+            if !file.is_file() {
+                return Err(format!("Ignore file not found: {}", file.display()).into());
+            }
+            if let Some(error) = builder.add(file) {
+                return Err(error.into());
+            }
+        }
+        Ok(IgnoreList {
+            root: root.to_path_buf(),
+            matcher: builder.build()?,
+        })
+    }
+
+    /// Build the skip-list for a command working on `root`, honoring `--ignore-file` and `--no-ignore`.
+    pub fn from_matches(matches: &ArgMatches, root: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        if matches.get_flag("no_ignore") {
+            return Ok(IgnoreList::empty());
+        }
+        let extra_files: Vec<PathBuf> = matches
+            .get_many::<String>("ignore_file")
+            .into_iter()
+            .flatten()
+            .map(PathBuf::from)
+            .collect();
+        IgnoreList::load(root, &extra_files)
+    }
+
+    /// Check a path (or any of its parent directories) against the skip-list.
+    ///
+    /// Returns the matching pattern, so commands can explain why a file was skipped.
+    pub fn matched(&self, path: &Path, is_dir: bool) -> Option<&str> {
+        let relative = self.relative(path);
+        let mut current = relative.as_path();
+        let mut current_is_dir = is_dir;
+        loop {
+            match self.matcher.matched(current, current_is_dir) {
+                Match::Ignore(glob) => return Some(glob.original()),
+                Match::Whitelist(_) => return None,
+                Match::None => {}
+            }
+            match current.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => {
+                    current = parent;
+                    current_is_dir = true;
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        self.matched(path, is_dir).is_some()
+    }
+
+    fn relative(&self, path: &Path) -> PathBuf {
+        if let Ok(relative) = path.strip_prefix(&self.root) {
+            return relative.to_path_buf();
+        }
+        // Outside the root only unanchored patterns (names, extensions) can apply
+        path.components()
+            .filter(|component| matches!(component, Component::Normal(_)))
+            .collect()
+    }
+}
+
+/// `--ignore-file` / `--no-ignore` arguments for commands that consult the skip-list.
+pub fn args() -> Vec<Arg> {
+    vec![
+        Arg::new("ignore_file")
+            .long("ignore-file")
+            .value_name("FILE")
+            .help(format!("Additional ignore file, on top of {}", IGNORE_FILE_NAME))
+            .action(ArgAction::Append),
+        Arg::new("no_ignore")
+            .long("no-ignore")
+            .help(format!("Don't skip files matched by {}", IGNORE_FILE_NAME))
+            .action(ArgAction::SetTrue),
+    ]
+}
+
+pub fn build() -> Command {
+    Command::new("ignore")
+        .about("Skip-list (.eagleignore) used by import, watch, and export")
+        .subcommand(
+            Command::new("check")
+                .about("Show which of the given paths are ignored")
+                .arg(
+                    Arg::new("paths")
+                        .value_name("PATH")
+                        .help("Paths to check")
+                        .required(true)
+                        .num_args(1..),
+                )
+                .arg(
+                    Arg::new("verbose")
+                        .short('v')
+                        .long("verbose")
+                        .help("Show the matching pattern for each ignored path")
+                        .action(ArgAction::SetTrue),
+                )
+                .args(args())
+                .args(output::args()),
+        )
+}
+
+pub async fn execute(matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(("check", check_matches)) = matches.subcommand() {
+        let root = std::env::current_dir()?;
+        let ignore_list = IgnoreList::from_matches(check_matches, &root)?;
+        let verbose = check_matches.get_flag("verbose");
+
+        let ignored: Vec<(&str, String)> = check_matches
+            .get_many::<String>("paths")
+            .unwrap()
+            .filter_map(|path| {
+                let pattern = ignore_list.matched(Path::new(path), Path::new(path).is_dir())?;
+                Some((path.as_str(), pattern.to_string()))
+            })
+            .collect();
+        // One path per line, like `git check-ignore`, unless a format is asked for
+        if output::is_explicit(check_matches) {
+            let rows = ignored
+                .iter()
+                .map(|(path, pattern)| json!({ "path": path, "pattern": pattern }))
+                .collect();
+            return output::output(&Value::Array(rows), check_matches);
+        }
+        for (path, pattern) in ignored {
+            match verbose {
+                true => println!("{}\t{}", pattern, path),
+                false => println!("{}", path),
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,453 @@
+use crate::cli::folder::folder_paths;
+use crate::cli::item::list::{self, filter::ItemFilter};
+use crate::cli::{output, stats};
+use crate::lib::client::EagleClient;
+use crate::lib::library::LibraryDir;
+use crate::lib::types::{Child, GetItemListParams, ItemListData};
+use crate::cli::datetime;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use rusqlite::{params, params_from_iter, Connection};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Instant, UNIX_EPOCH};
+
+const INDEX_FILE_NAME: &str = "index.sqlite";
+
+const SCHEMA: &str = "
+    CREATE TABLE meta (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+    CREATE TABLE items (
+        id TEXT PRIMARY KEY,
+        name TEXT NOT NULL,
+        ext TEXT NOT NULL,
+        size INTEGER NOT NULL,
+        url TEXT NOT NULL,
+        annotation TEXT NOT NULL,
+        width INTEGER,
+        height INTEGER,
+        star INTEGER,
+        is_deleted INTEGER NOT NULL,
+        modification_time INTEGER NOT NULL,
+        last_modified INTEGER,
+        json TEXT NOT NULL
+    );
+    CREATE TABLE item_tags (item_id TEXT NOT NULL, tag TEXT NOT NULL);
+    CREATE INDEX item_tags_tag ON item_tags (tag);
+    CREATE TABLE item_folders (item_id TEXT NOT NULL, folder_id TEXT NOT NULL);
+    CREATE INDEX item_folders_folder ON item_folders (folder_id);
+    CREATE TABLE folders (id TEXT PRIMARY KEY, name TEXT NOT NULL, path TEXT NOT NULL);
+    CREATE TABLE palettes (item_id TEXT NOT NULL, r INTEGER, g INTEGER, b INTEGER, ratio REAL);
+    CREATE VIRTUAL TABLE items_fts USING fts5(id UNINDEXED, name, annotation, tags);
+";
+
+pub fn build() -> Command {
+    Command::new("index")
+        .about("Keep a local SQLite index of the library for instant and offline search")
+        .arg_required_else_help(true)
+        .subcommand(
+            Command::new("build")
+                .about("Index every item, tag, folder, and palette of the library")
+                .arg(
+                    Arg::new("offline")
+                        .long("offline")
+                        .help("Read the library files instead of asking Eagle, which may be closed")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("path")
+                        .long("path")
+                        .value_name("LIBRARY")
+                        .help("Library folder read with --offline [default: the library indexed last]")
+                        .num_args(1)
+                        .requires("offline"),
+                ),
+        )
+        .subcommand(
+            Command::new("update")
+                .about("Refresh only the items whose modificationTime changed since the last build or update")
+                .arg(
+                    Arg::new("offline")
+                        .long("offline")
+                        .help("Read the library files instead of asking Eagle, which may be closed")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("search")
+                .about("Full-text search of item names, annotations, and tags in the index")
+                .arg(
+                    Arg::new("text")
+                        .value_name("TEXT")
+                        .help("Words to find, in SQLite FTS5 syntax, e.g. 'logo OR icon' or 'shot*'")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("limit")
+                        .short('n')
+                        .long("limit")
+                        .value_name("LIMIT")
+                        .help("Show at most LIMIT items, best matches first")
+                        .num_args(1)
+                        .default_value("50")
+                        .value_parser(clap::value_parser!(usize)),
+                )
+                .arg(auto_index_arg())
+                .args(output::args()),
+        )
+}
+
+/// `--auto-index` for commands that search the index, refreshing it first when it's stale.
+pub fn auto_index_arg() -> Arg {
+    Arg::new("auto_index")
+        .long("auto-index")
+        .value_name("AGE")
+        .help("Update the index first when it wasn't for AGE, e.g. --auto-index=1h [default: 5m]")
+        .num_args(0..=1)
+        .require_equals(true)
+        .default_missing_value("5m")
+        .value_parser(|value: &str| {
+            datetime::parse_duration_millis(value)
+                .ok_or_else(|| format!("invalid age: {} (use 30s, 5m, ...)", value))
+        })
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match matches.subcommand() {
+        Some(("build", build_matches)) => {
+            let started = Instant::now();
+            let (library, items, folders) = match build_matches.get_flag("offline") {
+                true => {
+                    let library = match build_matches.get_one::<String>("path") {
+                        Some(path) => LibraryDir::new(path),
+                        None => library_of(&open()?)?,
+                    };
+                    let folders = serde_json::from_value(library.read_metadata()?["folders"].take())?;
+                    let items = library.items()?;
+                    (library, items, folders)
+                }
+                false => {
+                    let library = LibraryDir::new(&client.library().info().await?.data.library.path);
+                    (library, list::all_items(client).await?, client.folder().list().await?.data)
+                }
+            };
+            write(&library, &items, &folders)?;
+            eprintln!(
+                "Indexed {} items and {} folders in {:.2}s",
+                items.len(),
+                folder_paths(&folders).len(),
+                started.elapsed().as_secs_f64()
+            );
+        }
+        Some(("update", update_matches)) => {
+            let started = Instant::now();
+            let changes = update(client, update_matches.get_flag("offline")).await?;
+            eprintln!(
+                "Updated the index: {} added, {} changed, {} removed in {:.2}s",
+                changes.added,
+                changes.changed,
+                changes.removed,
+                started.elapsed().as_secs_f64()
+            );
+        }
+        Some(("search", search_matches)) => {
+            refresh_if_stale(client, search_matches).await?;
+            let connection = open()?;
+            let library = library_of(&connection)?;
+            let text = search_matches.get_one::<String>("text").unwrap();
+            let limit = *search_matches.get_one::<usize>("limit").unwrap();
+            let mut statement = connection.prepare(
+                "SELECT items.json FROM items_fts JOIN items ON items.id = items_fts.id \
+                 WHERE items_fts MATCH ?1 AND items.is_deleted = 0 ORDER BY items_fts.rank LIMIT ?2",
+            )?;
+            let items = statement
+                .query_map(params![text, limit as i64], |row| row.get::<_, String>(0))?
+                .map(|json| Ok(serde_json::from_str(&json?)?))
+                .collect::<Result<Vec<ItemListData>, Box<dyn std::error::Error>>>()
+                .map_err(|e| format!("Index search failed: {}", e))?;
+            output::output(&Value::Array(list::item_rows(&items, &library, false)), search_matches)?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn index_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(stats::data_dir()
+        .ok_or("Can't locate the data directory, set EAGLE_EYE_DATA_DIR")?
+        .join(INDEX_FILE_NAME))
+}
+
+/// Open the index, which `index build` must have created.
+pub fn open() -> Result<Connection, Box<dyn std::error::Error>> {
+    let path = index_path()?;
+    if !path.exists() {
+        return Err("No index yet; run `eagle-eye index build` first".into());
+    }
+    Ok(Connection::open(path)?)
+}
+
+/// The library the index was built from
+pub fn library_of(connection: &Connection) -> Result<LibraryDir, Box<dyn std::error::Error>> {
+    let path: String = connection.query_row("SELECT value FROM meta WHERE key = 'library'", [], |row| row.get(0))?;
+    Ok(LibraryDir::new(path))
+}
+
+/// Write a new index next to the old one and swap it in, so searches never see half of one.
+fn write(library: &LibraryDir, items: &[ItemListData], folders: &[Child]) -> Result<(), Box<dyn std::error::Error>> {
+    let path = index_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let tmp_path = path.with_extension("sqlite.tmp");
+    let _ = std::fs::remove_file(&tmp_path);
+    let mut connection = Connection::open(&tmp_path)?;
+    connection.execute_batch(SCHEMA)?;
+
+    let transaction = connection.transaction()?;
+    transaction.execute(
+        "INSERT INTO meta (key, value) VALUES ('library', ?1), ('built', ?2), ('updated', ?2)",
+        params![library.root().to_string_lossy(), chrono::Utc::now().timestamp_millis().to_string()],
+    )?;
+    for (id, path) in folder_paths(folders) {
+        let name = path.rsplit('/').next().unwrap_or_default().to_string();
+        transaction.execute("INSERT INTO folders (id, name, path) VALUES (?1, ?2, ?3)", params![id, name, path])?;
+    }
+    for item in items {
+        insert_item(&transaction, item)?;
+    }
+    transaction.commit()?;
+    drop(connection);
+    std::fs::rename(&tmp_path, &path)?;
+    Ok(())
+}
+
+fn insert_item(connection: &Connection, item: &ItemListData) -> Result<(), Box<dyn std::error::Error>> {
+    connection.execute(
+        "INSERT INTO items (id, name, ext, size, url, annotation, width, height, star, is_deleted, \
+         modification_time, last_modified, json) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+        params![
+            item.id,
+            item.name,
+            item.ext.to_lowercase(),
+            item.size as i64,
+            item.url,
+            item.annotation,
+            item.width.map(|width| width as i64),
+            item.height.map(|height| height as i64),
+            item.star,
+            item.is_deleted,
+            item.modification_time as i64,
+            item.last_modified.map(|time| time as i64),
+            serde_json::to_string(item)?,
+        ],
+    )?;
+    for tag in &item.tags {
+        connection.execute("INSERT INTO item_tags (item_id, tag) VALUES (?1, ?2)", params![item.id, tag])?;
+    }
+    for folder in item.folders.iter().flatten() {
+        connection.execute("INSERT INTO item_folders (item_id, folder_id) VALUES (?1, ?2)", params![item.id, folder])?;
+    }
+    for palette in item.palettes.iter().flatten() {
+        let [r, g, b, ..] = palette.color[..] else {
+            continue;
+        };
+        connection.execute(
+            "INSERT INTO palettes (item_id, r, g, b, ratio) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![item.id, r as i64, g as i64, b as i64, palette.ratio],
+        )?;
+    }
+    connection.execute(
+        "INSERT INTO items_fts (id, name, annotation, tags) VALUES (?1, ?2, ?3, ?4)",
+        params![item.id, item.name, item.annotation, item.tags.join(" ")],
+    )?;
+    Ok(())
+}
+
+/// How many items an update added, changed, and removed
+struct Changes {
+    added: usize,
+    changed: usize,
+    removed: usize,
+}
+
+/// Bring the index up to date with the library.
+///
+/// Every item is listed, but only those whose `modificationTime` differs from the index are
+/// written again. Offline, an item's `metadata.json` is only read when the file changed since
+/// the last update or the item is new.
+async fn update(client: &EagleClient, offline: bool) -> Result<Changes, Box<dyn std::error::Error>> {
+    let started = chrono::Utc::now().timestamp_millis();
+    let mut connection = open()?;
+    let library = library_of(&connection)?;
+    let updated: i64 = connection
+        .query_row("SELECT value FROM meta WHERE key = 'updated'", [], |row| row.get::<_, String>(0))?
+        .parse()?;
+    let known: HashMap<String, u64> = connection
+        .prepare("SELECT id, modification_time FROM items")?
+        .query_map([], |row| Ok((row.get(0)?, row.get::<_, i64>(1)? as u64)))?
+        .collect::<Result<_, _>>()?;
+
+    let mut seen = HashSet::new();
+    let (items, folders): (Vec<ItemListData>, Vec<Child>) = match offline {
+        true => {
+            let mut items = Vec::new();
+            for entry in fs::read_dir(library.images_dir())? {
+                let entry = entry?;
+                let Some(id) = entry.file_name().to_string_lossy().strip_suffix(".info").map(str::to_string) else {
+                    continue;
+                };
+                let modified = fs::metadata(entry.path().join("metadata.json"))
+                    .and_then(|metadata| metadata.modified())
+                    .ok()
+                    .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                    .map(|elapsed| elapsed.as_millis() as i64);
+                if known.contains_key(&id) && modified.is_some_and(|modified| modified < updated) {
+                    seen.insert(id);
+                    continue;
+                }
+                if let Ok(item) = library.item(&id) {
+                    items.push(item);
+                    seen.insert(id);
+                }
+            }
+            (items, serde_json::from_value(library.read_metadata()?["folders"].take())?)
+        }
+        false => {
+            let items = list::all_items(client).await?;
+            seen.extend(items.iter().map(|item| item.id.clone()));
+            (items, client.folder().list().await?.data)
+        }
+    };
+
+    let mut changes = Changes { added: 0, changed: 0, removed: 0 };
+    let transaction = connection.transaction()?;
+    for item in &items {
+        match known.get(&item.id) {
+            Some(&time) if time == item.modification_time => continue,
+            Some(_) => {
+                changes.changed += 1;
+                delete_item(&transaction, &item.id)?;
+            }
+            None => changes.added += 1,
+        }
+        insert_item(&transaction, item)?;
+    }
+    for id in known.keys().filter(|id| !seen.contains(*id)) {
+        changes.removed += 1;
+        delete_item(&transaction, id)?;
+    }
+    transaction.execute("DELETE FROM folders", [])?;
+    for (id, path) in folder_paths(&folders) {
+        let name = path.rsplit('/').next().unwrap_or_default().to_string();
+        transaction.execute("INSERT INTO folders (id, name, path) VALUES (?1, ?2, ?3)", params![id, name, path])?;
+    }
+    transaction.execute(
+        "UPDATE meta SET value = ?1 WHERE key = 'updated'",
+        params![started.to_string()],
+    )?;
+    transaction.commit()?;
+    Ok(changes)
+}
+
+fn delete_item(connection: &Connection, id: &str) -> Result<(), Box<dyn std::error::Error>> {
+    for table in ["items", "item_tags", "item_folders", "palettes", "items_fts"] {
+        let column = match table {
+            "items" | "items_fts" => "id",
+            _ => "item_id",
+        };
+        connection.execute(&format!("DELETE FROM {} WHERE {} = ?1", table, column), params![id])?;
+    }
+    Ok(())
+}
+
+/// With `--auto-index`, build the index when there is none, or update it when it's older than
+/// the given age. Eagle is asked for the items when it's running, else the library files are read.
+pub async fn refresh_if_stale(client: &EagleClient, matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(&max_age) = matches.try_get_one::<i64>("auto_index").ok().flatten() else {
+        return Ok(());
+    };
+    if !index_path()?.exists() {
+        let library = LibraryDir::new(&client.library().info().await?.data.library.path);
+        let items = list::all_items(client).await?;
+        return write(&library, &items, &client.folder().list().await?.data);
+    }
+    let updated: i64 = open()?
+        .query_row("SELECT value FROM meta WHERE key = 'updated'", [], |row| row.get::<_, String>(0))?
+        .parse()?;
+    if chrono::Utc::now().timestamp_millis() - updated < max_age {
+        return Ok(());
+    }
+    if update(client, false).await.is_err() {
+        update(client, true).await?;
+    }
+    Ok(())
+}
+
+/// Items in the index selected by the `/api/item/list` parameters, in the order Eagle listed
+/// them, and passing `item_filter`; `limit` and `offset` page through the matches like the API.
+pub fn query_items(
+    params: &GetItemListParams,
+    item_filter: &ItemFilter,
+) -> Result<Vec<ItemListData>, Box<dyn std::error::Error>> {
+    let connection = open()?;
+    let mut conditions = vec!["is_deleted = 0".to_string()];
+    let mut values: Vec<String> = Vec::new();
+    if let Some(keyword) = &params.keyword {
+        values.push(format!("%{}%", keyword));
+        let n = values.len();
+        conditions.push(format!(
+            "(name LIKE ?{n} OR annotation LIKE ?{n} OR url LIKE ?{n} \
+             OR EXISTS (SELECT 1 FROM item_tags WHERE item_id = id AND tag LIKE ?{n}))"
+        ));
+    }
+    if let Some(ext) = &params.ext {
+        values.push(ext.to_lowercase());
+        conditions.push(format!("ext = ?{}", values.len()));
+    }
+    if let Some(tags) = &params.tags {
+        let placeholders = push_list(&mut values, tags);
+        conditions.push(format!(
+            "EXISTS (SELECT 1 FROM item_tags WHERE item_id = id AND tag IN ({}))",
+            placeholders
+        ));
+    }
+    // Folders may be given by id, path, or name, like with the API
+    if let Some(folders) = &params.folders {
+        let placeholders = push_list(&mut values, folders);
+        conditions.push(format!(
+            "EXISTS (SELECT 1 FROM item_folders WHERE item_id = id AND folder_id IN \
+             (SELECT id FROM folders WHERE id IN ({0}) OR path IN ({0}) OR name IN ({0})))",
+            placeholders
+        ));
+    }
+
+    let sql = format!("SELECT json FROM items WHERE {} ORDER BY rowid", conditions.join(" AND "));
+    let mut statement = connection.prepare(&sql)?;
+    let mut items = Vec::new();
+    for json in statement.query_map(params_from_iter(values), |row| row.get::<_, String>(0))? {
+        let item: ItemListData = serde_json::from_str(&json?)?;
+        if item_filter.matches(&item) {
+            items.push(item);
+        }
+    }
+    if let Some(limit) = params.limit {
+        items = items.into_iter().skip(limit * params.offset.unwrap_or(0)).take(limit).collect();
+    }
+    Ok(items)
+}
+
+/// Bind each value of a comma-separated list, returning their placeholders
+fn push_list(values: &mut Vec<String>, list: &str) -> String {
+    let placeholders: Vec<String> = list
+        .split(',')
+        .map(|value| {
+            values.push(value.trim().to_string());
+            format!("?{}", values.len())
+        })
+        .collect();
+    placeholders.join(", ")
+}
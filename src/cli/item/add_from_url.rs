@@ -0,0 +1,297 @@
+use crate::cli::folder::resolve_folder;
+use crate::cli::http::{self, Download, HttpClient};
+use crate::cli::item::list::{self, filter::split_list};
+use crate::lib::client::EagleClient;
+use crate::lib::types::{AddFromPathParams, AddFromUrlParams};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use hyper::Uri;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+pub fn build() -> Command {
+    Command::new("add-from-url")
+        .about("Add an image from a URL to the library")
+        .arg(
+            Arg::new("url")
+                .value_name("URL")
+                .help("Address of the image")
+                .required(true)
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("name")
+                .long("name")
+                .value_name("NAME")
+                .help("Item name; defaults to the file name in the URL")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("website")
+                .long("website")
+                .value_name("URL")
+                .help("Source page recorded on the item")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("tags")
+                .short('t')
+                .long("tags")
+                .value_name("TAG")
+                .help("Tags to add. Comma separated")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("annotation")
+                .long("annotation")
+                .value_name("TEXT")
+                .help("Annotation of the item")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("folder")
+                .short('f')
+                .long("folder")
+                .value_name("FOLDER")
+                .help("Folder to add the item to, by id, path (Brand/Logos) or unique name")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("header")
+                .short('H')
+                .long("header")
+                .value_name("NAME: VALUE")
+                .help("Extra request header, e.g. 'Referer: https://example.com'. Repeatable")
+                .action(ArgAction::Append)
+                .value_parser(parse_header),
+        )
+        .arg(
+            Arg::new("cookie_jar")
+                .long("cookie-jar")
+                .value_name("FILE")
+                .help("Netscape cookies.txt whose matching cookies are sent with the request")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("skip_if_url_exists")
+                .long("skip-if-url-exists")
+                .help("Do nothing when an item with the same source URL is already in the library")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("error_if_url_exists"),
+        )
+        .arg(
+            Arg::new("error_if_url_exists")
+                .long("error-if-url-exists")
+                .help("Fail when an item with the same source URL is already in the library")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("download_locally")
+                .long("download-locally")
+                .help("Download the file with eagle-eye and import it from disk, for sites Eagle can't fetch from")
+                .action(ArgAction::SetTrue),
+        )
+}
+
+fn parse_header(value: &str) -> Result<(String, String), String> {
+    let (name, value) = value
+        .split_once(':')
+        .ok_or_else(|| format!("expected NAME: VALUE, got {}", value))?;
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("empty header name".to_string());
+    }
+    Ok((name.to_string(), value.trim().to_string()))
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let url = matches.get_one::<String>("url").unwrap();
+    let uri: Uri = url.parse().map_err(|e| format!("Invalid URL {}: {}", url, e))?;
+
+    let mut headers: Vec<(String, String)> = matches
+        .get_many::<(String, String)>("header")
+        .map(|headers| headers.cloned().collect())
+        .unwrap_or_default();
+    let cookie_jar = matches.get_one::<String>("cookie_jar").map(Path::new);
+
+    let skip = matches.get_flag("skip_if_url_exists");
+    if skip || matches.get_flag("error_if_url_exists") {
+        let existing = existing_urls(client).await?;
+        let candidates = [Some(url), matches.get_one::<String>("website")];
+        if let Some(id) = candidates.into_iter().flatten().find_map(|url| existing.get(&normalize_url(url))) {
+            if skip {
+                println!("Skipped {}: already in the library as {}", url, id);
+                return Ok(());
+            }
+            return Err(format!("{} is already in the library as {}", url, id).into());
+        }
+    }
+
+    let folder_id = match matches.get_one::<String>("folder") {
+        Some(folder) => Some(resolve_folder(&client.folder().list().await?.data, folder)?),
+        None => None,
+    };
+    let tags = Some(split_list(matches, "tags")).filter(|tags| !tags.is_empty());
+    let annotation = matches.get_one::<String>("annotation").cloned();
+    let website = matches.get_one::<String>("website").cloned();
+
+    if matches.get_flag("download_locally") {
+        // Matches the jar against every URL redirected to
+        let download = HttpClient::new().get(url, &headers, cookie_jar).await?;
+        let file_name = file_name(&download);
+        let name = matches.get_one::<String>("name").cloned().unwrap_or_else(|| stem(&file_name));
+
+        let dir = std::env::temp_dir().join(format!("eagle-eye-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(&file_name);
+        let result = import_file(client, &path, &download.bytes, AddFromPathParams {
+            path: path.to_string_lossy().into_owned(),
+            name: name.clone(),
+            website: website.or_else(|| Some(url.clone())),
+            annotation,
+            tags,
+            folder_id,
+        })
+        .await;
+        // Eagle has copied the file into the library once the request returns
+        let _ = fs::remove_dir_all(&dir);
+        result?;
+        println!("Added {} ({} bytes downloaded)", name, download.bytes.len());
+        return Ok(());
+    }
+
+    if let Some(jar) = cookie_jar {
+        let cookie = http::cookie_header(jar, &uri)
+            .map_err(|e| format!("Failed to read cookie jar {}: {}", jar.display(), e))?;
+        if let Some(cookie) = cookie {
+            headers.push(("Cookie".to_string(), cookie));
+        }
+    }
+    let name = matches
+        .get_one::<String>("name")
+        .cloned()
+        .unwrap_or_else(|| stem(&url_file_name(&uri).unwrap_or_else(|| "image".to_string())));
+    let params = AddFromUrlParams {
+        url: url.clone(),
+        name: name.clone(),
+        website,
+        tags,
+        annotation,
+        folder_id,
+        headers: (!headers.is_empty()).then(|| headers.into_iter().collect()),
+        ..Default::default()
+    };
+    client.item().add_from_url(&params).await?;
+    println!("Added {}", name);
+    Ok(())
+}
+
+/// Ids of the items in the library by their normalized source URL
+pub async fn existing_urls(client: &EagleClient) -> Result<HashMap<String, String>, Box<dyn std::error::Error>> {
+    let mut urls = HashMap::new();
+    for item in list::all_items(client).await? {
+        if !item.url.is_empty() {
+            urls.entry(normalize_url(&item.url)).or_insert(item.id);
+        }
+    }
+    Ok(urls)
+}
+
+/// Canonical form of a URL for duplicate checks: lowercase scheme and host, no default port,
+/// fragment, trailing slash or `utm_*` tracking parameters, and sorted query parameters.
+pub fn normalize_url(url: &str) -> String {
+    let url = url.trim();
+    let url = url.split_once('#').map_or(url, |(url, _)| url);
+    let (scheme, rest) = url.split_once("://").unwrap_or(("http", url));
+    let scheme = scheme.to_lowercase();
+    let (location, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let (host, path) = location.split_once('/').unwrap_or((location, ""));
+    let mut host = host.to_lowercase();
+    let default_port = match scheme.as_str() {
+        "https" => ":443",
+        _ => ":80",
+    };
+    if let Some(stripped) = host.strip_suffix(default_port) {
+        host = stripped.to_string();
+    }
+    let host = host.strip_prefix("www.").unwrap_or(&host);
+
+    let mut params: Vec<&str> = query
+        .split('&')
+        .filter(|param| !param.is_empty() && !param.starts_with("utm_"))
+        .collect();
+    params.sort_unstable();
+
+    let mut normalized = format!("{}://{}/{}", scheme, host, path.trim_end_matches('/'));
+    if normalized.ends_with('/') {
+        normalized.pop();
+    }
+    if !params.is_empty() {
+        normalized.push('?');
+        normalized.push_str(&params.join("&"));
+    }
+    normalized
+}
+
+async fn import_file(
+    client: &EagleClient,
+    path: &Path,
+    bytes: &[u8],
+    params: AddFromPathParams,
+) -> Result<(), Box<dyn std::error::Error>> {
+    fs::write(path, bytes)?;
+    client.item().add_from_path(&params).await?;
+    Ok(())
+}
+
+/// File name for a download: from `Content-Disposition`, else the URL, with an extension
+/// guessed from the content type when it has none
+fn file_name(download: &Download) -> String {
+    let name = download
+        .file_name
+        .clone()
+        .or_else(|| url_file_name(&download.url))
+        .unwrap_or_else(|| "image".to_string());
+    if Path::new(&name).extension().is_some() {
+        return name;
+    }
+    match download.content_type.as_deref().and_then(extension_for) {
+        Some(ext) => format!("{}.{}", name, ext),
+        None => name,
+    }
+}
+
+/// Last path segment of a URL, made safe to use as a file name
+fn url_file_name(uri: &Uri) -> Option<String> {
+    let segment = uri.path().rsplit('/').next()?;
+    (!segment.is_empty()).then(|| crate::cli::item::export::sanitize(segment))
+}
+
+fn stem(file_name: &str) -> String {
+    Path::new(file_name)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| file_name.to_string())
+}
+
+fn extension_for(content_type: &str) -> Option<&'static str> {
+    let mime = content_type.split(';').next()?.trim().to_lowercase();
+    Some(match mime.as_str() {
+        "image/jpeg" | "image/jpg" => "jpg",
+        "image/png" => "png",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        "image/avif" => "avif",
+        "image/svg+xml" => "svg",
+        "image/tiff" => "tif",
+        "image/bmp" => "bmp",
+        "image/heic" => "heic",
+        "video/mp4" => "mp4",
+        "video/webm" => "webm",
+        "application/pdf" => "pdf",
+        _ => return None,
+    })
+}
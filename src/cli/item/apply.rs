@@ -0,0 +1,104 @@
+use crate::cli::handlers::{self, HandlerError};
+use crate::cli::progress::Progress;
+use crate::cli::report::{self, Failures};
+use crate::lib::client::EagleClient;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use serde_json::Value;
+
+pub fn build() -> Command {
+    Command::new("apply")
+        .about("Apply updates given as JSON lines, e.g. {\"id\":\"X\",\"tags\":[\"a\"],\"star\":5}")
+        .long_about(
+            "Apply updates given as JSON lines, one object per line, e.g.\n\n\
+             \x20 {\"id\":\"KBHG6KA0Y8B2D\",\"tags\":[\"a\"],\"star\":5}\n\n\
+             `id` is required; name, tags, folders, annotation, url and star are set when present, \
+             and other fields are ignored, so objects printed by `item list` can be edited and fed back.",
+        )
+        .arg(
+            Arg::new("file")
+                .value_name("FILE")
+                .help("File of JSON lines")
+                .required_unless_present("stdin")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("stdin")
+                .long("stdin")
+                .help("Read the JSON lines from stdin")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("file"),
+        )
+        .arg(
+            Arg::new("dry_run")
+                .long("dry-run")
+                .help("Check every line without updating anything")
+                .action(ArgAction::SetTrue),
+        )
+        .args(report::args())
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let content = match matches.get_one::<String>("file") {
+        Some(file) => std::fs::read_to_string(file).map_err(|e| format!("Failed to read {}: {}", file, e))?,
+        None => std::io::read_to_string(std::io::stdin())?,
+    };
+    let resume = report::resume_targets(matches)?;
+    let dry_run = matches.get_flag("dry_run");
+
+    // Every line is parsed before the first update, so the progress bar knows the total
+    let mut updates = Vec::new();
+    let mut failures = Failures::default();
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let target = format!("line {}", index + 1);
+        let update: Value = match serde_json::from_str(line) {
+            Ok(update @ Value::Object(_)) => update,
+            Ok(_) => {
+                eprintln!("Skipping {}: not a JSON object", target);
+                failures.add(&target, "not a JSON object");
+                continue;
+            }
+            Err(error) => {
+                eprintln!("Skipping {}: {}", target, error);
+                failures.add(&target, error);
+                continue;
+            }
+        };
+        let Some(id) = update["id"].as_str().map(String::from) else {
+            eprintln!("Skipping {}: no id", target);
+            failures.add(&target, "no id");
+            continue;
+        };
+        if resume.as_ref().is_some_and(|targets| !targets.contains(&id)) {
+            continue;
+        }
+        updates.push((id, update));
+    }
+    if dry_run {
+        println!("{} updates to apply ({} invalid lines)", updates.len(), failures.len());
+        return report::finish(matches, "item apply", updates.len(), failures);
+    }
+
+    let mut updated = 0;
+    let mut progress = Progress::new(updates.len(), "updating", matches);
+    for (id, update) in &updates {
+        progress.inc();
+        match handlers::update_item(client, update).await {
+            Ok(_) => updated += 1,
+            Err(HandlerError(_, message)) => {
+                progress.finish();
+                eprintln!("Failed to update {}: {}", id, message);
+                failures.add(id, message);
+            }
+        }
+    }
+    progress.finish();
+
+    eprintln!("Updated {} items ({} failed)", updated, failures.len());
+    report::finish(matches, "item apply", updated, failures)
+}
@@ -0,0 +1,159 @@
+use crate::cli::item::list::{self, filter::ItemFilter};
+use crate::cli::progress::Progress;
+use crate::cli::report::{self, Failures};
+use crate::cli::tag::NAMESPACE_SEPARATOR;
+use crate::cli::{confirm, output};
+use crate::lib::client::EagleClient;
+use crate::lib::library::LibraryDir;
+use crate::lib::types::{ItemListData, UpdateItemParams};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use regex::Regex;
+use serde_json::{json, Value};
+
+pub fn build() -> Command {
+    Command::new("autotag")
+        .about("Tag items with the named capture groups of a pattern matched on their names")
+        .long_about(
+            "Tag items with the named capture groups of a pattern matched on their names.\n\n\
+             Each named group that matches becomes a tag GROUP/VALUE, e.g. client/acme for \
+             (?P<client>\\w+) on acme_2024; --values-only tags with the value alone. Eagle doesn't \
+             keep the path a file was imported from, so --from-url matches the item's source URL, \
+             where importers that record the original path put it.",
+        )
+        .arg(
+            Arg::new("pattern")
+                .long("pattern")
+                .value_name("REGEX")
+                .help("Pattern with named groups, e.g. '(?P<client>\\w+)_(?P<year>\\d{4})'")
+                .required(true)
+                .num_args(1)
+                .value_parser(|value: &str| -> Result<Regex, String> {
+                    let regex = Regex::new(value).map_err(|e| e.to_string())?;
+                    match regex.capture_names().flatten().next() {
+                        Some(_) => Ok(regex),
+                        None => Err("the pattern has no named groups like (?P<name>...)".to_string()),
+                    }
+                }),
+        )
+        .arg(
+            Arg::new("from_name")
+                .long("from-name")
+                .help("Match the pattern on item names [default]")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("from_url"),
+        )
+        .arg(
+            Arg::new("from_url")
+                .long("from-url")
+                .help("Match the pattern on the item's source URL or original path instead")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("values_only")
+                .long("values-only")
+                .help("Tag with the captured values alone, without the group name")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("dry_run")
+                .long("dry-run")
+                .help("Show the tags each item would get without changing anything")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(list::stdin_arg())
+        .args(list::query_args())
+        .args(list::filter::args())
+        .args(output::args())
+        .args(report::args())
+}
+
+/// Tags from the named groups of `pattern` that match `text`, in the order of the groups
+fn captured_tags(pattern: &Regex, text: &str, values_only: bool) -> Vec<String> {
+    let Some(captures) = pattern.captures(text) else {
+        return Vec::new();
+    };
+    pattern
+        .capture_names()
+        .flatten()
+        .filter_map(|group| {
+            let value = captures.name(group)?.as_str().trim();
+            match (value, values_only) {
+                ("", _) => None,
+                (value, true) => Some(value.to_string()),
+                (value, false) => Some(format!("{}{}{}", group, NAMESPACE_SEPARATOR, value)),
+            }
+        })
+        .collect()
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let pattern = matches.get_one::<Regex>("pattern").unwrap();
+    let values_only = matches.get_flag("values_only");
+    let source = |item: &ItemListData| -> String {
+        match matches.get_flag("from_url") {
+            true => item.url.clone(),
+            false => item.name.clone(),
+        }
+    };
+
+    let library_data = client.library().info().await?.data;
+    let library = LibraryDir::new(&library_data.library.path);
+    let item_filter = ItemFilter::from_matches(matches)?;
+    let items = list::select_items(client, matches, &item_filter, &library).await?;
+
+    let changes: Vec<(ItemListData, Vec<String>)> = items
+        .into_iter()
+        .filter_map(|item| {
+            let added: Vec<String> = captured_tags(pattern, &source(&item), values_only)
+                .into_iter()
+                .filter(|tag| !item.tags.contains(tag))
+                .collect();
+            Some((item, added)).filter(|(_, added)| !added.is_empty())
+        })
+        .collect();
+
+    if matches.get_flag("dry_run") {
+        let rows: Vec<Value> = changes
+            .iter()
+            .map(|(item, added)| json!({ "id": item.id, "name": item.name, "add": added }))
+            .collect();
+        return output::output(&Value::Array(rows), matches);
+    }
+
+    if changes.is_empty() {
+        eprintln!("No new tags to add");
+        return Ok(());
+    }
+    confirm::confirm(matches, &format!("{} items will be tagged", changes.len()))?;
+
+    let mut tagged = 0;
+    let mut failures = Failures::default();
+    let mut progress = Progress::new(changes.len(), "tagging", matches);
+    for (item, added) in &changes {
+        progress.inc();
+        let params = UpdateItemParams {
+            id: item.id.clone(),
+            tags: Some(item.tags.iter().chain(added).cloned().collect()),
+            ..Default::default()
+        };
+        match client.item().update(&params).await {
+            Ok(_) => {
+                progress.finish();
+                println!("{} +{}", item.name, added.join(" +"));
+                tagged += 1;
+            }
+            Err(error) => {
+                progress.finish();
+                eprintln!("Failed to tag {} ({}): {}", item.id, item.name, error);
+                failures.add(&item.id, error);
+            }
+        }
+    }
+
+    progress.finish();
+    eprintln!("Tagged {} items ({} failed)", tagged, failures.len());
+    report::finish(matches, "item autotag", tagged, failures)
+}
@@ -0,0 +1,96 @@
+use crate::cli::color::Rgb;
+use crate::cli::item::list::{self, filter::ItemFilter};
+use crate::cli::output;
+use crate::lib::client::EagleClient;
+use clap::{Arg, ArgMatches, Command};
+use serde_json::{json, Value};
+
+pub fn build() -> Command {
+    Command::new("color-search")
+        .about("Find items whose palette contains a color close to the given one")
+        .arg(
+            Arg::new("color")
+                .value_name("COLOR")
+                .help("Color to look for, as #rrggbb, #rgb, or a CSS color name")
+                .required(true)
+                .value_parser(clap::value_parser!(Rgb)),
+        )
+        .arg(
+            Arg::new("distance")
+                .short('d')
+                .long("distance")
+                .value_name("DELTA-E")
+                .help("Maximum color difference (CIE76 ΔE; about 2 is barely noticeable, 50 is a different color)")
+                .num_args(1)
+                .default_value("30")
+                .value_parser(clap::value_parser!(f64)),
+        )
+        .arg(
+            Arg::new("min_ratio")
+                .long("min-ratio")
+                .value_name("PERCENT")
+                .help("Ignore palette colors covering less than this share of the image")
+                .num_args(1)
+                .default_value("0")
+                .value_parser(clap::value_parser!(f64)),
+        )
+        .args(list::query_args())
+        .args(list::filter::args())
+        // Matching colors may be on any page
+        .mut_arg("all", |arg| arg.default_value("true").hide(true))
+        .args(output::args())
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let target = matches.get_one::<Rgb>("color").unwrap().to_lab();
+    let max_distance = *matches.get_one::<f64>("distance").unwrap();
+    let min_ratio = *matches.get_one::<f64>("min_ratio").unwrap();
+
+    let item_filter = ItemFilter::from_matches(matches)?;
+    let items = list::fetch_items(client, matches, &item_filter).await?;
+
+    // Per item: the closest palette color, and how much of the image is within the distance
+    let mut found: Vec<(f64, f64, Rgb, Value)> = Vec::new();
+    for item in &items {
+        let mut closest: Option<(f64, Rgb)> = None;
+        let mut coverage = 0.0;
+        for palette in item.palettes.iter().flatten() {
+            let Some(color) = Rgb::from_palette(&palette.color) else {
+                continue;
+            };
+            if palette.ratio < min_ratio {
+                continue;
+            }
+            let distance = color.to_lab().distance(&target);
+            if distance <= max_distance {
+                coverage += palette.ratio;
+            }
+            if closest.is_none_or(|(best, _)| distance < best) {
+                closest = Some((distance, color));
+            }
+        }
+        if let Some((distance, color)) = closest.filter(|(distance, _)| *distance <= max_distance) {
+            let row = json!({ "id": item.id, "name": format!("{}.{}", item.name, item.ext) });
+            found.push((distance, coverage, color, row));
+        }
+    }
+
+    // Closest first; among equally close items, those where the color covers more of the image
+    found.sort_by(|a, b| a.0.total_cmp(&b.0).then(b.1.total_cmp(&a.1)));
+    let rows: Vec<Value> = found
+        .into_iter()
+        .map(|(distance, coverage, color, row)| {
+            json!({
+                "distance": (distance * 10.0).round() / 10.0,
+                "coverage": (coverage * 10.0).round() / 10.0,
+                "color": color.to_hex(),
+                "id": row["id"],
+                "name": row["name"],
+            })
+        })
+        .collect();
+    output::output(&Value::Array(rows), matches)
+}
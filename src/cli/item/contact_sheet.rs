@@ -0,0 +1,146 @@
+use crate::cli::color::Rgb;
+use crate::cli::font;
+use crate::cli::item::list::{self, filter::ItemFilter};
+use crate::cli::progress::Progress;
+use crate::lib::client::EagleClient;
+use crate::lib::library::LibraryDir;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use image::imageops::{self, FilterType};
+use image::{Rgba, RgbaImage};
+
+/// Scale of the label font (5x7 pixels per glyph at 1)
+const LABEL_SCALE: u32 = 2;
+
+pub fn build() -> Command {
+    Command::new("contact-sheet")
+        .about("Composite the thumbnails of matching items into a labeled grid image")
+        .arg(
+            Arg::new("out")
+                .short('o')
+                .long("out")
+                .value_name("FILE")
+                .help("Image to write; the format follows the extension (png, jpg, ...)")
+                .required(true)
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("columns")
+                .short('c')
+                .long("columns")
+                .value_name("N")
+                .help("Number of columns")
+                .num_args(1)
+                .default_value("6")
+                .value_parser(clap::builder::RangedU64ValueParser::<u32>::new().range(1..)),
+        )
+        .arg(
+            Arg::new("cell")
+                .long("cell")
+                .value_name("PIXELS")
+                .help("Width and height of each thumbnail cell")
+                .num_args(1)
+                .default_value("240")
+                .value_parser(clap::builder::RangedU64ValueParser::<u32>::new().range(16..)),
+        )
+        .arg(
+            Arg::new("gap")
+                .long("gap")
+                .value_name("PIXELS")
+                .help("Space between and around cells")
+                .num_args(1)
+                .default_value("12")
+                .value_parser(clap::value_parser!(u32)),
+        )
+        .arg(
+            Arg::new("background")
+                .long("background")
+                .value_name("COLOR")
+                .help("Background color")
+                .num_args(1)
+                .default_value("#ffffff")
+                .value_parser(|value: &str| value.parse::<Rgb>()),
+        )
+        .arg(
+            Arg::new("no_labels")
+                .long("no-labels")
+                .help("Don't print item names under the thumbnails")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(list::stdin_arg())
+        .args(list::query_args())
+        .args(list::filter::args())
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let out = matches.get_one::<String>("out").unwrap();
+    let columns = *matches.get_one::<u32>("columns").unwrap();
+    let cell = *matches.get_one::<u32>("cell").unwrap();
+    let gap = *matches.get_one::<u32>("gap").unwrap();
+    let Rgb(r, g, b) = *matches.get_one::<Rgb>("background").unwrap();
+    let background = Rgba([r, g, b, 255]);
+    let labels = !matches.get_flag("no_labels");
+
+    let library_data = client.library().info().await?.data;
+    let library = LibraryDir::new(&library_data.library.path);
+    let item_filter = ItemFilter::from_matches(matches)?;
+    let items = list::select_items(client, matches, &item_filter, &library).await?;
+    if items.is_empty() {
+        return Err("No matching items".into());
+    }
+
+    // Dark text on light backgrounds and the other way around
+    let luminance = 0.299 * r as f64 + 0.587 * g as f64 + 0.114 * b as f64;
+    let text_color = if luminance > 128.0 { Rgba([40, 40, 40, 255]) } else { Rgba([220, 220, 220, 255]) };
+    let placeholder = Rgba([160, 160, 160, 255]);
+
+    let label_height = if labels { font::GLYPH_HEIGHT * LABEL_SCALE + gap / 2 + 4 } else { 0 };
+    let rows = (items.len() as u32).div_ceil(columns);
+    let grid_columns = columns.min(items.len() as u32);
+    let width = grid_columns * cell + (grid_columns + 1) * gap;
+    let height = rows * (cell + label_height) + (rows + 1) * gap;
+    let mut sheet = RgbaImage::from_pixel(width, height, background);
+
+    let mut progress = Progress::new(items.len(), "compositing", matches);
+    for (index, item) in items.iter().enumerate() {
+        progress.inc();
+        let x = gap + (index as u32 % columns) * (cell + gap);
+        let y = gap + (index as u32 / columns) * (cell + label_height + gap);
+
+        let thumbnail = library
+            .item_thumbnail(&item.id, &item.name)
+            .or_else(|| library.item_file(&item.id, &item.name, &item.ext))
+            .and_then(|path| image::open(path).ok());
+        match thumbnail {
+            Some(thumbnail) => {
+                let thumbnail = thumbnail.resize(cell, cell, FilterType::Triangle).to_rgba8();
+                // Center inside the square cell
+                let offset_x = x + (cell - thumbnail.width()) / 2;
+                let offset_y = y + (cell - thumbnail.height()) / 2;
+                imageops::overlay(&mut sheet, &thumbnail, offset_x as i64, offset_y as i64);
+            }
+            None => {
+                let frame = RgbaImage::from_pixel(cell, cell, placeholder);
+                imageops::overlay(&mut sheet, &frame, x as i64, y as i64);
+                let ext = font::fit_text(&item.ext.to_uppercase(), cell, LABEL_SCALE);
+                let text_x = x + cell.saturating_sub(font::text_width(&ext, LABEL_SCALE)) / 2;
+                font::draw_text(&mut sheet, text_x, y + cell / 2, &ext, LABEL_SCALE, background);
+            }
+        }
+
+        if labels {
+            let label = font::fit_text(&item.name, cell, LABEL_SCALE);
+            let text_x = x + cell.saturating_sub(font::text_width(&label, LABEL_SCALE)) / 2;
+            font::draw_text(&mut sheet, text_x, y + cell + 4, &label, LABEL_SCALE, text_color);
+        }
+    }
+    progress.finish();
+
+    // JPEG has no alpha channel
+    let sheet = image::DynamicImage::ImageRgba8(sheet).to_rgb8();
+    sheet.save(out).map_err(|e| format!("Failed to write {}: {}", out, e))?;
+    println!("Wrote {} items to {} ({}x{})", items.len(), out, width, height);
+    Ok(())
+}
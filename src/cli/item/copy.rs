@@ -0,0 +1,62 @@
+use crate::cli::system;
+use crate::lib::client::EagleClient;
+use crate::lib::library::LibraryDir;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+
+pub fn build() -> Command {
+    Command::new("copy")
+        .about("Copy the file path or the image of items to the clipboard")
+        .arg(
+            Arg::new("ids")
+                .value_name("ID")
+                .help("Item ids")
+                .required(true)
+                .num_args(1..),
+        )
+        .arg(
+            Arg::new("path")
+                .short('p')
+                .long("path")
+                .help("Copy the file paths, one per line (default)")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("image"),
+        )
+        .arg(
+            Arg::new("image")
+                .short('i')
+                .long("image")
+                .help("Copy the image data itself (a single item)")
+                .action(ArgAction::SetTrue),
+        )
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let ids: Vec<&String> = matches.get_many::<String>("ids").unwrap().collect();
+    let library_data = client.library().info().await?.data;
+    let library = LibraryDir::new(&library_data.library.path);
+
+    let mut paths = Vec::new();
+    for id in &ids {
+        let item = library.item(id)?;
+        let path = library
+            .item_file(&item.id, &item.name, &item.ext)
+            .ok_or_else(|| format!("No file found for item {}", id))?;
+        paths.push(path);
+    }
+
+    if matches.get_flag("image") {
+        if paths.len() > 1 {
+            return Err("The clipboard holds a single image, pass one id with --image".into());
+        }
+        system::copy_image(&paths[0])?;
+        eprintln!("Copied image of {}", ids[0]);
+    } else {
+        let text: Vec<String> = paths.iter().map(|path| path.display().to_string()).collect();
+        system::copy_text(&text.join("\n"))?;
+        eprintln!("Copied {} path(s)", paths.len());
+    }
+    Ok(())
+}
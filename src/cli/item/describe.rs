@@ -0,0 +1,224 @@
+use crate::cli::graphics::png_base64;
+use crate::cli::http::HttpClient;
+use crate::cli::item::list::{self, filter::ItemFilter};
+use crate::cli::progress::Progress;
+use crate::cli::report::{self, Failures};
+use crate::cli::stats;
+use crate::lib::client::EagleClient;
+use crate::lib::library::LibraryDir;
+use crate::lib::types::{ItemListData, UpdateItemParams};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+const EMBEDDINGS_FILE_NAME: &str = "embeddings.json";
+
+/// Longest side of the image sent to the endpoint
+const IMAGE_SIZE: u32 = 512;
+
+/// Embedding vectors by item id
+pub type Embeddings = BTreeMap<String, Vec<f32>>;
+
+/// `--endpoint` argument shared with `item semantic-search`.
+pub fn endpoint_arg() -> Arg {
+    Arg::new("endpoint")
+        .long("endpoint")
+        .value_name("URL")
+        .help("Captioning/embedding service to POST to")
+        .required(true)
+        .num_args(1)
+        .env("EAGLE_EYE_DESCRIBE_ENDPOINT")
+}
+
+pub fn build() -> Command {
+    Command::new("describe")
+        .about("Caption items with an external service and cache their embeddings")
+        .long_about(
+            "Caption items with an external service and cache their embeddings\n\n\
+             Each item's thumbnail is POSTed to the endpoint as JSON:\n  \
+             {\"id\", \"name\", \"ext\", \"tags\", \"annotation\", \"image\": <base64 PNG>}\n\
+             The service answers with any of:\n  \
+             {\"caption\": \"...\", \"tags\": [\"...\"], \"embedding\": [0.1, ...]}\n\
+             Captions become the annotation, tags are added to the item, and embeddings are \
+             kept locally for `item semantic-search`.",
+        )
+        .arg(endpoint_arg())
+        .arg(
+            Arg::new("store")
+                .long("store")
+                .value_name("WHAT")
+                .help("What to write back to the items; embeddings are cached regardless")
+                .num_args(1)
+                .default_value("both")
+                .value_parser(["annotation", "tags", "both", "none"]),
+        )
+        .arg(
+            Arg::new("append")
+                .long("append")
+                .help("Append the caption to the existing annotation instead of replacing it")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("dry_run")
+                .long("dry-run")
+                .help("Print the service's answers without updating items or the cache")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(list::stdin_arg())
+        .args(list::query_args())
+        .args(list::filter::args())
+        .args(report::args())
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let endpoint = matches.get_one::<String>("endpoint").unwrap();
+    let store = matches.get_one::<String>("store").unwrap().as_str();
+    let store_annotation = matches!(store, "annotation" | "both");
+    let store_tags = matches!(store, "tags" | "both");
+    let append = matches.get_flag("append");
+    let dry_run = matches.get_flag("dry_run");
+
+    let library_data = client.library().info().await?.data;
+    let library = LibraryDir::new(&library_data.library.path);
+    let item_filter = ItemFilter::from_matches(matches)?;
+    let items = list::select_items(client, matches, &item_filter, &library).await?;
+
+    let http = HttpClient::new();
+    let mut embeddings = load_embeddings(&library_data.library.path)?;
+    let mut progress = Progress::new(items.len(), "describing", matches);
+    let mut described = 0;
+    let mut failures = Failures::default();
+    for item in &items {
+        progress.inc();
+        let answer = match describe(&http, endpoint, &library, item).await {
+            Ok(answer) => answer,
+            Err(error) => {
+                progress.finish();
+                eprintln!("Failed to describe {}: {}", item.id, error);
+                failures.add(&item.id, error);
+                continue;
+            }
+        };
+
+        if dry_run {
+            progress.finish();
+            println!("{}: {}", item.id, answer);
+            described += 1;
+            continue;
+        }
+
+        if let Some(embedding) = answer.get("embedding").and_then(parse_embedding) {
+            embeddings.insert(item.id.clone(), embedding);
+        }
+
+        let mut params = UpdateItemParams {
+            id: item.id.clone(),
+            ..Default::default()
+        };
+        if let Some(caption) = answer.get("caption").and_then(Value::as_str).map(str::trim) {
+            if store_annotation && !caption.is_empty() {
+                params.annotation = Some(match append && !item.annotation.is_empty() {
+                    true => format!("{}\n\n{}", item.annotation, caption),
+                    false => caption.to_string(),
+                });
+            }
+        }
+        if let Some(tags) = answer.get("tags").and_then(Value::as_array) {
+            let mut merged = item.tags.clone();
+            for tag in tags.iter().filter_map(Value::as_str).map(str::trim) {
+                if !tag.is_empty() && !merged.iter().any(|existing| existing == tag) {
+                    merged.push(tag.to_string());
+                }
+            }
+            if store_tags && merged.len() > item.tags.len() {
+                params.tags = Some(merged);
+            }
+        }
+        if params.annotation.is_none() && params.tags.is_none() {
+            described += 1;
+            continue;
+        }
+        match client.item().update(&params).await {
+            Ok(_) => described += 1,
+            Err(error) => {
+                progress.finish();
+                eprintln!("Failed to update {}: {}", item.id, error);
+                failures.add(&item.id, error);
+            }
+        }
+    }
+    progress.finish();
+
+    if !dry_run {
+        save_embeddings(&library_data.library.path, embeddings)?;
+    }
+    eprintln!("Described {} items ({} failed)", described, failures.len());
+    report::finish(matches, "item describe", described, failures)
+}
+
+/// Send an item's thumbnail (or file, for images without one) to the endpoint
+async fn describe(
+    http: &HttpClient,
+    endpoint: &str,
+    library: &LibraryDir,
+    item: &ItemListData,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let path = library
+        .item_thumbnail(&item.id, &item.name)
+        .or_else(|| library.item_file(&item.id, &item.name, &item.ext))
+        .ok_or("no thumbnail or file")?;
+    let image = image::open(&path)?.thumbnail(IMAGE_SIZE, IMAGE_SIZE);
+    let body = json!({
+        "id": item.id,
+        "name": item.name,
+        "ext": item.ext,
+        "tags": item.tags,
+        "annotation": item.annotation,
+        "image": png_base64(&image)?,
+    });
+    http.post_json(endpoint, &body).await
+}
+
+pub fn parse_embedding(value: &Value) -> Option<Vec<f32>> {
+    value
+        .as_array()?
+        .iter()
+        .map(|number| number.as_f64().map(|number| number as f32))
+        .collect()
+}
+
+/// Cache file holding the embeddings of every library, keyed by library path
+fn embeddings_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(stats::data_dir()
+        .ok_or("Can't locate the data directory; set EAGLE_EYE_DATA_DIR")?
+        .join(EMBEDDINGS_FILE_NAME))
+}
+
+fn load_cache() -> Result<BTreeMap<String, Embeddings>, Box<dyn std::error::Error>> {
+    match fs::read(embeddings_path()?) {
+        Ok(data) => Ok(serde_json::from_slice(&data)?),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(error) => Err(error.into()),
+    }
+}
+
+/// Cached embeddings of the items in the library at `library_path`.
+pub fn load_embeddings(library_path: &str) -> Result<Embeddings, Box<dyn std::error::Error>> {
+    Ok(load_cache()?.remove(library_path).unwrap_or_default())
+}
+
+fn save_embeddings(library_path: &str, embeddings: Embeddings) -> Result<(), Box<dyn std::error::Error>> {
+    let path = embeddings_path()?;
+    let mut cache = load_cache()?;
+    cache.insert(library_path.to_string(), embeddings);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, serde_json::to_vec(&cache)?)?;
+    Ok(())
+}
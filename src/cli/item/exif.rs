@@ -0,0 +1,59 @@
+use crate::cli::exif::ExifInfo;
+use crate::cli::{exit, output};
+use crate::lib::client::EagleClient;
+use crate::lib::library::LibraryDir;
+use clap::{Arg, ArgMatches, Command};
+use serde_json::{Map, Value};
+
+pub fn build() -> Command {
+    Command::new("exif")
+        .about("Show the camera, lens, exposure, capture date, and GPS position stored in item files")
+        .arg(
+            Arg::new("id")
+                .value_name("ID")
+                .help("Items to read")
+                .required(true)
+                .num_args(1..),
+        )
+        .args(output::args())
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let library_data = client.library().info().await?.data;
+    let library = LibraryDir::new(&library_data.library.path);
+
+    let ids: Vec<&String> = matches.get_many::<String>("id").unwrap().collect();
+    let mut rows = Vec::new();
+    for id in &ids {
+        match read(&library, id) {
+            Ok(row) => rows.push(row),
+            Err(error) => eprintln!("{}", error),
+        }
+    }
+    let failed = ids.len() - rows.len();
+
+    // A single item reads best as one record; several as rows of a table
+    match rows.len() {
+        0 => {}
+        1 if ids.len() == 1 => output::output(&rows.remove(0), matches)?,
+        _ => output::output(&Value::Array(rows), matches)?,
+    }
+    exit::outcome(ids.len() - failed, failed)
+}
+
+/// EXIF fields of an item's file, led by the item id
+fn read(library: &LibraryDir, id: &str) -> Result<Value, Box<dyn std::error::Error>> {
+    let item = library.item(id)?;
+    let path = library
+        .item_file(&item.id, &item.name, &item.ext)
+        .ok_or_else(|| format!("No file found for item {}", item.id))?;
+    let mut row = Map::new();
+    row.insert("id".to_string(), Value::from(item.id.clone()));
+    if let Value::Object(info) = serde_json::to_value(ExifInfo::read(&path)?)? {
+        row.extend(info);
+    }
+    Ok(Value::Object(row))
+}
@@ -0,0 +1,345 @@
+use crate::cli::datetime::{self, TimeZone};
+use crate::cli::folder::folder_paths;
+use crate::cli::ignore::{self, IgnoreList};
+use crate::cli::item::list::{self, filter::ItemFilter};
+use crate::cli::progress::Progress;
+use crate::lib::client::EagleClient;
+use crate::lib::library::LibraryDir;
+use crate::lib::types::ItemListData;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+
+pub const DEFAULT_TEMPLATE: &str = "{name}.{ext}";
+
+pub fn build() -> Command {
+    Command::new("export")
+        .about("Copy items out of the library")
+        .arg(
+            Arg::new("dest")
+                .short('d')
+                .long("dest")
+                .value_name("DIR")
+                .help("Directory to export into")
+                .required(true)
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("template")
+                .long("template")
+                .value_name("TEMPLATE")
+                .help("Destination path relative to DIR. Fields: {id} {name} {ext} {folder} {tag} {tags} {size} {width} {height} {date} {year} {month}")
+                .num_args(1)
+                .default_value(DEFAULT_TEMPLATE)
+                .value_parser(|value: &str| value.parse::<FileTemplate>()),
+        )
+        .arg(
+            Arg::new("on_conflict")
+                .long("on-conflict")
+                .value_name("ACTION")
+                .help("What to do when the destination file exists")
+                .num_args(1)
+                .default_value("rename")
+                .value_parser(["rename", "skip", "overwrite"]),
+        )
+        .arg(
+            Arg::new("hardlink")
+                .long("hardlink")
+                .help("Hard-link instead of copying (same volume only)")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("symlink"),
+        )
+        .arg(
+            Arg::new("symlink")
+                .long("symlink")
+                .help("Symlink to the library files instead of copying")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("dry_run")
+                .long("dry-run")
+                .help("Print what would be exported without writing anything")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(list::stdin_arg())
+        .args(list::query_args())
+        .args(list::filter::args())
+        .args(ignore::args())
+}
+
+/// A field of an item usable in a file template
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    Id,
+    Name,
+    Ext,
+    Folder,
+    Tag,
+    Tags,
+    Size,
+    Width,
+    Height,
+    Date,
+    Year,
+    Month,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Literal(String),
+    Field(Field),
+}
+
+/// Destination path template such as `{folder}/{name}-{id}.{ext}`
+#[derive(Debug, Clone)]
+pub struct FileTemplate {
+    parts: Vec<Part>,
+}
+
+impl FromStr for FileTemplate {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut parts = Vec::new();
+        let mut rest = value;
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                parts.push(Part::Literal(rest[..start].to_string()));
+            }
+            let end = rest[start..]
+                .find('}')
+                .ok_or_else(|| format!("unclosed {{ in template: {}", value))?;
+            let field = match &rest[start + 1..start + end] {
+                "id" => Field::Id,
+                "name" => Field::Name,
+                "ext" => Field::Ext,
+                "folder" => Field::Folder,
+                "tag" => Field::Tag,
+                "tags" => Field::Tags,
+                "size" => Field::Size,
+                "width" => Field::Width,
+                "height" => Field::Height,
+                "date" => Field::Date,
+                "year" => Field::Year,
+                "month" => Field::Month,
+                other => return Err(format!("unknown template field: {{{}}}", other)),
+            };
+            parts.push(Part::Field(field));
+            rest = &rest[start + end + 1..];
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Literal(rest.to_string()));
+        }
+        if parts.is_empty() {
+            return Err("empty template".to_string());
+        }
+        Ok(FileTemplate { parts })
+    }
+}
+
+impl FileTemplate {
+    /// Whether rendering needs the folder paths (`{folder}`)
+    pub fn uses_folders(&self) -> bool {
+        self.parts.contains(&Part::Field(Field::Folder))
+    }
+
+    /// Render the relative destination path of an item.
+    ///
+    /// Field values are sanitized so they can't add path components, except `{folder}`,
+    /// which expands to the nested folder path.
+    pub fn render(&self, item: &ItemListData, folders: &HashMap<String, String>, tz: &TimeZone) -> PathBuf {
+        let date = tz.format_millis(item.modification_time as i64);
+        let mut path = String::new();
+        for part in &self.parts {
+            let value = match part {
+                Part::Literal(literal) => {
+                    path.push_str(literal);
+                    continue;
+                }
+                Part::Field(Field::Folder) => {
+                    let folder = item
+                        .folders
+                        .iter()
+                        .flatten()
+                        .find_map(|id| folders.get(id))
+                        .map(|folder| folder.split('/').map(sanitize).collect::<Vec<_>>().join("/"))
+                        .unwrap_or_else(|| "Unfiled".to_string());
+                    path.push_str(&folder);
+                    continue;
+                }
+                Part::Field(Field::Id) => item.id.clone(),
+                Part::Field(Field::Name) => item.name.clone(),
+                Part::Field(Field::Ext) => item.ext.clone(),
+                Part::Field(Field::Tag) => item.tags.first().cloned().unwrap_or_else(|| "untagged".to_string()),
+                Part::Field(Field::Tags) => item.tags.join(","),
+                Part::Field(Field::Size) => item.size.to_string(),
+                Part::Field(Field::Width) => item.width.unwrap_or(0).to_string(),
+                Part::Field(Field::Height) => item.height.unwrap_or(0).to_string(),
+                Part::Field(Field::Date) => date.get(..10).unwrap_or_default().to_string(),
+                Part::Field(Field::Year) => date.get(..4).unwrap_or_default().to_string(),
+                Part::Field(Field::Month) => date.get(5..7).unwrap_or_default().to_string(),
+            };
+            path.push_str(&sanitize(&value));
+        }
+        PathBuf::from(path)
+    }
+}
+
+/// Replace characters that are path separators or invalid in file names on common systems.
+///
+/// An empty value, `.` or `..` becomes `_`, so it can't stand for the current or parent directory.
+pub fn sanitize(value: &str) -> String {
+    let sanitized: String = value
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    match sanitized.as_str() {
+        "" | "." | ".." => "_".to_string(),
+        _ => sanitized,
+    }
+}
+
+/// Whether joining `relative` onto a directory stays inside it: no `..`, root or prefix components
+pub fn stays_inside(relative: &Path) -> bool {
+    relative
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+    Copy,
+    Hardlink,
+    Symlink,
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let dest = PathBuf::from(matches.get_one::<String>("dest").unwrap());
+    let template = matches.get_one::<FileTemplate>("template").unwrap();
+    let on_conflict = matches.get_one::<String>("on_conflict").unwrap().as_str();
+    let dry_run = matches.get_flag("dry_run");
+    let mode = if matches.get_flag("hardlink") {
+        Mode::Hardlink
+    } else if matches.get_flag("symlink") {
+        Mode::Symlink
+    } else {
+        Mode::Copy
+    };
+    let tz = datetime::from_matches(matches);
+    let ignore_list = IgnoreList::from_matches(matches, &dest)?;
+
+    let library_data = client.library().info().await?.data;
+    let library = LibraryDir::new(&library_data.library.path);
+    let item_filter = ItemFilter::from_matches(matches)?;
+    let items = list::select_items(client, matches, &item_filter, &library).await?;
+
+    let folders = match template.uses_folders() {
+        true => folder_paths(&client.folder().list().await?.data),
+        false => HashMap::new(),
+    };
+
+    let mut progress = Progress::new(items.len(), "exporting", matches);
+    let (mut exported, mut skipped, mut failed) = (0, 0, 0);
+    // Targets chosen earlier in this run, which a dry run doesn't create
+    let mut claimed = HashSet::new();
+    for item in &items {
+        progress.inc();
+        let relative = template.render(item, &folders, &tz);
+        if !stays_inside(&relative) {
+            progress.finish();
+            eprintln!("Not exporting {}: {} is outside {}", item.id, relative.display(), dest.display());
+            failed += 1;
+            continue;
+        }
+        if ignore_list.is_ignored(&relative, false) {
+            skipped += 1;
+            continue;
+        }
+        let Some(source) = library.item_file(&item.id, &item.name, &item.ext) else {
+            progress.finish();
+            eprintln!("No file found for item {}", item.id);
+            failed += 1;
+            continue;
+        };
+
+        let mut target = dest.join(&relative);
+        if target.symlink_metadata().is_ok() || claimed.contains(&target) {
+            match on_conflict {
+                "skip" => {
+                    skipped += 1;
+                    continue;
+                }
+                "overwrite" => {}
+                _ => target = free_path(&target, &claimed),
+            }
+        }
+        claimed.insert(target.clone());
+
+        if dry_run {
+            println!("{} -> {}", source.display(), target.display());
+            exported += 1;
+            continue;
+        }
+        match export_file(&source, &target, mode) {
+            Ok(()) => exported += 1,
+            Err(error) => {
+                progress.finish();
+                eprintln!("Failed to export {}: {}", item.id, error);
+                failed += 1;
+            }
+        }
+    }
+    progress.finish();
+
+    let action = if dry_run { "Would export" } else { "Exported" };
+    println!("{} {} items to {} ({} skipped, {} failed)", action, exported, dest.display(), skipped, failed);
+    if failed > 0 {
+        return Err(format!("{} items failed to export", failed).into());
+    }
+    Ok(())
+}
+
+fn export_file(source: &Path, target: &Path, mode: Mode) -> std::io::Result<()> {
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if target.symlink_metadata().is_ok() {
+        // Links can't replace an existing file in place, and copying onto a link left by an
+        // earlier --symlink or --hardlink export would write through it into the library
+        fs::remove_file(target)?;
+    }
+    match mode {
+        Mode::Copy => fs::copy(source, target).map(|_| ()),
+        Mode::Hardlink => fs::hard_link(source, target),
+        Mode::Symlink => symlink(source, target),
+    }
+}
+
+#[cfg(unix)]
+pub fn symlink(source: &Path, target: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(source, target)
+}
+
+#[cfg(windows)]
+pub fn symlink(source: &Path, target: &Path) -> std::io::Result<()> {
+    std::os::windows::fs::symlink_file(source, target)
+}
+
+/// First `<stem>_<n>.<ext>` next to `path` that doesn't exist yet and isn't in `claimed`
+pub fn free_path(path: &Path, claimed: &HashSet<PathBuf>) -> PathBuf {
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+    let ext = path.extension().map(|ext| format!(".{}", ext.to_string_lossy())).unwrap_or_default();
+    (1..)
+        .map(|n| path.with_file_name(format!("{}_{}{}", stem, n, ext)))
+        .find(|candidate| candidate.symlink_metadata().is_err() && !claimed.contains(candidate))
+        .unwrap()
+}
@@ -0,0 +1,142 @@
+use crate::cli::item::list::{self, filter::ItemFilter};
+use crate::lib::client::EagleClient;
+use crate::lib::library::LibraryDir;
+use crate::lib::types::{Child, ItemListData};
+use clap::{Arg, ArgMatches, Command};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+pub fn build() -> Command {
+    Command::new("export-bookmarks")
+        .about("Write items with a source URL as a Netscape bookmark file")
+        .arg(
+            Arg::new("out")
+                .short('o')
+                .long("out")
+                .value_name("FILE")
+                .help("File to write; prints to stdout when omitted")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("title")
+                .long("title")
+                .value_name("TITLE")
+                .help("Title of the bookmark file")
+                .num_args(1)
+                .default_value("Eagle"),
+        )
+        .arg(list::stdin_arg())
+        .args(list::query_args())
+        .args(list::filter::args())
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let title = matches.get_one::<String>("title").unwrap();
+
+    let library_data = client.library().info().await?.data;
+    let library = LibraryDir::new(&library_data.library.path);
+    let item_filter = ItemFilter::from_matches(matches)?;
+    let items: Vec<ItemListData> = list::select_items(client, matches, &item_filter, &library)
+        .await?
+        .into_iter()
+        .filter(|item| !item.url.trim().is_empty())
+        .collect();
+    let folders = client.folder().list().await?.data;
+
+    // An item in several folders is bookmarked in each of them, as Eagle shows it
+    let mut by_folder: HashMap<&str, Vec<&ItemListData>> = HashMap::new();
+    let mut unfiled = Vec::new();
+    for item in &items {
+        match item.folders.as_deref() {
+            Some(ids) if !ids.is_empty() => {
+                for id in ids {
+                    by_folder.entry(id.as_str()).or_default().push(item);
+                }
+            }
+            _ => unfiled.push(item),
+        }
+    }
+
+    let mut html = String::new();
+    writeln!(html, "<!DOCTYPE NETSCAPE-Bookmark-file-1>")?;
+    writeln!(html, "<!-- This is an automatically generated file. It will be read and overwritten. DO NOT EDIT! -->")?;
+    writeln!(html, r#"<META HTTP-EQUIV="Content-Type" CONTENT="text/html; charset=UTF-8">"#)?;
+    writeln!(html, "<TITLE>{}</TITLE>", escape(title))?;
+    writeln!(html, "<H1>{}</H1>", escape(title))?;
+    writeln!(html, "<DL><p>")?;
+    for folder in &folders {
+        write_folder(&mut html, folder, &by_folder, 1)?;
+    }
+    for item in &unfiled {
+        write_item(&mut html, item, 1)?;
+    }
+    writeln!(html, "</DL><p>")?;
+
+    match matches.get_one::<String>("out") {
+        Some(out) => {
+            std::fs::write(out, html)?;
+            eprintln!("Wrote {} bookmarks to {}", items.len(), out);
+        }
+        None => print!("{}", html),
+    }
+    Ok(())
+}
+
+/// Write a folder with its bookmarks and subfolders; folders without any bookmark are left out
+fn write_folder(
+    html: &mut String,
+    folder: &Child,
+    by_folder: &HashMap<&str, Vec<&ItemListData>>,
+    depth: usize,
+) -> std::fmt::Result {
+    if !has_bookmarks(folder, by_folder) {
+        return Ok(());
+    }
+    let indent = "    ".repeat(depth);
+    writeln!(
+        html,
+        r#"{}<DT><H3 ADD_DATE="{}">{}</H3>"#,
+        indent,
+        folder.modification_time / 1000,
+        escape(&folder.name)
+    )?;
+    writeln!(html, "{}<DL><p>", indent)?;
+    for child in &folder.children {
+        write_folder(html, child, by_folder, depth + 1)?;
+    }
+    for item in by_folder.get(folder.id.as_str()).into_iter().flatten() {
+        write_item(html, item, depth + 1)?;
+    }
+    writeln!(html, "{}</DL><p>", indent)
+}
+
+fn has_bookmarks(folder: &Child, by_folder: &HashMap<&str, Vec<&ItemListData>>) -> bool {
+    by_folder.contains_key(folder.id.as_str()) || folder.children.iter().any(|child| has_bookmarks(child, by_folder))
+}
+
+fn write_item(html: &mut String, item: &ItemListData, depth: usize) -> std::fmt::Result {
+    let indent = "    ".repeat(depth);
+    write!(html, r#"{}<DT><A HREF="{}" ADD_DATE="{}""#, indent, escape(item.url.trim()), item.modification_time / 1000)?;
+    if let Some(last_modified) = item.last_modified {
+        write!(html, r#" LAST_MODIFIED="{}""#, last_modified / 1000)?;
+    }
+    if !item.tags.is_empty() {
+        write!(html, r#" TAGS="{}""#, escape(&item.tags.join(",")))?;
+    }
+    writeln!(html, ">{}</A>", escape(&item.name))?;
+    if !item.annotation.trim().is_empty() {
+        writeln!(html, "{}<DD>{}", indent, escape(item.annotation.trim()))?;
+    }
+    Ok(())
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,171 @@
+use crate::cli::item::list::{self, filter::{ItemFilter, Media}};
+use crate::cli::output;
+use crate::lib::client::EagleClient;
+use crate::lib::library::LibraryDir;
+use crate::lib::types::ItemListData;
+use ab_glyph::{point, Font, FontRef, PxScale, ScaleFont};
+use clap::{ArgMatches, Command};
+use image::{Rgba, RgbaImage};
+use rayon::prelude::*;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+use ttf_parser::{name_id, Face};
+
+/// Family of the fonts whose file can't be read
+const UNKNOWN_FAMILY: &str = "(unknown)";
+
+pub fn build() -> Command {
+    Command::new("fonts")
+        .about("Sum up font items by family")
+        .long_about(
+            "Sum up font items by family, with their styles and glyph counts.\n\n\
+             Family, style and glyph count come from the item when Eagle has them, otherwise \
+             from the font file. Collections (.ttc) are read by their first font; WOFF files \
+             can't be read.",
+        )
+        .arg(list::stdin_arg())
+        .args(list::query_args())
+        .args(list::filter::args())
+        .args(output::args())
+}
+
+/// What a font file says about itself
+#[derive(Debug, Clone, PartialEq)]
+pub struct FontFace {
+    pub family: String,
+    pub style: String,
+    pub glyphs: u64,
+}
+
+/// Read the family, style and glyph count of a font file.
+pub fn read_face(path: &Path) -> Result<FontFace, String> {
+    let data = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let face = Face::parse(&data, 0).map_err(|e| format!("Can't read font {}: {}", path.display(), e))?;
+    // Typographic names group the styles beyond regular, bold and italic into one family
+    let name = |ids: [u16; 2]| {
+        ids.iter().find_map(|id| {
+            face.names()
+                .into_iter()
+                .filter(|name| name.name_id == *id)
+                .find_map(|name| name.to_string())
+        })
+    };
+    Ok(FontFace {
+        family: name([name_id::TYPOGRAPHIC_FAMILY, name_id::FAMILY]).unwrap_or_else(|| UNKNOWN_FAMILY.to_string()),
+        style: name([name_id::TYPOGRAPHIC_SUBFAMILY, name_id::SUBFAMILY]).unwrap_or_default(),
+        glyphs: face.number_of_glyphs() as u64,
+    })
+}
+
+/// Render `text` in the font at `path`, black on white, `size` pixels high.
+pub fn render_sample(path: &Path, text: &str, size: f32) -> Result<RgbaImage, String> {
+    let data = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let font = FontRef::try_from_slice(&data).map_err(|e| format!("Can't read font {}: {}", path.display(), e))?;
+    let font = font.as_scaled(PxScale::from(size));
+    let margin = size / 4.0;
+
+    let mut glyphs = Vec::new();
+    let mut x = margin;
+    let mut previous = None;
+    for c in text.chars() {
+        let id = font.glyph_id(c);
+        if let Some(previous) = previous {
+            x += font.kern(previous, id);
+        }
+        glyphs.push(id.with_scale_and_position(size, point(x, margin + font.ascent())));
+        x += font.h_advance(id);
+        previous = Some(id);
+    }
+
+    let width = (x + margin).ceil().max(1.0) as u32;
+    let height = (font.height() + 2.0 * margin).ceil().max(1.0) as u32;
+    let mut image = RgbaImage::from_pixel(width, height, Rgba([255, 255, 255, 255]));
+    for glyph in glyphs {
+        let Some(outlined) = font.outline_glyph(glyph) else {
+            continue;
+        };
+        let bounds = outlined.px_bounds();
+        outlined.draw(|gx, gy, coverage| {
+            let (x, y) = (bounds.min.x as i64 + gx as i64, bounds.min.y as i64 + gy as i64);
+            if x < 0 || y < 0 || x >= width as i64 || y >= height as i64 {
+                return;
+            }
+            let shade = (255.0 * (1.0 - coverage.clamp(0.0, 1.0))) as u8;
+            let pixel = image.get_pixel_mut(x as u32, y as u32);
+            for channel in &mut pixel.0[..3] {
+                *channel = (*channel).min(shade);
+            }
+        });
+    }
+    Ok(image)
+}
+
+/// The face of a font item, preferring what Eagle recorded over reading the file
+fn face_of(library: &LibraryDir, item: &ItemListData) -> Result<FontFace, String> {
+    if let (Some(family), Some(glyphs)) = (&item.font_family, item.glyph_count) {
+        return Ok(FontFace {
+            family: family.clone(),
+            style: item.font_style.clone().unwrap_or_default(),
+            glyphs,
+        });
+    }
+    let path = library
+        .item_file(&item.id, &item.name, &item.ext)
+        .ok_or_else(|| format!("No file found for item {}", item.id))?;
+    read_face(&path)
+}
+
+#[derive(Default)]
+struct Family {
+    fonts: u64,
+    styles: BTreeSet<String>,
+    /// Most glyphs of any font in the family
+    glyphs: u64,
+    size: u64,
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let library = LibraryDir::new(&client.library().info().await?.data.library.path);
+    let item_filter = ItemFilter::from_matches(matches)?;
+    let items: Vec<ItemListData> = list::select_items(client, matches, &item_filter, &library)
+        .await?
+        .into_iter()
+        .filter(|item| !item.is_deleted && Media::of(&item.ext) == Some(Media::Font))
+        .collect();
+
+    let faces: Vec<(&ItemListData, Result<FontFace, String>)> =
+        items.par_iter().map(|item| (item, face_of(&library, item))).collect();
+    let mut families: BTreeMap<String, Family> = BTreeMap::new();
+    for (item, face) in faces {
+        let face = face.unwrap_or_else(|e| {
+            eprintln!("Warning: {}", e);
+            FontFace { family: UNKNOWN_FAMILY.to_string(), style: String::new(), glyphs: 0 }
+        });
+        let family = families.entry(face.family).or_default();
+        family.fonts += 1;
+        family.glyphs = family.glyphs.max(face.glyphs);
+        family.size += item.size;
+        if !face.style.is_empty() {
+            family.styles.insert(face.style);
+        }
+    }
+
+    let rows: Vec<Value> = families
+        .into_iter()
+        .map(|(name, family)| {
+            json!({
+                "family": name,
+                "fonts": family.fonts,
+                "styles": family.styles,
+                "glyphs": family.glyphs,
+                "size": family.size,
+            })
+        })
+        .collect();
+    output::output(&Value::Array(rows), matches)
+}
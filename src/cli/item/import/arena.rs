@@ -0,0 +1,55 @@
+use super::{fallback_title, parse_date, Entry};
+use serde_json::Value;
+
+/// Parse an Are.na channel as returned by `GET /v2/channels/<slug>` or `.../contents`:
+/// an object with a `contents` array of blocks, or the bare array.
+///
+/// Are.na has no tags, so the channel title becomes one. Text blocks carry nothing to
+/// import and are left out.
+pub fn parse(content: &str) -> Result<Vec<Entry>, Box<dyn std::error::Error>> {
+    let channel: Value = serde_json::from_str(content)?;
+    let blocks = match &channel {
+        Value::Array(blocks) => blocks,
+        _ => channel
+            .get("contents")
+            .and_then(Value::as_array)
+            .ok_or("Are.na export has no contents array")?,
+    };
+    let channel_tag = channel
+        .get("title")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|title| !title.is_empty());
+
+    let text = |block: &Value, key: &str| {
+        block
+            .get(key)
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(String::from)
+    };
+
+    let mut entries = Vec::new();
+    for block in blocks {
+        let image = block.pointer("/image/original/url").and_then(Value::as_str).map(String::from);
+        let source = block.pointer("/source/url").and_then(Value::as_str).map(String::from);
+        let attachment = block.pointer("/attachment/url").and_then(Value::as_str).map(String::from);
+        let Some(url) = source.or(attachment).or_else(|| image.clone()) else {
+            continue;
+        };
+        entries.push(Entry {
+            title: text(block, "title")
+                .or_else(|| text(block, "generated_title"))
+                .unwrap_or_else(|| fallback_title(&url)),
+            image,
+            tags: channel_tag.map(String::from).into_iter().collect(),
+            note: text(block, "description"),
+            created: text(block, "connected_at")
+                .or_else(|| text(block, "created_at"))
+                .and_then(|date| parse_date(&date)),
+            url,
+        });
+    }
+    Ok(entries)
+}
@@ -0,0 +1,267 @@
+use crate::cli::folder::resolve_folder;
+use crate::cli::ignore::{self, IgnoreList};
+use crate::cli::item::add_from_url::{existing_urls, normalize_url};
+use crate::cli::item::list::filter::split_list;
+use crate::cli::output;
+use crate::cli::progress::Progress;
+use crate::cli::report::{self, Failures};
+use crate::lib::client::EagleClient;
+use crate::lib::types::{AddBookmarkParams, AddFromUrlParams};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use serde_json::{json, Value};
+use std::path::Path;
+
+pub mod arena;
+pub mod pocket;
+pub mod raindrop;
+
+/// One saved reference read from an export file
+#[derive(Debug, Default, Clone)]
+pub struct Entry {
+    /// The saved page
+    pub url: String,
+    /// Image to import; entries without one become bookmarks of `url`
+    pub image: Option<String>,
+    pub title: String,
+    pub tags: Vec<String>,
+    pub note: Option<String>,
+    /// When it was saved, in epoch milliseconds
+    pub created: Option<u64>,
+}
+
+impl Entry {
+    /// The URL Eagle is asked to add: the image, or the page for bookmarks
+    pub fn target(&self) -> &str {
+        self.image.as_deref().unwrap_or(&self.url)
+    }
+
+    /// The target as a path for the skip-list: `host/path`, without scheme, query or fragment
+    pub fn ignore_path(&self) -> String {
+        let target = self.target();
+        let end = target.find(['?', '#']).unwrap_or(target.len());
+        fallback_title(&target[..end])
+    }
+}
+
+pub fn build() -> Command {
+    Command::new("import")
+        .about("Import a reference collection exported from Pocket, Raindrop.io or Are.na")
+        .long_about(
+            "Import a reference collection exported from Pocket, Raindrop.io or Are.na.\n\n\
+             Entries are checked against the .eagleignore next to the export file as `host/path` \
+             of their image (or page, for bookmarks), so `*.gif` or `/example.com/` leave them out.",
+        )
+        .arg(
+            Arg::new("file")
+                .value_name("FILE")
+                .help("Export file: Pocket HTML or CSV, Raindrop CSV, or Are.na channel JSON")
+                .required(true)
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_name("FORMAT")
+                .help("Service the file was exported from")
+                .required(true)
+                .num_args(1)
+                .value_parser(["pocket", "raindrop", "arena"]),
+        )
+        .arg(
+            Arg::new("folder")
+                .short('f')
+                .long("folder")
+                .value_name("FOLDER")
+                .help("Folder to import into, by id, path (Brand/Logos) or unique name")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("tags")
+                .short('t')
+                .long("tags")
+                .value_name("TAG")
+                .help("Tags added to every imported item. Comma separated")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("skip_existing")
+                .long("skip-existing")
+                .help("Leave out entries whose URL is already the source of an item")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("batch_size")
+                .long("batch-size")
+                .value_name("N")
+                .help("Images sent per /api/item/addFromURLs request")
+                .num_args(1)
+                .default_value("50")
+                .value_parser(clap::builder::RangedU64ValueParser::<usize>::new().range(1..)),
+        )
+        .arg(
+            Arg::new("dry_run")
+                .long("dry-run")
+                .help("List the entries that would be imported without adding anything")
+                .action(ArgAction::SetTrue),
+        )
+        .args(ignore::args())
+        .args(output::args())
+        .args(report::args())
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let file = matches.get_one::<String>("file").unwrap();
+    let format = matches.get_one::<String>("format").unwrap().as_str();
+    let batch_size = *matches.get_one::<usize>("batch_size").unwrap();
+
+    let content = std::fs::read_to_string(file).map_err(|e| format!("Failed to read {}: {}", file, e))?;
+    let mut entries = match format {
+        "pocket" => pocket::parse(&content)?,
+        "raindrop" => raindrop::parse(&content)?,
+        _ => arena::parse(&content)?,
+    };
+
+    let extra_tags = split_list(matches, "tags");
+    for entry in &mut entries {
+        for tag in &extra_tags {
+            if !entry.tags.contains(tag) {
+                entry.tags.push(tag.clone());
+            }
+        }
+    }
+
+    if let Some(targets) = report::resume_targets(matches)? {
+        entries.retain(|entry| targets.iter().any(|target| target == entry.target()));
+    }
+    let root = Path::new(file).parent().unwrap_or(Path::new(""));
+    let ignore_list = IgnoreList::from_matches(matches, root)?;
+    let before = entries.len();
+    entries.retain(|entry| !ignore_list.is_ignored(Path::new(&entry.ignore_path()), false));
+    if entries.len() < before {
+        eprintln!("Skipping {} ignored entries", before - entries.len());
+    }
+    let total = entries.len();
+    if matches.get_flag("skip_existing") {
+        let existing = existing_urls(client).await?;
+        entries.retain(|entry| {
+            let mut urls = std::iter::once(&entry.url).chain(entry.image.as_ref());
+            !urls.any(|url| existing.contains_key(&normalize_url(url)))
+        });
+    }
+    let skipped = total - entries.len();
+
+    if matches.get_flag("dry_run") {
+        let rows: Vec<Value> = entries
+            .iter()
+            .map(|entry| {
+                json!({
+                    "kind": if entry.image.is_some() { "image" } else { "bookmark" },
+                    "title": entry.title,
+                    "url": entry.url,
+                    "tags": entry.tags,
+                })
+            })
+            .collect();
+        return output::output(&Value::Array(rows), matches);
+    }
+
+    let folder_id = match matches.get_one::<String>("folder") {
+        Some(folder) => Some(resolve_folder(&client.folder().list().await?.data, folder)?),
+        None => None,
+    };
+
+    let mut progress = Progress::new(entries.len(), "importing", matches);
+    let (imported, failures) = add_entries(client, entries, folder_id.as_deref(), batch_size, &mut progress).await;
+    progress.finish();
+    println!(
+        "Imported {} of {} entries ({} already in the library, {} failed)",
+        imported,
+        total,
+        skipped,
+        failures.len()
+    );
+    report::finish(matches, "item import", imported, failures)
+}
+
+/// Add entries with an image through `addFromURLs` in batches of `batch_size`, and the others
+/// as bookmarks, advancing `progress` per entry. Failures are reported on stderr; returns the
+/// number added and the failures, by [`Entry::target`].
+pub async fn add_entries(
+    client: &EagleClient,
+    entries: Vec<Entry>,
+    folder_id: Option<&str>,
+    batch_size: usize,
+    progress: &mut Progress,
+) -> (usize, Failures) {
+    let (images, bookmarks): (Vec<Entry>, Vec<Entry>) = entries.into_iter().partition(|entry| entry.image.is_some());
+    let mut imported = 0;
+    let mut failures = Failures::default();
+
+    let images: Vec<AddFromUrlParams> = images
+        .into_iter()
+        .map(|entry| AddFromUrlParams {
+            url: entry.image.unwrap_or_default(),
+            name: entry.title,
+            website: Some(entry.url),
+            tags: Some(entry.tags).filter(|tags| !tags.is_empty()),
+            annotation: entry.note,
+            modification_time: entry.created,
+            ..Default::default()
+        })
+        .collect();
+    for batch in images.chunks(batch_size) {
+        let result = client.item().add_from_urls(batch, folder_id).await;
+        for _ in batch {
+            progress.inc();
+        }
+        match result {
+            Ok(_) => imported += batch.len(),
+            Err(error) => {
+                progress.finish();
+                eprintln!("Failed to import {} images: {}", batch.len(), error);
+                // The whole batch is one request, so each of its images is retried
+                for params in batch {
+                    failures.add(&params.url, &error);
+                }
+            }
+        }
+    }
+
+    for entry in bookmarks {
+        let params = AddBookmarkParams {
+            url: entry.url,
+            name: entry.title,
+            tags: Some(entry.tags).filter(|tags| !tags.is_empty()),
+            modification_time: entry.created,
+            folder_id: folder_id.map(String::from),
+            ..Default::default()
+        };
+        let result = client.item().add_bookmark(&params).await;
+        progress.inc();
+        match result {
+            Ok(_) => imported += 1,
+            Err(error) => {
+                progress.finish();
+                eprintln!("Failed to bookmark {}: {}", params.url, error);
+                failures.add(&params.url, error);
+            }
+        }
+    }
+
+    (imported, failures)
+}
+
+/// Epoch milliseconds of an RFC 3339 date such as `2023-01-15T10:20:30.000Z`
+pub fn parse_date(value: &str) -> Option<u64> {
+    chrono::DateTime::parse_from_rfc3339(value.trim())
+        .ok()
+        .and_then(|date| u64::try_from(date.timestamp_millis()).ok())
+}
+
+/// Title to use when the export has none: the URL without its scheme
+pub fn fallback_title(url: &str) -> String {
+    url.split_once("://").map_or(url, |(_, rest)| rest).trim_end_matches('/').to_string()
+}
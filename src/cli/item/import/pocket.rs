@@ -0,0 +1,85 @@
+use super::{fallback_title, Entry};
+use regex::Regex;
+
+/// Parse a Pocket export: the `ril_export.html` bookmark list, or the newer `part_000000.csv`.
+pub fn parse(content: &str) -> Result<Vec<Entry>, Box<dyn std::error::Error>> {
+    if content.trim_start().starts_with('<') {
+        parse_html(content)
+    } else {
+        parse_csv(content)
+    }
+}
+
+/// `<a href="URL" time_added="1507000000" tags="design,reference">Title</a>`
+fn parse_html(content: &str) -> Result<Vec<Entry>, Box<dyn std::error::Error>> {
+    let link = Regex::new(r#"(?is)<a\s([^>]*)>(.*?)</a>"#)?;
+    let attribute = Regex::new(r#"(?i)([a-z_]+)="([^"]*)""#)?;
+
+    let mut entries = Vec::new();
+    for captures in link.captures_iter(content) {
+        let mut entry = Entry::default();
+        for attribute in attribute.captures_iter(&captures[1]) {
+            let value = unescape(&attribute[2]);
+            match attribute[1].to_lowercase().as_str() {
+                "href" => entry.url = value,
+                "time_added" => entry.created = value.parse::<u64>().ok().map(|seconds| seconds * 1000),
+                "tags" => entry.tags = split_tags(&value, ','),
+                _ => {}
+            }
+        }
+        if entry.url.is_empty() {
+            continue;
+        }
+        let title = unescape(captures[2].trim());
+        entry.title = if title.is_empty() { fallback_title(&entry.url) } else { title };
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// `title,url,time_added,tags,status` with tags separated by `|`
+fn parse_csv(content: &str) -> Result<Vec<Entry>, Box<dyn std::error::Error>> {
+    let mut reader = csv::Reader::from_reader(content.as_bytes());
+    let headers = reader.headers()?.clone();
+    let column = |name: &str| headers.iter().position(|header| header.eq_ignore_ascii_case(name));
+    let url_column = column("url").ok_or("Pocket CSV has no url column")?;
+    let (title_column, time_column, tags_column) = (column("title"), column("time_added"), column("tags"));
+
+    let mut entries = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let field = |index: Option<usize>| index.and_then(|index| record.get(index)).unwrap_or_default().trim();
+        let url = field(Some(url_column)).to_string();
+        if url.is_empty() {
+            continue;
+        }
+        let title = field(title_column);
+        entries.push(Entry {
+            title: if title.is_empty() { fallback_title(&url) } else { title.to_string() },
+            tags: split_tags(field(tags_column), '|'),
+            created: field(time_column).parse::<u64>().ok().map(|seconds| seconds * 1000),
+            url,
+            ..Default::default()
+        });
+    }
+    Ok(entries)
+}
+
+fn split_tags(value: &str, separator: char) -> Vec<String> {
+    value
+        .split(separator)
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Decode the entities Pocket writes into its HTML export
+fn unescape(value: &str) -> String {
+    value
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
@@ -0,0 +1,48 @@
+use super::{fallback_title, parse_date, Entry};
+
+/// Parse a Raindrop.io CSV export:
+/// `id,title,note,excerpt,url,folder,tags,created,cover,highlights,favorite`.
+///
+/// The cover image, when there is one, is imported with the bookmarked page as its source.
+pub fn parse(content: &str) -> Result<Vec<Entry>, Box<dyn std::error::Error>> {
+    let mut reader = csv::Reader::from_reader(content.as_bytes());
+    let headers = reader.headers()?.clone();
+    let column = |name: &str| headers.iter().position(|header| header.eq_ignore_ascii_case(name));
+    let url_column = column("url").ok_or("Raindrop CSV has no url column")?;
+    let title_column = column("title");
+    let note_column = column("note");
+    let excerpt_column = column("excerpt");
+    let tags_column = column("tags");
+    let created_column = column("created");
+    let cover_column = column("cover");
+
+    let mut entries = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let field = |index: Option<usize>| index.and_then(|index| record.get(index)).unwrap_or_default().trim();
+        let url = field(Some(url_column)).to_string();
+        if url.is_empty() {
+            continue;
+        }
+        let title = field(title_column);
+        let note = match field(note_column) {
+            "" => field(excerpt_column),
+            note => note,
+        };
+        let cover = field(cover_column);
+        entries.push(Entry {
+            title: if title.is_empty() { fallback_title(&url) } else { title.to_string() },
+            image: (!cover.is_empty()).then(|| cover.to_string()),
+            tags: field(tags_column)
+                .split(',')
+                .map(str::trim)
+                .filter(|tag| !tag.is_empty())
+                .map(String::from)
+                .collect(),
+            note: (!note.is_empty()).then(|| note.to_string()),
+            created: parse_date(field(created_column)),
+            url,
+        });
+    }
+    Ok(entries)
+}
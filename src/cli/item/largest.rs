@@ -0,0 +1,43 @@
+use crate::cli::item::list::{self, filter::ItemFilter};
+use crate::cli::output;
+use crate::lib::client::EagleClient;
+use crate::lib::types::ItemListData;
+use clap::{Arg, ArgMatches, Command};
+use serde_json::{json, Value};
+
+pub fn build() -> Command {
+    Command::new("largest")
+        .about("Report the largest items")
+        .arg(
+            Arg::new("top")
+                .long("top")
+                .value_name("N")
+                .help("Number of items to report")
+                .num_args(1)
+                .default_value("10")
+                .value_parser(clap::value_parser!(usize)),
+        )
+        .args(list::query_args())
+        .args(list::filter::args())
+        // The largest files may be on any page
+        .mut_arg("all", |arg| arg.default_value("true").hide(true))
+        .args(output::args())
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let top = *matches.get_one::<usize>("top").unwrap();
+    let item_filter = ItemFilter::from_matches(matches)?;
+
+    let mut items: Vec<ItemListData> = list::fetch_items(client, matches, &item_filter).await?;
+    items.sort_by_key(|item| std::cmp::Reverse(item.size));
+
+    let rows: Vec<Value> = items
+        .iter()
+        .take(top)
+        .map(|item| json!({ "size": item.size, "id": item.id, "name": item.name, "ext": item.ext }))
+        .collect();
+    output::output(&Value::Array(rows), matches)
+}
@@ -0,0 +1,147 @@
+use crate::cli::confirm;
+use crate::cli::folder::folder_paths;
+use crate::cli::item::export::{sanitize, stays_inside, symlink};
+use crate::cli::item::list::{self, filter::ItemFilter};
+use crate::lib::client::EagleClient;
+use crate::lib::library::LibraryDir;
+use crate::lib::types::ItemListData;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub fn build() -> Command {
+    Command::new("linkfarm")
+        .about("Build a directory tree of symlinks to library files, one directory per tag, folder, or rating")
+        .arg(
+            Arg::new("by")
+                .long("by")
+                .value_name("PROPERTY")
+                .help("What the directories represent")
+                .num_args(1)
+                .default_value("tag")
+                .value_parser(["tag", "folder", "rating"]),
+        )
+        .arg(
+            Arg::new("dest")
+                .short('d')
+                .long("dest")
+                .value_name("DIR")
+                .help("Directory to build the link farm in")
+                .required(true)
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("clean")
+                .long("clean")
+                .help("Remove existing symlinks (and directories left empty) in DIR first")
+                .action(ArgAction::SetTrue),
+        )
+        .args(list::query_args())
+        .args(list::filter::args())
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let by = matches.get_one::<String>("by").unwrap().as_str();
+    let dest = PathBuf::from(matches.get_one::<String>("dest").unwrap());
+
+    let library_data = client.library().info().await?.data;
+    let library = LibraryDir::new(&library_data.library.path);
+    let item_filter = ItemFilter::from_matches(matches)?;
+    let items = list::fetch_items(client, matches, &item_filter).await?;
+    let folders = match by {
+        "folder" => folder_paths(&client.folder().list().await?.data),
+        _ => HashMap::new(),
+    };
+
+    if matches.get_flag("clean") && dest.is_dir() {
+        confirm::confirm(matches, &format!("Every symlink under {} will be removed", dest.display()))?;
+        remove_links(&dest)?;
+    }
+
+    let (mut links, mut missing) = (0, 0);
+    for item in &items {
+        let Some(source) = library.item_file(&item.id, &item.name, &item.ext) else {
+            missing += 1;
+            continue;
+        };
+        let file_name = source.file_name().unwrap();
+        for dir in directories(item, by, &folders) {
+            if !stays_inside(&dir) {
+                eprintln!("Not linking {} into {}: it is outside {}", item.id, dir.display(), dest.display());
+                continue;
+            }
+            let dir = dest.join(dir);
+            fs::create_dir_all(&dir)?;
+            if let Some(target) = link_target(&dir.join(file_name), &source) {
+                symlink(&source, &target)?;
+            }
+            links += 1;
+        }
+    }
+
+    println!("Linked {} items into {} ({} links, {} items without a file)", items.len() - missing, dest.display(), links, missing);
+    Ok(())
+}
+
+/// Directories (relative to the link farm root) an item is linked into
+fn directories(item: &ItemListData, by: &str, folders: &HashMap<String, String>) -> Vec<PathBuf> {
+    let dirs: Vec<PathBuf> = match by {
+        "folder" => item
+            .folders
+            .iter()
+            .flatten()
+            .filter_map(|id| folders.get(id))
+            .map(|path| path.split('/').map(sanitize).collect())
+            .collect(),
+        "rating" => match item.star.unwrap_or(0) {
+            0 => vec![PathBuf::from("unrated")],
+            1 => vec![PathBuf::from("1 star")],
+            stars => vec![PathBuf::from(format!("{} stars", stars))],
+        },
+        _ => item.tags.iter().map(|tag| PathBuf::from(sanitize(tag))).collect(),
+    };
+    if dirs.is_empty() {
+        let none = if by == "folder" { "Unfiled" } else { "untagged" };
+        return vec![PathBuf::from(none)];
+    }
+    dirs
+}
+
+/// Free path for a new link to `source`, or `None` when a previous run already linked it.
+///
+/// Names taken by other items get a `_<n>` suffix, like `item export` does.
+fn link_target(target: &Path, source: &Path) -> Option<PathBuf> {
+    let stem = target.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+    let ext = target.extension().map(|ext| format!(".{}", ext.to_string_lossy())).unwrap_or_default();
+    let candidates = std::iter::once(target.to_path_buf())
+        .chain((1..).map(|n| target.with_file_name(format!("{}_{}{}", stem, n, ext))));
+    for candidate in candidates {
+        match fs::read_link(&candidate) {
+            Ok(existing) if existing == source => return None,
+            _ if candidate.symlink_metadata().is_err() => return Some(candidate),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Remove symlinks below `dir` and the directories they leave empty; regular files are kept.
+fn remove_links(dir: &Path) -> std::io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let metadata = path.symlink_metadata()?;
+        if metadata.file_type().is_symlink() {
+            fs::remove_file(&path)?;
+        } else if metadata.is_dir() {
+            remove_links(&path)?;
+            if fs::read_dir(&path)?.next().is_none() {
+                fs::remove_dir(&path)?;
+            }
+        }
+    }
+    Ok(())
+}
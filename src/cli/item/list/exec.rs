@@ -0,0 +1,112 @@
+use crate::cli::system;
+use crate::lib::library::LibraryDir;
+use crate::lib::types::ItemListData;
+use clap::{Arg, ArgMatches};
+use rayon::prelude::*;
+use std::io::Write;
+use std::path::Path;
+
+/// Placeholders replaced in an `--exec` command, each with its shell-quoted value
+pub const PLACEHOLDERS: [&str; 7] = ["{}", "{path}", "{id}", "{name}", "{ext}", "{thumbnail}", "{url}"];
+
+pub fn args() -> Vec<Arg> {
+    vec![
+        Arg::new("exec")
+            .short('x')
+            .long("exec")
+            .value_name("COMMAND")
+            .help("Run COMMAND through the shell for each item, replacing {} or {path}, {id}, {name}, {ext}, {thumbnail}, and {url}")
+            .num_args(1),
+        Arg::new("jobs")
+            .short('j')
+            .long("jobs")
+            .value_name("N")
+            .help("Run up to N --exec commands at once")
+            .num_args(1)
+            .default_value("1")
+            .requires("exec")
+            .value_parser(clap::builder::RangedU64ValueParser::<usize>::new().range(1..)),
+    ]
+}
+
+/// Fill in the placeholders of `template` for `item`, quoting every value for the shell.
+pub fn command_line(template: &str, item: &ItemListData, path: &Path, thumbnail: Option<&Path>) -> String {
+    let path = path.display().to_string();
+    let thumbnail = thumbnail.map(|path| path.display().to_string()).unwrap_or_default();
+    let values = [&path, &path, &item.id, &item.name, &item.ext, &thumbnail, &item.url];
+
+    // Replace in one left-to-right pass, so values containing `{...}` are left alone
+    let mut line = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        line.push_str(&rest[..start]);
+        rest = &rest[start..];
+        match PLACEHOLDERS.iter().position(|placeholder| rest.starts_with(placeholder)) {
+            Some(index) => {
+                line.push_str(&system::shell_quote(values[index]));
+                rest = &rest[PLACEHOLDERS[index].len()..];
+            }
+            None => {
+                line.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    line.push_str(rest);
+    line
+}
+
+/// Run the `--exec` command for every item, `--jobs` at a time.
+///
+/// With a single job, commands share the terminal like `find -exec`; with more, each command's
+/// output is captured and printed in one piece so lines of different items don't interleave.
+pub fn run(matches: &ArgMatches, items: &[ItemListData], library: &LibraryDir) -> Result<(), Box<dyn std::error::Error>> {
+    let template = matches.get_one::<String>("exec").unwrap();
+    let jobs = *matches.get_one::<usize>("jobs").unwrap();
+    let lines: Vec<String> = items
+        .iter()
+        .map(|item| {
+            let path = super::item_path(library, item, false);
+            let thumbnail = library.item_thumbnail(&item.id, &item.name);
+            command_line(template, item, &path, thumbnail.as_deref())
+        })
+        .collect();
+
+    let failed = if jobs == 1 {
+        lines.iter().filter(|line| !run_attached(line)).count()
+    } else {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(jobs)
+            .build()?
+            .install(|| lines.par_iter().filter(|line| !run_captured(line)).count())
+    };
+
+    if failed > 0 {
+        return Err(format!("{} of {} commands failed", failed, lines.len()).into());
+    }
+    Ok(())
+}
+
+fn run_attached(line: &str) -> bool {
+    match system::shell(line).status() {
+        Ok(status) => status.success(),
+        Err(error) => {
+            eprintln!("Failed to run {}: {}", line, error);
+            false
+        }
+    }
+}
+
+fn run_captured(line: &str) -> bool {
+    match system::shell(line).output() {
+        Ok(output) => {
+            std::io::stdout().lock().write_all(&output.stdout).ok();
+            std::io::stderr().lock().write_all(&output.stderr).ok();
+            output.status.success()
+        }
+        Err(error) => {
+            eprintln!("Failed to run {}: {}", line, error);
+            false
+        }
+    }
+}
@@ -0,0 +1,348 @@
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+/// Client-side filter expression used by `item list --where`.
+///
+/// Example: `ext == 'png' && size > 1000000 && tags contains 'logo'`
+///
+/// `matches` compares against a glob: `name matches 'shot_*'`.
+///
+/// Fields are looked up on the item as returned by the API (`ext`, `size`,
+/// `tags`, `width`, `annotation`, ...). Nested fields use dots and snake_case
+/// aliases such as `modification_time` are accepted.
+#[derive(Debug, Clone)]
+pub enum Expr {
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Operand, Op, Operand),
+    Truthy(Operand),
+}
+
+#[derive(Debug, Clone)]
+pub enum Operand {
+    Field(Vec<String>),
+    Literal(Value),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Contains,
+    StartsWith,
+    EndsWith,
+    Matches,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Num(f64),
+    LParen,
+    RParen,
+    And,
+    Or,
+    Not,
+    Op(Op),
+}
+
+impl FromStr for Expr {
+    type Err = String;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser { tokens, position: 0 };
+        let expr = parser.parse_or()?;
+        match parser.peek() {
+            None => Ok(expr),
+            Some(token) => Err(format!("unexpected {:?} in expression", token)),
+        }
+    }
+}
+
+impl Expr {
+    /// Check whether the item (serialized to JSON) satisfies the expression.
+    pub fn matches(&self, item: &Value) -> bool {
+        match self {
+            Expr::Or(left, right) => left.matches(item) || right.matches(item),
+            Expr::And(left, right) => left.matches(item) && right.matches(item),
+            Expr::Not(inner) => !inner.matches(item),
+            Expr::Compare(left, op, right) => compare(&left.resolve(item), *op, &right.resolve(item)),
+            Expr::Truthy(operand) => is_truthy(&operand.resolve(item)),
+        }
+    }
+}
+
+impl Operand {
+    fn resolve(&self, item: &Value) -> Value {
+        match self {
+            Operand::Literal(value) => value.clone(),
+            Operand::Field(path) => {
+                let mut current = item;
+                for segment in path {
+                    let next = match current {
+                        Value::Object(map) => map.get(segment).or_else(|| map.get(&to_camel_case(segment))),
+                        Value::Array(values) => segment.parse::<usize>().ok().and_then(|index| values.get(index)),
+                        _ => None,
+                    };
+                    match next {
+                        Some(value) => current = value,
+                        None => return Value::Null,
+                    }
+                }
+                current.clone()
+            }
+        }
+    }
+}
+
+fn to_camel_case(name: &str) -> String {
+    let mut result = String::with_capacity(name.len());
+    let mut upper = false;
+    for c in name.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            result.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64().is_some_and(|n| n != 0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(values) => !values.is_empty(),
+        Value::Object(map) => !map.is_empty(),
+    }
+}
+
+fn equals(left: &Value, right: &Value) -> bool {
+    match (left.as_f64(), right.as_f64()) {
+        (Some(left), Some(right)) => left == right,
+        _ => left == right,
+    }
+}
+
+fn ordering(left: &Value, right: &Value) -> Option<Ordering> {
+    match (left, right) {
+        (Value::Number(left), Value::Number(right)) => left.as_f64()?.partial_cmp(&right.as_f64()?),
+        (Value::String(left), Value::String(right)) => Some(left.cmp(right)),
+        _ => None,
+    }
+}
+
+fn compare(left: &Value, op: Op, right: &Value) -> bool {
+    match op {
+        Op::Eq => equals(left, right),
+        Op::Ne => !equals(left, right),
+        Op::Gt => ordering(left, right) == Some(Ordering::Greater),
+        Op::Ge => matches!(ordering(left, right), Some(Ordering::Greater | Ordering::Equal)),
+        Op::Lt => ordering(left, right) == Some(Ordering::Less),
+        Op::Le => matches!(ordering(left, right), Some(Ordering::Less | Ordering::Equal)),
+        Op::Contains => match left {
+            Value::Array(values) => values.iter().any(|value| equals(value, right)),
+            Value::String(s) => right.as_str().is_some_and(|needle| s.contains(needle)),
+            _ => false,
+        },
+        Op::StartsWith => match (left.as_str(), right.as_str()) {
+            (Some(s), Some(prefix)) => s.starts_with(prefix),
+            _ => false,
+        },
+        Op::EndsWith => match (left.as_str(), right.as_str()) {
+            (Some(s), Some(suffix)) => s.ends_with(suffix),
+            _ => false,
+        },
+        Op::Matches => match (left.as_str(), right.as_str()) {
+            (Some(s), Some(pattern)) => globset::Glob::new(pattern).is_ok_and(|glob| glob.compile_matcher().is_match(s)),
+            _ => false,
+        },
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '(' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                i += 1;
+            }
+            '\'' | '"' => {
+                let quote = c;
+                let mut value = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err("unterminated string in expression".to_string()),
+                        Some('\\') if chars.get(i + 1).is_some() => {
+                            value.push(chars[i + 1]);
+                            i += 2;
+                        }
+                        Some(&c) if c == quote => {
+                            i += 1;
+                            break;
+                        }
+                        Some(&c) => {
+                            value.push(c);
+                            i += 1;
+                        }
+                    }
+                }
+                tokens.push(Token::Str(value));
+            }
+            '&' | '|' | '=' => {
+                if chars.get(i + 1) != Some(&c) {
+                    return Err(format!("expected '{}{}' in expression", c, c));
+                }
+                tokens.push(match c {
+                    '&' => Token::And,
+                    '|' => Token::Or,
+                    _ => Token::Op(Op::Eq),
+                });
+                i += 2;
+            }
+            '!' | '>' | '<' => {
+                let with_eq = chars.get(i + 1) == Some(&'=');
+                tokens.push(match (c, with_eq) {
+                    ('!', true) => Token::Op(Op::Ne),
+                    ('!', false) => Token::Not,
+                    ('>', true) => Token::Op(Op::Ge),
+                    ('>', false) => Token::Op(Op::Gt),
+                    ('<', true) => Token::Op(Op::Le),
+                    _ => Token::Op(Op::Lt),
+                });
+                i += if with_eq { 2 } else { 1 };
+            }
+            c if c.is_ascii_digit() || (c == '-' && chars.get(i + 1).is_some_and(|c| c.is_ascii_digit())) => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let literal: String = chars[start..i].iter().collect();
+                let number = literal
+                    .parse::<f64>()
+                    .map_err(|_| format!("invalid number in expression: {}", literal))?;
+                tokens.push(Token::Num(number));
+            }
+            c if c.is_alphabetic() || c == '_' || c == '$' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || matches!(chars[i], '_' | '.' | '$')) {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                tokens.push(match word.to_lowercase().as_str() {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    "contains" => Token::Op(Op::Contains),
+                    "startswith" => Token::Op(Op::StartsWith),
+                    "endswith" => Token::Op(Op::EndsWith),
+                    "matches" => Token::Op(Op::Matches),
+                    _ => Token::Ident(word),
+                });
+            }
+            c => return Err(format!("unexpected character '{}' in expression", c)),
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            left = Expr::Or(Box::new(left), Box::new(self.parse_and()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_not()?;
+        while self.peek() == Some(&Token::And) {
+            self.next();
+            left = Expr::And(Box::new(left), Box::new(self.parse_not()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_not(&mut self) -> Result<Expr, String> {
+        if self.peek() == Some(&Token::Not) {
+            self.next();
+            return Ok(Expr::Not(Box::new(self.parse_not()?)));
+        }
+        if self.peek() == Some(&Token::LParen) {
+            self.next();
+            let inner = self.parse_or()?;
+            if self.next() != Some(Token::RParen) {
+                return Err("expected ')' in expression".to_string());
+            }
+            return Ok(inner);
+        }
+
+        let left = self.parse_operand()?;
+        match self.peek() {
+            Some(Token::Op(op)) => {
+                let op = *op;
+                self.next();
+                let right = self.parse_operand()?;
+                Ok(Expr::Compare(left, op, right))
+            }
+            _ => Ok(Expr::Truthy(left)),
+        }
+    }
+
+    fn parse_operand(&mut self) -> Result<Operand, String> {
+        match self.next() {
+            Some(Token::Str(value)) => Ok(Operand::Literal(Value::String(value))),
+            Some(Token::Num(number)) => Ok(Operand::Literal(serde_json::json!(number))),
+            Some(Token::Ident(word)) => Ok(match word.as_str() {
+                "true" => Operand::Literal(Value::Bool(true)),
+                "false" => Operand::Literal(Value::Bool(false)),
+                "null" => Operand::Literal(Value::Null),
+                _ => Operand::Field(word.split('.').map(String::from).collect()),
+            }),
+            Some(token) => Err(format!("unexpected {:?} in expression", token)),
+            None => Err("unexpected end of expression".to_string()),
+        }
+    }
+}
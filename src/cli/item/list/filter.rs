@@ -0,0 +1,480 @@
+use super::expr::Expr;
+use super::query::Query;
+use crate::cli::datetime::{self, parse_duration_millis, DateBound};
+use crate::cli::exif::ExifFilter;
+use crate::cli::tag::in_namespace;
+use crate::cli::units::parse_size;
+use crate::lib::types::ItemListData;
+use clap::{Arg, ArgMatches};
+use globset::{Glob, GlobMatcher};
+use regex::Regex;
+
+/// Orientation of an item, derived from its width and height
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shape {
+    Landscape,
+    Portrait,
+    Square,
+}
+
+impl Shape {
+    fn of(width: u64, height: u64) -> Shape {
+        match width.cmp(&height) {
+            std::cmp::Ordering::Greater => Shape::Landscape,
+            std::cmp::Ordering::Less => Shape::Portrait,
+            std::cmp::Ordering::Equal => Shape::Square,
+        }
+    }
+}
+
+/// Kind of file an item is, going by its extension
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Media {
+    Image,
+    Video,
+    Audio,
+    Font,
+}
+
+impl Media {
+    const IMAGE: &'static [&'static str] = &[
+        "png", "jpg", "jpeg", "gif", "webp", "avif", "svg", "bmp", "tif", "tiff", "heic", "psd", "ai", "eps",
+    ];
+    const VIDEO: &'static [&'static str] = &["mp4", "mov", "m4v", "webm", "mkv", "avi", "wmv", "flv", "mpg", "mpeg"];
+    const AUDIO: &'static [&'static str] = &["mp3", "wav", "aac", "m4a", "flac", "ogg", "aiff", "aif"];
+    const FONT: &'static [&'static str] = &["ttf", "otf", "ttc", "woff", "woff2"];
+
+    /// The kind of an item with extension `ext`, if it's one eagle-eye knows
+    pub fn of(ext: &str) -> Option<Media> {
+        let ext = ext.to_lowercase();
+        [
+            (Media::Image, Media::IMAGE),
+            (Media::Video, Media::VIDEO),
+            (Media::Audio, Media::AUDIO),
+            (Media::Font, Media::FONT),
+        ]
+        .into_iter()
+        .find(|(_, exts)| exts.contains(&ext.as_str()))
+        .map(|(media, _)| media)
+    }
+}
+
+/// Timestamp of an item that `--since` / `--until` compare against
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum DateField {
+    #[default]
+    ModificationTime,
+    LastModified,
+}
+
+impl DateField {
+    fn of(&self, item: &ItemListData) -> u64 {
+        match self {
+            DateField::ModificationTime => item.modification_time,
+            DateField::LastModified => item.last_modified.unwrap_or(item.modification_time),
+        }
+    }
+}
+
+/// Filters the Eagle API can't evaluate, applied to items after they are fetched.
+#[derive(Debug, Default)]
+pub struct ItemFilter {
+    pub url: Option<String>,
+    /// Lowercase extensions an item must have one of
+    pub exts: Vec<String>,
+    pub not_exts: Vec<String>,
+    /// Tags an item must carry all of
+    pub all_tags: Vec<String>,
+    /// Tag namespaces like `brand/`, an item must have a tag in one of
+    pub tag_prefixes: Vec<String>,
+    pub not_tags: Vec<String>,
+    pub not_folders: Vec<String>,
+    pub name_regex: Option<Regex>,
+    pub name_glob: Option<GlobMatcher>,
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    pub min_width: Option<u64>,
+    pub max_width: Option<u64>,
+    pub min_height: Option<u64>,
+    pub max_height: Option<u64>,
+    pub shape: Option<Shape>,
+    pub media: Option<Media>,
+    /// Length bounds in milliseconds, for video and audio items
+    pub min_duration: Option<i64>,
+    pub max_duration: Option<i64>,
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+    pub date_field: DateField,
+    pub where_expr: Option<Expr>,
+    /// Set by commands offering `exif::filter_args()`, once the library is known
+    pub exif: Option<ExifFilter>,
+}
+
+/// Arguments for the client-side filters, shared by commands that select items.
+pub fn args() -> Vec<Arg> {
+    vec![
+        Arg::new("url")
+            .short('u')
+            .long("url")
+            .value_name("KEYWORD")
+            .help("Get the list of items with url")
+            .num_args(1)
+            .default_value(""),
+        Arg::new("not_ext")
+            .long("not-ext")
+            .value_name("EXTENSION")
+            .help("Exclude extensions. Comma separated")
+            .num_args(1),
+        Arg::new("tags_all")
+            .long("tags-all")
+            .value_name("TAG")
+            .help("Only items with every one of these tags. Comma separated. It works like AND")
+            .num_args(1),
+        Arg::new("tag_prefix")
+            .long("tag-prefix")
+            .value_name("PREFIX")
+            .help("Only items with a tag under one of these namespaces, e.g. brand/ for brand/acme. Comma separated")
+            .num_args(1),
+        Arg::new("not_tags")
+            .long("not-tags")
+            .value_name("TAG")
+            .help("Exclude items with any of these tags. Comma separated")
+            .num_args(1),
+        Arg::new("not_folders")
+            .long("not-folders")
+            .value_name("FOLDER-ID")
+            .help("Exclude items in any of these folders. Comma separated")
+            .num_args(1),
+        Arg::new("name_regex")
+            .long("name-regex")
+            .value_name("REGEX")
+            .help("Only items whose name matches REGEX, e.g. '^final_v\\d+'")
+            .num_args(1)
+            .value_parser(Regex::new),
+        Arg::new("name_glob")
+            .long("name-glob")
+            .value_name("GLOB")
+            .help("Only items whose name (with or without extension) matches GLOB, e.g. 'IMG_*.png'")
+            .num_args(1)
+            .value_parser(|value: &str| Glob::new(value).map(|glob| glob.compile_matcher())),
+        Arg::new("min_size")
+            .long("min-size")
+            .value_name("SIZE")
+            .help("Only items at least this large, e.g. 500KB or 10MB")
+            .num_args(1)
+            .value_parser(parse_size),
+        Arg::new("max_size")
+            .long("max-size")
+            .value_name("SIZE")
+            .help("Only items at most this large, e.g. 500KB or 10MB")
+            .num_args(1)
+            .value_parser(parse_size),
+        Arg::new("min_width")
+            .long("min-width")
+            .value_name("PIXELS")
+            .help("Only items at least this wide")
+            .num_args(1)
+            .value_parser(clap::value_parser!(u64)),
+        Arg::new("max_width")
+            .long("max-width")
+            .value_name("PIXELS")
+            .help("Only items at most this wide")
+            .num_args(1)
+            .value_parser(clap::value_parser!(u64)),
+        Arg::new("min_height")
+            .long("min-height")
+            .value_name("PIXELS")
+            .help("Only items at least this tall")
+            .num_args(1)
+            .value_parser(clap::value_parser!(u64)),
+        Arg::new("max_height")
+            .long("max-height")
+            .value_name("PIXELS")
+            .help("Only items at most this tall")
+            .num_args(1)
+            .value_parser(clap::value_parser!(u64)),
+        Arg::new("shape")
+            .long("shape")
+            .value_name("SHAPE")
+            .help("Only items with this orientation")
+            .num_args(1)
+            .value_parser(["landscape", "portrait", "square"]),
+        Arg::new("media")
+            .long("media")
+            .value_name("KIND")
+            .help("Only items of this kind, going by their extension")
+            .num_args(1)
+            .value_parser(["image", "video", "audio", "font"]),
+        Arg::new("min_duration")
+            .long("min-duration")
+            .value_name("LENGTH")
+            .help("Only videos and audio at least this long, e.g. 10s or 2m")
+            .num_args(1)
+            .value_parser(parse_length),
+        Arg::new("max_duration")
+            .long("max-duration")
+            .value_name("LENGTH")
+            .help("Only videos and audio at most this long, e.g. 10s or 2m")
+            .num_args(1)
+            .value_parser(parse_length),
+        Arg::new("since")
+            .long("since")
+            .value_name("DATE")
+            .help("Only items modified at or after DATE, e.g. 2024-01-01 or 7d (seven days ago)")
+            .num_args(1)
+            .value_parser(|value: &str| value.parse::<DateBound>()),
+        Arg::new("until")
+            .long("until")
+            .value_name("DATE")
+            .help("Only items modified before DATE, e.g. 2024-02-01 or 1d (one day ago)")
+            .num_args(1)
+            .value_parser(|value: &str| value.parse::<DateBound>()),
+        Arg::new("date_field")
+            .long("date-field")
+            .value_name("FIELD")
+            .help("Timestamp used by --since and --until")
+            .num_args(1)
+            .default_value("modificationTime")
+            .value_parser(["modificationTime", "lastModified"]),
+        Arg::new("where")
+            .short('w')
+            .long("where")
+            .value_name("EXPRESSION")
+            .help("Filter items client-side, e.g. \"ext == 'png' && size > 1000000 && tags contains 'logo'\"; `matches` takes a glob")
+            .num_args(1)
+            .value_parser(|value: &str| value.parse::<Expr>()),
+        Arg::new("query")
+            .long("query")
+            .value_name("QUERY")
+            .help("Filter with a compact search, e.g. 'ext:png tag:logo folder:\"Brand/2024\" star:>=4 size:<5MB'")
+            .num_args(1)
+            .allow_hyphen_values(true)
+            .value_parser(|value: &str| value.parse::<Query>()),
+    ]
+}
+
+fn parse_length(value: &str) -> Result<i64, String> {
+    parse_duration_millis(value).ok_or_else(|| format!("invalid length: {} (use 10s, 2m, ...)", value))
+}
+
+/// Split a comma separated argument into its trimmed, non-empty values.
+pub fn split_list(matches: &ArgMatches, id: &str) -> Vec<String> {
+    matches
+        .try_get_one::<String>(id)
+        .ok()
+        .flatten()
+        .map(|value| {
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn lowercase(values: Vec<String>) -> Vec<String> {
+    values.into_iter().map(|value| value.to_lowercase()).collect()
+}
+
+impl ItemFilter {
+    pub fn from_matches(matches: &ArgMatches) -> Result<Self, Box<dyn std::error::Error>> {
+        let tz = datetime::from_matches(matches);
+        let resolve = |id: &str| -> Result<Option<i64>, String> {
+            matches
+                .try_get_one::<DateBound>(id)
+                .ok()
+                .flatten()
+                .map(|bound| bound.resolve(&tz))
+                .transpose()
+        };
+
+        let mut filter = ItemFilter {
+            url: matches
+                .get_one::<String>("url")
+                .filter(|url| !url.is_empty())
+                .cloned(),
+            // A single extension goes to the API, several are matched here
+            exts: lowercase(split_list(matches, "ext")),
+            not_exts: lowercase(split_list(matches, "not_ext")),
+            all_tags: split_list(matches, "tags_all"),
+            tag_prefixes: split_list(matches, "tag_prefix"),
+            not_tags: split_list(matches, "not_tags"),
+            not_folders: split_list(matches, "not_folders"),
+            name_regex: matches.get_one::<Regex>("name_regex").cloned(),
+            name_glob: matches.get_one::<GlobMatcher>("name_glob").cloned(),
+            min_size: matches.get_one::<u64>("min_size").copied(),
+            max_size: matches.get_one::<u64>("max_size").copied(),
+            min_width: matches.get_one::<u64>("min_width").copied(),
+            max_width: matches.get_one::<u64>("max_width").copied(),
+            min_height: matches.get_one::<u64>("min_height").copied(),
+            max_height: matches.get_one::<u64>("max_height").copied(),
+            shape: matches.get_one::<String>("shape").map(|shape| match shape.as_str() {
+                "landscape" => Shape::Landscape,
+                "portrait" => Shape::Portrait,
+                _ => Shape::Square,
+            }),
+            media: matches.get_one::<String>("media").map(|media| match media.as_str() {
+                "image" => Media::Image,
+                "video" => Media::Video,
+                "audio" => Media::Audio,
+                _ => Media::Font,
+            }),
+            min_duration: matches.get_one::<i64>("min_duration").copied(),
+            max_duration: matches.get_one::<i64>("max_duration").copied(),
+            since: resolve("since")?,
+            until: resolve("until")?,
+            date_field: match matches.get_one::<String>("date_field").map(String::as_str) {
+                Some("lastModified") => DateField::LastModified,
+                _ => DateField::ModificationTime,
+            },
+            where_expr: matches.get_one::<Expr>("where").cloned(),
+            exif: None,
+        };
+        if let Some(query) = matches.try_get_one::<Query>("query").ok().flatten() {
+            query.apply_filter(&mut filter, &tz)?;
+        }
+        Ok(filter)
+    }
+
+    fn has_date_range(&self) -> bool {
+        self.since.is_some() || self.until.is_some()
+    }
+
+    /// Whether any filter beyond what `/api/item/list` evaluates is set.
+    ///
+    /// Matches of such a filter can be on any page of the API results.
+    pub fn is_active(&self) -> bool {
+        self.url.is_some()
+            || self.exts.len() > 1
+            || !self.not_exts.is_empty()
+            || !self.all_tags.is_empty()
+            || !self.tag_prefixes.is_empty()
+            || !self.not_tags.is_empty()
+            || !self.not_folders.is_empty()
+            || self.name_regex.is_some()
+            || self.name_glob.is_some()
+            || self.min_size.is_some()
+            || self.max_size.is_some()
+            || self.needs_dimensions()
+            || self.media.is_some()
+            || self.needs_duration()
+            || self.has_date_range()
+            || self.where_expr.is_some()
+            || self.exif.is_some()
+    }
+
+    fn needs_dimensions(&self) -> bool {
+        self.min_width.is_some()
+            || self.max_width.is_some()
+            || self.min_height.is_some()
+            || self.max_height.is_some()
+            || self.shape.is_some()
+    }
+
+    fn needs_duration(&self) -> bool {
+        self.min_duration.is_some() || self.max_duration.is_some()
+    }
+
+    pub fn matches(&self, item: &ItemListData) -> bool {
+        if let Some(url) = &self.url {
+            if !item.url.contains(url.as_str()) {
+                return false;
+            }
+        }
+
+        let ext = item.ext.to_lowercase();
+        if (!self.exts.is_empty() && !self.exts.contains(&ext)) || self.not_exts.contains(&ext) {
+            return false;
+        }
+        if !self.all_tags.iter().all(|tag| item.tags.contains(tag))
+            || item.tags.iter().any(|tag| self.not_tags.contains(tag))
+        {
+            return false;
+        }
+        if !self.tag_prefixes.is_empty()
+            && !item.tags.iter().any(|tag| self.tag_prefixes.iter().any(|prefix| in_namespace(tag, prefix)))
+        {
+            return false;
+        }
+        if item
+            .folders
+            .iter()
+            .flatten()
+            .any(|folder| self.not_folders.contains(folder))
+        {
+            return false;
+        }
+
+        if self.name_regex.as_ref().is_some_and(|regex| !regex.is_match(&item.name)) {
+            return false;
+        }
+        if let Some(glob) = &self.name_glob {
+            if !glob.is_match(&item.name) && !glob.is_match(format!("{}.{}", item.name, item.ext)) {
+                return false;
+            }
+        }
+
+        if self.min_size.is_some_and(|min| item.size < min)
+            || self.max_size.is_some_and(|max| item.size > max)
+        {
+            return false;
+        }
+
+        if self.has_date_range() {
+            let time = self.date_field.of(item) as i64;
+            if self.since.is_some_and(|since| time < since)
+                || self.until.is_some_and(|until| time >= until)
+            {
+                return false;
+            }
+        }
+
+        if self.needs_dimensions() {
+            // Items without known dimensions can't satisfy a dimension filter
+            let (width, height) = match (item.width, item.height) {
+                (Some(width), Some(height)) if width > 0 && height > 0 => (width, height),
+                _ => return false,
+            };
+            if self.min_width.is_some_and(|min| width < min)
+                || self.max_width.is_some_and(|max| width > max)
+                || self.min_height.is_some_and(|min| height < min)
+                || self.max_height.is_some_and(|max| height > max)
+                || self.shape.is_some_and(|shape| Shape::of(width, height) != shape)
+            {
+                return false;
+            }
+        }
+
+        if self.media.is_some_and(|media| Media::of(&item.ext) != Some(media)) {
+            return false;
+        }
+        if self.needs_duration() {
+            // Items without a known length can't satisfy a duration filter
+            let millis = match item.duration {
+                Some(seconds) if seconds > 0.0 => (seconds * 1000.0).round() as i64,
+                _ => return false,
+            };
+            if self.min_duration.is_some_and(|min| millis < min)
+                || self.max_duration.is_some_and(|max| millis > max)
+            {
+                return false;
+            }
+        }
+
+        if let Some(expr) = &self.where_expr {
+            if !expr.matches(&serde_json::to_value(item).unwrap_or_default()) {
+                return false;
+            }
+        }
+
+        // Last, since it reads the item file
+        if let Some(exif) = &self.exif {
+            if !exif.matches(item) {
+                return false;
+            }
+        }
+        true
+    }
+}
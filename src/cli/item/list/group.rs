@@ -0,0 +1,85 @@
+use crate::cli::datetime::TimeZone;
+use crate::cli::folder::folder_paths;
+use crate::lib::types::{Child, ItemListData};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// Label of the group for items without any tag or folder
+const NONE_GROUP: &str = "(none)";
+
+/// Property `item list --group-by` aggregates on
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GroupBy {
+    Ext,
+    Folder,
+    Tag,
+    Month,
+}
+
+impl GroupBy {
+    pub const VALUES: [&'static str; 4] = ["ext", "folder", "tag", "month"];
+
+    pub fn parse(value: &str) -> Option<GroupBy> {
+        match value {
+            "ext" => Some(GroupBy::Ext),
+            "folder" => Some(GroupBy::Folder),
+            "tag" => Some(GroupBy::Tag),
+            "month" => Some(GroupBy::Month),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Group {
+    count: u64,
+    size: u64,
+}
+
+/// Count items and sum their sizes per group, largest group first.
+///
+/// Items with several tags or folders count towards each of them, so the group
+/// counts can add up to more than the number of items.
+pub fn group(
+    items: &[ItemListData],
+    group_by: GroupBy,
+    folders: &[Child],
+    tz: &TimeZone,
+) -> Vec<Value> {
+    let folder_paths = folder_paths(folders);
+    let mut groups: HashMap<String, Group> = HashMap::new();
+
+    for item in items {
+        let keys: Vec<String> = match group_by {
+            GroupBy::Ext => vec![item.ext.to_lowercase()],
+            GroupBy::Tag => item.tags.clone(),
+            GroupBy::Folder => item
+                .folders
+                .iter()
+                .flatten()
+                .map(|id| folder_paths.get(id).cloned().unwrap_or_else(|| id.clone()))
+                .collect(),
+            // `YYYY-MM` prefix of the ISO date in the selected zone
+            GroupBy::Month => vec![tz.format_millis(item.modification_time as i64).chars().take(7).collect()],
+        };
+        let keys = if keys.is_empty() { vec![NONE_GROUP.to_string()] } else { keys };
+
+        for key in keys {
+            let group = groups.entry(key).or_default();
+            group.count += 1;
+            group.size += item.size;
+        }
+    }
+
+    let mut groups: Vec<(String, Group)> = groups.into_iter().collect();
+    match group_by {
+        // Months read best in calendar order
+        GroupBy::Month => groups.sort_by(|a, b| a.0.cmp(&b.0)),
+        _ => groups.sort_by(|a, b| b.1.count.cmp(&a.1.count).then_with(|| a.0.cmp(&b.0))),
+    }
+
+    groups
+        .into_iter()
+        .map(|(name, group)| json!({ "group": name, "count": group.count, "size": group.size }))
+        .collect()
+}
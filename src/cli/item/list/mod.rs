@@ -1,8 +1,24 @@
+use crate::cli::exif::{self, ExifFilter};
+use crate::cli::folder::resolve_folder;
+use crate::cli::pager::{self, Pager};
+use crate::cli::{datetime, index, output, picker, report, selection};
+use crate::cli::output::{OutputFormat, OutputOptions};
 use crate::lib::client::EagleClient;
+use crate::lib::library::LibraryDir;
 use crate::lib::types::{GetItemListParams, ItemListData, Order};
 use clap::{Arg, ArgAction, ArgMatches, Command};
 use rayon::prelude::*;
-use std::path::Path;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+pub mod exec;
+pub mod expr;
+pub mod filter;
+pub mod group;
+pub mod query;
+use filter::ItemFilter;
+use query::Query;
+use group::GroupBy;
 
 pub fn build() -> Command {
     Command::new("list")
@@ -15,15 +31,6 @@ pub fn build() -> Command {
                 .help("Get the length of the list")
                 .num_args(0),
         )
-        .arg(
-            Arg::new("limit")
-                .short('n')
-                .long("limit")
-                .value_name("LIMIT")
-                .help("Limit the number of items")
-                .num_args(1)
-                .value_parser(clap::value_parser!(usize)),
-        )
         .arg(
             Arg::new("offset")
                 .short('O')
@@ -38,43 +45,14 @@ pub fn build() -> Command {
                 .short('o')
                 .long("order-by")
                 .value_name("ORDER-BY")
-                .help("The sorting order")
-                .num_args(1),
-        )
-        .arg(
-            Arg::new("keyword")
-                .short('k')
-                .value_name("KEYWORD")
-                .long("keyword")
-                .help("Filter by keyword that in filename")
-                .num_args(1),
-        )
-        .arg(
-            Arg::new("ext")
-                .short('e')
-                .value_name("EXTENSION")
-                .long("ext")
-                .help("Filter by extension")
-                .num_args(1),
-        )
-        .arg(
-            Arg::new("tags")
-                .short('t')
-                .long("tags")
-                .value_name("TAG")
-                .help("Filter by tags. Comma separated. It works like OR")
-                .num_args(1)
-                .value_parser(clap::value_parser!(String)),
-        )
-        .arg(
-            Arg::new("folders")
-                .short('f')
-                .long("folders")
-                .value_name("FOLDER-ID")
-                .help("Filter by folders ids. Comma separated. It works like OR")
+                .help("The sorting order, `-` reversing it")
                 .num_args(1)
-                .value_parser(clap::value_parser!(String)),
+                .allow_hyphen_values(true)
+                .ignore_case(true)
+                .value_parser(Order::VALUES),
         )
+        .arg(picker::arg())
+        .args(query_args())
         .arg(
             Arg::new("thumbnails")
                 .short('T')
@@ -83,92 +61,473 @@ pub fn build() -> Command {
                 .help("Get the list of path to thumbnails")
                 .num_args(0),
         )
+        .args(filter::args())
+        .args(exif::filter_args())
         .arg(
-            Arg::new("url")
-                .short('u')
-                .long("url")
-                .value_name("KEYWORD")
-                .help("Get the list of items with url")
+            Arg::new("group_by")
+                .long("group-by")
+                .value_name("PROPERTY")
+                .help("Count items and total their sizes per group, over every matching item in the library")
                 .num_args(1)
-                .default_value(""),
+                .value_parser(GroupBy::VALUES)
+                .conflicts_with("limit"),
+        )
+        .arg(
+            Arg::new("count")
+                .long("count")
+                .help("Print the number of matching items instead of listing them")
+                .action(ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("use_index")
+                .long("use-index")
+                .help("Query the local index from `index build` instead of Eagle, which may be closed")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(index::auto_index_arg())
+        .args(exec::args())
+        .args(output::args())
 }
 
-pub async fn execute(
-    client: &EagleClient,
-    matches: &ArgMatches,
-) -> Result<(), Box<dyn std::error::Error>> {
+/// Arguments passed through to `/api/item/list`, shared by commands that select items.
+pub fn query_args() -> Vec<Arg> {
+    vec![
+        Arg::new("limit")
+            .short('n')
+            .long("limit")
+            .value_name("LIMIT")
+            .help("Limit the number of items")
+            .num_args(1)
+            .value_parser(clap::value_parser!(usize))
+            .conflicts_with("all"),
+        Arg::new("all")
+            .short('a')
+            .long("all")
+            .help("Fetch every page instead of the API's first 200 items")
+            .action(ArgAction::SetTrue),
+        Arg::new("page_size")
+            .long("page-size")
+            .value_name("N")
+            .help("Number of items requested per page when paging")
+            .num_args(1)
+            .default_value("200")
+            .value_parser(clap::builder::RangedU64ValueParser::<usize>::new().range(1..)),
+        Arg::new("max")
+            .long("max")
+            .value_name("N")
+            .help("Stop after N matching items")
+            .num_args(1)
+            .value_parser(clap::value_parser!(usize)),
+        Arg::new("keyword")
+            .short('k')
+            .value_name("KEYWORD")
+            .long("keyword")
+            .help("Filter by keyword that in filename")
+            .num_args(1),
+        Arg::new("ext")
+            .short('e')
+            .value_name("EXTENSION")
+            .long("ext")
+            .help("Filter by extension. Comma separated. It works like OR")
+            .num_args(1),
+        Arg::new("tags")
+            .short('t')
+            .long("tags")
+            .value_name("TAG")
+            .help("Filter by tags. Comma separated. It works like OR")
+            .num_args(1)
+            .value_parser(clap::value_parser!(String)),
+        Arg::new("folders")
+            .short('f')
+            .long("folders")
+            .value_name("FOLDER-ID")
+            .help("Filter by folders ids. Comma separated. It works like OR")
+            .num_args(1)
+            .value_parser(clap::value_parser!(String)),
+    ]
+}
+
+/// Build the `/api/item/list` parameters from `query_args()` (and `--offset` when defined).
+pub fn query_params(matches: &ArgMatches) -> GetItemListParams {
     let mut query_params: GetItemListParams = GetItemListParams::new();
 
     if let Some(limit) = matches.get_one::<usize>("limit") {
         query_params.limit = Some(*limit);
     }
 
-    if let Some(offset) = matches.get_one::<usize>("offset") {
+    if let Ok(Some(offset)) = matches.try_get_one::<usize>("offset") {
         query_params.offset = Some(*offset);
     }
 
-    if let Some(order_by) = matches.get_one::<String>("order_by") {
-        todo!()
-    }
-
     if let Some(keyword) = matches.get_one::<String>("keyword") {
         query_params.keyword = Some(keyword.to_owned());
     }
 
     if let Some(ext) = matches.get_one::<String>("ext") {
-        query_params.ext = Some(ext.to_owned());
+        // The API takes a single extension; a list is matched by `ItemFilter`
+        if !ext.contains(',') {
+            query_params.ext = Some(ext.to_owned());
+        }
     }
 
     if let Some(tags) = matches.get_one::<String>("tags") {
         query_params.tags = Some(tags.to_owned());
     }
 
+    // Every result of `--tags-all` carries its first tag, so let the API narrow by it
+    if query_params.tags.is_none() {
+        query_params.tags = filter::split_list(matches, "tags_all").into_iter().next();
+    }
+
     if let Some(folders) = matches.get_one::<String>("folders") {
         query_params.folders = Some(folders.to_owned());
     }
 
-    let library_data = client.library().info().await?.data;
-    let library_path = Path::new(&library_data.library.path).join("images");
+    if let Ok(Some(order_by)) = matches.try_get_one::<String>("order_by") {
+        query_params.order_by = order_by.parse::<Order>().ok();
+    }
 
-    let thumbnails_flag = matches.get_flag("thumbnails");
-    let url_flag = matches.get_one::<String>("url").unwrap().len() > 0;
-    let url_keyword = matches.get_one::<String>("url").unwrap();
+    if let Some(query) = matches.try_get_one::<Query>("query").ok().flatten() {
+        query.apply_params(&mut query_params);
+    }
 
-    let items: Vec<ItemListData> = client.item().list(query_params).await?.data;
+    query_params
+}
 
-    let paths: Vec<_> = items
-        .par_iter()
-        .filter(|item| {
-            if url_flag && url_keyword.len() > 0 {
-                item.url.contains(url_keyword)
-            } else {
-                true
+/// Run `item list` with `args` as if they were given on the command line, global options
+/// included.
+pub async fn run_with_args(client: &EagleClient, args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let matches = parse_args(args)?;
+    let (_, item_matches) = matches.subcommand().unwrap();
+    let (_, list_matches) = item_matches.subcommand().unwrap();
+    execute(client, list_matches).await
+}
+
+/// `args` parsed as `eagle-eye item list ARGS`, to check them before they are stored.
+pub fn parse_args(args: &[String]) -> Result<ArgMatches, Box<dyn std::error::Error>> {
+    let command_line = ["eagle-eye", "item", "list"].into_iter().map(String::from).chain(args.iter().cloned());
+    crate::cli::build().try_get_matches_from(command_line).map_err(|e| {
+        let message = e.render().to_string();
+        let first_line = message.lines().next().unwrap_or_default();
+        format!("Invalid item list arguments: {}", first_line.trim_start_matches("error: ")).into()
+    })
+}
+
+/// Default page size when walking through `/api/item/list`, the API's own default limit.
+pub const PAGE_SIZE: usize = 200;
+
+/// Fetch the items selected by `query_args()` and keep those passing `item_filter`.
+pub async fn fetch_items(
+    client: &EagleClient,
+    matches: &ArgMatches,
+    item_filter: &ItemFilter,
+) -> Result<Vec<ItemListData>, Box<dyn std::error::Error>> {
+    let mut items = Vec::new();
+    for_each_page(client, matches, item_filter, |page| items.extend(page)).await?;
+    Ok(items)
+}
+
+/// Every item in the library, page by page, regardless of any arguments.
+pub async fn all_items(client: &EagleClient) -> Result<Vec<ItemListData>, Box<dyn std::error::Error>> {
+    let mut items = Vec::new();
+    for page in 0.. {
+        let params = GetItemListParams {
+            limit: Some(PAGE_SIZE),
+            offset: Some(page),
+            ..GetItemListParams::new()
+        };
+        let data = client.item().list(params).await?.data;
+        let last_page = data.len() < PAGE_SIZE;
+        items.extend(data);
+        if last_page {
+            break;
+        }
+    }
+    Ok(items)
+}
+
+/// `--stdin` argument for commands that can also take the items to work on from a pipe.
+pub fn stdin_arg() -> Arg {
+    Arg::new("stdin")
+        .long("stdin")
+        .help("Read item ids (or paths printed by `item list`) from stdin instead of querying")
+        .action(ArgAction::SetTrue)
+}
+
+/// The items that failed in a `--resume` report, else those selected in Eagle for `selection`
+/// subcommands, else those named on stdin with `--stdin`, otherwise those selected by the query
+/// arguments.
+///
+/// Items read from a report, the selection or stdin are loaded from the library folder and still pass through
+/// `item_filter`.
+pub async fn select_items(
+    client: &EagleClient,
+    matches: &ArgMatches,
+    item_filter: &ItemFilter,
+    library: &LibraryDir,
+) -> Result<Vec<ItemListData>, Box<dyn std::error::Error>> {
+    let mut items = Vec::new();
+    if let Some(ids) = report::resume_targets(matches)? {
+        for id in ids {
+            let item = library.item(&id)?;
+            if item_filter.matches(&item) {
+                items.push(item);
             }
-        })
-        .map(|item| {
-            // let item_dir_name = &item.id + ".info";
-            let item_id = String::from(&item.id);
-            let item_dir_name = item_id + ".info";
-            let basename = &item.name;
-
-            if thumbnails_flag {
-                let thumbnail_filename = basename.to_owned() + "_thumbnail" + ".png";
-                let potential_path = library_path.join(&item_dir_name).join(&thumbnail_filename);
-
-                if potential_path.exists() {
-                    return potential_path;
-                }
+        }
+        return Ok(items);
+    }
+    if selection::is_requested(matches) {
+        for id in selection::selected_ids(matches).await? {
+            let item = library.item(&id)?;
+            if item_filter.matches(&item) {
+                items.push(item);
             }
+        }
+        return Ok(items);
+    }
+    if !matches.get_flag("stdin") {
+        return fetch_items(client, matches, item_filter).await;
+    }
 
-            let filename = basename.to_owned() + "." + item.ext.as_str();
-            library_path.join(item_dir_name).join(filename)
-        })
-        .collect();
+    for line in std::io::stdin().lines() {
+        let line = line?;
+        let Some(id) = item_id_of(line.trim()) else {
+            continue;
+        };
+        let item = library.item(id)?;
+        if item_filter.matches(&item) {
+            items.push(item);
+        }
+    }
+    Ok(items)
+}
+
+/// Extract the item id from an id or from a path inside an `<id>.info` folder.
+pub fn item_id_of(value: &str) -> Option<&str> {
+    if value.is_empty() {
+        return None;
+    }
+    let info_dir = value
+        .split(['/', '\\'])
+        .find_map(|component| component.strip_suffix(".info"));
+    Some(info_dir.unwrap_or(value))
+}
+
+/// Whether the items come from the local index rather than Eagle (`--use-index` or `--auto-index`)
+fn uses_index(matches: &ArgMatches) -> bool {
+    matches.try_get_one::<bool>("use_index").ok().flatten().copied().unwrap_or(false)
+        || matches.try_get_one::<i64>("auto_index").ok().flatten().is_some()
+}
+
+/// The library folder, from the index when the items come from it
+async fn library_dir(client: &EagleClient, matches: &ArgMatches) -> Result<LibraryDir, Box<dyn std::error::Error>> {
+    match uses_index(matches) {
+        true => index::library_of(&index::open()?),
+        false => Ok(LibraryDir::new(&client.library().info().await?.data.library.path)),
+    }
+}
+
+/// Pass the matching items to `on_page` one page at a time, as they arrive.
+///
+/// With `--all` or `--group-by`, or when client-side filters are set and no explicit `--limit`
+/// is given, keeps requesting pages until the library is exhausted or `--max` items matched, since
+/// matching items may be on any page. From the index, every match comes at once.
+pub async fn for_each_page<F>(
+    client: &EagleClient,
+    matches: &ArgMatches,
+    item_filter: &ItemFilter,
+    mut on_page: F,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    F: FnMut(Vec<ItemListData>),
+{
+    let mut query_params = query_params(matches);
+    let max = matches.try_get_one::<usize>("max").ok().flatten().copied();
+    if uses_index(matches) {
+        let mut items = index::query_items(&query_params, item_filter)?;
+        if let Some(max) = max {
+            items.truncate(max);
+        }
+        on_page(items);
+        return Ok(());
+    }
+    // Folders may be given by path or name too, which the API doesn't know
+    if let Some(folders) = &query_params.folders {
+        let list = client.folder().list().await?.data;
+        let ids = folders
+            .split(',')
+            .map(|folder| resolve_folder(&list, folder.trim()))
+            .collect::<Result<Vec<_>, _>>()?;
+        query_params.folders = Some(ids.join(","));
+    }
+    // Groups add up the whole library, not the first page of it
+    let all = matches.try_get_one::<bool>("all").ok().flatten().copied().unwrap_or(false)
+        || matches.try_get_one::<String>("group_by").ok().flatten().is_some();
+
+    if !all && (!item_filter.is_active() || query_params.limit.is_some()) {
+        let mut items: Vec<ItemListData> = client
+            .item()
+            .list(query_params)
+            .await?
+            .data
+            .into_iter()
+            .filter(|item| item_filter.matches(item))
+            .collect();
+        if let Some(max) = max {
+            items.truncate(max);
+        }
+        on_page(items);
+        return Ok(());
+    }
+
+    let page_size = matches
+        .try_get_one::<usize>("page_size")
+        .ok()
+        .flatten()
+        .copied()
+        .unwrap_or(PAGE_SIZE);
+    let mut remaining = max.unwrap_or(usize::MAX);
+    let mut page = query_params.offset.unwrap_or(0);
+    while remaining > 0 {
+        let page_params = GetItemListParams {
+            limit: Some(page_size),
+            offset: Some(page),
+            ..query_params.clone()
+        };
+        let data = client.item().list(page_params).await?.data;
+        let last_page = data.len() < page_size;
+        let items: Vec<ItemListData> = data
+            .into_iter()
+            .filter(|item| item_filter.matches(item))
+            .take(remaining)
+            .collect();
+        remaining -= items.len();
+        on_page(items);
+        if last_page {
+            break;
+        }
+        page += 1;
+    }
+    Ok(())
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    index::refresh_if_stale(client, matches).await?;
+    let mut item_filter = ItemFilter::from_matches(matches)?;
+    // The library folder is only looked up once something needs the item files
+    let mut library = None;
+    if exif::is_requested(matches) {
+        let dir = library_dir(client, matches).await?;
+        item_filter.exif = ExifFilter::from_matches(matches, &dir)?;
+        library = Some(dir);
+    }
+
+    if let Some(group_by) = matches.get_one::<String>("group_by").and_then(|value| GroupBy::parse(value)) {
+        let items = fetch_items(client, matches, &item_filter).await?;
+        let folders = match group_by {
+            GroupBy::Folder => client.folder().list().await?.data,
+            _ => Vec::new(),
+        };
+        let groups = group::group(&items, group_by, &folders, &datetime::from_matches(matches));
+        return output::output(&Value::Array(groups), matches);
+    }
+
+    if picker::is_requested(matches) {
+        let items = fetch_items(client, matches, &item_filter).await?;
+        let entries: Vec<picker::Entry> = items
+            .iter()
+            .map(|item| picker::Entry {
+                key: item.id.clone(),
+                text: format!("{}.{}  {}  {}", item.name, item.ext, item.tags.join(", "), item.id),
+            })
+            .collect();
+        return picker::pick_and_print(&entries);
+    }
+
+    if matches.get_flag("count") {
+        let mut count = 0;
+        for_each_page(client, matches, &item_filter, |items| count += items.len()).await?;
+        return output::output(&Value::from(count), matches);
+    }
+
+    let library = match library {
+        Some(library) => library,
+        None => library_dir(client, matches).await?,
+    };
+    let thumbnails_flag = matches.get_flag("thumbnails");
+
+    if matches.contains_id("exec") {
+        let items = fetch_items(client, matches, &item_filter).await?;
+        return exec::run(matches, &items, &library);
+    }
+
+    // Paths are the default; structured output is opt-in
+    if output::is_explicit(matches) {
+        let items = fetch_items(client, matches, &item_filter).await?;
+        return output::output(&Value::Array(item_rows(&items, &library, thumbnails_flag)), matches);
+    }
 
-    for path in &paths {
-        println!("{}", path.display());
+    // Sorting needs every item at once, so sorted paths can't be streamed page by page
+    if output::is_sorted(matches) {
+        let items = fetch_items(client, matches, &item_filter).await?;
+        let mut options = OutputOptions::from_matches(matches);
+        options.format = OutputFormat::Path;
+        let rows = Value::Array(item_rows(&items, &library, thumbnails_flag));
+        pager::print(&output::render(&rows, &options)?, matches)?;
+        let (count, bytes) = output::count(&rows);
+        output::summary(matches, count, bytes);
+        return Ok(());
     }
 
+    let mut pager = Pager::new(matches);
+    let mut written = Ok(());
+    let (mut count, mut bytes) = (0, 0);
+    for_each_page(client, matches, &item_filter, |items| {
+        count += items.len();
+        bytes += items.iter().map(|item| item.size).sum::<u64>();
+        let paths: Vec<PathBuf> = items
+            .par_iter()
+            .map(|item| item_path(&library, item, thumbnails_flag))
+            .collect();
+
+        let lines: String = paths.iter().map(|path| format!("{}\n", path.display())).collect();
+        if written.is_ok() {
+            written = pager.write(&lines);
+        }
+    })
+    .await?;
+    written?;
+    pager.finish()?;
+    output::summary(matches, Some(count), Some(bytes));
+
     Ok(())
 }
+
+/// Items as output rows, with the resolved `path` of each added.
+pub fn item_rows(items: &[ItemListData], library: &LibraryDir, thumbnail: bool) -> Vec<Value> {
+    items
+        .par_iter()
+        .map(|item| {
+            let mut row = serde_json::to_value(item).unwrap_or_default();
+            row["path"] = json!(item_path(library, item, thumbnail));
+            row
+        })
+        .collect()
+}
+
+/// Path of an item's file (or its thumbnail, when it has one) as laid out on disk.
+///
+/// Falls back to the `<id>.info/<name>.<ext>` path Eagle would use when the file is missing.
+pub fn item_path(library: &LibraryDir, item: &ItemListData, thumbnail: bool) -> PathBuf {
+    if thumbnail {
+        if let Some(path) = library.item_thumbnail(&item.id, &item.name) {
+            return path;
+        }
+    }
+    library
+        .item_file(&item.id, &item.name, &item.ext)
+        .unwrap_or_else(|| library.item_dir(&item.id).join(format!("{}.{}", item.name, item.ext)))
+}
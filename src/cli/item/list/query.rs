@@ -0,0 +1,218 @@
+use super::expr::{Expr, Op, Operand};
+use super::filter::ItemFilter;
+use crate::cli::datetime::{parse_duration_millis, DateBound, TimeZone};
+use crate::cli::units::parse_size;
+use crate::lib::types::GetItemListParams;
+use globset::{Glob, GlobMatcher};
+use serde_json::Value;
+use std::str::FromStr;
+
+/// Fields a query term can name, for the error on an unknown one
+const FIELDS: &str = "ext, tag, folder, name, url, star, size, width, height, duration, since, until";
+
+/// A compact search such as `ext:png tag:logo folder:"Brand/2024" star:>=4 size:<5MB`.
+///
+/// Terms are separated by spaces and all have to match. `field:value` terms filter on a field,
+/// other words are keywords searched by Eagle. `ext` and `tag` can be negated with a leading
+/// `-`; `star`, `size`, `width`, and `height` take `>`, `>=`, `<`, `<=`, or `=` before the
+/// number. Values holding spaces go in double quotes, and a keyword starting with `-` is
+/// written `\-word` or `"-word"`. Only one `folder:` term is allowed, since Eagle would match
+/// items in any of several folders.
+///
+/// A query adds to the other item filters: keywords, the folder, one extension, and one tag
+/// go to `/api/item/list`, everything else is matched client-side like the `ItemFilter` flags.
+#[derive(Debug, Clone, Default)]
+pub struct Query {
+    keywords: Vec<String>,
+    exts: Vec<String>,
+    not_exts: Vec<String>,
+    tags: Vec<String>,
+    not_tags: Vec<String>,
+    /// Folder id, path, or unique name, resolved when the items are fetched
+    folder: Option<String>,
+    name: Option<GlobMatcher>,
+    url: Option<String>,
+    comparisons: Vec<(String, Op, f64)>,
+    since: Option<DateBound>,
+    until: Option<DateBound>,
+}
+
+impl FromStr for Query {
+    type Err = String;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let mut query = Query::default();
+        for (term, escaped) in terms(source)? {
+            let (negated, term) = match term.strip_prefix('-') {
+                Some(rest) if !rest.is_empty() && !escaped => (true, rest),
+                _ => (false, term.as_str()),
+            };
+            let Some((field, value)) = term.split_once(':').filter(|(field, _)| is_field_name(field)) else {
+                if negated {
+                    return Err(format!(
+                        "only ext: and tag: terms can be negated: -{} (write \\-{} for a keyword)",
+                        term, term
+                    ));
+                }
+                query.keywords.push(term.to_string());
+                continue;
+            };
+            if value.is_empty() {
+                return Err(format!("missing value in query term {}:", field));
+            }
+            match (field, negated) {
+                ("ext", false) => query.exts.extend(split(value)),
+                ("ext", true) => query.not_exts.extend(split(value)),
+                ("tag", false) => query.tags.push(value.to_string()),
+                ("tag", true) => query.not_tags.push(value.to_string()),
+                (_, true) => return Err(format!("only ext: and tag: terms can be negated: -{}", term)),
+                ("folder", _) => {
+                    if let Some(folder) = &query.folder {
+                        return Err(format!(
+                            "only one folder: term is allowed, items in {} and {} can't both be required",
+                            folder, value
+                        ));
+                    }
+                    query.folder = Some(value.to_string());
+                }
+                ("name", _) => {
+                    let glob = Glob::new(value).map_err(|e| format!("invalid name pattern {}: {}", value, e))?;
+                    query.name = Some(glob.compile_matcher());
+                }
+                ("url", _) => query.url = Some(value.to_string()),
+                ("since", _) => query.since = Some(value.parse()?),
+                ("until", _) => query.until = Some(value.parse()?),
+                ("star" | "size" | "width" | "height" | "duration", _) => {
+                    let (op, number) = comparison(value);
+                    let number = match (field, parse_duration_millis(number)) {
+                        ("size", _) => parse_size(number)? as f64,
+                        // Eagle keeps lengths in seconds
+                        ("duration", Some(millis)) => millis as f64 / 1000.0,
+                        _ => number
+                            .parse::<f64>()
+                            .map_err(|_| format!("invalid number in query term {}:{}", field, value))?,
+                    };
+                    query.comparisons.push((field.to_string(), op, number));
+                }
+                _ => return Err(format!("unknown field {} in query (expected {})", field, FIELDS)),
+            }
+        }
+        Ok(query)
+    }
+}
+
+/// Whether `field` looks like a field name rather than part of a keyword such as a URL
+fn is_field_name(field: &str) -> bool {
+    !field.is_empty() && field.chars().all(|c| c.is_ascii_lowercase())
+}
+
+/// Split a query into its terms at spaces outside double quotes, dropping the quotes.
+///
+/// Each term comes with whether it starts quoted or with `\`, so a leading `-` is literal.
+fn terms(source: &str) -> Result<Vec<(String, bool)>, String> {
+    let mut terms = Vec::new();
+    let mut term = String::new();
+    let mut escaped = false;
+    let mut quoted = false;
+    let mut chars = source.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                escaped |= term.is_empty();
+                quoted = !quoted;
+            }
+            '\\' if quoted => term.extend(chars.next()),
+            '\\' if term.is_empty() => {
+                escaped = true;
+                term.extend(chars.next());
+            }
+            c if c.is_whitespace() && !quoted => {
+                if !term.is_empty() {
+                    terms.push((std::mem::take(&mut term), escaped));
+                }
+                escaped = false;
+            }
+            c => term.push(c),
+        }
+    }
+    if quoted {
+        return Err(format!("unterminated quote in query: {}", source));
+    }
+    if !term.is_empty() {
+        terms.push((term, escaped));
+    }
+    Ok(terms)
+}
+
+fn split(value: &str) -> impl Iterator<Item = String> + '_ {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_lowercase)
+}
+
+/// The operator in front of a number, `=` when there is none
+fn comparison(value: &str) -> (Op, &str) {
+    for (prefix, op) in [(">=", Op::Ge), ("<=", Op::Le), (">", Op::Gt), ("<", Op::Lt), ("=", Op::Eq)] {
+        if let Some(rest) = value.strip_prefix(prefix) {
+            return (op, rest);
+        }
+    }
+    (Op::Eq, value)
+}
+
+impl Query {
+    /// Narrow the `/api/item/list` parameters with what the API can evaluate, where no
+    /// argument set them already.
+    pub fn apply_params(&self, params: &mut GetItemListParams) {
+        if params.keyword.is_none() && !self.keywords.is_empty() {
+            params.keyword = Some(self.keywords.join(" "));
+        }
+        if params.folders.is_none() {
+            params.folders = self.folder.clone();
+        }
+        if params.ext.is_none() {
+            if let [ext] = self.exts.as_slice() {
+                params.ext = Some(ext.clone());
+            }
+        }
+        if params.tags.is_none() {
+            params.tags = self.tags.first().cloned();
+        }
+    }
+
+    /// Add the client-side part of the query to `filter`.
+    pub fn apply_filter(&self, filter: &mut ItemFilter, tz: &TimeZone) -> Result<(), String> {
+        filter.exts.extend(self.exts.iter().cloned());
+        filter.not_exts.extend(self.not_exts.iter().cloned());
+        filter.all_tags.extend(self.tags.iter().cloned());
+        filter.not_tags.extend(self.not_tags.iter().cloned());
+        if self.name.is_some() {
+            filter.name_glob = self.name.clone();
+        }
+        if self.url.is_some() {
+            filter.url = self.url.clone();
+        }
+        if let Some(since) = &self.since {
+            let since = since.resolve(tz)?;
+            filter.since = Some(filter.since.map_or(since, |other| other.max(since)));
+        }
+        if let Some(until) = &self.until {
+            let until = until.resolve(tz)?;
+            filter.until = Some(filter.until.map_or(until, |other| other.min(until)));
+        }
+        for (field, op, number) in &self.comparisons {
+            let comparison = Expr::Compare(
+                Operand::Field(vec![field.clone()]),
+                *op,
+                Operand::Literal(Value::from(*number)),
+            );
+            filter.where_expr = Some(match filter.where_expr.take() {
+                Some(expr) => Expr::And(Box::new(expr), Box::new(comparison)),
+                None => comparison,
+            });
+        }
+        Ok(())
+    }
+}
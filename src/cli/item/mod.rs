@@ -1,8 +1,37 @@
-use clap::{Arg, ArgMatches, ArgAction, Command};
+use clap::{ArgMatches, Command};
 use crate::lib::client::EagleClient;
+pub mod add_from_url;
+pub mod apply;
+pub mod autotag;
+pub mod color_search;
+pub mod contact_sheet;
+pub mod copy;
+pub mod describe;
+pub mod exif;
+pub mod export;
+pub mod export_bookmarks;
+pub mod fonts;
+pub mod import;
 pub mod info;
+pub mod largest;
+pub mod linkfarm;
 pub mod list;
+pub mod move_items;
+pub mod ocr;
+pub mod open;
+pub mod palette;
+pub mod palette_clusters;
+pub mod palettes;
+pub mod path;
+pub mod preview;
+pub mod random;
+pub mod rename;
+pub mod semantic_search;
+pub mod sidecar;
+pub mod similar;
 pub mod thumbnail;
+pub mod thumbnails;
+pub mod trash;
 
 pub fn build() -> Command {
                 Command::new("item")
@@ -21,6 +50,35 @@ pub fn build() -> Command {
             .subcommand(list::build())
             .subcommand(thumbnail::build())
             .subcommand(info::build())
+            .subcommand(palette_clusters::build())
+            .subcommand(largest::build())
+            .subcommand(path::build())
+            .subcommand(export::build())
+            .subcommand(linkfarm::build())
+            .subcommand(open::build())
+            .subcommand(copy::build())
+            .subcommand(preview::build())
+            .subcommand(contact_sheet::build())
+            .subcommand(random::build())
+            .subcommand(rename::build())
+            .subcommand(move_items::build())
+            .subcommand(trash::build())
+            .subcommand(similar::build())
+            .subcommand(color_search::build())
+            .subcommand(palette::build())
+            .subcommand(exif::build())
+            .subcommand(sidecar::build())
+            .subcommand(ocr::build())
+            .subcommand(describe::build())
+            .subcommand(semantic_search::build())
+            .subcommand(add_from_url::build())
+            .subcommand(import::build())
+            .subcommand(export_bookmarks::build())
+            .subcommand(apply::build())
+            .subcommand(autotag::build())
+            .subcommand(thumbnails::build())
+            .subcommand(palettes::build())
+            .subcommand(fonts::build())
 }
 
 pub async fn execute(
@@ -29,13 +87,100 @@ pub async fn execute(
 ) -> Result<(), Box<dyn std::error::Error>> {
     match matches.subcommand() {
         Some(("info", info_matches)) => {
-            info::execute(&client, info_matches).await?;
+            info::execute(client, info_matches).await?;
         },
         Some(("list", list_matches)) => {
-            list::execute(&client, list_matches).await?;
+            list::execute(client, list_matches).await?;
         },
         Some(("thumbnail", thumbnail_matches)) => {
-            thumbnail::execute(&client, thumbnail_matches).await?;
+            thumbnail::execute(client, thumbnail_matches).await?;
+        },
+        Some(("palette-clusters", palette_clusters_matches)) => {
+            palette_clusters::execute(client, palette_clusters_matches).await?;
+        },
+        Some(("largest", largest_matches)) => {
+            largest::execute(client, largest_matches).await?;
+        },
+        Some(("path", path_matches)) => {
+            path::execute(client, path_matches).await?;
+        },
+        Some(("export", export_matches)) => {
+            export::execute(client, export_matches).await?;
+        },
+        Some(("linkfarm", linkfarm_matches)) => {
+            linkfarm::execute(client, linkfarm_matches).await?;
+        },
+        Some(("open", open_matches)) => {
+            open::execute(client, open_matches).await?;
+        },
+        Some(("copy", copy_matches)) => {
+            copy::execute(client, copy_matches).await?;
+        },
+        Some(("preview", preview_matches)) => {
+            preview::execute(client, preview_matches).await?;
+        },
+        Some(("contact-sheet", contact_sheet_matches)) => {
+            contact_sheet::execute(client, contact_sheet_matches).await?;
+        },
+        Some(("random", random_matches)) => {
+            random::execute(client, random_matches).await?;
+        },
+        Some(("rename", rename_matches)) => {
+            rename::execute(client, rename_matches).await?;
+        },
+        Some(("move", move_matches)) => {
+            move_items::execute(client, move_matches).await?;
+        },
+        Some(("trash", trash_matches)) => {
+            trash::execute(client, trash_matches).await?;
+        },
+        Some(("similar", similar_matches)) => {
+            similar::execute(client, similar_matches).await?;
+        },
+        Some(("color-search", color_search_matches)) => {
+            color_search::execute(client, color_search_matches).await?;
+        },
+        Some(("palette", palette_matches)) => {
+            palette::execute(client, palette_matches).await?;
+        },
+        Some(("exif", exif_matches)) => {
+            exif::execute(client, exif_matches).await?;
+        },
+        Some(("sidecar", sidecar_matches)) => {
+            sidecar::execute(client, sidecar_matches).await?;
+        },
+        Some(("ocr", ocr_matches)) => {
+            ocr::execute(client, ocr_matches).await?;
+        },
+        Some(("describe", describe_matches)) => {
+            describe::execute(client, describe_matches).await?;
+        },
+        Some(("semantic-search", semantic_search_matches)) => {
+            semantic_search::execute(client, semantic_search_matches).await?;
+        },
+        Some(("add-from-url", add_from_url_matches)) => {
+            add_from_url::execute(client, add_from_url_matches).await?;
+        },
+        Some(("import", import_matches)) => {
+            import::execute(client, import_matches).await?;
+        },
+        Some(("export-bookmarks", export_bookmarks_matches)) => {
+            export_bookmarks::execute(client, export_bookmarks_matches).await?;
+        },
+        Some(("apply", apply_matches)) => {
+            apply::execute(client, apply_matches).await?;
+        },
+        Some(("autotag", autotag_matches)) => {
+            autotag::execute(client, autotag_matches).await?;
+        },
+        Some(("thumbnails", thumbnails_matches)) => {
+            thumbnails::execute(client, thumbnails_matches).await?;
+        },
+        Some(("palettes", palettes_matches)) => {
+            palettes::execute(client, palettes_matches).await?;
+        },
+        Some(("fonts", fonts_matches)) => {
+            fonts::execute(client, fonts_matches).await?;
         },
         _ => {
             println!("No subcommand was used");
@@ -0,0 +1,115 @@
+use crate::cli::confirm;
+use crate::cli::folder::resolve_folder;
+use crate::cli::item::list::{self, filter::ItemFilter};
+use crate::cli::progress::Progress;
+use crate::cli::report::{self, Failures};
+use crate::lib::client::EagleClient;
+use crate::lib::library::LibraryDir;
+use crate::lib::types::{ItemListData, UpdateItemParams};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+
+pub fn build() -> Command {
+    Command::new("move")
+        .about("Move items to another folder")
+        .arg(
+            Arg::new("to")
+                .long("to")
+                .value_name("FOLDER")
+                .help("Destination folder, as an id, a path like Brand/Logos, or a unique name")
+                .required(true)
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("from")
+                .long("from")
+                .value_name("FOLDER")
+                .help("Only move items out of this folder, keeping their other folders")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("copy")
+                .long("copy")
+                .help("Add the items to the destination without removing them from any folder")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("from"),
+        )
+        .arg(list::stdin_arg())
+        .args(list::query_args())
+        .args(list::filter::args())
+        .args(report::args())
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let folders = client.folder().list().await?.data;
+    let to = resolve_folder(&folders, matches.get_one::<String>("to").unwrap())?;
+    let from = match matches.get_one::<String>("from") {
+        Some(from) => Some(resolve_folder(&folders, from)?),
+        None => None,
+    };
+    let copy = matches.get_flag("copy");
+
+    let library_data = client.library().info().await?.data;
+    let library = LibraryDir::new(&library_data.library.path);
+    let item_filter = ItemFilter::from_matches(matches)?;
+    let items = list::select_items(client, matches, &item_filter, &library).await?;
+
+    let moves: Vec<(&ItemListData, Vec<String>)> = items
+        .iter()
+        .filter_map(|item| {
+            let current = item.folders.clone().unwrap_or_default();
+            let mut new_folders: Vec<String> = match &from {
+                Some(from) if !current.contains(from) => return None,
+                Some(from) => current.iter().filter(|id| *id != from).cloned().collect(),
+                None if copy => current.clone(),
+                None => Vec::new(),
+            };
+            if !new_folders.contains(&to) {
+                new_folders.push(to.clone());
+            }
+            (new_folders != current).then_some((item, new_folders))
+        })
+        .collect();
+    let unchanged = items.len() - moves.len();
+    // Adding to a folder takes nothing away
+    if !copy && !moves.is_empty() {
+        confirm::confirm(
+            matches,
+            &format!("{} items will be moved to {}", moves.len(), matches.get_one::<String>("to").unwrap()),
+        )?;
+    }
+
+    let mut moved = 0;
+    let mut failures = Failures::default();
+    let mut progress = Progress::new(moves.len(), "moving", matches);
+    for (item, new_folders) in moves {
+        progress.inc();
+        let params = UpdateItemParams {
+            id: item.id.clone(),
+            folders: Some(new_folders),
+            ..Default::default()
+        };
+        match client.item().update(&params).await {
+            Ok(_) => moved += 1,
+            Err(error) => {
+                progress.finish();
+                eprintln!("Failed to move {} ({}.{}): {}", item.id, item.name, item.ext, error);
+                failures.add(&item.id, error);
+            }
+        }
+    }
+
+    progress.finish();
+
+    let action = if copy { "Added" } else { "Moved" };
+    println!(
+        "{} {} items ({} already there or not in the source folder, {} failed)",
+        action,
+        moved,
+        unchanged,
+        failures.len()
+    );
+    report::finish(matches, "item move", moved, failures)
+}
@@ -0,0 +1,144 @@
+use crate::cli::item::list::{self, filter::ItemFilter};
+use crate::cli::progress::Progress;
+use crate::cli::report::{self, Failures};
+use crate::lib::client::EagleClient;
+use crate::lib::library::LibraryDir;
+use crate::lib::types::{ItemListData, UpdateItemParams};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+/// Formats tesseract reads directly; anything else is recognized from its thumbnail
+const READABLE: &[&str] = &["png", "jpg", "jpeg", "tif", "tiff", "bmp", "gif", "webp", "pbm", "pgm", "ppm"];
+
+pub fn build() -> Command {
+    Command::new("ocr")
+        .about("Recognize text in item images with tesseract and store it in the annotation")
+        .arg(
+            Arg::new("lang")
+                .long("lang")
+                .value_name("LANG")
+                .help("Tesseract language(s), e.g. eng or eng+deu")
+                .num_args(1)
+                .default_value("eng"),
+        )
+        .arg(
+            Arg::new("tesseract")
+                .long("tesseract")
+                .value_name("PATH")
+                .help("Tesseract binary to run")
+                .num_args(1)
+                .env("EAGLE_EYE_TESSERACT")
+                .default_value("tesseract"),
+        )
+        .arg(
+            Arg::new("append")
+                .long("append")
+                .help("Append the text to the existing annotation instead of replacing it")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("dry_run")
+                .long("dry-run")
+                .help("Print the recognized text without updating any item")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(list::stdin_arg())
+        .args(list::query_args())
+        .args(list::filter::args())
+        .args(report::args())
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let lang = matches.get_one::<String>("lang").unwrap();
+    let tesseract = matches.get_one::<String>("tesseract").unwrap();
+    let append = matches.get_flag("append");
+    let dry_run = matches.get_flag("dry_run");
+
+    let library_data = client.library().info().await?.data;
+    let library = LibraryDir::new(&library_data.library.path);
+    let item_filter = ItemFilter::from_matches(matches)?;
+    let items = list::select_items(client, matches, &item_filter, &library).await?;
+
+    let mut progress = Progress::new(items.len(), "recognizing", matches);
+    let (mut updated, mut empty) = (0, 0);
+    let mut failures = Failures::default();
+    for item in &items {
+        progress.inc();
+        let text = match ocr_source(&library, item)
+            .ok_or_else(|| "no readable file or thumbnail".to_string())
+            .and_then(|path| recognize(tesseract, &path, lang))
+        {
+            Ok(text) => text,
+            Err(error) => {
+                progress.finish();
+                eprintln!("Failed to recognize {}: {}", item.id, error);
+                failures.add(&item.id, error);
+                continue;
+            }
+        };
+        if text.is_empty() {
+            empty += 1;
+            continue;
+        }
+
+        if dry_run {
+            progress.finish();
+            println!("{}:\n{}\n", item.id, text);
+            updated += 1;
+            continue;
+        }
+
+        let annotation = match append && !item.annotation.is_empty() {
+            true => format!("{}\n\n{}", item.annotation, text),
+            false => text,
+        };
+        let params = UpdateItemParams {
+            id: item.id.clone(),
+            annotation: Some(annotation),
+            ..Default::default()
+        };
+        match client.item().update(&params).await {
+            Ok(_) => updated += 1,
+            Err(error) => {
+                progress.finish();
+                eprintln!("Failed to update {}: {}", item.id, error);
+                failures.add(&item.id, error);
+            }
+        }
+    }
+    progress.finish();
+
+    let action = if dry_run { "Recognized text in" } else { "Annotated" };
+    eprintln!("{} {} items ({} without text, {} failed)", action, updated, empty, failures.len());
+    report::finish(matches, "item ocr", updated + empty, failures)
+}
+
+/// The item file when tesseract can read it, otherwise its thumbnail
+fn ocr_source(library: &LibraryDir, item: &ItemListData) -> Option<PathBuf> {
+    if READABLE.contains(&item.ext.to_lowercase().as_str()) {
+        if let Some(path) = library.item_file(&item.id, &item.name, &item.ext) {
+            return Some(path);
+        }
+    }
+    library.item_thumbnail(&item.id, &item.name)
+}
+
+/// Run tesseract on `path` and return the recognized text, trimmed
+fn recognize(tesseract: &str, path: &Path, lang: &str) -> Result<String, String> {
+    let output = std::process::Command::new(tesseract)
+        .arg(path)
+        .arg("stdout")
+        .args(["-l", lang])
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| format!("failed to run {}: {}", tesseract, e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("{} exited with {}: {}", tesseract, output.status, stderr.trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
@@ -0,0 +1,62 @@
+use crate::cli::system;
+use crate::lib::client::EagleClient;
+use crate::lib::library::LibraryDir;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+
+pub fn build() -> Command {
+    Command::new("open")
+        .about("Open items in Eagle, the file manager, or another application")
+        .arg(
+            Arg::new("ids")
+                .value_name("ID")
+                .help("Item ids")
+                .required(true)
+                .num_args(1..),
+        )
+        .arg(
+            Arg::new("reveal")
+                .short('r')
+                .long("reveal")
+                .help("Show the original file in Finder / Explorer")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("with"),
+        )
+        .arg(
+            Arg::new("with")
+                .short('w')
+                .long("with")
+                .value_name("APP")
+                .help("Open the original file with this application")
+                .num_args(1),
+        )
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let ids: Vec<&String> = matches.get_many::<String>("ids").unwrap().collect();
+    let reveal = matches.get_flag("reveal");
+    let with = matches.get_one::<String>("with");
+
+    if !reveal && with.is_none() {
+        for id in ids {
+            system::open(&format!("eagle://item/{}", id))?;
+        }
+        return Ok(());
+    }
+
+    let library_data = client.library().info().await?.data;
+    let library = LibraryDir::new(&library_data.library.path);
+    for id in ids {
+        let item = library.item(id)?;
+        let path = library
+            .item_file(&item.id, &item.name, &item.ext)
+            .ok_or_else(|| format!("No file found for item {}", id))?;
+        match with {
+            Some(app) => system::open_with(app, &path)?,
+            None => system::reveal(&path)?,
+        }
+    }
+    Ok(())
+}
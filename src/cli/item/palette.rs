@@ -0,0 +1,149 @@
+use crate::cli::color::Rgb;
+use crate::lib::client::EagleClient;
+use crate::lib::types::GetItemInfoParams;
+use clap::{Arg, ArgMatches, Command};
+use serde_json::json;
+use std::fmt::Write;
+use std::io::Write as _;
+
+pub fn build() -> Command {
+    Command::new("palette")
+        .about("Export the palette Eagle extracted from an item as swatches")
+        .arg(
+            Arg::new("id")
+                .value_name("ID")
+                .help("Item to export the palette of")
+                .required(true),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_name("FORMAT")
+                .help("Swatch format")
+                .num_args(1)
+                .default_value("css")
+                .value_parser(["css", "scss", "ase", "json"]),
+        )
+        .arg(
+            Arg::new("name")
+                .long("name")
+                .value_name("PREFIX")
+                .help("Prefix of the swatch names, numbered from 1 in palette order")
+                .num_args(1)
+                .default_value("color"),
+        )
+        .arg(
+            Arg::new("out")
+                .short('o')
+                .long("out")
+                .value_name("FILE")
+                .help("Write to FILE instead of stdout")
+                .num_args(1),
+        )
+}
+
+/// A palette color with its swatch name and share of the image in percent
+struct Swatch {
+    name: String,
+    color: Rgb,
+    ratio: f64,
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let id = matches.get_one::<String>("id").unwrap();
+    let prefix = matches.get_one::<String>("name").unwrap();
+    let item = client.item().info(GetItemInfoParams { id: id.clone() }).await?.data;
+
+    let swatches: Vec<Swatch> = item
+        .palettes
+        .iter()
+        .filter_map(|palette| Rgb::from_palette(&palette.color).map(|color| (color, palette.ratio)))
+        .enumerate()
+        .map(|(index, (color, ratio))| Swatch {
+            name: format!("{}-{}", prefix, index + 1),
+            color,
+            ratio,
+        })
+        .collect();
+    if swatches.is_empty() {
+        return Err(format!("Item {} has no palette", id).into());
+    }
+
+    let data = match matches.get_one::<String>("format").unwrap().as_str() {
+        "scss" => scss(&swatches, prefix).into_bytes(),
+        "ase" => ase(&swatches),
+        "json" => json(&swatches)?.into_bytes(),
+        _ => css(&swatches).into_bytes(),
+    };
+    match matches.get_one::<String>("out") {
+        Some(path) => std::fs::write(path, data)?,
+        None => std::io::stdout().write_all(&data)?,
+    }
+    Ok(())
+}
+
+fn css(swatches: &[Swatch]) -> String {
+    let mut out = String::from(":root {\n");
+    for swatch in swatches {
+        writeln!(out, "  --{}: {}; /* {:.1}% */", swatch.name, swatch.color.to_hex(), swatch.ratio).unwrap();
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn scss(swatches: &[Swatch], prefix: &str) -> String {
+    let mut out = String::new();
+    for swatch in swatches {
+        writeln!(out, "${}: {};", swatch.name, swatch.color.to_hex()).unwrap();
+    }
+    writeln!(out, "\n${}s: (", prefix).unwrap();
+    for swatch in swatches {
+        writeln!(out, "  \"{}\": ${},", swatch.name, swatch.name).unwrap();
+    }
+    out.push_str(");\n");
+    out
+}
+
+fn json(swatches: &[Swatch]) -> Result<String, serde_json::Error> {
+    let swatches: Vec<_> = swatches
+        .iter()
+        .map(|swatch| {
+            let Rgb(r, g, b) = swatch.color;
+            json!({ "name": swatch.name, "hex": swatch.color.to_hex(), "rgb": [r, g, b], "ratio": swatch.ratio })
+        })
+        .collect();
+    Ok(serde_json::to_string_pretty(&swatches)? + "\n")
+}
+
+/// Adobe Swatch Exchange: a header, then one block per RGB color with a UTF-16 name
+fn ase(swatches: &[Swatch]) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(b"ASEF");
+    out.extend_from_slice(&1u16.to_be_bytes());
+    out.extend_from_slice(&0u16.to_be_bytes());
+    out.extend_from_slice(&(swatches.len() as u32).to_be_bytes());
+
+    for swatch in swatches {
+        let name: Vec<u16> = swatch.name.encode_utf16().chain(std::iter::once(0)).collect();
+        let mut block = Vec::new();
+        block.extend_from_slice(&(name.len() as u16).to_be_bytes());
+        for unit in &name {
+            block.extend_from_slice(&unit.to_be_bytes());
+        }
+        block.extend_from_slice(b"RGB ");
+        let Rgb(r, g, b) = swatch.color;
+        for channel in [r, g, b] {
+            block.extend_from_slice(&(channel as f32 / 255.0).to_be_bytes());
+        }
+        // Color type 2 is a "normal" (process) color
+        block.extend_from_slice(&2u16.to_be_bytes());
+
+        out.extend_from_slice(&1u16.to_be_bytes());
+        out.extend_from_slice(&(block.len() as u32).to_be_bytes());
+        out.extend_from_slice(&block);
+    }
+    out
+}
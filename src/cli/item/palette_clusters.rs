@@ -0,0 +1,191 @@
+use crate::cli::color::{Lab, Rgb};
+use crate::cli::item::list::{self, filter::ItemFilter};
+use crate::cli::output;
+use crate::lib::client::EagleClient;
+use crate::lib::types::ItemListData;
+use clap::{Arg, ArgMatches, Command};
+use serde_json::{json, Value};
+
+pub fn build() -> Command {
+    Command::new("palette-clusters")
+        .about("Cluster the dominant colors of matching items")
+        .arg(
+            Arg::new("clusters")
+                .short('c')
+                .long("clusters")
+                .value_name("K")
+                .help("Number of color clusters")
+                .num_args(1)
+                .default_value("8")
+                .value_parser(clap::value_parser!(usize)),
+        )
+        .arg(
+            Arg::new("samples")
+                .short('s')
+                .long("samples")
+                .value_name("N")
+                .help("Number of sample item ids shown per cluster")
+                .num_args(1)
+                .default_value("5")
+                .value_parser(clap::value_parser!(usize)),
+        )
+        .arg(
+            Arg::new("iterations")
+                .long("iterations")
+                .value_name("N")
+                .help("Maximum number of k-means iterations")
+                .num_args(1)
+                .default_value("50")
+                .value_parser(clap::value_parser!(usize)),
+        )
+        .args(list::query_args())
+        .args(list::filter::args())
+        // Clusters describe every selected item, not only those on the first page
+        .mut_arg("all", |arg| arg.default_value("true").hide(true))
+        .args(output::args())
+}
+
+/// One palette entry of one item
+struct Point<'a> {
+    lab: Lab,
+    weight: f64,
+    item_id: &'a str,
+}
+
+pub struct Cluster<'a> {
+    pub centroid: Lab,
+    pub weight: f64,
+    pub colors: usize,
+    /// Item ids ordered by how much of their palette falls into this cluster
+    pub items: Vec<(&'a str, f64)>,
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let k = *matches.get_one::<usize>("clusters").unwrap();
+    let samples = *matches.get_one::<usize>("samples").unwrap();
+    let iterations = *matches.get_one::<usize>("iterations").unwrap();
+
+    let item_filter = ItemFilter::from_matches(matches)?;
+    let items: Vec<ItemListData> = list::fetch_items(client, matches, &item_filter).await?;
+
+    let points: Vec<Point> = items
+        .iter()
+        .flat_map(|item| {
+            item.palettes.iter().flatten().filter_map(|palette| {
+                Some(Point {
+                    lab: Rgb::from_palette(&palette.color)?.to_lab(),
+                    weight: palette.ratio.max(0.0),
+                    item_id: &item.id,
+                })
+            })
+        })
+        .collect();
+
+    if points.is_empty() {
+        eprintln!("No palette data in {} matching items", items.len());
+        return output::output(&Value::Array(Vec::new()), matches);
+    }
+
+    let clusters = kmeans(&points, k, iterations);
+    let total_weight: f64 = clusters.iter().map(|cluster| cluster.weight).sum();
+
+    let rows: Vec<Value> = clusters
+        .iter()
+        .map(|cluster| {
+            let sample_ids: Vec<&str> = cluster.items.iter().take(samples).map(|(id, _)| *id).collect();
+            json!({
+                "color": cluster.centroid.to_rgb().to_hex(),
+                "items": cluster.items.len(),
+                "colors": cluster.colors,
+                // Percent of the palette weight of every matching item
+                "share": (cluster.weight / total_weight * 1000.0).round() / 10.0,
+                "samples": sample_ids,
+            })
+        })
+        .collect();
+    output::output(&Value::Array(rows), matches)
+}
+
+/// Weighted k-means in Lab space, seeded deterministically with farthest-point picks.
+fn kmeans<'a>(points: &[Point<'a>], k: usize, iterations: usize) -> Vec<Cluster<'a>> {
+    let k = k.clamp(1, points.len());
+
+    // Seed with the heaviest color, then repeatedly the color farthest from all seeds
+    let heaviest = points
+        .iter()
+        .max_by(|a, b| a.weight.total_cmp(&b.weight))
+        .unwrap();
+    let mut centroids = vec![heaviest.lab];
+    while centroids.len() < k {
+        let farthest = points
+            .iter()
+            .max_by(|a, b| nearest(&centroids, &a.lab).1.total_cmp(&nearest(&centroids, &b.lab).1))
+            .unwrap();
+        centroids.push(farthest.lab);
+    }
+
+    let mut assignment = vec![0; points.len()];
+    for _ in 0..iterations {
+        let mut changed = false;
+        for (point, assigned) in points.iter().zip(assignment.iter_mut()) {
+            let (index, _) = nearest(&centroids, &point.lab);
+            if *assigned != index {
+                *assigned = index;
+                changed = true;
+            }
+        }
+
+        let mut sums = vec![(0.0, 0.0, 0.0, 0.0); k];
+        for (point, &index) in points.iter().zip(&assignment) {
+            // Give every color some pull even if Eagle reports a zero ratio
+            let weight = point.weight.max(f64::EPSILON);
+            let sum = &mut sums[index];
+            sum.0 += point.lab.l * weight;
+            sum.1 += point.lab.a * weight;
+            sum.2 += point.lab.b * weight;
+            sum.3 += weight;
+        }
+        for (centroid, (l, a, b, weight)) in centroids.iter_mut().zip(sums) {
+            if weight > 0.0 {
+                *centroid = Lab { l: l / weight, a: a / weight, b: b / weight };
+            }
+        }
+
+        if !changed {
+            break;
+        }
+    }
+
+    let mut clusters: Vec<Cluster> = centroids
+        .into_iter()
+        .map(|centroid| Cluster { centroid, weight: 0.0, colors: 0, items: Vec::new() })
+        .collect();
+    for (point, &index) in points.iter().zip(&assignment) {
+        let cluster = &mut clusters[index];
+        cluster.weight += point.weight;
+        cluster.colors += 1;
+        match cluster.items.iter_mut().find(|(id, _)| *id == point.item_id) {
+            Some((_, weight)) => *weight += point.weight,
+            None => cluster.items.push((point.item_id, point.weight)),
+        }
+    }
+
+    clusters.retain(|cluster| cluster.colors > 0);
+    for cluster in &mut clusters {
+        cluster.items.sort_by(|a, b| b.1.total_cmp(&a.1));
+    }
+    clusters.sort_by(|a, b| b.items.len().cmp(&a.items.len()).then(b.weight.total_cmp(&a.weight)));
+    clusters
+}
+
+fn nearest(centroids: &[Lab], lab: &Lab) -> (usize, f64) {
+    centroids
+        .iter()
+        .enumerate()
+        .map(|(index, centroid)| (index, centroid.distance(lab)))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap()
+}
@@ -0,0 +1,105 @@
+use crate::cli::item::list::{self, filter::ItemFilter};
+use crate::cli::item::thumbnails::{jobs_arg, refresh_concurrently};
+use crate::cli::output;
+use crate::cli::report;
+use crate::lib::client::EagleClient;
+use crate::lib::library::LibraryDir;
+use crate::lib::types::ItemListData;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use serde_json::{json, Value};
+use std::time::UNIX_EPOCH;
+
+/// How much newer than Eagle's record a file may be before its palette counts as stale, for
+/// file systems with coarse timestamps
+const MTIME_TOLERANCE_MS: u64 = 2000;
+
+pub fn build() -> Command {
+    Command::new("palettes")
+        .about("Check item color palettes")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("verify")
+                .about("Find items with no palette, or whose file changed after Eagle took its palette")
+                .long_about(
+                    "Find items with no palette, or whose file changed after Eagle took its palette.\n\n\
+                     Eagle takes palettes from thumbnails, so items without one are left to \
+                     `item thumbnails verify`.",
+                )
+                .arg(
+                    Arg::new("fix")
+                        .long("fix")
+                        .help("Have Eagle extract the palettes of the items found again")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(jobs_arg())
+                .arg(list::stdin_arg())
+                .args(list::query_args())
+                .args(list::filter::args())
+                .args(output::args())
+                .args(report::args()),
+        )
+}
+
+/// Why an item needs its palette extracted again, if it does
+fn problem(library: &LibraryDir, item: &ItemListData) -> Option<&'static str> {
+    library.item_thumbnail(&item.id, &item.name)?;
+    let palettes = item.palettes.as_deref().unwrap_or_default();
+    if palettes.is_empty() || palettes.iter().all(|palette| palette.ratio <= 0.0) {
+        return Some("empty");
+    }
+    let modified = library
+        .item_file(&item.id, &item.name, &item.ext)
+        .and_then(|file| file.metadata().ok()?.modified().ok())
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|since| since.as_millis() as u64)?;
+    match item.last_modified {
+        Some(recorded) if modified > recorded + MTIME_TOLERANCE_MS => Some("stale"),
+        _ => None,
+    }
+}
+
+async fn verify(client: &EagleClient, matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let library = LibraryDir::new(&client.library().info().await?.data.library.path);
+    let item_filter = ItemFilter::from_matches(matches)?;
+    let items = list::select_items(client, matches, &item_filter, &library).await?;
+    let broken: Vec<(ItemListData, &str)> = items
+        .into_iter()
+        .filter(|item| !item.is_deleted)
+        .filter_map(|item| problem(&library, &item).map(|problem| (item, problem)))
+        .collect();
+
+    if !matches.get_flag("fix") {
+        let rows: Vec<Value> = broken
+            .iter()
+            .map(|(item, problem)| json!({ "id": item.id, "name": item.name, "ext": item.ext, "problem": problem }))
+            .collect();
+        return output::output(&Value::Array(rows), matches);
+    }
+    if broken.is_empty() {
+        eprintln!("Every palette is up to date");
+        return Ok(());
+    }
+
+    let ids = broken.iter().map(|(item, _)| item.id.clone()).collect();
+    let (refreshed, failures) = refresh_concurrently(client, ids, matches, "palette", |client, id| async move {
+        client.item().refresh_palette(&id).await.map(|_| ()).map_err(|e| e.to_string())
+    })
+    .await?;
+    eprintln!("Refreshed {} palettes ({} failed)", refreshed, failures.len());
+    report::finish(matches, "item palettes verify", refreshed, failures)
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match matches.subcommand() {
+        Some(("verify", verify_matches)) => {
+            verify(client, verify_matches).await?;
+        },
+        _ => {
+            println!("No subcommand was used");
+        },
+    }
+    Ok(())
+}
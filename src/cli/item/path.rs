@@ -0,0 +1,53 @@
+use crate::lib::client::EagleClient;
+use crate::lib::library::LibraryDir;
+use crate::lib::types::GetItemInfoParams;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+
+pub fn build() -> Command {
+    Command::new("path")
+        .about("Print the on-disk path of items")
+        .arg(
+            Arg::new("ids")
+                .value_name("ID")
+                .help("Item ids")
+                .required(true)
+                .num_args(1..),
+        )
+        .arg(
+            Arg::new("thumbnail")
+                .short('T')
+                .long("thumbnail")
+                .help("Print the thumbnail path instead, when the item has one")
+                .action(ArgAction::SetTrue),
+        )
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let library_data = client.library().info().await?.data;
+    let library = LibraryDir::new(&library_data.library.path);
+    let thumbnail = matches.get_flag("thumbnail");
+
+    for id in matches.get_many::<String>("ids").unwrap() {
+        let item = client
+            .item()
+            .info(GetItemInfoParams { id: id.to_string() })
+            .await?
+            .data;
+
+        let path = match thumbnail {
+            true => library.item_thumbnail(&item.id, &item.name),
+            false => library.item_file(&item.id, &item.name, &item.ext),
+        };
+        match path {
+            Some(path) => println!("{}", path.display()),
+            None => {
+                let kind = if thumbnail { "thumbnail" } else { "file" };
+                return Err(format!("No {} found for item {} in {}", kind, id, library.item_dir(id).display()).into());
+            }
+        }
+    }
+    Ok(())
+}
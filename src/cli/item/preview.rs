@@ -0,0 +1,87 @@
+use crate::cli::graphics::{self, Protocol};
+use crate::cli::item::fonts;
+use crate::cli::item::list::filter::Media;
+use crate::lib::client::EagleClient;
+use crate::lib::library::LibraryDir;
+use clap::{Arg, ArgMatches, Command};
+use image::DynamicImage;
+use std::io::Write;
+
+/// Height in pixels of the text rendered for font items
+const SAMPLE_SIZE: f32 = 64.0;
+
+pub fn build() -> Command {
+    Command::new("preview")
+        .about("Show item thumbnails inline in the terminal, and a sample of the text of font items")
+        .arg(
+            Arg::new("ids")
+                .value_name("ID")
+                .help("Item ids")
+                .required(true)
+                .num_args(1..),
+        )
+        .arg(
+            Arg::new("protocol")
+                .long("protocol")
+                .value_name("PROTOCOL")
+                .help("Graphics protocol, detected from the terminal by default")
+                .num_args(1)
+                .value_parser(Protocol::VALUES),
+        )
+        .arg(
+            Arg::new("width")
+                .short('W')
+                .long("width")
+                .value_name("COLUMNS")
+                .help("Width of the preview in terminal columns")
+                .num_args(1)
+                .default_value("40")
+                .value_parser(clap::value_parser!(u32)),
+        )
+        .arg(
+            Arg::new("text")
+                .long("text")
+                .value_name("TEXT")
+                .help("Sample text to render for font items")
+                .num_args(1)
+                .default_value("The quick brown fox jumps over the lazy dog"),
+        )
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let protocol = match matches.get_one::<String>("protocol") {
+        Some(protocol) => protocol.parse::<Protocol>()?,
+        None => Protocol::detect(),
+    };
+    let width = *matches.get_one::<u32>("width").unwrap();
+    let text = matches.get_one::<String>("text").unwrap();
+
+    let library_data = client.library().info().await?.data;
+    let library = LibraryDir::new(&library_data.library.path);
+
+    let mut stdout = std::io::stdout().lock();
+    for id in matches.get_many::<String>("ids").unwrap() {
+        let item = library.item(id)?;
+        let image = if Media::of(&item.ext) == Some(Media::Font) {
+            let path = library
+                .item_file(&item.id, &item.name, &item.ext)
+                .ok_or_else(|| format!("No file found for item {}", id))?;
+            DynamicImage::ImageRgba8(fonts::render_sample(&path, text, SAMPLE_SIZE)?)
+        } else {
+            // Thumbnails are small PNGs; items without one may still be a decodable image
+            let path = library
+                .item_thumbnail(&item.id, &item.name)
+                .or_else(|| library.item_file(&item.id, &item.name, &item.ext))
+                .ok_or_else(|| format!("No thumbnail or file found for item {}", id))?;
+            image::open(&path).map_err(|e| format!("Can't preview {}: {}", path.display(), e))?
+        };
+
+        writeln!(stdout, "{}  {}.{}", item.id, item.name, item.ext)?;
+        stdout.write_all(&graphics::render(&image, protocol, width)?)?;
+    }
+    stdout.flush()?;
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use crate::cli::item::list::{self, filter::ItemFilter};
+use crate::cli::output;
+use crate::lib::client::EagleClient;
+use crate::lib::library::LibraryDir;
+use crate::lib::types::ItemListData;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde_json::Value;
+
+pub fn build() -> Command {
+    Command::new("random")
+        .about("Pick a uniform random sample of the matching items")
+        .arg(
+            Arg::new("count")
+                .short('c')
+                .long("count")
+                .value_name("N")
+                .help("Number of items to sample")
+                .num_args(1)
+                .default_value("1")
+                .value_parser(clap::value_parser!(usize)),
+        )
+        .arg(
+            Arg::new("seed")
+                .long("seed")
+                .value_name("SEED")
+                .help("Seed the generator to get the same sample again")
+                .num_args(1)
+                .value_parser(clap::value_parser!(u64)),
+        )
+        .arg(
+            Arg::new("thumbnails")
+                .short('T')
+                .long("thumbnails")
+                .help("Print thumbnail paths instead of file paths")
+                .action(ArgAction::SetTrue),
+        )
+        .args(list::query_args())
+        .args(list::filter::args())
+        // Every matching item needs a fair chance, not only those on the first page
+        .mut_arg("all", |arg| arg.default_value("true").hide(true))
+        .args(output::args())
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let count = *matches.get_one::<usize>("count").unwrap();
+    let mut rng = match matches.get_one::<u64>("seed") {
+        Some(seed) => StdRng::seed_from_u64(*seed),
+        None => StdRng::from_os_rng(),
+    };
+
+    let library_data = client.library().info().await?.data;
+    let library = LibraryDir::new(&library_data.library.path);
+    let item_filter = ItemFilter::from_matches(matches)?;
+
+    // Reservoir sampling keeps memory bounded by `count` however large the library is
+    let mut sample: Vec<ItemListData> = Vec::with_capacity(count);
+    let mut seen = 0usize;
+    list::for_each_page(client, matches, &item_filter, |items| {
+        for item in items {
+            seen += 1;
+            if sample.len() < count {
+                sample.push(item);
+            } else {
+                let slot = rng.random_range(0..seen);
+                if slot < count {
+                    sample[slot] = item;
+                }
+            }
+        }
+    })
+    .await?;
+
+    let thumbnails = matches.get_flag("thumbnails");
+    if output::is_explicit(matches) {
+        return output::output(&Value::Array(list::item_rows(&sample, &library, thumbnails)), matches);
+    }
+    for item in &sample {
+        println!("{}", list::item_path(&library, item, thumbnails).display());
+    }
+    Ok(())
+}
@@ -0,0 +1,124 @@
+use crate::cli::item::list::{self, filter::ItemFilter};
+use crate::cli::{confirm, output};
+use crate::cli::progress::Progress;
+use crate::cli::report::{self, Failures};
+use crate::lib::client::EagleClient;
+use crate::lib::library::LibraryDir;
+use crate::lib::types::UpdateItemParams;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use regex::Regex;
+use serde_json::{json, Value};
+
+pub fn build() -> Command {
+    Command::new("rename")
+        .about("Rename items by replacing a regular expression in their names")
+        .arg(
+            Arg::new("match")
+                .long("match")
+                .value_name("REGEX")
+                .help("Pattern to look for in item names; items without a match are left alone")
+                .required(true)
+                .num_args(1)
+                .value_parser(|value: &str| Regex::new(value).map_err(|e| e.to_string())),
+        )
+        .arg(
+            Arg::new("replace")
+                .long("replace")
+                .value_name("REPLACEMENT")
+                .help("Replacement, where $1 or ${name} insert capture groups (write ${1} when letters follow)")
+                .required(true)
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("global")
+                .short('g')
+                .long("global")
+                .help("Replace every match in a name, not only the first")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("dry_run")
+                .long("dry-run")
+                .help("Show the old and new names without renaming anything")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(list::stdin_arg())
+        .args(list::query_args())
+        .args(list::filter::args())
+        .args(output::args())
+        .args(report::args())
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let pattern = matches.get_one::<Regex>("match").unwrap();
+    let replacement = matches.get_one::<String>("replace").unwrap();
+    let global = matches.get_flag("global");
+
+    let library_data = client.library().info().await?.data;
+    let library = LibraryDir::new(&library_data.library.path);
+    let item_filter = ItemFilter::from_matches(matches)?;
+    let items = list::select_items(client, matches, &item_filter, &library).await?;
+
+    let renames: Vec<(String, String, String)> = items
+        .into_iter()
+        .filter(|item| pattern.is_match(&item.name))
+        .map(|item| {
+            let new_name = match global {
+                true => pattern.replace_all(&item.name, replacement.as_str()),
+                false => pattern.replace(&item.name, replacement.as_str()),
+            }
+            .into_owned();
+            (item.id, item.name, new_name)
+        })
+        .filter(|(_, old_name, new_name)| old_name != new_name)
+        .collect();
+
+    if matches.get_flag("dry_run") {
+        let rows: Vec<Value> = renames
+            .iter()
+            .map(|(id, old_name, new_name)| json!({ "id": id, "old": old_name, "new": new_name }))
+            .collect();
+        return output::output(&Value::Array(rows), matches);
+    }
+
+    if !renames.is_empty() {
+        confirm::confirm(matches, &format!("{} items will be renamed", renames.len()))?;
+    }
+
+    let mut renamed = 0;
+    let mut failures = Failures::default();
+    let mut progress = Progress::new(renames.len(), "renaming", matches);
+    for (id, old_name, new_name) in &renames {
+        progress.inc();
+        if new_name.trim().is_empty() {
+            progress.finish();
+            eprintln!("Not renaming {} ({}): the new name would be empty", id, old_name);
+            failures.add(id, "the new name would be empty");
+            continue;
+        }
+        let params = UpdateItemParams {
+            id: id.clone(),
+            name: Some(new_name.clone()),
+            ..Default::default()
+        };
+        match client.item().update(&params).await {
+            Ok(_) => {
+                progress.finish();
+                println!("{} → {}", old_name, new_name);
+                renamed += 1;
+            }
+            Err(error) => {
+                progress.finish();
+                eprintln!("Failed to rename {} ({}): {}", id, old_name, error);
+                failures.add(id, error);
+            }
+        }
+    }
+
+    progress.finish();
+    eprintln!("Renamed {} items ({} failed)", renamed, failures.len());
+    report::finish(matches, "item rename", renamed, failures)
+}
@@ -0,0 +1,86 @@
+use crate::cli::http::HttpClient;
+use crate::cli::item::describe::{self, parse_embedding};
+use crate::cli::output;
+use crate::lib::client::EagleClient;
+use crate::lib::library::LibraryDir;
+use clap::{Arg, ArgMatches, Command};
+use serde_json::{json, Value};
+
+pub fn build() -> Command {
+    Command::new("semantic-search")
+        .about("Find items whose cached embeddings are closest to a text query")
+        .long_about(
+            "Find items whose cached embeddings are closest to a text query\n\n\
+             The query is POSTed to the endpoint as {\"text\": \"...\"} and the \"embedding\" \
+             of the answer is compared with those cached by `item describe`.",
+        )
+        .arg(
+            Arg::new("query")
+                .value_name("QUERY")
+                .help("What to look for, in plain words")
+                .required(true)
+                .num_args(1),
+        )
+        .arg(describe::endpoint_arg())
+        .arg(
+            Arg::new("limit")
+                .short('n')
+                .long("limit")
+                .value_name("N")
+                .help("Number of results")
+                .num_args(1)
+                .default_value("20")
+                .value_parser(clap::value_parser!(usize)),
+        )
+        .args(output::args())
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let query = matches.get_one::<String>("query").unwrap();
+    let endpoint = matches.get_one::<String>("endpoint").unwrap();
+    let limit = *matches.get_one::<usize>("limit").unwrap();
+
+    let library_data = client.library().info().await?.data;
+    let library = LibraryDir::new(&library_data.library.path);
+    let embeddings = describe::load_embeddings(&library_data.library.path)?;
+    if embeddings.is_empty() {
+        return Err("No embeddings cached for this library; run `item describe` first".into());
+    }
+
+    let answer = HttpClient::new().post_json(endpoint, &json!({ "text": query })).await?;
+    let target = answer
+        .get("embedding")
+        .and_then(parse_embedding)
+        .ok_or_else(|| format!("{} returned no embedding for the query", endpoint))?;
+
+    let mut scored: Vec<(f32, &String)> = embeddings
+        .iter()
+        .filter(|(_, embedding)| embedding.len() == target.len())
+        .map(|(id, embedding)| (cosine_similarity(&target, embedding), id))
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let rows: Vec<Value> = scored
+        .into_iter()
+        .filter_map(|(score, id)| {
+            // Items deleted since they were described are skipped
+            let item = library.item(id).ok().filter(|item| !item.is_deleted)?;
+            Some(json!({ "score": (score as f64 * 1000.0).round() / 1000.0, "id": item.id, "name": item.name }))
+        })
+        .take(limit)
+        .collect();
+    output::output(&Value::Array(rows), matches)
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a * norm_b)
+}
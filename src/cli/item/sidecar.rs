@@ -0,0 +1,339 @@
+use crate::cli::datetime;
+use crate::cli::exit;
+use crate::cli::folder::folder_paths;
+use crate::cli::item::export::{FileTemplate, DEFAULT_TEMPLATE};
+use crate::cli::item::list::{self, filter::ItemFilter};
+use crate::lib::client::EagleClient;
+use crate::lib::library::LibraryDir;
+use crate::lib::types::{ItemListData, UpdateItemParams};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use quick_xml::escape::{escape, resolve_predefined_entity};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub fn build() -> Command {
+    Command::new("sidecar")
+        .about("Write tags, rating, and annotation to XMP sidecars, or read them back")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("write")
+                .about("Write an .xmp sidecar next to the exported copy of each item")
+                .arg(
+                    Arg::new("dest")
+                        .short('d')
+                        .long("dest")
+                        .value_name("DIR")
+                        .help("Directory the items were exported to")
+                        .required(true)
+                        .num_args(1),
+                )
+                .arg(
+                    Arg::new("template")
+                        .long("template")
+                        .value_name("TEMPLATE")
+                        .help("Template the items were exported with (see `item export --help`)")
+                        .num_args(1)
+                        .default_value(DEFAULT_TEMPLATE)
+                        .value_parser(|value: &str| value.parse::<FileTemplate>()),
+                )
+                .arg(list::stdin_arg())
+                .args(list::query_args())
+                .args(list::filter::args()),
+        )
+        .subcommand(
+            Command::new("read")
+                .about("Update items from the .xmp sidecars written by `sidecar write`, e.g. after editing in Lightroom")
+                .arg(
+                    Arg::new("dir")
+                        .value_name("DIR")
+                        .help("Directory to search for .xmp files, recursively")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("merge")
+                        .long("merge")
+                        .help("Add the sidecar tags to the item instead of replacing its tags")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("dry_run")
+                        .long("dry-run")
+                        .help("Print the changes without updating any item")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match matches.subcommand() {
+        Some(("write", write_matches)) => write(client, write_matches).await,
+        Some(("read", read_matches)) => read(client, read_matches).await,
+        _ => Ok(()),
+    }
+}
+
+/// The metadata kept in a sidecar; `id` ties the file back to its Eagle item
+#[derive(Debug, Default)]
+struct Xmp {
+    id: Option<String>,
+    tags: Vec<String>,
+    rating: Option<u8>,
+    description: Option<String>,
+}
+
+impl Xmp {
+    fn of(item: &ItemListData) -> Xmp {
+        Xmp {
+            id: Some(item.id.clone()),
+            tags: item.tags.clone(),
+            rating: item.star,
+            description: Some(item.annotation.clone()).filter(|annotation| !annotation.is_empty()),
+        }
+    }
+
+    fn to_xml(&self) -> String {
+        let mut xml = String::from("<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\n");
+        xml.push_str("<x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\n");
+        xml.push_str(" <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n");
+        xml.push_str("  <rdf:Description rdf:about=\"\"\n");
+        xml.push_str("    xmlns:xmp=\"http://ns.adobe.com/xap/1.0/\"\n");
+        xml.push_str("    xmlns:dc=\"http://purl.org/dc/elements/1.1/\"\n");
+        xml.push_str("    xmlns:lr=\"http://ns.adobe.com/lightroom/1.0/\"\n");
+        xml.push_str("    xmlns:eagle=\"https://eagle.cool/xmp/1.0/\"");
+        if let Some(id) = &self.id {
+            write!(xml, "\n    eagle:id=\"{}\"", escape(id.as_str())).unwrap();
+        }
+        write!(xml, "\n    xmp:Rating=\"{}\">\n", self.rating.unwrap_or(0)).unwrap();
+
+        if !self.tags.is_empty() {
+            push_bag(&mut xml, "dc:subject", self.tags.iter().map(String::as_str));
+            // Lightroom shows nested keywords from `|` separated paths; Eagle nests with `/`
+            let nested: Vec<String> = self.tags.iter().filter(|tag| tag.contains('/')).map(|tag| tag.replace('/', "|")).collect();
+            if !nested.is_empty() {
+                push_bag(&mut xml, "lr:hierarchicalSubject", nested.iter().map(String::as_str));
+            }
+        }
+        if let Some(description) = &self.description {
+            xml.push_str("   <dc:description>\n    <rdf:Alt>\n");
+            writeln!(xml, "     <rdf:li xml:lang=\"x-default\">{}</rdf:li>", escape(description.as_str())).unwrap();
+            xml.push_str("    </rdf:Alt>\n   </dc:description>\n");
+        }
+        xml.push_str("  </rdf:Description>\n </rdf:RDF>\n</x:xmpmeta>\n<?xpacket end=\"w\"?>\n");
+        xml
+    }
+
+    /// Read the fields this command writes, whether stored as attributes or as elements.
+    fn parse(xml: &str) -> Result<Xmp, Box<dyn std::error::Error>> {
+        let mut xmp = Xmp::default();
+        let mut reader = Reader::from_str(xml);
+        // Names of the open elements, innermost last
+        let mut open: Vec<String> = Vec::new();
+        let mut text = String::new();
+
+        loop {
+            match reader.read_event()? {
+                Event::Start(element) => {
+                    xmp.read_attributes(&element)?;
+                    open.push(String::from_utf8_lossy(element.name().as_ref()).into_owned());
+                    text.clear();
+                }
+                Event::Empty(element) => xmp.read_attributes(&element)?,
+                Event::Text(content) => text.push_str(&content.decode()?),
+                Event::GeneralRef(reference) => {
+                    if let Some(c) = reference.resolve_char_ref()? {
+                        text.push(c);
+                    } else if let Some(resolved) = resolve_predefined_entity(&reference.decode()?) {
+                        text.push_str(resolved);
+                    }
+                }
+                Event::End(_) => {
+                    let name = open.pop().unwrap_or_default();
+                    let parent = |depth: usize| open.iter().rev().nth(depth).map(String::as_str);
+                    let value = std::mem::take(&mut text).trim().to_string();
+                    match name.as_str() {
+                        "eagle:id" => xmp.id = Some(value),
+                        "xmp:Rating" => xmp.rating = parse_rating(&value),
+                        "rdf:li" if parent(1) == Some("dc:subject") && !value.is_empty() => xmp.tags.push(value),
+                        "rdf:li" if parent(1) == Some("dc:description") => {
+                            xmp.description = Some(value).filter(|value| !value.is_empty());
+                        }
+                        _ => {}
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        Ok(xmp)
+    }
+}
+
+impl Xmp {
+    /// Pick up `eagle:id` and `xmp:Rating` written in the short, attribute form
+    fn read_attributes(&mut self, element: &BytesStart) -> Result<(), quick_xml::Error> {
+        for attribute in element.attributes().flatten() {
+            let value = attribute.unescape_value()?;
+            match attribute.key.as_ref() {
+                b"eagle:id" => self.id = Some(value.into_owned()),
+                b"xmp:Rating" => self.rating = parse_rating(&value),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+fn push_bag<'a>(xml: &mut String, property: &str, values: impl Iterator<Item = &'a str>) {
+    writeln!(xml, "   <{}>\n    <rdf:Bag>", property).unwrap();
+    for value in values {
+        writeln!(xml, "     <rdf:li>{}</rdf:li>", escape(value)).unwrap();
+    }
+    writeln!(xml, "    </rdf:Bag>\n   </{}>", property).unwrap();
+}
+
+/// XMP ratings run from -1 (rejected) to 5; Eagle only knows 0 to 5
+fn parse_rating(value: &str) -> Option<u8> {
+    value.trim().parse::<f64>().ok().map(|rating| rating.clamp(0.0, 5.0).round() as u8)
+}
+
+/// `photo.jpg` → `photo.xmp`, the name Lightroom and Bridge look for
+fn sidecar_path(file: &Path) -> PathBuf {
+    file.with_extension("xmp")
+}
+
+async fn write(client: &EagleClient, matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let dest = PathBuf::from(matches.get_one::<String>("dest").unwrap());
+    let template = matches.get_one::<FileTemplate>("template").unwrap();
+    let tz = datetime::from_matches(matches);
+
+    let library_data = client.library().info().await?.data;
+    let library = LibraryDir::new(&library_data.library.path);
+    let item_filter = ItemFilter::from_matches(matches)?;
+    let items = list::select_items(client, matches, &item_filter, &library).await?;
+    let folders = match template.uses_folders() {
+        true => folder_paths(&client.folder().list().await?.data),
+        false => HashMap::new(),
+    };
+
+    let (mut written, mut without_copy, mut failed) = (0, 0, 0);
+    for item in &items {
+        let copy = dest.join(template.render(item, &folders, &tz));
+        if !copy.exists() {
+            without_copy += 1;
+        }
+        let path = sidecar_path(&copy);
+        let result = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&path, Xmp::of(item).to_xml()));
+        match result {
+            Ok(()) => written += 1,
+            Err(error) => {
+                eprintln!("Failed to write {}: {}", path.display(), error);
+                failed += 1;
+            }
+        }
+    }
+
+    println!(
+        "Wrote {} sidecars to {} ({} without an exported copy next to them, {} failed)",
+        written,
+        dest.display(),
+        without_copy,
+        failed
+    );
+    exit::outcome(written, failed)
+}
+
+async fn read(client: &EagleClient, matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = PathBuf::from(matches.get_one::<String>("dir").unwrap());
+    let merge = matches.get_flag("merge");
+    let dry_run = matches.get_flag("dry_run");
+
+    let library_data = client.library().info().await?.data;
+    let library = LibraryDir::new(&library_data.library.path);
+
+    let (mut updated, mut unchanged, mut failed) = (0, 0, 0);
+    for path in xmp_files(&dir)? {
+        let xmp = match fs::read_to_string(&path).map_err(|e| e.into()).and_then(|xml| Xmp::parse(&xml)) {
+            Ok(xmp) => xmp,
+            Err(error) => {
+                eprintln!("Failed to read {}: {}", path.display(), error);
+                failed += 1;
+                continue;
+            }
+        };
+        let Some(id) = &xmp.id else {
+            // Not written by `sidecar write`, so there is no item to update
+            continue;
+        };
+        let item = match library.item(id) {
+            Ok(item) => item,
+            Err(error) => {
+                eprintln!("{}: {}", path.display(), error);
+                failed += 1;
+                continue;
+            }
+        };
+
+        let mut tags = if merge { item.tags.clone() } else { Vec::new() };
+        for tag in &xmp.tags {
+            if !tags.contains(tag) {
+                tags.push(tag.clone());
+            }
+        }
+        let params = UpdateItemParams {
+            id: id.clone(),
+            tags: Some(tags).filter(|tags| *tags != item.tags),
+            star: xmp.rating.filter(|rating| Some(*rating) != item.star && !(*rating == 0 && item.star.is_none())),
+            annotation: Some(xmp.description.unwrap_or_default()).filter(|annotation| *annotation != item.annotation),
+            ..Default::default()
+        };
+        if params.tags.is_none() && params.star.is_none() && params.annotation.is_none() {
+            unchanged += 1;
+            continue;
+        }
+
+        if dry_run {
+            println!("{}: {}", id, serde_json::to_string(&params)?);
+            updated += 1;
+            continue;
+        }
+        match client.item().update(&params).await {
+            Ok(_) => updated += 1,
+            Err(error) => {
+                eprintln!("Failed to update {}: {}", id, error);
+                failed += 1;
+            }
+        }
+    }
+
+    let action = if dry_run { "Would update" } else { "Updated" };
+    println!("{} {} items ({} unchanged, {} failed)", action, updated, unchanged, failed);
+    exit::outcome(updated, failed)
+}
+
+/// `.xmp` files below `dir`, in a stable order
+fn xmp_files(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("xmp")) {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
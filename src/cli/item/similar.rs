@@ -0,0 +1,126 @@
+use crate::cli::item::list::{self, filter::ItemFilter};
+use crate::cli::output;
+use crate::lib::client::EagleClient;
+use crate::lib::library::LibraryDir;
+use crate::lib::types::ItemListData;
+use clap::{Arg, ArgMatches, Command};
+use image::imageops::FilterType;
+use image::DynamicImage;
+use rayon::prelude::*;
+use serde_json::{json, Value};
+
+pub fn build() -> Command {
+    Command::new("similar")
+        .about("Find visually similar images with perceptual hashes of their thumbnails")
+        .arg(
+            Arg::new("to")
+                .long("to")
+                .value_name("ID")
+                .help("Only look for images similar to this item")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("threshold")
+                .long("threshold")
+                .value_name("N")
+                .help("Maximum number of differing hash bits (0-64) for two images to count as similar")
+                .num_args(1)
+                .default_value("10")
+                .value_parser(clap::builder::RangedU64ValueParser::<u32>::new().range(0..=64)),
+        )
+        .args(list::query_args())
+        .args(list::filter::args())
+        // Near-duplicates may be on any page
+        .mut_arg("all", |arg| arg.default_value("true").hide(true))
+        .args(output::args())
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let threshold = *matches.get_one::<u32>("threshold").unwrap();
+
+    let library_data = client.library().info().await?.data;
+    let library = LibraryDir::new(&library_data.library.path);
+    let item_filter = ItemFilter::from_matches(matches)?;
+    let items = list::fetch_items(client, matches, &item_filter).await?;
+
+    let hashed: Vec<(&ItemListData, u64)> = items
+        .par_iter()
+        .filter_map(|item| load_image(&library, item).map(|image| (item, dhash(&image))))
+        .collect();
+    if hashed.len() < items.len() {
+        eprintln!("Skipped {} items without a readable image", items.len() - hashed.len());
+    }
+
+    let rows: Vec<Value> = match matches.get_one::<String>("to") {
+        Some(id) => {
+            let target = library.item(id)?;
+            let image = load_image(&library, &target).ok_or_else(|| format!("Item {} has no readable image", id))?;
+            let target_hash = dhash(&image);
+            let mut similar: Vec<(&ItemListData, u32)> = hashed
+                .iter()
+                .filter(|(item, _)| item.id != target.id)
+                .map(|(item, hash)| (*item, (hash ^ target_hash).count_ones()))
+                .filter(|(_, distance)| *distance <= threshold)
+                .collect();
+            similar.sort_by_key(|(_, distance)| *distance);
+            similar
+                .into_iter()
+                .map(|(item, distance)| {
+                    json!({ "distance": distance, "id": item.id, "name": format!("{}.{}", item.name, item.ext) })
+                })
+                .collect()
+        }
+        None => {
+            let mut pairs: Vec<(usize, usize, u32)> = (0..hashed.len())
+                .into_par_iter()
+                .flat_map_iter(|a| {
+                    let hashed = &hashed;
+                    (a + 1..hashed.len()).filter_map(move |b| {
+                        let distance = (hashed[a].1 ^ hashed[b].1).count_ones();
+                        (distance <= threshold).then_some((a, b, distance))
+                    })
+                })
+                .collect();
+            pairs.sort_by_key(|&(a, b, distance)| (distance, a, b));
+            pairs
+                .into_iter()
+                .map(|(a, b, distance)| {
+                    let (a, b) = (hashed[a].0, hashed[b].0);
+                    json!({
+                        "distance": distance,
+                        "id": a.id,
+                        "name": format!("{}.{}", a.name, a.ext),
+                        "similar_id": b.id,
+                        "similar_name": format!("{}.{}", b.name, b.ext),
+                    })
+                })
+                .collect()
+        }
+    };
+    output::output(&Value::Array(rows), matches)
+}
+
+/// The thumbnail of an item, or its file when it has no thumbnail
+fn load_image(library: &LibraryDir, item: &ItemListData) -> Option<DynamicImage> {
+    library
+        .item_thumbnail(&item.id, &item.name)
+        .or_else(|| library.item_file(&item.id, &item.name, &item.ext))
+        .and_then(|path| image::open(path).ok())
+}
+
+/// 64-bit difference hash: each bit tells whether a pixel of a 9x8 grayscale version is
+/// brighter than its right neighbour, which survives resizing and recompression.
+pub fn dhash(image: &DynamicImage) -> u64 {
+    let small = image.resize_exact(9, 8, FilterType::Triangle).to_luma8();
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            let bit = small.get_pixel(x, y).0[0] > small.get_pixel(x + 1, y).0[0];
+            hash = (hash << 1) | u64::from(bit);
+        }
+    }
+    hash
+}
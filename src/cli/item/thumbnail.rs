@@ -1,14 +1,33 @@
+use crate::cli::output;
 use crate::lib::client::EagleClient;
 use clap::{Arg,ArgMatches,ArgAction, Command};
 use crate::lib::types::{GetItemThumbnailParams, ItemThumbnailData};
+use std::io::Write;
 
 pub fn build() -> Command {
-    Command::new("thumbnail").about("Get item thumbnail").arg(
-        Arg::new("id")
-            .required(false)
-            .value_name("ID")
-            .action(ArgAction::Set), //do not require a flag to be passed
-    )
+    Command::new("thumbnail")
+        .about("Get item thumbnail")
+        .arg(
+            Arg::new("id")
+                .required(true)
+                .value_name("ID")
+                .action(ArgAction::Set), //do not require a flag to be passed
+        )
+        .arg(
+            Arg::new("save")
+                .short('s')
+                .long("save")
+                .value_name("PATH")
+                .help("Copy the thumbnail to PATH (a file, or a directory to keep its name)")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("raw")
+                .long("raw")
+                .help("Write the thumbnail image bytes to stdout")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("save"),
+        )
 }
 
 pub async fn execute(
@@ -24,6 +43,24 @@ pub async fn execute(
     };
     let thumbnail_path: ItemThumbnailData = client.item().thumbnail(query_params).await?.data;
     let path = percent_encoding::percent_decode_str(&thumbnail_path).decode_utf8()?;
-    println!("{}", path);
+    let path = std::path::Path::new(path.as_ref());
+
+    if matches.get_flag("raw") {
+        let bytes = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let mut stdout = std::io::stdout().lock();
+        stdout.write_all(&bytes)?;
+        stdout.flush()?;
+    } else if let Some(save) = matches.get_one::<String>("save") {
+        let mut target = std::path::PathBuf::from(save);
+        if target.is_dir() {
+            target = target.join(path.file_name().unwrap_or_default());
+        }
+        std::fs::copy(path, &target).map_err(|e| format!("Failed to copy {}: {}", path.display(), e))?;
+        println!("{}", target.display());
+    } else if output::is_explicit(matches) {
+        output::output(&serde_json::json!({ "id": raw_id, "path": path.display().to_string() }), matches)?;
+    } else {
+        println!("{}", path.display());
+    }
     Ok(())
 }
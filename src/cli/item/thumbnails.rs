@@ -0,0 +1,149 @@
+use crate::cli::item::list::{self, filter::ItemFilter};
+use crate::cli::output;
+use crate::cli::progress::Progress;
+use crate::cli::report::{self, Failures};
+use crate::lib::client::EagleClient;
+use crate::lib::library::LibraryDir;
+use crate::lib::types::ItemListData;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use serde_json::{json, Value};
+use std::future::Future;
+use tokio::task::JoinSet;
+
+pub fn build() -> Command {
+    Command::new("thumbnails")
+        .about("Check item thumbnails")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("verify")
+                .about("Find items whose thumbnail is missing or that Eagle flagged as having none")
+                .arg(
+                    Arg::new("fix")
+                        .long("fix")
+                        .help("Have Eagle render the thumbnails of the items found again")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(jobs_arg())
+                .arg(list::stdin_arg())
+                .args(list::query_args())
+                .args(list::filter::args())
+                .args(output::args())
+                .args(report::args()),
+        )
+}
+
+/// Why an item needs its thumbnail rendered again, if it does
+fn problem(library: &LibraryDir, item: &ItemListData) -> Option<&'static str> {
+    if item.no_thumbnail == Some(true) {
+        Some("noThumbnail")
+    } else if library.item_thumbnail(&item.id, &item.name).is_none() {
+        Some("missing")
+    } else {
+        None
+    }
+}
+
+async fn verify(client: &EagleClient, matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let library = LibraryDir::new(&client.library().info().await?.data.library.path);
+    let item_filter = ItemFilter::from_matches(matches)?;
+    let items = list::select_items(client, matches, &item_filter, &library).await?;
+    let broken: Vec<(ItemListData, &str)> = items
+        .into_iter()
+        .filter(|item| !item.is_deleted)
+        .filter_map(|item| problem(&library, &item).map(|problem| (item, problem)))
+        .collect();
+
+    if !matches.get_flag("fix") {
+        let rows: Vec<Value> = broken
+            .iter()
+            .map(|(item, problem)| json!({ "id": item.id, "name": item.name, "ext": item.ext, "problem": problem }))
+            .collect();
+        return output::output(&Value::Array(rows), matches);
+    }
+    if broken.is_empty() {
+        eprintln!("Every thumbnail is there");
+        return Ok(());
+    }
+
+    let ids = broken.iter().map(|(item, _)| item.id.clone()).collect();
+    let (refreshed, failures) = refresh_concurrently(client, ids, matches, "thumbnail", |client, id| async move {
+        client.item().refresh_thumbnail(&id).await.map(|_| ()).map_err(|e| e.to_string())
+    })
+    .await?;
+    eprintln!("Refreshed {} thumbnails ({} failed)", refreshed, failures.len());
+    report::finish(matches, "item thumbnails verify", refreshed, failures)
+}
+
+/// Run `refresh` for every item in `ids`, `--jobs` at a time, with a progress bar.
+///
+/// Returns how many succeeded and the failures. `refresh` gets a client of its own and gives
+/// errors as messages, since it runs on another task.
+pub async fn refresh_concurrently<F, R>(
+    client: &EagleClient,
+    ids: Vec<String>,
+    matches: &ArgMatches,
+    what: &str,
+    refresh: F,
+) -> Result<(usize, Failures), Box<dyn std::error::Error>>
+where
+    F: Fn(EagleClient, String) -> R,
+    R: Future<Output = Result<(), String>> + Send + 'static,
+{
+    let jobs = *matches.get_one::<usize>("jobs").unwrap();
+    let mut progress = Progress::new(ids.len(), "refreshing", matches);
+    let mut pending = ids.into_iter();
+    let mut running = JoinSet::new();
+    let mut refreshed = 0;
+    let mut failures = Failures::default();
+    loop {
+        while running.len() < jobs {
+            let Some(id) = pending.next() else {
+                break;
+            };
+            let result = refresh(client.clone(), id.clone());
+            running.spawn(async move { (id, result.await) });
+        }
+        let Some(joined) = running.join_next().await else {
+            break;
+        };
+        let (id, result) = joined?;
+        progress.inc();
+        match result {
+            Ok(()) => refreshed += 1,
+            Err(error) => {
+                progress.finish();
+                eprintln!("Failed to refresh the {} of {}: {}", what, id, error);
+                failures.add(&id, error);
+            }
+        }
+    }
+    progress.finish();
+    Ok((refreshed, failures))
+}
+
+/// `--jobs` for commands using `refresh_concurrently`
+pub fn jobs_arg() -> Arg {
+    Arg::new("jobs")
+        .short('j')
+        .long("jobs")
+        .value_name("N")
+        .help("Refresh up to N items at once with --fix")
+        .num_args(1)
+        .default_value("4")
+        .value_parser(clap::builder::RangedU64ValueParser::<usize>::new().range(1..))
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match matches.subcommand() {
+        Some(("verify", verify_matches)) => {
+            verify(client, verify_matches).await?;
+        },
+        _ => {
+            println!("No subcommand was used");
+        },
+    }
+    Ok(())
+}
@@ -0,0 +1,129 @@
+use crate::cli::item::list;
+use crate::cli::{confirm, exit, output};
+use crate::lib::client::EagleClient;
+use crate::lib::library::LibraryDir;
+use crate::lib::types::ItemListData;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use serde_json::Value;
+
+pub fn build() -> Command {
+    Command::new("trash")
+        .about("List, restore, or permanently delete items in the trash")
+        .long_about(
+            "List, restore, or permanently delete items in the trash.\n\n\
+             The API can only move items to the trash, so these commands work on the library \
+             folder directly. Restart Eagle (or switch libraries) to see the changes there.",
+        )
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("list")
+                .about("List trashed items")
+                .args(output::args()),
+        )
+        .subcommand(
+            Command::new("restore")
+                .about("Take items out of the trash")
+                .arg(
+                    Arg::new("id")
+                        .value_name("ID")
+                        .help("Items to restore")
+                        .required_unless_present("stdin")
+                        .num_args(1..),
+                )
+                .arg(list::stdin_arg()),
+        )
+        .subcommand(
+            Command::new("empty")
+                .about("Permanently delete every trashed item")
+                .arg(
+                    Arg::new("force")
+                        .long("force")
+                        .help("Delete without asking; without it or --yes, only the number of items is reported when not on a terminal")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let library_data = client.library().info().await?.data;
+    let library = LibraryDir::new(&library_data.library.path);
+
+    match matches.subcommand() {
+        Some(("list", list_matches)) => {
+            let items = trashed(&library)?;
+            if output::is_explicit(list_matches) {
+                return output::output(&Value::Array(list::item_rows(&items, &library, false)), list_matches);
+            }
+            for item in &items {
+                println!("{}", list::item_path(&library, item, false).display());
+            }
+        }
+        Some(("restore", restore_matches)) => restore(&library, restore_matches)?,
+        Some(("empty", empty_matches)) => {
+            let items = trashed(&library)?;
+            let force = empty_matches.get_flag("force") || confirm::is_assumed(empty_matches);
+            if !force && !confirm::is_interactive() {
+                println!("{} items in the trash; rerun with --force to delete them permanently", items.len());
+                return Ok(());
+            }
+            if !force {
+                confirm::confirm(empty_matches, &format!("{} items will be deleted permanently", items.len()))?;
+            }
+            let (mut deleted, mut failed) = (0, 0);
+            for item in &items {
+                match library.remove_item(&item.id) {
+                    Ok(()) => deleted += 1,
+                    Err(error) => {
+                        eprintln!("{}", error);
+                        failed += 1;
+                    }
+                }
+            }
+            println!("Deleted {} items ({} failed)", deleted, failed);
+            exit::outcome(deleted, failed)?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Items of the library marked as deleted, oldest change first
+fn trashed(library: &LibraryDir) -> Result<Vec<ItemListData>, Box<dyn std::error::Error>> {
+    let mut items: Vec<ItemListData> = library.items()?.into_iter().filter(|item| item.is_deleted).collect();
+    items.sort_by_key(|item| item.modification_time);
+    Ok(items)
+}
+
+fn restore(library: &LibraryDir, matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let mut ids: Vec<String> = matches.get_many::<String>("id").into_iter().flatten().cloned().collect();
+    if matches.get_flag("stdin") {
+        for line in std::io::stdin().lines() {
+            if let Some(id) = list::item_id_of(line?.trim()) {
+                ids.push(id.to_string());
+            }
+        }
+    }
+
+    let (mut restored, mut failed) = (0, 0);
+    for id in &ids {
+        let result = library.read_item_metadata(id).and_then(|mut metadata| {
+            if metadata["isDeleted"] != Value::Bool(true) {
+                return Err(format!("Item {} is not in the trash", id).into());
+            }
+            metadata["isDeleted"] = Value::Bool(false);
+            library.write_item_metadata(id, &metadata)
+        });
+        match result {
+            Ok(()) => restored += 1,
+            Err(error) => {
+                eprintln!("{}", error);
+                failed += 1;
+            }
+        }
+    }
+    println!("Restored {} items ({} failed)", restored, failed);
+    exit::outcome(restored, failed)
+}
@@ -0,0 +1,68 @@
+use clap::ArgMatches;
+use serde_json::Value;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// A `--jq` filter run over the JSON output by the `jq` program
+#[derive(Debug, Clone)]
+pub struct Jq {
+    filter: String,
+    /// Print strings without quotes, like `jq -r`
+    raw: bool,
+    /// `--jq-arg` variables, bound like `jq --arg NAME VALUE`
+    args: Vec<(String, String)>,
+}
+
+impl Jq {
+    /// The filter given with `--jq`, `None` without it.
+    pub fn from_matches(matches: &ArgMatches) -> Option<Self> {
+        let filter = matches.try_get_one::<String>("jq").ok().flatten()?;
+        let raw = matches.try_get_one::<bool>("jq_raw").ok().flatten().copied().unwrap_or(false);
+        let args = matches
+            .try_get_occurrences::<String>("jq_arg")
+            .ok()
+            .flatten()
+            .map(|occurrences| {
+                occurrences
+                    .filter_map(|mut pair| Some((pair.next()?.clone(), pair.next()?.clone())))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Some(Jq {
+            filter: filter.clone(),
+            raw,
+            args,
+        })
+    }
+
+    /// Run the filter over `value` and return what jq printed.
+    pub fn run(&self, value: &Value) -> Result<String, Box<dyn std::error::Error>> {
+        let mut command = Command::new("jq");
+        if self.raw {
+            command.arg("-r");
+        }
+        for (name, value) in &self.args {
+            command.arg("--arg").arg(name).arg(value);
+        }
+        let mut child = command
+            .arg(&self.filter)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to run jq (is it installed?): {}", e))?;
+
+        // Written from another thread so a large input can't block on jq's full stdout
+        let input = serde_json::to_vec(value)?;
+        let mut stdin = child.stdin.take().unwrap();
+        let writer = std::thread::spawn(move || stdin.write_all(&input));
+        let output = child.wait_with_output()?;
+        let _ = writer.join();
+
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("jq failed: {}", error.trim()).into());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
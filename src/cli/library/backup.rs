@@ -0,0 +1,176 @@
+use crate::cli::confirm;
+use crate::cli::units::format_size;
+use crate::lib::library::LibraryDir;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use serde_json::Value;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Library-level files kept in a backup: folders, smart folders, and tag groups live in
+/// `metadata.json`, the tag list and starred tags in `tags.json`
+const LIBRARY_FILES: [&str; 2] = ["metadata.json", "tags.json"];
+
+pub fn backup_command() -> Command {
+    Command::new("backup")
+        .about("Save the library's metadata (tags, folders, annotations, ratings) without the originals")
+        .long_about(
+            "Save the library's metadata without the originals: metadata.json with the folder \
+             tree, smart folders, and tag groups, tags.json, and the metadata.json of every item \
+             with its tags, folders, annotation, rating, and URL. The archive is a zstd-compressed \
+             tar and stays small even for large libraries; bring it back with `library restore-metadata`.",
+        )
+        .arg(
+            Arg::new("out")
+                .short('o')
+                .long("out")
+                .value_name("FILE")
+                .help("Archive to write [default: <library>-metadata-<date>.tar.zst]")
+                .num_args(1),
+        )
+}
+
+pub fn restore_command() -> Command {
+    Command::new("restore-metadata")
+        .about("Put back the metadata saved by `library backup`, for items still in the library")
+        .long_about(
+            "Put back the metadata saved by `library backup`.\n\n\
+             The library files are overwritten, so quit Eagle first or restart it afterwards, as \
+             Eagle keeps its own copy in memory. Items deleted from the library since the backup are \
+             skipped, since their originals are gone.",
+        )
+        .arg(
+            Arg::new("file")
+                .value_name("FILE")
+                .help("Archive written by `library backup`")
+                .required(true),
+        )
+        .arg(
+            Arg::new("dry_run")
+                .long("dry-run")
+                .help("Only report what would be restored")
+                .action(ArgAction::SetTrue),
+        )
+}
+
+/// What a backup archive entry restores
+enum Entry {
+    Library(String),
+    Item(String),
+}
+
+/// Where an archive path belongs, refusing anything outside the layout `backup` writes.
+fn entry(path: &Path) -> Option<Entry> {
+    let parts: Vec<&str> = path.iter().map(|part| part.to_str()).collect::<Option<_>>()?;
+    match parts.as_slice() {
+        [name] if LIBRARY_FILES.contains(name) => Some(Entry::Library(name.to_string())),
+        ["images", dir, "metadata.json"] => {
+            let id = dir.strip_suffix(".info")?;
+            match !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric()) {
+                true => Some(Entry::Item(id.to_string())),
+                false => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+pub fn backup(library: &LibraryDir, name: &str, matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let out = match matches.get_one::<String>("out") {
+        Some(out) => PathBuf::from(out),
+        None => PathBuf::from(format!("{}-metadata-{}.tar.zst", name, chrono::Local::now().format("%Y-%m-%d"))),
+    };
+    let file = File::create(&out).map_err(|e| format!("Failed to create {}: {}", out.display(), e))?;
+    let mut archive = tar::Builder::new(zstd::Encoder::new(file, 0)?.auto_finish());
+    let mtime = chrono::Utc::now().timestamp() as u64;
+    let mut append = |path: &str, data: &[u8]| {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(mtime);
+        archive.append_data(&mut header, path, data)
+    };
+
+    for file_name in LIBRARY_FILES {
+        match fs::read(library.root().join(file_name)) {
+            Ok(data) => append(file_name, &data)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(format!("Failed to read {}: {}", file_name, e).into()),
+        }
+    }
+    let mut items = 0;
+    for dir in fs::read_dir(library.images_dir())? {
+        let dir = dir?.path();
+        let Some(dir_name) = dir.file_name().and_then(|name| name.to_str()).filter(|name| name.ends_with(".info")) else {
+            continue;
+        };
+        // Unreadable metadata is left out rather than failing the whole backup, or the restore
+        let data = fs::read(dir.join("metadata.json")).unwrap_or_default();
+        if serde_json::from_slice::<Value>(&data).is_err() {
+            eprintln!("Skipping {}: no valid metadata.json", dir.display());
+            continue;
+        }
+        append(&format!("images/{}/metadata.json", dir_name), &data)?;
+        items += 1;
+    }
+    archive.into_inner()?;
+
+    let size = fs::metadata(&out).map(|metadata| metadata.len()).unwrap_or(0);
+    println!("Backed up the metadata of {} items to {} ({})", items, out.display(), format_size(size));
+    Ok(())
+}
+
+pub fn restore(library: &LibraryDir, matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let path = matches.get_one::<String>("file").unwrap();
+    let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path, e))?;
+    let mut archive = tar::Archive::new(zstd::Decoder::new(file)?);
+
+    // Read and check everything first, so a damaged archive changes nothing
+    let mut entries = Vec::new();
+    for archive_entry in archive.entries()? {
+        let mut archive_entry = archive_entry?;
+        let entry_path = archive_entry.path()?.into_owned();
+        let Some(entry) = entry(&entry_path) else {
+            return Err(format!("Unexpected file {} in {}; is it a `library backup` archive?", entry_path.display(), path).into());
+        };
+        let mut data = String::new();
+        archive_entry.read_to_string(&mut data)?;
+        let value: Value = serde_json::from_str(&data)
+            .map_err(|e| format!("Invalid JSON in {} of {}: {}", entry_path.display(), path, e))?;
+        entries.push((entry, value));
+    }
+
+    let (present, missing): (Vec<_>, Vec<_>) = entries.into_iter().partition(|(entry, _)| match entry {
+        Entry::Library(_) => true,
+        Entry::Item(id) => library.item_dir(id).is_dir(),
+    });
+    let items = present.iter().filter(|(entry, _)| matches!(entry, Entry::Item(_))).count();
+    if matches.get_flag("dry_run") {
+        println!(
+            "Would restore {} library files and the metadata of {} items; {} items are no longer in the library",
+            present.len() - items,
+            items,
+            missing.len()
+        );
+        return Ok(());
+    }
+    confirm::confirm(
+        matches,
+        &format!("The metadata of {} items in {} will be overwritten", items, library.root().display()),
+    )?;
+
+    for (entry, value) in &present {
+        match entry {
+            Entry::Library(file_name) => library.write_json_file(file_name, value)?,
+            Entry::Item(id) => library.write_item_metadata(id, value)?,
+        }
+    }
+    println!(
+        "Restored {} library files and the metadata of {} items ({} no longer in the library were skipped)",
+        present.len() - items,
+        items,
+        missing.len()
+    );
+    eprintln!("Restart Eagle (or switch libraries) to see the restored metadata");
+    Ok(())
+}
@@ -0,0 +1,179 @@
+use crate::cli::folder::folder_paths;
+use crate::cli::library::compare::file_hash;
+use crate::cli::output;
+use crate::lib::client::EagleClient;
+use crate::lib::library::LibraryDir;
+use clap::{Arg, ArgGroup, ArgMatches, Command};
+use rayon::prelude::*;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+/// Items without a folder, in the summary
+const UNFILED: &str = "(unfiled)";
+
+pub fn checksum_command() -> Command {
+    Command::new("checksum")
+        .about("Write the SHA-256 of every original file to a manifest, or check the files against one")
+        .long_about(
+            "Write the SHA-256 of every original file to a manifest, or check the files against one \
+             to find bit rot and files changed outside Eagle.\n\n\
+             The manifest has sha256sum's format, with paths relative to the library folder, so \
+             `sha256sum -c` run there reads it too. Checking lists every file that changed, went \
+             missing, or isn't in the manifest, then sums them up by folder.",
+        )
+        .arg(
+            Arg::new("out")
+                .short('o')
+                .long("out")
+                .value_name("FILE")
+                .help("Manifest to write, e.g. manifest.sha256")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("verify")
+                .long("verify")
+                .value_name("FILE")
+                .help("Manifest to check the files against")
+                .num_args(1),
+        )
+        .group(ArgGroup::new("mode").args(["out", "verify"]).required(true))
+        .args(output::args())
+}
+
+/// Original files of the items not in the trash, as (item id, path relative to the library)
+fn original_files(library: &LibraryDir) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
+    let mut files: Vec<(String, String)> = library
+        .items()?
+        .into_iter()
+        .filter(|item| !item.is_deleted)
+        .filter_map(|item| {
+            let path = library.item_file(&item.id, &item.name, &item.ext)?;
+            let relative = path.strip_prefix(library.root()).ok()?.to_string_lossy().replace('\\', "/");
+            Some((item.id, relative))
+        })
+        .collect();
+    files.sort_by(|a, b| a.1.cmp(&b.1));
+    Ok(files)
+}
+
+fn write_manifest(library: &LibraryDir, out: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let files = original_files(library)?;
+    let lines: Vec<String> = files
+        .par_iter()
+        .map(|(_, relative)| {
+            let hash = file_hash(&library.root().join(relative))
+                .map_err(|e| format!("Failed to read {}: {}", relative, e))?;
+            Ok(format!("{}  {}\n", hash, relative))
+        })
+        .collect::<Result<_, String>>()?;
+    fs::write(out, lines.concat()).map_err(|e| format!("Failed to write {}: {}", out, e))?;
+    eprintln!("Hashed {} files into {}", lines.len(), out);
+    Ok(())
+}
+
+/// Hashes by relative path from a manifest in sha256sum's format
+fn read_manifest(path: &str) -> Result<BTreeMap<String, String>, Box<dyn std::error::Error>> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let mut hashes = BTreeMap::new();
+    for (number, line) in text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+        // Binary mode marks the path with `*` instead of the second space
+        let (hash, relative) = line
+            .split_once("  ")
+            .or_else(|| line.split_once(" *"))
+            .ok_or_else(|| format!("{}:{}: expected `<sha256>  <path>`", path, number + 1))?;
+        hashes.insert(relative.to_string(), hash.to_lowercase());
+    }
+    Ok(hashes)
+}
+
+/// Check the files against a manifest, returning the per-folder summary and how many files
+/// have problems
+fn verify_manifest(
+    library: &LibraryDir,
+    manifest: &str,
+    folder_names: &HashMap<String, String>,
+) -> Result<(Vec<Value>, usize), Box<dyn std::error::Error>> {
+    let expected = read_manifest(manifest)?;
+    let current: BTreeMap<String, String> = original_files(library)?
+        .into_iter()
+        .map(|(id, relative)| (relative, id))
+        .collect();
+
+    let mut statuses: Vec<(String, &str)> = expected
+        .par_iter()
+        .map(|(relative, hash)| {
+            let path = library.root().join(relative);
+            let status = match file_hash(&path) {
+                Ok(actual) if &actual == hash => "ok",
+                Ok(_) => "modified",
+                Err(_) => "missing",
+            };
+            (relative.clone(), status)
+        })
+        .collect();
+    statuses.extend(current.keys().filter(|relative| !expected.contains_key(*relative)).map(|relative| (relative.clone(), "new")));
+
+    let mut summary: BTreeMap<String, BTreeMap<&str, usize>> = BTreeMap::new();
+    let mut problems = 0;
+    for (relative, status) in &statuses {
+        if *status != "ok" {
+            eprintln!("{:<8} {}", status, relative);
+            problems += 1;
+        }
+        for folder in folders_of(library, relative, folder_names) {
+            *summary.entry(folder).or_default().entry(status).or_default() += 1;
+        }
+    }
+    let rows = summary
+        .into_iter()
+        .map(|(folder, counts)| {
+            let count = |status| counts.get(status).copied().unwrap_or(0);
+            json!({
+                "folder": folder,
+                "ok": count("ok"),
+                "modified": count("modified"),
+                "missing": count("missing"),
+                "new": count("new"),
+            })
+        })
+        .collect();
+    Ok((rows, problems))
+}
+
+/// Folder paths of the item a manifest path belongs to, read from its `<id>.info` folder
+fn folders_of(library: &LibraryDir, relative: &str, folder_names: &HashMap<String, String>) -> Vec<String> {
+    let folders: Vec<String> = Path::new(relative)
+        .components()
+        .find_map(|component| component.as_os_str().to_str()?.strip_suffix(".info").map(str::to_string))
+        .and_then(|id| library.item(&id).ok())
+        .and_then(|item| item.folders)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|folder| folder_names.get(folder).cloned())
+        .collect();
+    match folders.is_empty() {
+        true => vec![UNFILED.to_string()],
+        false => folders,
+    }
+}
+
+pub async fn checksum(
+    client: &EagleClient,
+    library: &LibraryDir,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(out) = matches.get_one::<String>("out") {
+        return write_manifest(library, out);
+    }
+    let manifest = matches.get_one::<String>("verify").unwrap();
+    let folder_names = folder_paths(&client.folder().list().await?.data);
+    let (summary, problems) = verify_manifest(library, manifest, &folder_names)?;
+    output::output(&Value::Array(summary), matches)?;
+    if problems > 0 {
+        return Err(format!("{} files don't match {}", problems, manifest).into());
+    }
+    eprintln!("Every file matches {}", manifest);
+    Ok(())
+}
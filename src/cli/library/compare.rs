@@ -0,0 +1,431 @@
+use crate::cli::confirm;
+use crate::cli::folder::folder_chains;
+use crate::cli::ignore::{self, IgnoreList};
+use crate::cli::item::add_from_url::normalize_url;
+use crate::cli::item::list;
+use crate::cli::output;
+use crate::cli::progress::Progress;
+use crate::cli::report::{self, Failures};
+use crate::lib::client::EagleClient;
+use crate::lib::library::LibraryDir;
+use crate::lib::types::{AddFromPathParams, Child, ItemListData, UpdateItemParams};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use rayon::prelude::*;
+use ring::digest;
+use serde_json::{json, Value};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+fn other_arg() -> Arg {
+    Arg::new("other")
+        .long("other")
+        .value_name("LIBRARY")
+        .help("The other library folder, e.g. ~/Pictures/Laptop.library")
+        .required(true)
+        .num_args(1)
+}
+
+pub fn compare_command() -> Command {
+    Command::new("compare")
+        .about("Match the items of another library with this one by content hash or URL")
+        .long_about(
+            "Match the items of another library with this one, by the SHA-256 of their files or else \
+             by their normalized URL, and list the items only one library has and the matched items \
+             whose name, tags, annotation, rating, or folders differ. The other library is read \
+             from disk, so Eagle doesn't need to have it open.",
+        )
+        .arg(other_arg())
+        .arg(
+            Arg::new("same")
+                .long("same")
+                .help("Also list the matched items that are alike")
+                .action(ArgAction::SetTrue),
+        )
+        .args(output::args())
+}
+
+pub fn merge_command() -> Command {
+    Command::new("merge-into")
+        .about("Import the items only another library has into this one, with their tags and folders")
+        .long_about(
+            "Import the items only another library has (as `library compare` finds them) into the \
+             library Eagle has open, keeping their name, tags, annotation, URL, rating, and folders. \
+             Folders are matched by path and created when this library lacks them. Items whose \
+             folder path and file name match the other library's .eagleignore are left out.",
+        )
+        .arg(other_arg())
+        .arg(
+            Arg::new("dry_run")
+                .long("dry-run")
+                .help("Only list the items that would be imported")
+                .action(ArgAction::SetTrue),
+        )
+        .args(ignore::args())
+        .args(report::args())
+}
+
+/// Items of both libraries, paired where they hold the same file or URL
+struct Comparison<'a> {
+    /// Matched items, with what matched them
+    pairs: Vec<(&'a ItemListData, &'a ItemListData, &'static str)>,
+    only_here: Vec<&'a ItemListData>,
+    only_there: Vec<&'a ItemListData>,
+}
+
+/// Hex SHA-256 of a file's content
+pub fn file_hash(path: &Path) -> std::io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut context = digest::Context::new(&digest::SHA256);
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        context.update(&buffer[..read]);
+    }
+    Ok(context.finish().as_ref().iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/// Hashes of the items whose size also occurs in `sizes`; no other item can have a twin.
+fn hashes<'a>(library: &LibraryDir, items: &[&'a ItemListData], sizes: &HashSet<u64>) -> HashMap<String, Vec<&'a ItemListData>> {
+    let hashed: Vec<(String, &ItemListData)> = items
+        .par_iter()
+        .filter(|item| sizes.contains(&item.size))
+        .filter_map(|item| {
+            let path = library.item_file(&item.id, &item.name, &item.ext)?;
+            Some((file_hash(&path).ok()?, *item))
+        })
+        .collect();
+    let mut hashes: HashMap<String, Vec<&ItemListData>> = HashMap::new();
+    for (hash, item) in hashed {
+        hashes.entry(hash).or_default().push(item);
+    }
+    hashes
+}
+
+fn compare<'a>(
+    here: (&LibraryDir, &'a [ItemListData]),
+    there: (&LibraryDir, &'a [ItemListData]),
+) -> Comparison<'a> {
+    let here_items: Vec<&ItemListData> = here.1.iter().filter(|item| !item.is_deleted).collect();
+    let there_items: Vec<&ItemListData> = there.1.iter().filter(|item| !item.is_deleted).collect();
+    let here_sizes: HashSet<u64> = here_items.iter().map(|item| item.size).collect();
+    let there_sizes: HashSet<u64> = there_items.iter().map(|item| item.size).collect();
+    let mut here_hashes = hashes(here.0, &here_items, &there_sizes);
+    let there_hashes = hashes(there.0, &there_items, &here_sizes);
+
+    let mut pairs = Vec::new();
+    let mut matched_here = HashSet::new();
+    let mut matched_there = HashSet::new();
+    for (hash, there_twins) in &there_hashes {
+        let Some(here_twins) = here_hashes.get_mut(hash) else {
+            continue;
+        };
+        for (there_item, here_item) in there_twins.iter().zip(here_twins.iter()) {
+            pairs.push((*here_item, *there_item, "hash"));
+            matched_here.insert(here_item.id.as_str());
+            matched_there.insert(there_item.id.as_str());
+        }
+    }
+
+    let mut here_urls: HashMap<String, Vec<&ItemListData>> = HashMap::new();
+    for item in here_items.iter().filter(|item| !item.url.is_empty() && !matched_here.contains(item.id.as_str())) {
+        here_urls.entry(normalize_url(&item.url)).or_default().push(item);
+    }
+    for there_item in &there_items {
+        if there_item.url.is_empty() || matched_there.contains(there_item.id.as_str()) {
+            continue;
+        }
+        let Some(here_item) = here_urls.get_mut(&normalize_url(&there_item.url)).and_then(|twins| twins.pop()) else {
+            continue;
+        };
+        pairs.push((here_item, *there_item, "url"));
+        matched_here.insert(here_item.id.as_str());
+        matched_there.insert(there_item.id.as_str());
+    }
+
+    pairs.sort_by(|a, b| a.0.id.cmp(&b.0.id));
+    Comparison {
+        pairs,
+        only_here: here_items.into_iter().filter(|item| !matched_here.contains(item.id.as_str())).collect(),
+        only_there: there_items.into_iter().filter(|item| !matched_there.contains(item.id.as_str())).collect(),
+    }
+}
+
+/// Folder paths of an item, which unlike folder ids mean the same in both libraries
+fn item_folder_paths(item: &ItemListData, paths: &HashMap<String, String>) -> BTreeSet<String> {
+    item.folders.iter().flatten().filter_map(|id| paths.get(id).cloned()).collect()
+}
+
+/// Folder name chains of an item, for recreating its folders in another library
+fn item_folder_chains(item: &ItemListData, chains: &HashMap<String, Vec<String>>) -> BTreeSet<Vec<String>> {
+    item.folders.iter().flatten().filter_map(|id| chains.get(id).cloned()).collect()
+}
+
+/// What differs between two matched items, empty when they are alike
+fn differences(here: &ItemListData, there: &ItemListData, here_paths: &HashMap<String, String>, there_paths: &HashMap<String, String>) -> Vec<String> {
+    let mut differences = Vec::new();
+    if (&here.name, &here.ext) != (&there.name, &there.ext) {
+        differences.push(format!("name {}.{} vs {}.{}", here.name, here.ext, there.name, there.ext));
+    }
+    if here.tags.iter().collect::<BTreeSet<_>>() != there.tags.iter().collect::<BTreeSet<_>>() {
+        differences.push(format!("tags [{}] vs [{}]", here.tags.join(", "), there.tags.join(", ")));
+    }
+    if here.annotation != there.annotation {
+        differences.push("annotation".to_string());
+    }
+    if here.star.unwrap_or(0) != there.star.unwrap_or(0) {
+        differences.push(format!("star {} vs {}", here.star.unwrap_or(0), there.star.unwrap_or(0)));
+    }
+    let (here_folders, there_folders) = (item_folder_paths(here, here_paths), item_folder_paths(there, there_paths));
+    if here_folders != there_folders {
+        differences.push(format!(
+            "folders [{}] vs [{}]",
+            here_folders.into_iter().collect::<Vec<_>>().join(", "),
+            there_folders.into_iter().collect::<Vec<_>>().join(", ")
+        ));
+    }
+    differences
+}
+
+/// Whether the skip-list matches the item as `<folder path>/<name>.<ext>` in any of its folders,
+/// or as `<name>.<ext>` when it has none
+fn is_ignored(ignore_list: &IgnoreList, item: &ItemListData, paths: &HashMap<String, String>) -> bool {
+    let file = format!("{}.{}", item.name, item.ext);
+    let folders = item_folder_paths(item, paths);
+    if folders.is_empty() {
+        return ignore_list.is_ignored(Path::new(&file), false);
+    }
+    folders.iter().any(|folder| ignore_list.is_ignored(&Path::new(folder).join(&file), false))
+}
+
+/// Folder name chains by id of a library folder's `metadata.json`
+fn library_folder_chains(library: &LibraryDir) -> Result<HashMap<String, Vec<String>>, Box<dyn std::error::Error>> {
+    let folders: Vec<Child> = serde_json::from_value(library.read_metadata()?["folders"].take())?;
+    Ok(folder_chains(&folders))
+}
+
+/// Folder paths by id of a library folder's `metadata.json`
+fn library_folder_paths(library: &LibraryDir) -> Result<HashMap<String, String>, Box<dyn std::error::Error>> {
+    Ok(library_folder_chains(library)?
+        .into_iter()
+        .map(|(id, chain)| (id, chain.join("/")))
+        .collect())
+}
+
+/// The other library, checked to be an Eagle library and not this one
+fn other_library(matches: &ArgMatches, here: &LibraryDir) -> Result<LibraryDir, Box<dyn std::error::Error>> {
+    let path = matches.get_one::<String>("other").unwrap();
+    let other = LibraryDir::new(path);
+    if !other.metadata_path().is_file() || !other.images_dir().is_dir() {
+        return Err(format!("{} is not an Eagle library (no metadata.json and images folder)", path).into());
+    }
+    if other.root().canonicalize().ok() == here.root().canonicalize().ok() {
+        return Err(format!("{} is the library Eagle has open", path).into());
+    }
+    Ok(other)
+}
+
+pub fn compare_libraries(library: &LibraryDir, matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let other = other_library(matches, library)?;
+    let (here_items, there_items) = (library.items()?, other.items()?);
+    let (here_paths, there_paths) = (library_folder_paths(library)?, library_folder_paths(&other)?);
+    let comparison = compare((library, &here_items), (&other, &there_items));
+    let same = matches.get_flag("same");
+
+    let mut rows = Vec::new();
+    let mut conflicts = 0;
+    for (here, there, matched_by) in &comparison.pairs {
+        let differences = differences(here, there, &here_paths, &there_paths);
+        if !differences.is_empty() {
+            conflicts += 1;
+        } else if !same {
+            continue;
+        }
+        rows.push(json!({
+            "status": if differences.is_empty() { "same" } else { "conflict" },
+            "here": here.id,
+            "there": there.id,
+            "name": format!("{}.{}", here.name, here.ext),
+            "matchedBy": matched_by,
+            "detail": differences.join("; "),
+        }));
+    }
+    for (status, items) in [("only-here", &comparison.only_here), ("only-there", &comparison.only_there)] {
+        for item in items {
+            let (here, there) = match status {
+                "only-here" => (Value::from(item.id.clone()), Value::Null),
+                _ => (Value::Null, Value::from(item.id.clone())),
+            };
+            rows.push(json!({
+                "status": status,
+                "here": here,
+                "there": there,
+                "name": format!("{}.{}", item.name, item.ext),
+                "matchedBy": Value::Null,
+                "detail": "",
+            }));
+        }
+    }
+    output::output(&Value::Array(rows), matches)?;
+    eprintln!(
+        "{} matched ({} conflicting), {} only here, {} only in {}",
+        comparison.pairs.len(),
+        conflicts,
+        comparison.only_here.len(),
+        comparison.only_there.len(),
+        other.root().display()
+    );
+    Ok(())
+}
+
+/// Ids of the folders at `chains` in this library, creating the missing ones parents first
+async fn ensure_folders(
+    client: &EagleClient,
+    chains: &BTreeSet<Vec<String>>,
+) -> Result<HashMap<Vec<String>, String>, Box<dyn std::error::Error>> {
+    let mut ids: HashMap<Vec<String>, String> = folder_chains(&client.folder().list().await?.data)
+        .into_iter()
+        .map(|(id, chain)| (chain, id))
+        .collect();
+    // Every ancestor too; a BTreeSet orders each parent before its children
+    let wanted: BTreeSet<Vec<String>> = chains
+        .iter()
+        .flat_map(|chain| (1..=chain.len()).map(move |depth| chain[..depth].to_vec()))
+        .collect();
+    for chain in wanted {
+        if ids.contains_key(&chain) {
+            continue;
+        }
+        let Some((name, parents)) = chain.split_last() else {
+            continue;
+        };
+        let parent = match parents {
+            [] => None,
+            parents => Some(
+                ids.get(parents)
+                    .ok_or_else(|| format!("Parent folder {} of {} is missing", parents.join("/"), name))?
+                    .clone(),
+            ),
+        };
+        let created = client.folder().create(name, parent.as_deref()).await?;
+        eprintln!("Created folder {}", chain.join("/"));
+        ids.insert(chain, created.data.id);
+    }
+    Ok(ids)
+}
+
+pub async fn merge(client: &EagleClient, library: &LibraryDir, matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let other = other_library(matches, library)?;
+    let (here_items, there_items) = (library.items()?, other.items()?);
+    let there_chains = library_folder_chains(&other)?;
+    let there_paths: HashMap<String, String> =
+        there_chains.iter().map(|(id, chain)| (id.clone(), chain.join("/"))).collect();
+    let comparison = compare((library, &here_items), (&other, &there_items));
+    let ignore_list = IgnoreList::from_matches(matches, other.root())?;
+    let (missing, ignored): (Vec<&ItemListData>, Vec<&ItemListData>) = comparison
+        .only_there
+        .into_iter()
+        .partition(|item| !is_ignored(&ignore_list, item, &there_paths));
+    if !ignored.is_empty() {
+        eprintln!("Skipping {} ignored items", ignored.len());
+    }
+
+    if matches.get_flag("dry_run") {
+        for item in &missing {
+            let folders: Vec<String> = item_folder_paths(item, &there_paths).into_iter().collect();
+            println!("{}.{}  [{}]  {}", item.name, item.ext, item.tags.join(", "), folders.join(", "));
+        }
+        println!("Would import {} items from {}", missing.len(), other.root().display());
+        return Ok(());
+    }
+    if missing.is_empty() {
+        println!("Nothing to import; every item of {} is already here", other.root().display());
+        return Ok(());
+    }
+    confirm::confirm(
+        matches,
+        &format!("{} items will be imported from {}", missing.len(), other.root().display()),
+    )?;
+
+    let needed: BTreeSet<Vec<String>> = missing.iter().flat_map(|item| item_folder_chains(item, &there_chains)).collect();
+    let folder_ids = ensure_folders(client, &needed).await?;
+    let known: HashSet<String> = list::all_items(client).await?.into_iter().map(|item| item.id).collect();
+
+    let mut imported = Vec::new();
+    let mut failures = Failures::default();
+    let mut progress = Progress::new(missing.len(), "importing", matches);
+    for item in &missing {
+        progress.inc();
+        let folders: Vec<String> = item_folder_chains(item, &there_chains)
+            .iter()
+            .filter_map(|chain| folder_ids.get(chain).cloned())
+            .collect();
+        let result = match other.item_file(&item.id, &item.name, &item.ext) {
+            Some(path) => {
+                // Eagle sizes the new item by its file, whatever the metadata said
+                let size = std::fs::metadata(&path).map_or(item.size, |metadata| metadata.len());
+                let params = AddFromPathParams {
+                    path: path.to_string_lossy().into_owned(),
+                    name: item.name.clone(),
+                    website: Some(item.url.clone()).filter(|url| !url.is_empty()),
+                    annotation: Some(item.annotation.clone()).filter(|annotation| !annotation.is_empty()),
+                    tags: Some(item.tags.clone()),
+                    folder_id: folders.first().cloned(),
+                };
+                client.item().add_from_path(&params).await.map(|_| size)
+            }
+            None => Err("original file is missing".into()),
+        };
+        match result {
+            Ok(size) => imported.push((*item, folders, size)),
+            Err(error) => {
+                progress.finish();
+                eprintln!("Failed to import {} ({}.{}): {}", item.id, item.name, item.ext, error);
+                failures.add(&item.id, error);
+            }
+        }
+    }
+    progress.finish();
+
+    // Importing takes a single folder and no rating, so the rest is set on the new items,
+    // found by name and size among those that weren't there before
+    let needs_update: Vec<_> = imported
+        .iter()
+        .filter(|(item, folders, _)| folders.len() > 1 || item.star.unwrap_or(0) > 0)
+        .collect();
+    if !needs_update.is_empty() {
+        let mut new_items: HashMap<(String, String, u64), String> = list::all_items(client)
+            .await?
+            .into_iter()
+            .filter(|item| !known.contains(&item.id))
+            .map(|item| ((item.name, item.ext.to_lowercase(), item.size), item.id))
+            .collect();
+        for (item, folders, size) in needs_update {
+            let Some(id) = new_items.remove(&(item.name.clone(), item.ext.to_lowercase(), *size)) else {
+                eprintln!("Couldn't find {}.{} after importing it to set its folders and rating", item.name, item.ext);
+                continue;
+            };
+            let params = UpdateItemParams {
+                id,
+                folders: Some(folders.clone()),
+                star: item.star.filter(|star| *star > 0),
+                ..Default::default()
+            };
+            if let Err(error) = client.item().update(&params).await {
+                eprintln!("Failed to set the folders and rating of {}.{}: {}", item.name, item.ext, error);
+            }
+        }
+    }
+
+    println!(
+        "Imported {} items from {} ({} failed)",
+        imported.len(),
+        other.root().display(),
+        failures.len()
+    );
+    report::finish(matches, "library merge-into", imported.len(), failures)
+}
@@ -0,0 +1,395 @@
+use crate::cli::folder::folder_paths;
+use crate::cli::item::list::{self, group::{self, GroupBy}};
+use crate::cli::output::OutputOptions;
+use crate::cli::{datetime, output, pager};
+use crate::lib::client::EagleClient;
+use crate::lib::library::LibraryDir;
+use crate::lib::types::{Child, ItemListData};
+use clap::{Arg, ArgMatches, Command};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+
+pub mod backup;
+pub mod checksum;
+pub mod compare;
+pub mod select;
+pub mod snapshot;
+
+#[derive(Default)]
+pub struct App;
+
+impl App {
+    pub fn new() -> Self {
+        App {}
+    }
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+    // Snapshots are compared without Eagle
+    if let Some(("diff", diff_matches)) = matches.subcommand() {
+        return snapshot::diff(diff_matches);
+    }
+    let data = client.library().info().await?.data;
+
+    match matches.subcommand() {
+        Some(("info", info_matches)) => {
+            let value = if info_matches.get_flag("folders") {
+                serde_json::to_value(data.folders)?
+            } else if info_matches.get_flag("smart_folders") {
+                serde_json::to_value(data.smart_folders)?
+            } else if info_matches.get_flag("quick_access") {
+                serde_json::to_value(data.quick_access)?
+            } else if info_matches.get_flag("tags_groups") {
+                serde_json::to_value(data.tags_groups)?
+            } else if info_matches.get_flag("modification_time") {
+                let tz = datetime::from_matches(info_matches);
+                println!("{}", tz.format_millis(data.modification_time as i64));
+                return Ok(());
+            } else {
+                serde_json::to_value(data)?
+            };
+            output::output(&value, info_matches)?;
+        },
+        Some(("history", history_matches)) => {
+            let current = std::path::Path::new(&data.library.path);
+            let rows: Vec<Value> = client
+                .library()
+                .history()
+                .await?
+                .data
+                .iter()
+                .map(|path| {
+                    let path = std::path::Path::new(path);
+                    let name = path.file_stem().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+                    json!({ "name": name, "path": path, "current": select::same_path(path, current) })
+                })
+                .collect();
+            output::output(&Value::Array(rows), history_matches)?;
+        },
+        Some(("switch", switch_matches)) => {
+            let library = select::resolve(client, switch_matches.get_one::<String>("path").unwrap()).await?;
+            select::switch(client, &library).await?;
+            println!("Switched to {}", library.display());
+        },
+        Some(("verify", verify_matches)) => {
+            let library = LibraryDir::new(&data.library.path);
+            let problems = verify(client, &library).await?;
+            let count = problems.len();
+            output::output(&Value::Array(problems), verify_matches)?;
+            if count > 0 {
+                return Err(format!("Found {} problems in {}", count, data.library.path).into());
+            }
+            eprintln!("No problems found in {}", data.library.path);
+        },
+        Some(("backup", backup_matches)) => {
+            backup::backup(&LibraryDir::new(&data.library.path), &data.library.name, backup_matches)?;
+        },
+        Some(("restore-metadata", restore_matches)) => {
+            backup::restore(&LibraryDir::new(&data.library.path), restore_matches)?;
+        },
+        Some(("checksum", checksum_matches)) => {
+            checksum::checksum(client, &LibraryDir::new(&data.library.path), checksum_matches).await?;
+        },
+        Some(("compare", compare_matches)) => {
+            compare::compare_libraries(&LibraryDir::new(&data.library.path), compare_matches)?;
+        },
+        Some(("merge-into", merge_matches)) => {
+            compare::merge(client, &LibraryDir::new(&data.library.path), merge_matches).await?;
+        },
+        Some(("snapshot", snapshot_matches)) => {
+            snapshot::snapshot(client, &data.library, snapshot_matches).await?;
+        },
+        Some(("stats", stats_matches)) => {
+            let items = list::all_items(client).await?;
+            let folders = client.folder().list().await?.data;
+            let top = *stats_matches.get_one::<usize>("top").unwrap();
+            let stats = stats(&items, &folders, top, &datetime::from_matches(stats_matches));
+            if output::is_explicit(stats_matches) {
+                return output::output(&stats, stats_matches);
+            }
+            // Each section as its own table, with sizes and dates readable
+            let mut options = OutputOptions::from_matches(stats_matches);
+            options.human = true;
+            let mut text = String::new();
+            for (title, section) in stats.as_object().into_iter().flatten() {
+                if section.is_array() {
+                    text.push_str(&options.theme.header(&format!("\n{}", title)));
+                    text.push('\n');
+                }
+                text.push_str(&output::render(section, &options)?);
+            }
+            pager::print(&text, stats_matches)?;
+        },
+        Some(("library", library_matches)) => {
+            if library_matches.get_flag("path") {
+                println!("{}", data.library.path);
+            } else if library_matches.get_flag("name") {
+                println!("{}", data.library.name);
+            } else {
+                output::output(&serde_json::to_value(data.library)?, library_matches)?;
+            }
+        },
+        _ => {
+        }
+    }
+    Ok(())
+}
+
+/// The overview and breakdowns of `library stats`, the `top` most used tags only.
+///
+/// Months are those items were added in; an item in folders at several depths counts at each.
+fn stats(items: &[ItemListData], folders: &[Child], top: usize, tz: &datetime::TimeZone) -> Value {
+    let sized: Vec<(u64, u64)> = items.iter().filter_map(|item| Some((item.width?, item.height?))).collect();
+    let average = |dimension: fn(&(u64, u64)) -> u64| match sized.len() {
+        0 => Value::Null,
+        count => json!(sized.iter().map(dimension).sum::<u64>() / count as u64),
+    };
+    let tags: HashSet<&String> = items.iter().flat_map(|item| &item.tags).collect();
+    let overview = json!({
+        "items": items.len(),
+        "size": items.iter().map(|item| item.size).sum::<u64>(),
+        "tags": tags.len(),
+        "folders": folder_paths(folders).len(),
+        "averageWidth": average(|(width, _)| *width),
+        "averageHeight": average(|(_, height)| *height),
+    });
+
+    let paths = folder_paths(folders);
+    let mut depths: BTreeMap<usize, u64> = BTreeMap::new();
+    for item in items {
+        let item_depths: HashSet<usize> = item
+            .folders
+            .iter()
+            .flatten()
+            .filter_map(|id| paths.get(id))
+            .map(|path| path.split('/').count())
+            .collect();
+        for depth in if item_depths.is_empty() { HashSet::from([0]) } else { item_depths } {
+            *depths.entry(depth).or_default() += 1;
+        }
+    }
+
+    let mut tag_groups = group::group(items, GroupBy::Tag, folders, tz);
+    tag_groups.retain(|group| group["group"] != "(none)");
+    tag_groups.truncate(top);
+    let rename = |groups: Vec<Value>, key: &str| -> Vec<Value> {
+        groups
+            .into_iter()
+            .map(|group| {
+                let mut row = Map::new();
+                row.insert(key.to_string(), group["group"].clone());
+                row.insert("count".to_string(), group["count"].clone());
+                row.insert("size".to_string(), group["size"].clone());
+                Value::Object(row)
+            })
+            .collect()
+    };
+    json!({
+        "overview": overview,
+        "extensions": rename(group::group(items, GroupBy::Ext, folders, tz), "ext"),
+        "tags": rename(tag_groups, "tag"),
+        "depths": depths.into_iter().map(|(depth, count)| json!({ "depth": depth, "count": count })).collect::<Vec<_>>(),
+        "months": rename(group::group(items, GroupBy::Month, folders, tz), "month"),
+    })
+}
+
+/// One problem found by `library verify`, with how to fix it
+fn problem(kind: &str, id: &str, path: &std::path::Path, fix: String) -> Value {
+    json!({ "kind": kind, "id": id, "path": path, "fix": fix })
+}
+
+/// Cross-check the items and folders Eagle reports with the `.info` folders on disk.
+async fn verify(client: &EagleClient, library: &LibraryDir) -> Result<Vec<Value>, Box<dyn std::error::Error>> {
+    let items = list::all_items(client).await?;
+    let folders = folder_paths(&client.folder().list().await?.data);
+    let listed: HashSet<&str> = items.iter().map(|item| item.id.as_str()).collect();
+    let mut problems = Vec::new();
+
+    let mut on_disk = HashSet::new();
+    for entry in fs::read_dir(library.images_dir())? {
+        let dir = entry?.path();
+        let Some(id) = dir.file_name().and_then(|name| name.to_str()).and_then(|name| name.strip_suffix(".info")) else {
+            continue;
+        };
+        on_disk.insert(id.to_string());
+        let metadata_path = dir.join("metadata.json");
+        let item = match library.item(id) {
+            Ok(item) => item,
+            Err(e) => {
+                problems.push(problem(
+                    "bad-metadata",
+                    id,
+                    &metadata_path,
+                    format!("Repair or restore the file ({}); Eagle skips the item until then", e),
+                ));
+                continue;
+            }
+        };
+        if item.is_deleted {
+            continue;
+        }
+        if library.item_file(id, &item.name, &item.ext).is_none() {
+            problems.push(problem(
+                "missing-original",
+                id,
+                &dir.join(format!("{}.{}", item.name, item.ext)),
+                "Put the original back, or delete the item in Eagle".to_string(),
+            ));
+        }
+        if library.item_thumbnail(id, &item.name).is_none() {
+            problems.push(problem(
+                "missing-thumbnail",
+                id,
+                &dir.join(format!("{}_thumbnail.png", item.name)),
+                format!("echo {} | eagle-eye item thumbnails verify --stdin --fix", id),
+            ));
+        }
+        if !listed.contains(id) {
+            problems.push(problem(
+                "not-in-eagle",
+                id,
+                &dir,
+                "Restart Eagle so it loads the item, or remove the folder if it's a leftover".to_string(),
+            ));
+        }
+    }
+
+    for item in &items {
+        if !on_disk.contains(&item.id) {
+            problems.push(problem(
+                "missing-info",
+                &item.id,
+                &library.item_dir(&item.id),
+                "Restore the folder from a backup, or delete the item in Eagle".to_string(),
+            ));
+        }
+        for folder in item.folders.iter().flatten().filter(|folder| !folders.contains_key(*folder)) {
+            problems.push(problem(
+                "dangling-folder",
+                &item.id,
+                &library.item_dir(&item.id),
+                format!(
+                    "Folder {} no longer exists: echo {} | eagle-eye item move --stdin --to FOLDER",
+                    folder, item.id
+                ),
+            ));
+        }
+    }
+    problems.sort_by_key(|problem| (problem["kind"].to_string(), problem["id"].to_string()));
+    Ok(problems)
+}
+
+pub fn build() -> Command {
+    Command::new("library")
+        .about("Library")
+        .subcommand(
+            Command::new("info")
+            .about("Library info")
+            .arg(
+                Arg::new("folders")
+                .short('f')
+                .long("folders")
+                .help("Show folders")
+                .num_args(0)
+                )
+            .arg(
+                Arg::new("smart_folders")
+                .short('s')
+                .long("smart-folders")
+                .help("Show smart folders")
+                .action(clap::ArgAction::SetTrue)
+                .num_args(0)
+                )
+            .arg(
+                Arg::new("quick_access")
+                .short('q')
+                .long("quick-access")
+                .help("Show quick access")
+                .action(clap::ArgAction::SetTrue)
+                .num_args(0)
+                )
+            .arg(
+                Arg::new("tags_groups")
+                .short('t')
+                .long("tags-groups")
+                .help("Show tags groups")
+                .action(clap::ArgAction::SetTrue)
+                .num_args(0)
+                )
+            .arg(
+                Arg::new("modification_time")
+                .short('m')
+                .long("modification-time")
+                .help("Show modification time")
+                .action(clap::ArgAction::SetTrue)
+                .num_args(0)
+                )
+            )
+            .subcommand(
+                Command::new("history")
+                .about("Libraries opened recently in Eagle")
+                )
+            .subcommand(
+                Command::new("switch")
+                .about("Switch library")
+                .arg(
+                    Arg::new("path")
+                    .short('p')
+                    .long("path")
+                    .help("Library path, or the name of a recent library")
+                    .required(true)
+                    .num_args(1)
+                    )
+                )
+            .subcommand(backup::backup_command())
+            .subcommand(backup::restore_command())
+            .subcommand(checksum::checksum_command())
+            .subcommand(compare::compare_command())
+            .subcommand(compare::merge_command())
+            .subcommand(snapshot::snapshot_command())
+            .subcommand(snapshot::diff_command())
+            .subcommand(
+                Command::new("stats")
+                .about("Item counts and sizes of the library by extension, tag, folder depth, and month")
+                .arg(
+                    Arg::new("top")
+                    .long("top")
+                    .value_name("N")
+                    .help("Number of most used tags to show")
+                    .num_args(1)
+                    .default_value("10")
+                    .value_parser(clap::value_parser!(usize))
+                    )
+                .args(output::args())
+                )
+            .subcommand(
+                Command::new("verify")
+                .about("Check the library folder against Eagle and list what needs fixing; exits non-zero on problems")
+                .args(output::args())
+                )
+            .subcommand(
+                Command::new("library")
+                .about("Library")
+                .arg(
+                    Arg::new("path")
+                    .short('p')
+                    .long("path")
+                    .help("Current working library path")
+                    .required(false)
+                    .num_args(0)
+                    )
+                .arg(
+                    Arg::new("name")
+                    .short('n')
+                    .long("name")
+                    .help("Current working library name")
+                    .required(false)
+                    .num_args(0)
+                    )
+                )
+
+}
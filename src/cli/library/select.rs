@@ -0,0 +1,98 @@
+use crate::lib::client::EagleClient;
+use clap::{Arg, ArgAction, ArgMatches};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// How long Eagle may take to open a library before giving up
+const SWITCH_TIMEOUT: Duration = Duration::from_secs(60);
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// `--library` and `--switch-back`, global so they can follow any subcommand.
+pub fn args() -> Vec<Arg> {
+    vec![
+        Arg::new("library_select")
+            .long("library")
+            .value_name("NAME|PATH")
+            .help("Run the command on this library, switching Eagle to it first; a name is looked up in the recent libraries")
+            .num_args(1)
+            .global(true),
+        Arg::new("switch_back")
+            .long("switch-back")
+            .help("Switch Eagle back to the library it had open once the command is done")
+            .action(ArgAction::SetTrue)
+            .requires("library_select")
+            .global(true),
+    ]
+}
+
+/// Library folder named by `value`: a path to a `.library` folder, or the name of one of
+/// Eagle's recent libraries, with or without the `.library` suffix.
+pub async fn resolve(client: &EagleClient, value: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let path = Path::new(value);
+    if path.is_dir() {
+        return Ok(path.to_path_buf());
+    }
+    let mut recent = client.library().history().await?.data;
+    recent.dedup();
+    let name = value.strip_suffix(".library").unwrap_or(value);
+    let matching: Vec<&String> = recent
+        .iter()
+        .filter(|recent| library_name(Path::new(recent)).eq_ignore_ascii_case(name))
+        .collect();
+    match matching.as_slice() {
+        [path] => Ok(PathBuf::from(path)),
+        [] => {
+            let names: Vec<String> = recent.iter().map(|recent| library_name(Path::new(recent))).collect();
+            Err(format!("No library named {} (recent libraries: {})", value, names.join(", ")).into())
+        }
+        _ => Err(format!("Several recent libraries are named {}; give its path instead", value).into()),
+    }
+}
+
+/// Name Eagle shows for a library folder: its file name without `.library`
+fn library_name(path: &Path) -> String {
+    let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    name.strip_suffix(".library").map(str::to_string).unwrap_or(name)
+}
+
+pub fn same_path(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Ask Eagle to open `library` and wait until it reports it as the current one.
+pub async fn switch(client: &EagleClient, library: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    client.library().switch(library).await?;
+    let started = Instant::now();
+    loop {
+        // Eagle doesn't answer, or answers for the old library, while the new one loads
+        if let Ok(info) = client.library().info().await {
+            if same_path(Path::new(&info.data.library.path), library) {
+                return Ok(());
+            }
+        }
+        if started.elapsed() > SWITCH_TIMEOUT {
+            return Err(format!("Eagle didn't open {} within {}s", library.display(), SWITCH_TIMEOUT.as_secs()).into());
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Switch to the `--library` one, when given and not already open.
+///
+/// Returns the library to switch back to afterwards with `--switch-back`.
+pub async fn select(client: &EagleClient, matches: &ArgMatches) -> Result<Option<PathBuf>, Box<dyn std::error::Error>> {
+    let Some(value) = matches.get_one::<String>("library_select") else {
+        return Ok(None);
+    };
+    let library = resolve(client, value).await?;
+    let current = PathBuf::from(client.library().info().await?.data.library.path);
+    if same_path(&current, &library) {
+        return Ok(None);
+    }
+    eprintln!("Switching Eagle to {}", library.display());
+    switch(client, &library).await?;
+    Ok(Some(current).filter(|_| matches.get_flag("switch_back")))
+}
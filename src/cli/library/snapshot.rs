@@ -0,0 +1,213 @@
+use crate::cli::folder::folder_paths;
+use crate::cli::item::list;
+use crate::cli::output;
+use crate::cli::pager;
+use crate::cli::theme::Theme;
+use crate::lib::client::EagleClient;
+use crate::lib::types::LibraryData;
+use clap::{Arg, ArgMatches, Command};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+/// The curation state of a library at one point in time, as written by `library snapshot`
+#[derive(Debug, Serialize, Deserialize)]
+struct Snapshot {
+    library: String,
+    /// When the snapshot was taken, in epoch milliseconds
+    time: i64,
+    /// Folder paths by folder id
+    folders: BTreeMap<String, String>,
+    items: Vec<SnapshotItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SnapshotItem {
+    id: String,
+    name: String,
+    ext: String,
+    tags: Vec<String>,
+    folders: Vec<String>,
+}
+
+impl SnapshotItem {
+    fn file_name(&self) -> String {
+        format!("{}.{}", self.name, self.ext)
+    }
+}
+
+pub fn snapshot_command() -> Command {
+    Command::new("snapshot")
+        .about("Save the names, tags, and folders of every item to compare later with `library diff`")
+        .arg(
+            Arg::new("out")
+                .short('o')
+                .long("out")
+                .value_name("FILE")
+                .help("Snapshot to write [default: <library>-snapshot-<date>.json]")
+                .num_args(1),
+        )
+        .args(output::args())
+}
+
+pub fn diff_command() -> Command {
+    Command::new("diff")
+        .about("Show the items added, removed, renamed, retagged, or moved, and the folders changed between two snapshots")
+        .arg(Arg::new("old").value_name("OLD").help("Earlier snapshot").required(true))
+        .arg(Arg::new("new").value_name("NEW").help("Later snapshot").required(true))
+        .args(output::args())
+}
+
+pub async fn snapshot(
+    client: &EagleClient,
+    library: &LibraryData,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let folders: BTreeMap<String, String> = folder_paths(&client.folder().list().await?.data).into_iter().collect();
+    let mut items: Vec<SnapshotItem> = list::all_items(client)
+        .await?
+        .into_iter()
+        .map(|item| SnapshotItem {
+            id: item.id,
+            name: item.name,
+            ext: item.ext,
+            tags: item.tags,
+            folders: item.folders.unwrap_or_default(),
+        })
+        .collect();
+    items.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let out = match matches.get_one::<String>("out") {
+        Some(out) => PathBuf::from(out),
+        None => PathBuf::from(format!("{}-snapshot-{}.json", library.name, chrono::Local::now().format("%Y-%m-%d"))),
+    };
+    let snapshot = Snapshot {
+        library: library.path.clone(),
+        time: chrono::Utc::now().timestamp_millis(),
+        folders,
+        items,
+    };
+    std::fs::write(&out, serde_json::to_string_pretty(&snapshot)? + "\n")
+        .map_err(|e| format!("Failed to write {}: {}", out.display(), e))?;
+    if output::is_explicit(matches) {
+        let summary = json!({ "file": out, "items": snapshot.items.len(), "folders": snapshot.folders.len() });
+        return output::output(&summary, matches);
+    }
+    println!(
+        "Saved {} items and {} folders to {}",
+        snapshot.items.len(),
+        snapshot.folders.len(),
+        out.display()
+    );
+    Ok(())
+}
+
+fn read_snapshot(path: &str) -> Result<Snapshot, Box<dyn std::error::Error>> {
+    let content = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    Ok(serde_json::from_str(&content).map_err(|e| format!("Invalid snapshot {}: {}", path, e))?)
+}
+
+fn change(kind: &str, change: &str, id: &str, name: &str, from: Value, to: Value) -> Value {
+    json!({ "kind": kind, "change": change, "id": id, "name": name, "from": from, "to": to })
+}
+
+/// Every difference between two snapshots, folders first, then items by id.
+///
+/// `from` and `to` hold the old and new name of a renamed item or folder, and the old and new
+/// tags or folder paths of a retagged or moved item.
+fn changes(old: &Snapshot, new: &Snapshot) -> Vec<Value> {
+    let mut changes = Vec::new();
+    for (id, path) in &old.folders {
+        match new.folders.get(id) {
+            None => changes.push(change("folder", "removed", id, path, Value::Null, Value::Null)),
+            Some(new_path) if new_path != path => {
+                changes.push(change("folder", "renamed", id, new_path, json!(path), json!(new_path)))
+            }
+            Some(_) => {}
+        }
+    }
+    for (id, path) in new.folders.iter().filter(|(id, _)| !old.folders.contains_key(*id)) {
+        changes.push(change("folder", "added", id, path, Value::Null, Value::Null));
+    }
+
+    let old_items: BTreeMap<&str, &SnapshotItem> = old.items.iter().map(|item| (item.id.as_str(), item)).collect();
+    let new_items: BTreeMap<&str, &SnapshotItem> = new.items.iter().map(|item| (item.id.as_str(), item)).collect();
+    let paths = |snapshot: &Snapshot, item: &SnapshotItem| -> BTreeSet<String> {
+        item.folders
+            .iter()
+            .map(|id| snapshot.folders.get(id).cloned().unwrap_or_else(|| id.clone()))
+            .collect()
+    };
+    let ids: BTreeSet<&str> = old_items.keys().chain(new_items.keys()).copied().collect();
+    for id in ids {
+        match (old_items.get(id), new_items.get(id)) {
+            (Some(item), None) => changes.push(change("item", "removed", id, &item.file_name(), Value::Null, Value::Null)),
+            (None, Some(item)) => changes.push(change("item", "added", id, &item.file_name(), Value::Null, Value::Null)),
+            (Some(old_item), Some(new_item)) => {
+                let name = new_item.file_name();
+                if old_item.file_name() != name {
+                    changes.push(change("item", "renamed", id, &name, json!(old_item.file_name()), json!(name)));
+                }
+                let (old_tags, new_tags): (BTreeSet<&String>, BTreeSet<&String>) =
+                    (old_item.tags.iter().collect(), new_item.tags.iter().collect());
+                if old_tags != new_tags {
+                    changes.push(change("item", "retagged", id, &name, json!(old_tags), json!(new_tags)));
+                }
+                let (old_paths, new_paths) = (paths(old, old_item), paths(new, new_item));
+                if old_paths != new_paths {
+                    changes.push(change("item", "moved", id, &name, json!(old_paths), json!(new_paths)));
+                }
+            }
+            (None, None) => {}
+        }
+    }
+    changes
+}
+
+/// A change as a line of a diff: `+` added, `-` removed, `~` changed
+fn diff_line(change: &Value, theme: &Theme) -> String {
+    let text = |value: &Value| value.as_str().unwrap_or_default().to_string();
+    let (kind, id, name) = (text(&change["kind"]), text(&change["id"]), text(&change["name"]));
+    let subject = match kind.as_str() {
+        "folder" => format!("folder {}", name),
+        _ => format!("{}  {}", id, name),
+    };
+    let (marker, line) = match text(&change["change"]).as_str() {
+        "added" => ('+', subject),
+        "removed" => ('-', subject),
+        "renamed" => ('~', format!("{}  renamed from {}", subject, text(&change["from"]))),
+        changed => {
+            let set = |value: &Value| -> BTreeSet<String> { value.as_array().into_iter().flatten().map(text).collect() };
+            let (from, to) = (set(&change["from"]), set(&change["to"]));
+            let edits: Vec<String> = to
+                .difference(&from)
+                .map(|value| format!("+{}", value))
+                .chain(from.difference(&to).map(|value| format!("-{}", value)))
+                .collect();
+            let label = match changed {
+                "retagged" => "tags",
+                _ => "folders",
+            };
+            ('~', format!("{}  {} {}", subject, label, edits.join(" ")))
+        }
+    };
+    theme.diff(marker, &format!("{} {}", marker, line))
+}
+
+pub fn diff(matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let old = read_snapshot(matches.get_one::<String>("old").unwrap())?;
+    let new = read_snapshot(matches.get_one::<String>("new").unwrap())?;
+    let changes = changes(&old, &new);
+    if output::is_explicit(matches) {
+        return output::output(&Value::Array(changes), matches);
+    }
+    if changes.is_empty() {
+        eprintln!("No changes");
+        return Ok(());
+    }
+    let theme = Theme::from_matches(matches);
+    let text: String = changes.iter().map(|change| diff_line(change, &theme) + "\n").collect();
+    pager::print(&text, matches)?;
+    Ok(())
+}
@@ -1,42 +1,245 @@
 use clap::{ArgMatches, Command};
 use crate::lib;
+use std::time::Instant;
 
+pub mod api;
 pub mod app;
+pub mod apply;
+pub mod capture;
+pub mod color;
+pub mod completions;
+pub mod confirm;
+pub mod datetime;
+pub mod decode;
+pub mod events;
+pub mod exif;
+pub mod exit;
+pub mod feed;
 pub mod folder;
+pub mod font;
+pub mod graphics;
+pub mod handlers;
+pub mod history;
+pub mod http;
+pub mod ignore;
+pub mod index;
 pub mod item;
+pub mod jq;
 pub mod library;
+pub mod output;
+pub mod pager;
+pub mod picker;
+pub mod plan;
+pub mod progress;
+pub mod query;
+pub mod report;
+pub mod rpc;
+pub mod rules;
+pub mod schema;
+pub mod search;
+pub mod selection;
+pub mod serve;
+pub mod smart_folder;
+pub mod stats;
+pub mod system;
+pub mod tag;
+pub mod template;
+pub mod theme;
+pub mod tui;
+pub mod units;
+pub mod watch;
 
-pub fn get_matches() -> ArgMatches {
-    Command::new("eagle-eye")
+/// Subcommands, as paths below the top level, whose results go through `output::output` but
+/// whose builders don't add the output flags themselves.
+const STRUCTURED_OUTPUT: [&[&str]; 7] = [
+    &["app"],
+    &["folder", "list"],
+    &["item", "info"],
+    &["item", "thumbnail"],
+    &["library", "history"],
+    &["library", "info"],
+    &["library", "library"],
+];
+
+/// Add `output::args()` to the subcommand at `path` below `command`.
+fn with_output_args(command: Command, path: &[&str]) -> Command {
+    match path.split_first() {
+        Some((name, rest)) => command.mut_subcommand(*name, |subcommand| with_output_args(subcommand, rest)),
+        None => command.args(output::args()),
+    }
+}
+
+/// The whole command line, for parsing arguments other than the process's own
+pub fn build() -> Command {
+    let command = Command::new("eagle-eye")
         .about("Tool for managing Eagle")
         .version("0.1.0")
         .author("Oleksii Luchnikov <oleksiiluchnikov@gmail.com>")
         .arg_required_else_help(true)
+        .arg(datetime::arg())
+        .arg(confirm::arg())
+        .arg(progress::arg())
+        .arg(theme::arg())
+        .arg(pager::arg())
+        .arg(decode::arg())
+        .args(library::select::args())
 
+        .subcommand(api::build())
         .subcommand(app::build())
+        .subcommand(apply::build())
+        .subcommand(capture::build())
+        .subcommand(completions::build())
+        .subcommand(events::build())
+        .subcommand(feed::build())
         .subcommand(folder::build())
+        .subcommand(history::build())
+        .subcommand(ignore::build())
+        .subcommand(index::build())
         .subcommand(item::build())
         .subcommand(library::build())
-        .get_matches()
+        .subcommand(plan::build())
+        .subcommand(query::build())
+        .subcommand(rpc::build())
+        .subcommand(rules::build())
+        .subcommand(schema::build())
+        .subcommand(search::build())
+        .subcommand(selection::build())
+        .subcommand(serve::build())
+        .subcommand(smart_folder::build())
+        .subcommand(stats::build())
+        .subcommand(tag::build())
+        .subcommand(tui::build())
+        .subcommand(watch::build());
+    STRUCTURED_OUTPUT
+        .iter()
+        .fold(command, |command, path| with_output_args(command, path))
+}
+
+pub fn get_matches() -> ArgMatches {
+    build().get_matches()
 }
 
 pub async fn execute() -> Result<(), Box<dyn std::error::Error>> {
     let matches = get_matches();
-    let eagle_client = lib::client::EagleClient::new("localhost", 41595);
+    let mut eagle_client = lib::client::EagleClient::new("localhost", 41595).with_strict_decode(decode::is_strict(&matches));
+    if history::is_long_running(&matches) {
+        eagle_client = eagle_client.without_changes();
+    }
 
-    // Handle rename subcommand
+    let started = Instant::now();
+    output::start_clock();
+    // Starting Eagle applies --library itself, once Eagle is up
+    let selected = match app::is_launching(&matches) {
+        true => Ok(None),
+        false => library::select::select(&eagle_client, &matches).await,
+    };
+    let result = match selected {
+        Ok(previous) => {
+            let result = dispatch(&eagle_client, &matches).await;
+            match previous {
+                Some(previous) => {
+                    eprintln!("Switching Eagle back to {}", previous.display());
+                    let switched = library::select::switch(&eagle_client, &previous).await;
+                    result.and(switched)
+                }
+                None => result,
+            }
+        }
+        Err(error) => Err(error),
+    };
+    if !matches!(matches.subcommand_name(), Some("stats")) {
+        // Stats are best effort and must never break the command itself
+        let _ = stats::record(&matches, started.elapsed(), result.is_ok());
+    }
+    if !matches!(matches.subcommand_name(), Some("history")) {
+        // Same for the audit log
+        let _ = history::record(&matches, &eagle_client, started.elapsed(), &result);
+    }
+    result
+}
+
+async fn dispatch(
+    eagle_client: &lib::client::EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
     match matches.subcommand() {
+        Some(("api", api_matches)) => {
+            api::execute(eagle_client, api_matches).await?;
+        },
         Some(("app", app_matches)) => {
-            app::execute(&eagle_client, app_matches).await?;
+            app::execute(eagle_client, app_matches).await?;
+        },
+        Some(("apply", apply_matches)) => {
+            apply::execute(eagle_client, apply_matches).await?;
+        },
+        Some(("capture", capture_matches)) => {
+            capture::execute(eagle_client, capture_matches).await?;
+        },
+        Some(("completions", completions_matches)) => {
+            completions::execute(eagle_client, completions_matches).await?;
+        },
+        Some(("events", events_matches)) => {
+            events::execute(eagle_client, events_matches).await?;
+        },
+        Some(("feed", feed_matches)) => {
+            feed::execute(eagle_client, feed_matches).await?;
         },
         Some(("folder", folder_matches)) => {
-            folder::execute(&eagle_client, folder_matches).await?;
+            folder::execute(eagle_client, folder_matches).await?;
+        },
+        Some(("history", history_matches)) => {
+            history::execute(history_matches).await?;
+        },
+        Some(("ignore", ignore_matches)) => {
+            ignore::execute(ignore_matches).await?;
+        },
+        Some(("index", index_matches)) => {
+            index::execute(eagle_client, index_matches).await?;
         },
         Some(("item", item_matches)) => {
-            item::execute(&eagle_client, item_matches).await?;
+            item::execute(eagle_client, item_matches).await?;
         },
         Some(("library", library_matches)) => {
-            library::execute(&eagle_client, library_matches).await?;
+            library::execute(eagle_client, library_matches).await?;
+        },
+        Some(("plan", plan_matches)) => {
+            plan::execute(eagle_client, plan_matches).await?;
+        },
+        Some(("query", query_matches)) => {
+            query::execute(eagle_client, query_matches).await?;
+        },
+        Some(("rpc", rpc_matches)) => {
+            rpc::execute(eagle_client, rpc_matches).await?;
+        },
+        Some(("rules", rules_matches)) => {
+            rules::execute(eagle_client, rules_matches).await?;
+        },
+        Some(("schema", schema_matches)) => {
+            schema::execute(schema_matches).await?;
+        },
+        Some(("search", search_matches)) => {
+            search::execute(eagle_client, search_matches).await?;
+        },
+        Some(("selection", selection_matches)) => {
+            selection::execute(eagle_client, selection_matches).await?;
+        },
+        Some(("serve", serve_matches)) => {
+            serve::execute(eagle_client, serve_matches).await?;
+        },
+        Some(("smart-folder", smart_folder_matches)) => {
+            smart_folder::execute(eagle_client, smart_folder_matches).await?;
+        },
+        Some(("stats", stats_matches)) => {
+            stats::execute(stats_matches).await?;
+        },
+        Some(("tag", tag_matches)) => {
+            tag::execute(eagle_client, tag_matches).await?;
+        },
+        Some(("tui", tui_matches)) => {
+            tui::execute(eagle_client, tui_matches).await?;
+        },
+        Some(("watch", watch_matches)) => {
+            watch::execute(eagle_client, watch_matches).await?;
         },
         _ => {
             println!("No subcommand was used");
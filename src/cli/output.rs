@@ -0,0 +1,769 @@
+use crate::cli::datetime::{self, TimeZone};
+use crate::cli::graphics::png_base64;
+use crate::cli::jq::Jq;
+use crate::cli::pager;
+use crate::cli::template::Template;
+use crate::cli::theme::Theme;
+use crate::cli::units::{format_length, format_size};
+use clap::parser::ValueSource;
+use clap::{Arg, ArgAction, ArgMatches};
+use serde_json::{Map, Value};
+use std::cmp::Ordering;
+use std::sync::OnceLock;
+use std::time::Instant;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Largest side of thumbnails inlined into HTML output, in pixels
+const HTML_THUMBNAIL_SIZE: u32 = 160;
+
+/// When the command started, for the elapsed time of the `--summary` footer
+static STARTED: OnceLock<Instant> = OnceLock::new();
+
+/// Fields holding epoch milliseconds, shown as dates with `--human`
+const TIME_FIELDS: [&str; 4] = ["modificationTime", "lastModified", "btime", "mtime"];
+
+/// How command results are rendered on stdout
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum OutputFormat {
+    #[default]
+    Table,
+    Json,
+    Csv,
+    /// One `path` field per line, for piping into other tools
+    Path,
+    /// GitHub-flavored Markdown table
+    Markdown,
+    /// Standalone HTML page holding a table
+    Html,
+    /// One `data[0].tags[1] = "logo";` assignment per value, for grep
+    Gron,
+}
+
+impl OutputFormat {
+    fn parse(value: &str) -> OutputFormat {
+        match value {
+            "json" => OutputFormat::Json,
+            "csv" => OutputFormat::Csv,
+            "path" => OutputFormat::Path,
+            "markdown" => OutputFormat::Markdown,
+            "html" => OutputFormat::Html,
+            "gron" => OutputFormat::Gron,
+            _ => OutputFormat::Table,
+        }
+    }
+}
+
+/// Output options selected with `args()`
+#[derive(Debug, Default)]
+pub struct OutputOptions {
+    pub format: OutputFormat,
+    /// Columns to keep, in this order; empty keeps every column
+    pub fields: Vec<String>,
+    /// Inline the image at each row's `path` into HTML output
+    pub thumbnails: bool,
+    /// Line rendered per row instead of `format`
+    pub template: Option<Template>,
+    /// Zone of dates formatted by the template
+    pub tz: TimeZone,
+    /// Keys rows are ordered by before rendering, most significant first
+    pub sort: Vec<SortKey>,
+    /// Widest a table column may get, in terminal cells
+    pub max_col_width: Option<usize>,
+    /// Wrap table cells wider than `max_col_width` onto more lines instead of cutting them
+    pub wrap: bool,
+    /// Colors of table output
+    pub theme: Theme,
+    /// Show sizes and timestamps in tables, CSV, Markdown, and HTML the way people read them
+    pub human: bool,
+    /// jq filter whose output is printed instead of `format`
+    pub jq: Option<Jq>,
+}
+
+/// A `--sort` key: a field, or a dotted path into nested objects, ordered descending when
+/// written with a leading `-`.
+#[derive(Debug, Clone)]
+pub struct SortKey {
+    path: Vec<String>,
+    descending: bool,
+}
+
+fn parse_sort(value: &str) -> Result<Vec<SortKey>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .map(|key| {
+            let (descending, field) = match key.strip_prefix('-') {
+                Some(field) => (true, field),
+                None => (false, key),
+            };
+            match field.is_empty() {
+                true => Err(format!("empty sort field in {}", value)),
+                false => Ok(SortKey {
+                    path: field.split('.').map(String::from).collect(),
+                    descending,
+                }),
+            }
+        })
+        .collect()
+}
+
+/// `--output`, `--json`, `--fields`, `--sort`, `--human`, `--max-col-width`, `--wrap`,
+/// `--html-thumbnails`, `--template`, `--jq`, and `--summary` arguments for commands producing
+/// structured results.
+pub fn args() -> Vec<Arg> {
+    vec![
+        Arg::new("output")
+            .long("output")
+            .value_name("FORMAT")
+            .help("Output format")
+            .num_args(1)
+            .default_value("table")
+            .value_parser(["table", "json", "csv", "path", "markdown", "html", "gron"]),
+        Arg::new("json")
+            .long("json")
+            .help("Shorthand for --output json")
+            .action(ArgAction::SetTrue),
+        Arg::new("fields")
+            .long("fields")
+            .value_name("FIELDS")
+            .help("Only show these fields. Comma separated")
+            .num_args(1),
+        Arg::new("sort")
+            .long("sort")
+            .value_name("FIELDS")
+            .help("Order rows by these fields, e.g. -star,name. Comma separated, - for descending")
+            .num_args(1)
+            .allow_hyphen_values(true)
+            .value_parser(parse_sort),
+        Arg::new("human")
+            .long("human")
+            .help("Show sizes like 2.1 MB and timestamps as dates in --tz; JSON keeps raw values")
+            .action(ArgAction::SetTrue),
+        Arg::new("max_col_width")
+            .long("max-col-width")
+            .value_name("WIDTH")
+            .help("Cut table cells wider than WIDTH, ending them with …")
+            .num_args(1)
+            .value_parser(clap::builder::RangedU64ValueParser::<usize>::new().range(1..)),
+        Arg::new("wrap")
+            .long("wrap")
+            .help("Wrap table cells wider than --max-col-width onto more lines instead of cutting them")
+            .action(ArgAction::SetTrue)
+            .requires("max_col_width"),
+        Arg::new("html_thumbnails")
+            .long("html-thumbnails")
+            .help("With --output html, embed a thumbnail of the image at each row's path")
+            .action(ArgAction::SetTrue),
+        Arg::new("template")
+            .long("template")
+            .value_name("TEMPLATE")
+            .help(
+                "Print each row as TEMPLATE, e.g. '{id}\\t{name}\\t{tags|join:, }'. Filters: join[:SEP], first, \
+                 count, date[:FORMAT], upper, lower, default:TEXT",
+            )
+            .num_args(1)
+            .value_parser(|value: &str| value.parse::<Template>()),
+        Arg::new("jq")
+            .long("jq")
+            .value_name("FILTER")
+            .help("Run the JSON output through jq FILTER, e.g. '.[] | select(.star > 3) | .id'")
+            .num_args(1)
+            .conflicts_with("template"),
+        Arg::new("jq_raw")
+            .long("jq-raw")
+            .help("Print strings from --jq without quotes, like jq -r")
+            .action(ArgAction::SetTrue)
+            .requires("jq"),
+        Arg::new("jq_arg")
+            .long("jq-arg")
+            .value_names(["NAME", "VALUE"])
+            .help("Bind $NAME to VALUE in --jq, like jq --arg. Repeatable")
+            .num_args(2)
+            .action(ArgAction::Append)
+            .requires("jq"),
+        Arg::new("summary")
+            .long("summary")
+            .help("Print the number of items, their total size, and the time taken to stderr afterwards")
+            .action(ArgAction::SetTrue),
+    ]
+}
+
+/// Start the clock of the `--summary` footer; called once when the command starts.
+pub fn start_clock() {
+    STARTED.get_or_init(Instant::now);
+}
+
+/// Print the `--summary` footer to stderr when it was asked for, so piped output stays clean.
+///
+/// Without a number of items (the output wasn't rows) only the time is printed.
+pub fn summary(matches: &ArgMatches, items: Option<usize>, bytes: Option<u64>) {
+    if !matches.try_get_one::<bool>("summary").ok().flatten().copied().unwrap_or(false) {
+        return;
+    }
+    let elapsed = STARTED.get().map(Instant::elapsed).unwrap_or_default();
+    let Some(items) = items else {
+        eprintln!("Done in {:.2}s", elapsed.as_secs_f64());
+        return;
+    };
+    let noun = if items == 1 { "item" } else { "items" };
+    match bytes {
+        Some(bytes) => eprintln!("{} {}, {} in {:.2}s", items, noun, format_size(bytes), elapsed.as_secs_f64()),
+        None => eprintln!("{} {} in {:.2}s", items, noun, elapsed.as_secs_f64()),
+    }
+}
+
+/// Number of rows in `value` and the total of their `size` fields, each `None` when there are
+/// no rows (`value` is neither an array nor an object) or no row has a size
+pub fn count(value: &Value) -> (Option<usize>, Option<u64>) {
+    let rows = match value {
+        Value::Array(rows) => rows.as_slice(),
+        Value::Object(_) => std::slice::from_ref(value),
+        _ => return (None, None),
+    };
+    let sizes: Vec<u64> = rows.iter().filter_map(|row| row.get("size").and_then(Value::as_u64)).collect();
+    (Some(rows.len()), (!sizes.is_empty()).then(|| sizes.iter().sum()))
+}
+
+/// Whether structured output was asked for on the command line, for commands that print
+/// plain paths unless told otherwise.
+pub fn is_explicit(matches: &ArgMatches) -> bool {
+    ["output", "json", "fields", "template", "jq"]
+        .iter()
+        .any(|id| matches.try_contains_id(id).unwrap_or(false) && matches.value_source(id) == Some(ValueSource::CommandLine))
+}
+
+impl OutputOptions {
+    pub fn from_matches(matches: &ArgMatches) -> Self {
+        let json = matches.try_get_one::<bool>("json").ok().flatten().copied().unwrap_or(false);
+        let format = match matches.try_get_one::<String>("output").ok().flatten() {
+            _ if json => OutputFormat::Json,
+            Some(format) => OutputFormat::parse(format),
+            None => OutputFormat::Table,
+        };
+        let fields = matches
+            .try_get_one::<String>("fields")
+            .ok()
+            .flatten()
+            .map(|fields| {
+                fields
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|field| !field.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+        let thumbnails = matches
+            .try_get_one::<bool>("html_thumbnails")
+            .ok()
+            .flatten()
+            .copied()
+            .unwrap_or(false);
+        OutputOptions {
+            format,
+            fields,
+            thumbnails,
+            template: matches.try_get_one::<Template>("template").ok().flatten().cloned(),
+            tz: datetime::from_matches(matches),
+            sort: sort_keys(matches),
+            max_col_width: matches.try_get_one::<usize>("max_col_width").ok().flatten().copied(),
+            wrap: matches.try_get_one::<bool>("wrap").ok().flatten().copied().unwrap_or(false),
+            theme: Theme::from_matches(matches),
+            human: matches.try_get_one::<bool>("human").ok().flatten().copied().unwrap_or(false),
+            jq: Jq::from_matches(matches),
+        }
+    }
+}
+
+fn sort_keys(matches: &ArgMatches) -> Vec<SortKey> {
+    matches
+        .try_get_one::<Vec<SortKey>>("sort")
+        .ok()
+        .flatten()
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Whether `--sort` was given, for commands that stream plain paths unless told otherwise.
+pub fn is_sorted(matches: &ArgMatches) -> bool {
+    !sort_keys(matches).is_empty()
+}
+
+/// Render `value` (an object or an array of objects) with the options from `matches` and print it.
+pub fn output(value: &Value, matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let options = OutputOptions::from_matches(matches);
+    pager::print(&render(value, &options)?, matches)?;
+    let (items, bytes) = count(value);
+    summary(matches, items, bytes);
+    Ok(())
+}
+
+pub fn render(value: &Value, options: &OutputOptions) -> Result<String, Box<dyn std::error::Error>> {
+    let sorted;
+    let value = match value {
+        Value::Array(rows) if !options.sort.is_empty() => {
+            sorted = Value::Array(sort_rows(rows, &options.sort));
+            &sorted
+        }
+        _ => value,
+    };
+    if let Some(template) = &options.template {
+        return Ok(template.render_all(value, &options.tz));
+    }
+    if let Some(jq) = &options.jq {
+        return jq.run(&select_fields(value, &options.fields));
+    }
+    let humanized;
+    let value = match options.format {
+        OutputFormat::Table | OutputFormat::Csv | OutputFormat::Markdown | OutputFormat::Html if options.human => {
+            humanized = humanize(value, &options.tz);
+            &humanized
+        }
+        _ => value,
+    };
+    Ok(match options.format {
+        OutputFormat::Json => {
+            let value = select_fields(value, &options.fields);
+            format!("{}\n", serde_json::to_string_pretty(&value)?)
+        }
+        OutputFormat::Csv => match value {
+            Value::Array(rows) => render_csv(rows, &options.fields),
+            _ => render_csv(std::slice::from_ref(value), &options.fields),
+        },
+        OutputFormat::Table => render_table(value, options),
+        OutputFormat::Path => render_paths(value),
+        OutputFormat::Markdown => render_markdown(value, &options.fields),
+        OutputFormat::Html => render_html(value, &options.fields, options.thumbnails),
+        OutputFormat::Gron => {
+            let mut lines = String::new();
+            render_gron(&select_fields(value, &options.fields), "data", &mut lines);
+            lines
+        }
+    })
+}
+
+/// `value` with the `size`, `duration` and [`TIME_FIELDS`] of its rows formatted for reading, e.g.
+/// `2.1 MB`, `1:05` and `2024-06-01T12:00:00+02:00`.
+fn humanize(value: &Value, tz: &TimeZone) -> Value {
+    match value {
+        Value::Array(rows) => Value::Array(rows.iter().map(|row| humanize(row, tz)).collect()),
+        Value::Object(object) => Value::Object(
+            object
+                .iter()
+                .map(|(key, value)| {
+                    let value = match (key.as_str(), value) {
+                        ("size", Value::Number(number)) => match number.as_u64() {
+                            Some(bytes) => Value::String(format_size(bytes)),
+                            None => value.clone(),
+                        },
+                        ("duration", Value::Number(number)) => match number.as_f64() {
+                            Some(seconds) => Value::String(format_length(seconds)),
+                            None => value.clone(),
+                        },
+                        (key, Value::Number(number)) if TIME_FIELDS.contains(&key) => match number.as_i64() {
+                            Some(millis) => Value::String(tz.format_millis(millis)),
+                            None => value.clone(),
+                        },
+                        _ => value.clone(),
+                    };
+                    (key.clone(), value)
+                })
+                .collect(),
+        ),
+        _ => value.clone(),
+    }
+}
+
+/// Rows ordered by `keys`, keeping the original order of ties. Rows missing a field come last
+/// in either direction.
+fn sort_rows(rows: &[Value], keys: &[SortKey]) -> Vec<Value> {
+    let mut rows = rows.to_vec();
+    rows.sort_by(|a, b| {
+        keys.iter()
+            .map(|key| {
+                let lookup = |row: &Value| {
+                    key.path
+                        .iter()
+                        .try_fold(row, |value, field| value.get(field))
+                        .filter(|value| !value.is_null())
+                        .cloned()
+                };
+                match (lookup(a), lookup(b)) {
+                    (Some(a), Some(b)) if key.descending => compare(&b, &a),
+                    (Some(a), Some(b)) => compare(&a, &b),
+                    (Some(_), None) => Ordering::Less,
+                    (None, Some(_)) => Ordering::Greater,
+                    (None, None) => Ordering::Equal,
+                }
+            })
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    });
+    rows
+}
+
+/// Numbers, and strings holding numbers, compare by value so `9` sorts before `10`; anything
+/// else compares by its cell text.
+fn compare(a: &Value, b: &Value) -> Ordering {
+    let number = |value: &Value| match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(string) => string.trim().parse::<f64>().ok(),
+        _ => None,
+    };
+    match (number(a), number(b)) {
+        (Some(a), Some(b)) => a.total_cmp(&b),
+        _ => cell(a).cmp(&cell(b)),
+    }
+}
+
+/// Flatten `value` into one assignment per value, gron style: `data[0].tags[1] = "logo";`.
+pub fn render_gron(value: &Value, path: &str, lines: &mut String) {
+    match value {
+        Value::Array(values) => {
+            lines.push_str(&format!("{} = [];\n", path));
+            for (index, value) in values.iter().enumerate() {
+                render_gron(value, &format!("{}[{}]", path, index), lines);
+            }
+        }
+        Value::Object(object) => {
+            lines.push_str(&format!("{} = {{}};\n", path));
+            for (key, value) in object {
+                let mut chars = key.chars();
+                let identifier = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+                    && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+                let path = match identifier {
+                    true => format!("{}.{}", path, key),
+                    false => format!("{}[{}]", path, Value::String(key.clone())),
+                };
+                render_gron(value, &path, lines);
+            }
+        }
+        value => lines.push_str(&format!("{} = {};\n", path, value)),
+    }
+}
+
+/// Print the `path` field of every object (or a bare string), one per line.
+pub fn render_paths(value: &Value) -> String {
+    let rows = match value {
+        Value::Array(rows) => rows.as_slice(),
+        _ => std::slice::from_ref(value),
+    };
+    rows.iter()
+        .filter_map(|row| match row {
+            Value::String(path) => Some(path.as_str()),
+            _ => row.get("path").and_then(Value::as_str),
+        })
+        .map(|path| format!("{}\n", path))
+        .collect()
+}
+
+/// Keep only `fields` of every object, in the given order.
+fn select_fields(value: &Value, fields: &[String]) -> Value {
+    if fields.is_empty() {
+        return value.clone();
+    }
+    match value {
+        Value::Array(rows) => Value::Array(rows.iter().map(|row| select_fields(row, fields)).collect()),
+        Value::Object(object) => {
+            let mut selected = Map::new();
+            for field in fields {
+                if let Some(value) = object.get(field) {
+                    selected.insert(field.clone(), value.clone());
+                }
+            }
+            Value::Object(selected)
+        }
+        _ => value.clone(),
+    }
+}
+
+/// Render an array of objects as columns, a single object as key/value rows,
+/// and anything else as plain text.
+pub fn render_table(value: &Value, options: &OutputOptions) -> String {
+    match value {
+        Value::Array(rows) => render_object_array_table(rows, options),
+        Value::Object(object) => {
+            let rows: Vec<Vec<String>> = match options.fields.is_empty() {
+                true => object.iter().map(|(key, value)| vec![key.clone(), cell(value)]).collect(),
+                false => options
+                    .fields
+                    .iter()
+                    .filter_map(|field| object.get(field).map(|value| vec![field.clone(), cell(value)]))
+                    .collect(),
+            };
+            layout(&rows, options, |row, column, text| match column {
+                0 => options.theme.header(text),
+                _ => paint_field(&options.theme, &rows[row][0], text),
+            })
+        }
+        _ => format!("{}\n", cell(value)),
+    }
+}
+
+/// Render objects as an aligned table with a header row.
+pub fn render_object_array_table(rows: &[Value], options: &OutputOptions) -> String {
+    let columns = columns(rows, &options.fields);
+    if columns.is_empty() {
+        return String::new();
+    }
+    let table: Vec<Vec<String>> = std::iter::once(columns.clone())
+        .chain(
+            rows.iter()
+                .map(|row| columns.iter().map(|column| cell(&row[column.as_str()])).collect()),
+        )
+        .collect();
+    layout(&table, options, |row, column, text| match row {
+        0 => options.theme.header(text),
+        _ => paint_field(&options.theme, &columns[column], text),
+    })
+}
+
+/// Color the value of `field` by what it holds
+fn paint_field(theme: &Theme, field: &str, text: &str) -> String {
+    match field {
+        "star" => theme.stars(text),
+        "size" => theme.size(text),
+        _ => text.to_string(),
+    }
+}
+
+/// Align `rows` of cell texts into columns by their display width, so wide characters such as
+/// CJK take the two cells they fill on a terminal. `paint` colors the text of each cell given
+/// its row and column, after the widths are known.
+fn layout(rows: &[Vec<String>], options: &OutputOptions, paint: impl Fn(usize, usize, &str) -> String) -> String {
+    let rows: Vec<Vec<Vec<String>>> = rows
+        .iter()
+        .map(|row| row.iter().map(|text| fit(text, options.max_col_width, options.wrap)).collect())
+        .collect();
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|index| {
+            rows.iter()
+                .filter_map(|row| row.get(index))
+                .flatten()
+                .map(|line| line.width())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let mut table = String::new();
+    for (index, row) in rows.iter().enumerate() {
+        let height = row.iter().map(Vec::len).max().unwrap_or(1);
+        for line in 0..height {
+            let cells: Vec<String> = row
+                .iter()
+                .zip(&widths)
+                .enumerate()
+                .map(|(column, (lines, width))| {
+                    let text = lines.get(line).map(String::as_str).unwrap_or("");
+                    format!("{}{}", paint(index, column, text), " ".repeat(width - text.width()))
+                })
+                .collect();
+            table.push_str(cells.join("  ").trim_end());
+            table.push('\n');
+        }
+    }
+    table
+}
+
+/// Lines of a table cell no wider than `max_width`: the text cut short with `…`, or wrapped at
+/// spaces when `wrap` is set. Cutting goes by whole characters, never inside one.
+fn fit(text: &str, max_width: Option<usize>, wrap: bool) -> Vec<String> {
+    let Some(max_width) = max_width.filter(|max_width| text.width() > *max_width) else {
+        return vec![text.to_string()];
+    };
+    if !wrap {
+        let mut cut = String::new();
+        let mut width = 0;
+        for c in text.chars() {
+            let char_width = c.width().unwrap_or(0);
+            if width + char_width > max_width - 1 {
+                break;
+            }
+            cut.push(c);
+            width += char_width;
+        }
+        cut.push('…');
+        return vec![cut];
+    }
+
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.width() + 1 + word.width() <= max_width {
+            line.push(' ');
+            line.push_str(word);
+            continue;
+        }
+        if !line.is_empty() {
+            lines.push(std::mem::take(&mut line));
+        }
+        // Words longer than a line are split wherever the line is full
+        for c in word.chars() {
+            if line.width() + c.width().unwrap_or(0) > max_width && !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            line.push(c);
+        }
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Render objects as CSV with a header row.
+pub fn render_csv(rows: &[Value], fields: &[String]) -> String {
+    let columns = columns(rows, fields);
+    let mut csv = String::new();
+    let header: Vec<String> = columns.iter().map(|column| csv_escape(column)).collect();
+    csv.push_str(&header.join(","));
+    csv.push('\n');
+    for row in rows {
+        let line: Vec<String> = columns
+            .iter()
+            .map(|column| csv_escape(&cell(&row[column.as_str()])))
+            .collect();
+        csv.push_str(&line.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Header and cell texts of `value` laid out as a table: an array of objects by column, a single
+/// object as field/value rows, anything else as one cell.
+fn grid(value: &Value, fields: &[String]) -> (Vec<String>, Vec<Vec<String>>) {
+    match value {
+        Value::Array(rows) => {
+            let columns = columns(rows, fields);
+            let cells = rows
+                .iter()
+                .map(|row| columns.iter().map(|column| cell(&row[column.as_str()])).collect())
+                .collect();
+            (columns, cells)
+        }
+        Value::Object(object) => {
+            let cells = object
+                .iter()
+                .filter(|(key, _)| fields.is_empty() || fields.contains(key))
+                .map(|(key, value)| vec![key.clone(), cell(value)])
+                .collect();
+            (vec!["field".to_string(), "value".to_string()], cells)
+        }
+        _ => (vec!["value".to_string()], vec![vec![cell(value)]]),
+    }
+}
+
+/// Render as a GitHub-flavored Markdown table.
+pub fn render_markdown(value: &Value, fields: &[String]) -> String {
+    let (columns, cells) = grid(value, fields);
+    if columns.is_empty() {
+        return String::new();
+    }
+    let line = |row: &[String]| {
+        let escaped: Vec<String> = row
+            .iter()
+            .map(|value| value.replace('\\', "\\\\").replace('|', "\\|").replace("\r\n", "<br>").replace('\n', "<br>"))
+            .collect();
+        format!("| {} |\n", escaped.join(" | "))
+    };
+    let mut table = line(&columns);
+    table.push_str(&format!("|{}\n", " --- |".repeat(columns.len())));
+    for row in &cells {
+        table.push_str(&line(row));
+    }
+    table
+}
+
+/// Render as a standalone HTML page, optionally with a thumbnail of each row's `path`.
+pub fn render_html(value: &Value, fields: &[String], thumbnails: bool) -> String {
+    let (mut columns, cells) = grid(value, fields);
+    let mut cells: Vec<Vec<String>> = cells
+        .iter()
+        .map(|row| row.iter().map(|cell| html_escape(cell)).collect())
+        .collect();
+    if let (true, Value::Array(rows)) = (thumbnails, value) {
+        columns.insert(0, String::new());
+        for (row, row_cells) in rows.iter().zip(&mut cells) {
+            let image = row["path"].as_str().and_then(inline_thumbnail).unwrap_or_default();
+            row_cells.insert(0, image);
+        }
+    }
+
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>eagle-eye</title>\n<style>\n\
+         body { font-family: -apple-system, sans-serif; margin: 2em; }\n\
+         table { border-collapse: collapse; }\n\
+         th, td { border: 1px solid #ddd; padding: 4px 8px; text-align: left; vertical-align: top; }\n\
+         th { background: #f4f4f4; }\n\
+         </style>\n</head>\n<body>\n<table>\n",
+    );
+    html.push_str("<tr>");
+    for column in &columns {
+        html.push_str(&format!("<th>{}</th>", html_escape(column)));
+    }
+    html.push_str("</tr>\n");
+    for row in &cells {
+        html.push_str("<tr>");
+        for cell in row {
+            html.push_str(&format!("<td>{}</td>", cell));
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</table>\n</body>\n</html>\n");
+    html
+}
+
+/// An `<img>` holding a small PNG of the image at `path`, `None` when it can't be read
+fn inline_thumbnail(path: &str) -> Option<String> {
+    let image = image::open(path).ok()?.thumbnail(HTML_THUMBNAIL_SIZE, HTML_THUMBNAIL_SIZE);
+    let data = png_base64(&image).ok()?;
+    Some(format!("<img src=\"data:image/png;base64,{}\" alt=\"\">", data))
+}
+
+fn html_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Columns of a table: the selected fields, or the keys of every row in the order they first
+/// appear, so fields only some rows have still get a column.
+fn columns(rows: &[Value], fields: &[String]) -> Vec<String> {
+    if !fields.is_empty() {
+        return fields.to_vec();
+    }
+    let mut columns = Map::new();
+    for object in rows.iter().filter_map(Value::as_object) {
+        for key in object.keys() {
+            if !columns.contains_key(key) {
+                columns.insert(key.clone(), Value::Null);
+            }
+        }
+    }
+    columns.into_iter().map(|(key, _)| key).collect()
+}
+
+/// Text of a single table cell
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(string) => string.clone(),
+        Value::Array(values) if values.iter().all(|value| !value.is_object() && !value.is_array()) => {
+            values.iter().map(cell).collect::<Vec<_>>().join(", ")
+        }
+        _ => value.to_string(),
+    }
+}
+
+fn csv_escape(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,113 @@
+use crate::cli::system;
+use clap::{Arg, ArgAction, ArgMatches};
+use std::io::{ErrorKind, IsTerminal, Write};
+use std::process::{Child, Stdio};
+
+/// Pager used when `$PAGER` isn't set
+const DEFAULT_PAGER: &str = "less -FRX";
+
+pub fn arg() -> Arg {
+    Arg::new("no_pager")
+        .long("no-pager")
+        .help("Don't page output longer than the terminal")
+        .action(ArgAction::SetTrue)
+        .global(true)
+}
+
+/// Stdout that switches to `$PAGER` once more lines were written than fit on the terminal,
+/// like git does.
+///
+/// Output is held back until it reaches the terminal height, so short output is printed
+/// as usual. Nothing is paged when stdout isn't a terminal, with `--no-pager`, or when
+/// `$PAGER` is empty or `cat`.
+pub struct Pager {
+    /// Pager to start, `None` when output goes straight to stdout
+    command: Option<String>,
+    height: usize,
+    held: String,
+    lines: usize,
+    child: Option<Child>,
+    /// The pager was quit, so the rest of the output has nowhere to go
+    closed: bool,
+}
+
+impl Pager {
+    pub fn new(matches: &ArgMatches) -> Self {
+        let disabled = matches.try_get_one::<bool>("no_pager").ok().flatten().copied().unwrap_or(false);
+        let command = match std::env::var("PAGER") {
+            Ok(pager) if pager.trim().is_empty() || pager.trim() == "cat" => None,
+            Ok(pager) => Some(pager),
+            Err(_) => Some(DEFAULT_PAGER.to_string()),
+        };
+        let height = crossterm::terminal::size().map(|(_, rows)| rows as usize).unwrap_or(0);
+        let paged = !disabled && height > 0 && std::io::stdout().is_terminal();
+        Pager {
+            command: command.filter(|_| paged),
+            height,
+            held: String::new(),
+            lines: 0,
+            child: None,
+            closed: false,
+        }
+    }
+
+    /// Whether the pager was quit before all output was written, so producing more is pointless
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    pub fn write(&mut self, text: &str) -> std::io::Result<()> {
+        if self.closed {
+            return Ok(());
+        }
+        if let Some(child) = &mut self.child {
+            return match child.stdin.as_mut().map(|stdin| stdin.write_all(text.as_bytes())) {
+                Some(Err(error)) if error.kind() == ErrorKind::BrokenPipe => {
+                    self.closed = true;
+                    Ok(())
+                }
+                Some(result) => result,
+                None => Ok(()),
+            };
+        }
+        let Some(command) = &self.command else {
+            print!("{}", text);
+            return Ok(());
+        };
+
+        self.held.push_str(text);
+        self.lines += text.matches('\n').count();
+        // One line is left for the shell prompt after the output
+        if self.lines < self.height {
+            return Ok(());
+        }
+        match system::shell(command).stdin(Stdio::piped()).spawn() {
+            Ok(child) => self.child = Some(child),
+            Err(error) => {
+                eprintln!("Failed to run pager {}: {}", command, error);
+                self.command = None;
+            }
+        }
+        let held = std::mem::take(&mut self.held);
+        self.write(&held)
+    }
+
+    /// Print output that never filled the terminal, or wait until the pager is quit.
+    pub fn finish(mut self) -> std::io::Result<()> {
+        match self.child.take() {
+            Some(mut child) => {
+                drop(child.stdin.take());
+                child.wait()?;
+            }
+            None => print!("{}", self.held),
+        }
+        Ok(())
+    }
+}
+
+/// Print `text`, through the pager when it is longer than the terminal.
+pub fn print(text: &str, matches: &ArgMatches) -> std::io::Result<()> {
+    let mut pager = Pager::new(matches);
+    pager.write(text)?;
+    pager.finish()
+}
@@ -0,0 +1,155 @@
+use clap::{Arg, ArgAction, ArgMatches};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{self, ClearType};
+use crossterm::{cursor, execute, queue};
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use std::collections::BTreeSet;
+use std::io::Write;
+
+/// One line the user can pick
+pub struct Entry {
+    /// Printed when the entry is picked, usually an id
+    pub key: String,
+    /// Shown and matched against the query
+    pub text: String,
+}
+
+/// `--pick` argument of the commands that list items or folders.
+pub fn arg() -> Arg {
+    Arg::new("pick")
+        .long("pick")
+        .help("Pick results interactively with a fuzzy finder and print their ids")
+        .action(ArgAction::SetTrue)
+}
+
+pub fn is_requested(matches: &ArgMatches) -> bool {
+    matches.get_flag("pick")
+}
+
+/// Let the user fuzzy-filter `entries` and pick some, then print the picked keys.
+pub fn pick_and_print(entries: &[Entry]) -> Result<(), Box<dyn std::error::Error>> {
+    for index in pick(entries)? {
+        println!("{}", entries[index].key);
+    }
+    Ok(())
+}
+
+/// Run the fuzzy finder on the terminal and return the indices of the picked entries.
+///
+/// Type to filter, Up/Down (or Ctrl-P/Ctrl-N) to move, Tab to mark several entries,
+/// Enter to accept, Esc or Ctrl-C to cancel. The finder draws on stderr, so stdout
+/// stays clean for the next command in a pipeline.
+pub fn pick(entries: &[Entry]) -> Result<Vec<usize>, Box<dyn std::error::Error>> {
+    if entries.is_empty() {
+        return Err("Nothing to pick from".into());
+    }
+
+    let mut stderr = std::io::stderr();
+    terminal::enable_raw_mode()?;
+    execute!(stderr, terminal::EnterAlternateScreen, cursor::Hide)?;
+    let result = run(entries, &mut stderr);
+    execute!(stderr, cursor::Show, terminal::LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()?;
+
+    match result? {
+        Some(picked) => Ok(picked),
+        None => Err("Selection cancelled".into()),
+    }
+}
+
+fn run(entries: &[Entry], out: &mut impl Write) -> Result<Option<Vec<usize>>, Box<dyn std::error::Error>> {
+    let matcher = SkimMatcherV2::default();
+    let mut query = String::new();
+    let mut cursor = 0;
+    let mut marked: BTreeSet<usize> = BTreeSet::new();
+
+    loop {
+        let filtered = filter(&matcher, entries, &query);
+        cursor = cursor.min(filtered.len().saturating_sub(1));
+        draw(out, entries, &filtered, &query, cursor, &marked)?;
+
+        let Event::Key(KeyEvent { code, modifiers, kind, .. }) = event::read()? else {
+            continue;
+        };
+        if kind == KeyEventKind::Release {
+            continue;
+        }
+        let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+        match code {
+            KeyCode::Esc => return Ok(None),
+            KeyCode::Char('c') if ctrl => return Ok(None),
+            KeyCode::Enter => {
+                if !marked.is_empty() {
+                    return Ok(Some(marked.into_iter().collect()));
+                }
+                return Ok(filtered.get(cursor).map(|&index| vec![index]));
+            }
+            KeyCode::Up => cursor = cursor.saturating_sub(1),
+            KeyCode::Char('p') if ctrl => cursor = cursor.saturating_sub(1),
+            KeyCode::Down => cursor += 1,
+            KeyCode::Char('n') if ctrl => cursor += 1,
+            KeyCode::Tab => {
+                if let Some(&index) = filtered.get(cursor) {
+                    if !marked.remove(&index) {
+                        marked.insert(index);
+                    }
+                    cursor += 1;
+                }
+            }
+            KeyCode::Backspace => {
+                query.pop();
+            }
+            KeyCode::Char('u') if ctrl => query.clear(),
+            KeyCode::Char(c) if !ctrl => query.push(c),
+            _ => {}
+        }
+    }
+}
+
+/// Indices of the entries matching `query`, best match first
+fn filter(matcher: &SkimMatcherV2, entries: &[Entry], query: &str) -> Vec<usize> {
+    if query.is_empty() {
+        return (0..entries.len()).collect();
+    }
+    let mut scored: Vec<(i64, usize)> = entries
+        .iter()
+        .enumerate()
+        .filter_map(|(index, entry)| matcher.fuzzy_match(&entry.text, query).map(|score| (score, index)))
+        .collect();
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    scored.into_iter().map(|(_, index)| index).collect()
+}
+
+fn draw(
+    out: &mut impl Write,
+    entries: &[Entry],
+    filtered: &[usize],
+    query: &str,
+    cursor: usize,
+    marked: &BTreeSet<usize>,
+) -> std::io::Result<()> {
+    let (width, height) = terminal::size()?;
+    let width = width as usize;
+    let visible = (height as usize).saturating_sub(2).max(1);
+    // Scroll so the cursor stays on screen
+    let first = cursor.saturating_sub(visible - 1);
+
+    queue!(out, cursor::MoveTo(0, 0), terminal::Clear(ClearType::All))?;
+    queue!(out, Print(format!("> {}", query)), cursor::MoveToNextLine(1))?;
+    let status = format!("  {}/{} ({} marked)", filtered.len(), entries.len(), marked.len());
+    queue!(out, SetAttribute(Attribute::Dim), Print(status), SetAttribute(Attribute::Reset))?;
+
+    for (row, &index) in filtered.iter().enumerate().skip(first).take(visible) {
+        let mark = if marked.contains(&index) { '*' } else { ' ' };
+        let line: String = format!("{} {}", mark, entries[index].text).chars().take(width).collect();
+        queue!(out, cursor::MoveToNextLine(1))?;
+        if row == cursor {
+            queue!(out, SetAttribute(Attribute::Reverse), Print(line), SetAttribute(Attribute::Reset))?;
+        } else {
+            queue!(out, Print(line))?;
+        }
+    }
+    out.flush()
+}
@@ -0,0 +1,328 @@
+use crate::cli::folder::{folder_paths, resolve_folder};
+use crate::cli::item::list::{self, expr::Expr};
+use crate::cli::output;
+use crate::lib::client::EagleClient;
+use crate::lib::types::Child;
+use clap::{Arg, ArgMatches, Command};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+
+/// A changes file: the state the library should be brought to
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Changes {
+    /// Folder tree that should exist, as nested mappings of folder names
+    #[serde(default)]
+    folders: serde_yaml::Value,
+    #[serde(default)]
+    rename: Renames,
+    /// Tags that items matching a condition should have, or not have
+    #[serde(default)]
+    tags: Vec<TagRule>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Renames {
+    /// Folder (id, path or unique name) to its new name
+    #[serde(default)]
+    folders: BTreeMap<String, String>,
+    /// Tag to the tag replacing it on every item; merges when the new tag is already used
+    #[serde(default)]
+    tags: BTreeMap<String, String>,
+    /// Item id to its new name
+    #[serde(default)]
+    items: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TagRule {
+    /// Condition in the `item list --where` language
+    #[serde(rename = "where")]
+    condition: String,
+    #[serde(default)]
+    add: Vec<String>,
+    #[serde(default)]
+    remove: Vec<String>,
+}
+
+/// One change of an execution plan, in the order `apply` makes them
+pub enum Step {
+    RenameFolder {
+        id: String,
+        path: String,
+        name: String,
+    },
+    CreateFolder {
+        path: String,
+        name: String,
+        /// Path of the parent folder, `None` at the top level
+        parent: Option<String>,
+    },
+    UpdateItem {
+        id: String,
+        name: String,
+        new_name: Option<String>,
+        /// The full new tag list, when the tags change
+        tags: Option<Vec<String>>,
+        added: Vec<String>,
+        removed: Vec<String>,
+    },
+}
+
+impl Step {
+    fn row(&self) -> Value {
+        match self {
+            Step::RenameFolder { id, path, name } => {
+                json!({ "action": "rename", "kind": "folder", "target": path, "id": id, "change": name })
+            }
+            Step::CreateFolder { path, .. } => {
+                json!({ "action": "create", "kind": "folder", "target": path, "id": "", "change": "" })
+            }
+            Step::UpdateItem { id, name, .. } => {
+                json!({ "action": "update", "kind": "item", "target": name, "id": id, "change": self.item_change() })
+            }
+        }
+    }
+
+    fn item_change(&self) -> String {
+        let Step::UpdateItem { new_name, added, removed, .. } = self else {
+            return String::new();
+        };
+        let mut parts = Vec::new();
+        if let Some(new_name) = new_name {
+            parts.push(format!("name → {:?}", new_name));
+        }
+        let tags: Vec<String> = added
+            .iter()
+            .map(|tag| format!("+{}", tag))
+            .chain(removed.iter().map(|tag| format!("-{}", tag)))
+            .collect();
+        if !tags.is_empty() {
+            parts.push(format!("tags {}", tags.join(" ")));
+        }
+        parts.join("; ")
+    }
+
+    /// The step as a line of the plan
+    pub fn describe(&self) -> String {
+        match self {
+            Step::RenameFolder { path, name, .. } => format!("~ rename folder {} → {}", path, name),
+            Step::CreateFolder { path, .. } => format!("+ create folder {}", path),
+            Step::UpdateItem { id, name, .. } => format!("~ update item {} ({}): {}", id, name, self.item_change()),
+        }
+    }
+}
+
+pub fn file_arg() -> Arg {
+    Arg::new("file")
+        .short('f')
+        .long("file")
+        .value_name("FILE")
+        .help("YAML file declaring the folder tree, renames and tags the library should have")
+        .required(true)
+        .num_args(1)
+}
+
+pub fn build() -> Command {
+    Command::new("plan")
+        .about("Show the changes `apply` would make to bring the library to a changes file")
+        .long_about(
+            "Show the changes `apply` would make to bring the library to a changes file.\n\n\
+             Example changes.yaml:\n\n\
+             folders:            # created when missing\n\
+             \x20 Brand:\n\
+             \x20   Logos: {}\n\
+             \x20 Inbox: {}\n\
+             rename:\n\
+             \x20 folders: { Photos: Pictures }\n\
+             \x20 tags: { screen-shot: screenshot }\n\
+             \x20 items: { KBHG6KA0Y8B2D: Cover }\n\
+             tags:\n\
+             \x20 - where: \"name matches 'shot_*'\"\n\
+             \x20   add: [screenshot]\n\
+             \x20   remove: [inbox]",
+        )
+        .arg(file_arg())
+        .args(output::args())
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let changes = load(matches.get_one::<String>("file").unwrap())?;
+    let steps = compute(client, &changes).await?;
+    if output::is_explicit(matches) {
+        let rows: Vec<Value> = steps.iter().map(Step::row).collect();
+        return output::output(&Value::Array(rows), matches);
+    }
+    print(&steps);
+    Ok(())
+}
+
+pub fn load(path: &str) -> Result<Changes, Box<dyn std::error::Error>> {
+    let content = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    Ok(serde_yaml::from_str(&content).map_err(|e| format!("Invalid {}: {}", path, e))?)
+}
+
+/// Print the plan with a summary line
+pub fn print(steps: &[Step]) {
+    if steps.is_empty() {
+        println!("No changes. The library already matches.");
+        return;
+    }
+    for step in steps {
+        println!("{}", step.describe());
+    }
+    let count = |kind: fn(&Step) -> bool| steps.iter().filter(|step| kind(step)).count();
+    println!(
+        "\nPlan: {} folders to create, {} to rename, {} items to update.",
+        count(|step| matches!(step, Step::CreateFolder { .. })),
+        count(|step| matches!(step, Step::RenameFolder { .. })),
+        count(|step| matches!(step, Step::UpdateItem { .. })),
+    );
+}
+
+/// Diff the changes file against the live library
+pub async fn compute(client: &EagleClient, changes: &Changes) -> Result<Vec<Step>, Box<dyn std::error::Error>> {
+    let mut steps = Vec::new();
+
+    let folders = client.folder().list().await?.data;
+    let mut paths = folder_paths(&folders);
+    for (folder, new_name) in &changes.rename.folders {
+        let id = match resolve_folder(&folders, folder) {
+            Ok(id) => id,
+            Err(error) => {
+                // Already renamed by an earlier apply
+                let renamed = match folder.rsplit_once('/') {
+                    Some((parent, _)) => format!("{}/{}", parent, new_name),
+                    None => new_name.clone(),
+                };
+                if resolve_folder(&folders, &renamed).is_ok() {
+                    continue;
+                }
+                return Err(error);
+            }
+        };
+        let old_path = paths[&id].clone();
+        let new_path = match old_path.rsplit_once('/') {
+            Some((parent, _)) => format!("{}/{}", parent, new_name),
+            None => new_name.clone(),
+        };
+        if new_path == old_path {
+            continue;
+        }
+        // Later lookups see the folder, and everything below it, under the new name
+        for path in paths.values_mut() {
+            if *path == old_path {
+                *path = new_path.clone();
+            } else if let Some(rest) = path.strip_prefix(&format!("{}/", old_path)) {
+                *path = format!("{}/{}", new_path, rest);
+            }
+        }
+        steps.push(Step::RenameFolder {
+            id,
+            path: old_path,
+            name: new_name.clone(),
+        });
+    }
+
+    let mut existing: Vec<String> = paths.into_values().collect();
+    let mut wanted = Vec::new();
+    desired_folders(&changes.folders, None, &mut wanted)?;
+    for (path, name, parent) in wanted {
+        if !existing.contains(&path) {
+            existing.push(path.clone());
+            steps.push(Step::CreateFolder { path, name, parent });
+        }
+    }
+
+    let mut rules = Vec::new();
+    for (index, rule) in changes.tags.iter().enumerate() {
+        let expr = rule
+            .condition
+            .parse::<Expr>()
+            .map_err(|e| format!("tags rule {}: {}", index + 1, e))?;
+        rules.push((expr, rule));
+    }
+
+    let items = list::all_items(client).await?;
+    if let Some(id) = changes.rename.items.keys().find(|id| !items.iter().any(|item| &item.id == *id)) {
+        return Err(format!("No item with id {} to rename", id).into());
+    }
+    for item in items.iter().filter(|item| !item.is_deleted) {
+        let mut tags = item.tags.clone();
+        for (old, new) in &changes.rename.tags {
+            if let Some(position) = tags.iter().position(|tag| tag == old) {
+                if tags.contains(new) {
+                    tags.remove(position);
+                } else {
+                    tags[position] = new.clone();
+                }
+            }
+        }
+        let value = serde_json::to_value(item)?;
+        for (expr, rule) in &rules {
+            if !expr.matches(&value) {
+                continue;
+            }
+            tags.retain(|tag| !rule.remove.contains(tag));
+            for tag in &rule.add {
+                if !tags.contains(tag) {
+                    tags.push(tag.clone());
+                }
+            }
+        }
+
+        let new_name = changes.rename.items.get(&item.id).filter(|name| **name != item.name).cloned();
+        let tags_changed = tags != item.tags;
+        if new_name.is_none() && !tags_changed {
+            continue;
+        }
+        steps.push(Step::UpdateItem {
+            id: item.id.clone(),
+            name: item.name.clone(),
+            new_name,
+            added: tags.iter().filter(|tag| !item.tags.contains(tag)).cloned().collect(),
+            removed: item.tags.iter().filter(|tag| !tags.contains(tag)).cloned().collect(),
+            tags: tags_changed.then_some(tags),
+        });
+    }
+
+    Ok(steps)
+}
+
+/// Walk the nested `folders` mapping, parents first, as (path, name, parent path)
+fn desired_folders(
+    tree: &serde_yaml::Value,
+    parent: Option<&str>,
+    out: &mut Vec<(String, String, Option<String>)>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mapping = match tree {
+        serde_yaml::Value::Null => return Ok(()),
+        serde_yaml::Value::Mapping(mapping) => mapping,
+        _ => return Err("folders must be a mapping of folder names to their subfolders".into()),
+    };
+    for (name, children) in mapping {
+        let name = match name {
+            serde_yaml::Value::String(name) => name.clone(),
+            serde_yaml::Value::Number(number) => number.to_string(),
+            _ => return Err("folder names must be strings".into()),
+        };
+        let path = match parent {
+            Some(parent) => format!("{}/{}", parent, name),
+            None => name.clone(),
+        };
+        out.push((path.clone(), name, parent.map(String::from)));
+        desired_folders(children, Some(&path), out)?;
+    }
+    Ok(())
+}
+
+/// Folder ids by slash separated path, to find the parents of new folders
+pub fn path_ids(folders: &[Child]) -> HashMap<String, String> {
+    folder_paths(folders).into_iter().map(|(id, path)| (path, id)).collect()
+}
@@ -0,0 +1,114 @@
+use clap::{Arg, ArgAction, ArgMatches};
+use std::io::{IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+/// Width of the bar itself, in characters
+const BAR_WIDTH: usize = 30;
+
+/// Time between redraws, so fast batches don't spend their time on the terminal
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+pub fn arg() -> Arg {
+    Arg::new("quiet")
+        .long("quiet")
+        .help("Don't draw progress bars")
+        .action(ArgAction::SetTrue)
+        .global(true)
+}
+
+pub fn is_quiet(matches: &ArgMatches) -> bool {
+    matches.try_get_one::<bool>("quiet").ok().flatten().copied().unwrap_or(false)
+}
+
+/// Progress bar for batch commands, drawn on stderr only when it is a terminal and `--quiet`
+/// wasn't given, so piped output and logs stay clean.
+pub struct Progress {
+    total: usize,
+    done: usize,
+    label: String,
+    visible: bool,
+    started: Instant,
+    drawn: Option<Instant>,
+}
+
+impl Progress {
+    pub fn new(total: usize, label: &str, matches: &ArgMatches) -> Self {
+        Progress {
+            total,
+            done: 0,
+            label: label.to_string(),
+            visible: std::io::stderr().is_terminal() && !is_quiet(matches),
+            started: Instant::now(),
+            drawn: None,
+        }
+    }
+
+    /// A bar that is never drawn, for callers that report progress their own way
+    pub fn hidden() -> Self {
+        Progress {
+            total: 0,
+            done: 0,
+            label: String::new(),
+            visible: false,
+            started: Instant::now(),
+            drawn: None,
+        }
+    }
+
+    pub fn inc(&mut self) {
+        self.done += 1;
+        // The last step is always drawn so the bar never stops short of the end
+        if self.done < self.total && self.drawn.is_some_and(|drawn| drawn.elapsed() < REDRAW_INTERVAL) {
+            return;
+        }
+        self.draw();
+    }
+
+    /// Clear the bar, leaving the terminal ready for a summary line
+    pub fn finish(&mut self) {
+        if self.visible {
+            eprint!("\r\x1b[2K");
+            let _ = std::io::stderr().flush();
+            // Redraw on the next step, after whatever is printed now
+            self.drawn = None;
+        }
+    }
+
+    fn draw(&mut self) {
+        if !self.visible || self.total == 0 {
+            return;
+        }
+        self.drawn = Some(Instant::now());
+        let filled = BAR_WIDTH * self.done.min(self.total) / self.total;
+        // Rates over the first moments of a batch say little about the rest of it
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let speed = match elapsed >= 1.0 {
+            true => {
+                let rate = self.done as f64 / elapsed;
+                let left = self.total.saturating_sub(self.done) as f64 / rate;
+                format!("{:.1}/s  ETA {}", rate, clock(left))
+            }
+            false => "-/s  ETA -:--".to_string(),
+        };
+        eprint!(
+            "\r\x1b[2K[{}{}] {}/{} {}  {}",
+            "#".repeat(filled),
+            "-".repeat(BAR_WIDTH - filled),
+            self.done,
+            self.total,
+            self.label,
+            speed
+        );
+        let _ = std::io::stderr().flush();
+    }
+}
+
+/// Seconds as `m:ss`, or `h:mm:ss` from an hour on
+fn clock(seconds: f64) -> String {
+    let seconds = seconds.round() as u64;
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    match hours {
+        0 => format!("{}:{:02}", minutes, seconds),
+        _ => format!("{}:{:02}:{:02}", hours, minutes, seconds),
+    }
+}
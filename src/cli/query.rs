@@ -0,0 +1,49 @@
+use crate::cli::item::list;
+use crate::lib::client::EagleClient;
+use clap::{Arg, ArgMatches, Command};
+
+pub fn build() -> Command {
+    Command::new("query")
+        .about("List items matching a compact search, e.g. 'ext:png tag:logo star:>=4 size:<5MB'")
+        .long_about(
+            "List items matching a compact search, e.g.\n\n\
+             \x20 eagle-eye query 'ext:png tag:logo folder:\"Brand/2024\" star:>=4 size:<5MB'\n\n\
+             Terms are separated by spaces and all have to match; other words are keywords.\n\
+             \x20 ext:png,jpg      extension, one of these\n\
+             \x20 tag:logo         tag; repeat for more tags\n\
+             \x20 folder:Brand/2024  folder by path, name, or id\n\
+             \x20 name:shot_*      name glob\n\
+             \x20 url:dribbble     URL containing this\n\
+             \x20 star:>=4         also size:<5MB, width:>1000, height:<=800\n\
+             \x20 since:7d         also until:2024-06-01\n\
+             -ext: and -tag: exclude. The same search is accepted by `item list --query` and by every \
+             command that selects items; more `item list` arguments can follow the query.",
+        )
+        .arg(
+            Arg::new("query")
+                .value_name("QUERY")
+                .help("The search")
+                .required(true)
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            Arg::new("args")
+                .value_name("ARGS")
+                .help("More `item list` arguments, e.g. --json or --sort -star")
+                .num_args(0..)
+                .trailing_var_arg(true)
+                .allow_hyphen_values(true),
+        )
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let query = matches.get_one::<String>("query").unwrap();
+    let args: Vec<String> = ["--query".to_string(), query.clone()]
+        .into_iter()
+        .chain(matches.get_many::<String>("args").unwrap_or_default().cloned())
+        .collect();
+    list::run_with_args(client, &args).await
+}
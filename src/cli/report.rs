@@ -0,0 +1,97 @@
+use crate::cli::exit;
+use clap::{Arg, ArgMatches};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// What a batch command could not do, written with `--report` and retried with `--resume`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Report {
+    /// Subcommand path that wrote the report, e.g. `item move`
+    pub command: String,
+    /// When the command finished, in epoch milliseconds
+    pub time: i64,
+    pub succeeded: usize,
+    pub failures: Vec<Failure>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Failure {
+    /// Item id, file, or URL, whatever the command takes as input
+    pub target: String,
+    pub error: String,
+}
+
+/// Failures collected while a batch command runs
+#[derive(Debug, Default)]
+pub struct Failures(Vec<Failure>);
+
+impl Failures {
+    pub fn add(&mut self, target: &str, error: impl Display) {
+        self.0.push(Failure {
+            target: target.to_string(),
+            error: error.to_string(),
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// `--report` and `--resume` arguments for batch commands.
+pub fn args() -> Vec<Arg> {
+    vec![
+        Arg::new("report")
+            .long("report")
+            .value_name("FILE")
+            .help("Write the targets that failed, with their errors, to FILE as JSON")
+            .num_args(1),
+        Arg::new("resume")
+            .long("resume")
+            .value_name("FILE")
+            .help("Retry only the targets that failed in a --report FILE of an earlier run")
+            .num_args(1),
+    ]
+}
+
+/// Targets listed in the `--resume` report, `None` without `--resume`.
+pub fn resume_targets(matches: &ArgMatches) -> Result<Option<Vec<String>>, Box<dyn std::error::Error>> {
+    let Some(path) = matches.try_get_one::<String>("resume").ok().flatten() else {
+        return Ok(None);
+    };
+    let content = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let report: Report = serde_json::from_str(&content).map_err(|e| format!("Invalid report {}: {}", path, e))?;
+    eprintln!("Resuming {} failed targets of `{}`", report.failures.len(), report.command);
+    Ok(Some(report.failures.into_iter().map(|failure| failure.target).collect()))
+}
+
+/// Write the `--report` file when one was asked for, then end like [`exit::outcome`].
+///
+/// The report is written even when nothing failed, so `--resume FILE --report FILE`
+/// can be repeated until it comes back empty.
+pub fn finish(
+    matches: &ArgMatches,
+    command: &str,
+    succeeded: usize,
+    failures: Failures,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let failed = failures.len();
+    if let Some(path) = matches.get_one::<String>("report") {
+        let report = Report {
+            command: command.to_string(),
+            time: chrono::Utc::now().timestamp_millis(),
+            succeeded,
+            failures: failures.0,
+        };
+        std::fs::write(path, serde_json::to_string_pretty(&report)? + "\n")
+            .map_err(|e| format!("Failed to write {}: {}", path, e))?;
+        if failed > 0 {
+            eprintln!("Wrote {} failures to {}; rerun with --resume {} to retry them", failed, path, path);
+        }
+    }
+    exit::outcome(succeeded, failed)
+}
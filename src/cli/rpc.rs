@@ -0,0 +1,65 @@
+use crate::cli::handlers::{self, HandlerError, HandlerResult};
+use crate::lib::client::EagleClient;
+use clap::{ArgMatches, Command};
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+pub fn build() -> Command {
+    Command::new("rpc")
+        .about("Run commands read as JSON lines from stdin, answering each on stdout")
+        .long_about(
+            "Run commands read as JSON lines from stdin, answering each with one line on stdout, \
+             so a program can drive eagle-eye without starting a process per operation.\n\n\
+             Request:  {\"cmd\":\"item.update\",\"id\":\"X\",\"tags\":[\"a\"],\"ref\":1}\n\
+             Response: {\"ref\":1,\"ok\":true,\"data\":{...}} or {\"ref\":1,\"ok\":false,\"error\":\"...\"}\n\n\
+             `ref` is optional and echoed back. Commands, with the fields they take:\n\
+             \x20 item.list     keyword, tags, ext, folders, orderBy, limit, offset\n\
+             \x20 item.info     id\n\
+             \x20 item.search   q, where, limit\n\
+             \x20 item.update   id, name, tags, folders, annotation, url, star\n\
+             \x20 item.import   url or path, bookmark, name, tags, folder, annotation, website\n\
+             \x20 item.trash    ids\n\
+             \x20 folder.list\n\
+             \x20 library.info",
+        )
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    _matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (reference, result) = match serde_json::from_str::<Value>(&line) {
+            Ok(request) => (request["ref"].clone(), call(client, &request).await),
+            Err(error) => (Value::Null, Err(HandlerError::bad_request(format!("Invalid JSON: {}", error)))),
+        };
+        let mut response = match result {
+            Ok(data) => json!({ "ok": true, "data": data }),
+            Err(HandlerError(_, message)) => json!({ "ok": false, "error": message }),
+        };
+        if !reference.is_null() {
+            response["ref"] = reference;
+        }
+        println!("{}", response);
+    }
+    Ok(())
+}
+
+async fn call(client: &EagleClient, request: &Value) -> HandlerResult {
+    let command = request["cmd"].as_str().ok_or_else(|| HandlerError::bad_request("cmd is required"))?;
+    match command {
+        "item.list" => handlers::list_items(client, request).await,
+        "item.info" => handlers::item_info(client, request).await,
+        "item.search" => handlers::search(client, request).await,
+        "item.update" => handlers::update_item(client, request).await,
+        "item.import" => handlers::import(client, request).await,
+        "item.trash" => handlers::trash(client, request).await,
+        "folder.list" => handlers::folders(client).await,
+        "library.info" => handlers::passthrough(client, "library", "info", None).await,
+        _ => Err(HandlerError::bad_request(format!("Unknown cmd: {}", command))),
+    }
+}
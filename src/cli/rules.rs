@@ -0,0 +1,315 @@
+use crate::cli::folder::{folder_paths, resolve_folder};
+use crate::cli::item::list::expr::Expr;
+use crate::cli::item::list::filter::ItemFilter;
+use crate::cli::item::list::{self, PAGE_SIZE};
+use crate::cli::progress::Progress;
+use crate::cli::{exit, output, stats};
+use crate::lib::client::EagleClient;
+use crate::lib::library::LibraryDir;
+use crate::lib::types::{Child, GetItemListParams, ItemListData, Order, UpdateItemParams};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::time::Duration;
+
+const RULES_FILE_NAME: &str = "rules.toml";
+
+/// `rules.toml`: a list of `[[rule]]` tables, applied in order
+#[derive(Debug, Deserialize)]
+struct RulesFile {
+    #[serde(default, rename = "rule")]
+    rules: Vec<RuleEntry>,
+}
+
+/// A rule as written in the file
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleEntry {
+    name: Option<String>,
+    /// Condition in the `item list --where` language
+    when: String,
+    #[serde(default)]
+    add_tags: Vec<String>,
+    #[serde(default)]
+    remove_tags: Vec<String>,
+    /// Folder the item is moved to, replacing its current folders
+    move_to: Option<String>,
+    star: Option<u8>,
+}
+
+/// A rule with its condition parsed and its folder resolved
+struct Rule {
+    name: String,
+    when: Expr,
+    add_tags: Vec<String>,
+    remove_tags: Vec<String>,
+    folder_id: Option<String>,
+    star: Option<u8>,
+}
+
+/// What the rules change on one item
+struct Outcome {
+    rules: Vec<String>,
+    params: UpdateItemParams,
+    changes: Vec<String>,
+}
+
+pub fn build() -> Command {
+    Command::new("rules")
+        .about("Tag and file items automatically with the rules in rules.toml")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("apply")
+                .about("Apply the rules to the selected items")
+                .arg(
+                    Arg::new("rules")
+                        .long("rules")
+                        .value_name("FILE")
+                        .help("Rules file [default: rules.toml in the eagle-eye data directory]")
+                        .env("EAGLE_EYE_RULES")
+                        .num_args(1),
+                )
+                .arg(
+                    Arg::new("dry_run")
+                        .long("dry-run")
+                        .help("List the changes without updating any item")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("watch")
+                        .long("watch")
+                        .help("Keep running and apply the rules to items added to the library")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("interval")
+                        .long("interval")
+                        .value_name("SECONDS")
+                        .help("How often --watch checks for new items")
+                        .num_args(1)
+                        .default_value("5")
+                        .value_parser(clap::value_parser!(u64).range(1..)),
+                )
+                .arg(list::stdin_arg())
+                .args(list::query_args())
+                .args(list::filter::args())
+                // The rules are meant for the whole library, not the first page of it
+                .mut_arg("all", |arg| arg.default_value("true").hide(true))
+                .args(output::args()),
+        )
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match matches.subcommand() {
+        Some(("apply", apply_matches)) => apply(client, apply_matches).await,
+        _ => Ok(()),
+    }
+}
+
+async fn apply(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let dry_run = matches.get_flag("dry_run");
+    let folders = client.folder().list().await?.data;
+    let rules = load_rules(matches.get_one::<String>("rules").map(PathBuf::from), &folders)?;
+    let paths = folder_paths(&folders);
+
+    let library_data = client.library().info().await?.data;
+    let library = LibraryDir::new(&library_data.library.path);
+    let item_filter = ItemFilter::from_matches(matches)?;
+    let items = list::select_items(client, matches, &item_filter, &library).await?;
+
+    let outcomes: Vec<(&ItemListData, Outcome)> = items
+        .iter()
+        .filter_map(|item| evaluate(&rules, item, &paths).map(|outcome| (item, outcome)))
+        .collect();
+
+    if dry_run && !matches.get_flag("watch") {
+        let rows: Vec<Value> = outcomes.iter().map(|(item, outcome)| row(item, outcome)).collect();
+        return output::output(&Value::Array(rows), matches);
+    }
+
+    let mut progress = Progress::new(outcomes.len(), "applying", matches);
+    let (mut updated, mut failed) = (0, 0);
+    for (item, outcome) in &outcomes {
+        progress.inc();
+        if dry_run {
+            progress.finish();
+            println!("{}", describe(item, outcome));
+            updated += 1;
+            continue;
+        }
+        match client.item().update(&outcome.params).await {
+            Ok(_) => updated += 1,
+            Err(error) => {
+                progress.finish();
+                eprintln!("Failed to update {}: {}", item.id, error);
+                failed += 1;
+            }
+        }
+    }
+    progress.finish();
+    let action = if dry_run { "Would update" } else { "Updated" };
+    eprintln!("{} {} of {} items ({} failed)", action, updated, items.len(), failed);
+
+    if !matches.get_flag("watch") {
+        return exit::outcome(updated + items.len() - outcomes.len(), failed);
+    }
+
+    let interval = Duration::from_secs(*matches.get_one::<u64>("interval").unwrap());
+    let mut seen: HashSet<String> = list::all_items(client).await?.into_iter().map(|item| item.id).collect();
+    eprintln!("Watching for new items, press Ctrl-C to stop");
+    loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => break,
+            _ = tokio::time::sleep(interval) => {}
+        }
+        let params = GetItemListParams {
+            limit: Some(PAGE_SIZE),
+            order_by: Some(Order::CREATEDATEDESC),
+            ..GetItemListParams::new()
+        };
+        let newest = match client.item().list(params).await {
+            Ok(result) => result.data,
+            Err(error) => {
+                eprintln!("Failed to list items: {}", error);
+                continue;
+            }
+        };
+        for item in newest {
+            if !seen.insert(item.id.clone()) || !item_filter.matches(&item) {
+                continue;
+            }
+            let Some(outcome) = evaluate(&rules, &item, &paths) else {
+                continue;
+            };
+            if !dry_run {
+                if let Err(error) = client.item().update(&outcome.params).await {
+                    eprintln!("Failed to update {}: {}", item.id, error);
+                    continue;
+                }
+            }
+            println!("{}", describe(&item, &outcome));
+        }
+    }
+    Ok(())
+}
+
+/// Read and check the rules file, resolving folders up front so a typo fails before any item changes
+fn load_rules(
+    path: Option<PathBuf>,
+    folders: &[Child],
+) -> Result<Vec<Rule>, Box<dyn std::error::Error>> {
+    let path = match path {
+        Some(path) => path,
+        None => stats::data_dir()
+            .ok_or("Can't locate the data directory, set EAGLE_EYE_DATA_DIR or pass --rules")?
+            .join(RULES_FILE_NAME),
+    };
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let file: RulesFile = toml::from_str(&content).map_err(|e| format!("Invalid {}: {}", path.display(), e))?;
+    if file.rules.is_empty() {
+        return Err(format!("No [[rule]] in {}", path.display()).into());
+    }
+
+    let mut rules = Vec::new();
+    for (index, entry) in file.rules.into_iter().enumerate() {
+        let name = entry.name.unwrap_or_else(|| format!("rule {}", index + 1));
+        let when = entry.when.parse::<Expr>().map_err(|e| format!("{}: {}", name, e))?;
+        if entry.star.is_some_and(|star| star > 5) {
+            return Err(format!("{}: star must be between 0 and 5", name).into());
+        }
+        let folder_id = match &entry.move_to {
+            Some(folder) => Some(resolve_folder(folders, folder).map_err(|e| format!("{}: {}", name, e))?),
+            None => None,
+        };
+        if entry.add_tags.is_empty() && entry.remove_tags.is_empty() && folder_id.is_none() && entry.star.is_none() {
+            return Err(format!("{}: no action; set add_tags, remove_tags, move_to or star", name).into());
+        }
+        rules.push(Rule {
+            name,
+            when,
+            add_tags: entry.add_tags,
+            remove_tags: entry.remove_tags,
+            folder_id,
+            star: entry.star,
+        });
+    }
+    Ok(rules)
+}
+
+/// Run the rules in order, each seeing the changes of the ones before it. `None` when nothing changes.
+fn evaluate(rules: &[Rule], item: &ItemListData, paths: &HashMap<String, String>) -> Option<Outcome> {
+    let mut tags = item.tags.clone();
+    let mut folders = item.folders.clone().unwrap_or_default();
+    let mut star = item.star.unwrap_or(0);
+    let mut fired = Vec::new();
+
+    for rule in rules {
+        let mut value = serde_json::to_value(item).unwrap_or_default();
+        value["tags"] = json!(tags);
+        value["folders"] = json!(folders);
+        value["star"] = json!(star);
+        if !rule.when.matches(&value) {
+            continue;
+        }
+        fired.push(rule.name.clone());
+        tags.retain(|tag| !rule.remove_tags.contains(tag));
+        for tag in &rule.add_tags {
+            if !tags.contains(tag) {
+                tags.push(tag.clone());
+            }
+        }
+        if let Some(folder_id) = &rule.folder_id {
+            folders = vec![folder_id.clone()];
+        }
+        if let Some(rule_star) = rule.star {
+            star = rule_star;
+        }
+    }
+
+    let mut params = UpdateItemParams {
+        id: item.id.clone(),
+        ..Default::default()
+    };
+    let mut changes = Vec::new();
+    if tags != item.tags {
+        changes.extend(tags.iter().filter(|tag| !item.tags.contains(tag)).map(|tag| format!("+{}", tag)));
+        changes.extend(item.tags.iter().filter(|tag| !tags.contains(tag)).map(|tag| format!("-{}", tag)));
+        params.tags = Some(tags);
+    }
+    if folders != item.folders.clone().unwrap_or_default() {
+        let names: Vec<&str> = folders.iter().map(|id| paths.get(id).map_or(id.as_str(), String::as_str)).collect();
+        changes.push(format!("folder {}", names.join(", ")));
+        params.folders = Some(folders);
+    }
+    if star != item.star.unwrap_or(0) {
+        changes.push(format!("star {}", star));
+        params.star = Some(star);
+    }
+
+    (!changes.is_empty()).then_some(Outcome {
+        rules: fired,
+        params,
+        changes,
+    })
+}
+
+fn row(item: &ItemListData, outcome: &Outcome) -> Value {
+    json!({
+        "id": item.id,
+        "name": item.name,
+        "rules": outcome.rules.join(", "),
+        "changes": outcome.changes.join(" "),
+    })
+}
+
+fn describe(item: &ItemListData, outcome: &Outcome) -> String {
+    format!("{} {} [{}]: {}", item.id, item.name, outcome.rules.join(", "), outcome.changes.join(" "))
+}
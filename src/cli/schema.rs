@@ -0,0 +1,80 @@
+use crate::lib::types::{
+    ApplicationData, Child, CreateFolderData, Folder, ItemInfoData, ItemListData, LibraryInfoData,
+    LibraryTags, RecentFolderListData, RenameFolderData, SmartFolders, TagsGroups, UpdateFolderData,
+};
+use clap::builder::PossibleValuesParser;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use schemars::schema::RootSchema;
+use schemars::schema_for;
+use serde_json::{Map, Value};
+
+/// A type's name, where Eagle returns it, and its schema
+type Entry = (&'static str, &'static str, fn() -> RootSchema);
+
+/// The types there are schemas for
+const TYPES: [Entry; 13] = [
+    ("ApplicationData", "/api/application/info", || schema_for!(ApplicationData)),
+    ("Child", "/api/folder/list", || schema_for!(Child)),
+    ("CreateFolderData", "/api/folder/create", || schema_for!(CreateFolderData)),
+    ("Folder", "folders of /api/library/info", || schema_for!(Folder)),
+    ("ItemInfoData", "/api/item/info", || schema_for!(ItemInfoData)),
+    ("ItemListData", "/api/item/list", || schema_for!(ItemListData)),
+    ("LibraryInfoData", "/api/library/info", || schema_for!(LibraryInfoData)),
+    ("LibraryTags", "tags.json in the library", || schema_for!(LibraryTags)),
+    ("RecentFolderListData", "/api/folder/listRecent", || schema_for!(RecentFolderListData)),
+    ("RenameFolderData", "/api/folder/rename", || schema_for!(RenameFolderData)),
+    ("SmartFolders", "smartFolders of /api/library/info", || schema_for!(SmartFolders)),
+    ("TagsGroups", "tagsGroups of /api/library/info", || schema_for!(TagsGroups)),
+    ("UpdateFolderData", "/api/folder/update", || schema_for!(UpdateFolderData)),
+];
+
+pub fn build() -> Command {
+    Command::new("schema")
+        .about("Print the JSON Schema of a response type, for validating output or generating clients")
+        .long_about(
+            "Print the JSON Schema of a response type, for validating output or generating clients.\n\n\
+             Without TYPE, prints one object with the schemas of all types by name.",
+        )
+        .arg(
+            Arg::new("type")
+                .value_name("TYPE")
+                .help("Type to print the schema of, e.g. ItemInfoData; case doesn't matter")
+                .value_parser(PossibleValuesParser::new(TYPES.map(|(name, _, _)| name)))
+                .ignore_case(true)
+                .conflicts_with("list"),
+        )
+        .arg(
+            Arg::new("list")
+                .long("list")
+                .help("List the types and where Eagle returns them")
+                .action(ArgAction::SetTrue),
+        )
+}
+
+fn lookup(name: &str) -> Option<RootSchema> {
+    TYPES
+        .iter()
+        .find(|(type_name, _, _)| type_name.eq_ignore_ascii_case(name))
+        .map(|(_, _, schema)| schema())
+}
+
+pub async fn execute(matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    if matches.get_flag("list") {
+        for (name, source, _) in TYPES {
+            println!("{}\t{}", name, source);
+        }
+        return Ok(());
+    }
+    let schema = match matches.get_one::<String>("type") {
+        Some(name) => serde_json::to_value(lookup(name).ok_or_else(|| format!("No schema for {}", name))?)?,
+        None => {
+            let mut schemas = Map::new();
+            for (name, _, schema) in TYPES {
+                schemas.insert(name.to_string(), serde_json::to_value(schema())?);
+            }
+            Value::Object(schemas)
+        }
+    };
+    println!("{}", serde_json::to_string_pretty(&schema)?);
+    Ok(())
+}
@@ -0,0 +1,143 @@
+use crate::cli::item::list;
+use crate::cli::{output, stats, system};
+use crate::lib::client::EagleClient;
+use clap::{Arg, ArgMatches, Command};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::PathBuf;
+
+const SEARCHES_FILE_NAME: &str = "searches.json";
+
+/// A named `item list` query, kept as its arguments so filters, `--jq`, and output
+/// preferences all come back as they were saved
+#[derive(Debug, Serialize, Deserialize)]
+struct Search {
+    name: String,
+    args: Vec<String>,
+}
+
+fn args_arg(help: &'static str) -> Arg {
+    Arg::new("args")
+        .value_name("ARGS")
+        .help(help)
+        .num_args(0..)
+        .trailing_var_arg(true)
+        .allow_hyphen_values(true)
+}
+
+pub fn build() -> Command {
+    Command::new("search")
+        .about("Save item list queries under a name and run them again")
+        .arg_required_else_help(true)
+        .subcommand(
+            Command::new("save")
+                .about("Save the arguments of an item list query, e.g. `search save logos --tags logo --star 4 --json`")
+                .arg(Arg::new("name").value_name("NAME").help("Name of the search").required(true))
+                .arg(args_arg("Arguments of `item list`: filters, --jq, output options")),
+        )
+        .subcommand(
+            Command::new("run")
+                .about("Run a saved search")
+                .arg(Arg::new("name").value_name("NAME").help("Name of the search").required(true))
+                .arg(args_arg("More `item list` arguments, added after the saved ones")),
+        )
+        .subcommand(Command::new("list").about("List saved searches").args(output::args()))
+        .subcommand(
+            Command::new("remove")
+                .about("Remove a saved search")
+                .arg(Arg::new("name").value_name("NAME").help("Name of the search").required(true)),
+        )
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut searches = read_searches()?;
+
+    match matches.subcommand() {
+        Some(("save", save_matches)) => {
+            let name = save_matches.get_one::<String>("name").unwrap();
+            let args: Vec<String> = save_matches.get_many::<String>("args").unwrap_or_default().cloned().collect();
+            // Parse now so a typo fails when saving rather than on every run
+            list::parse_args(&args)?;
+            let updated = searches.iter().any(|search| &search.name == name);
+            searches.retain(|search| &search.name != name);
+            searches.push(Search { name: name.clone(), args });
+            searches.sort_by(|a, b| a.name.cmp(&b.name));
+            write_searches(&searches)?;
+            match updated {
+                true => println!("Updated search {}", name),
+                false => println!("Saved search {}", name),
+            }
+        }
+        Some(("run", run_matches)) => {
+            let name = run_matches.get_one::<String>("name").unwrap();
+            let search = searches
+                .iter()
+                .find(|search| &search.name == name)
+                .ok_or_else(|| format!("No saved search named {} (see `search list`)", name))?;
+            let args: Vec<String> = search
+                .args
+                .iter()
+                .chain(run_matches.get_many::<String>("args").unwrap_or_default())
+                .cloned()
+                .collect();
+            list::run_with_args(client, &args).await?;
+        }
+        Some(("list", list_matches)) => {
+            let rows: Vec<Value> = searches
+                .iter()
+                .map(|search| {
+                    let args: Vec<String> = search.args.iter().map(|arg| quote(arg)).collect();
+                    json!({ "name": search.name, "args": args.join(" ") })
+                })
+                .collect();
+            output::output(&Value::Array(rows), list_matches)?;
+        }
+        Some(("remove", remove_matches)) => {
+            let name = remove_matches.get_one::<String>("name").unwrap();
+            let count = searches.len();
+            searches.retain(|search| &search.name != name);
+            if searches.len() == count {
+                return Err(format!("No saved search named {}", name).into());
+            }
+            write_searches(&searches)?;
+            println!("Removed search {}", name);
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// An argument as it would be typed, quoted only when it needs to be
+fn quote(arg: &str) -> String {
+    match arg.is_empty() || arg.contains(|c: char| c.is_whitespace() || "'\"$`\\|&;<>()*?[]{}!#".contains(c)) {
+        true => system::shell_quote(arg),
+        false => arg.to_string(),
+    }
+}
+
+fn searches_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(stats::data_dir()
+        .ok_or("Can't locate the data directory, set EAGLE_EYE_DATA_DIR")?
+        .join(SEARCHES_FILE_NAME))
+}
+
+fn read_searches() -> Result<Vec<Search>, Box<dyn std::error::Error>> {
+    match fs::read(searches_path()?) {
+        Ok(data) => Ok(serde_json::from_slice(&data)?),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(error) => Err(error.into()),
+    }
+}
+
+fn write_searches(searches: &[Search]) -> Result<(), Box<dyn std::error::Error>> {
+    let path = searches_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_vec_pretty(searches)?)?;
+    Ok(())
+}
@@ -0,0 +1,165 @@
+use crate::cli::http::HttpClient;
+use crate::cli::item::{export, list};
+use crate::cli::output;
+use crate::lib::client::EagleClient;
+use crate::lib::library::LibraryDir;
+use crate::lib::types::{ItemListData, UpdateItemParams};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use serde_json::Value;
+
+/// Where the companion plugin serves the selection unless told otherwise
+const DEFAULT_BRIDGE: &str = "http://localhost:41596/selection";
+
+pub fn build() -> Command {
+    Command::new("selection")
+        .about("Act on the items selected in Eagle right now")
+        .long_about(
+            "Act on the items selected in Eagle right now.\n\n\
+             Eagle's API can't see the selection, so this asks a companion Eagle plugin for it: a GET \
+             on the bridge URL has to answer with the ids of the selected items as a JSON array, or \
+             with items that have an id, optionally under `data`, as eagle.item.getSelected() gives them.",
+        )
+        .subcommand_required(true)
+        .arg(
+            Arg::new("bridge")
+                .long("bridge")
+                .value_name("URL")
+                .help("URL the companion plugin serves the selection on")
+                .env("EAGLE_EYE_SELECTION_URL")
+                .default_value(DEFAULT_BRIDGE)
+                .global(true),
+        )
+        .subcommand(
+            Command::new("list")
+                .about("List the selected items, as paths unless an output format is given")
+                .args(output::args()),
+        )
+        .subcommand(
+            Command::new("tag")
+                .about("Add tags to the selected items")
+                .arg(
+                    Arg::new("tags")
+                        .value_name("TAG")
+                        .help("Tags to add")
+                        .required(true)
+                        .num_args(1..),
+                )
+                .arg(
+                    Arg::new("remove")
+                        .long("remove")
+                        .help("Remove the tags instead")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(without_query_args(export::build().about("Copy the selected items out of the library")))
+}
+
+/// Hide the arguments that pick items another way from a command reused for the selection
+fn without_query_args(command: Command) -> Command {
+    let names: Vec<String> = list::query_args()
+        .iter()
+        .chain([&list::stdin_arg()])
+        .map(|arg| arg.get_id().to_string())
+        .collect();
+    names.iter().fold(command, |command, name| command.mut_arg(name, |arg| arg.hide(true)))
+}
+
+/// Whether `matches` belong to a `selection` subcommand, which then acts on the selection
+pub fn is_requested(matches: &ArgMatches) -> bool {
+    matches.try_get_one::<String>("bridge").ok().flatten().is_some()
+}
+
+/// Ids of the items selected in Eagle, from the companion plugin
+pub async fn selected_ids(matches: &ArgMatches) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let bridge = matches.get_one::<String>("bridge").unwrap();
+    let response = HttpClient::new().get(bridge, &[], None).await.map_err(|e| {
+        format!("Couldn't get the selection from {}: {}; is the companion plugin running?", bridge, e)
+    })?;
+    let value: Value = serde_json::from_slice(&response.bytes)
+        .map_err(|e| format!("{} returned invalid JSON: {}", bridge, e))?;
+    ids_of(&value).ok_or_else(|| format!("{} didn't return a list of items or ids", bridge).into())
+}
+
+fn ids_of(value: &Value) -> Option<Vec<String>> {
+    let entries = value.get("data").unwrap_or(value).as_array()?;
+    entries
+        .iter()
+        .map(|entry| match entry {
+            Value::String(id) => Some(id.clone()),
+            entry => entry["id"].as_str().map(str::to_string),
+        })
+        .collect()
+}
+
+async fn selected_items(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(Vec<ItemListData>, LibraryDir), Box<dyn std::error::Error>> {
+    let ids = selected_ids(matches).await?;
+    let library = LibraryDir::new(&client.library().info().await?.data.library.path);
+    let items = ids.iter().map(|id| library.item(id)).collect::<Result<Vec<_>, _>>()?;
+    Ok((items, library))
+}
+
+async fn list(client: &EagleClient, matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let (items, library) = selected_items(client, matches).await?;
+    if output::is_explicit(matches) {
+        return output::output(&Value::Array(list::item_rows(&items, &library, false)), matches);
+    }
+    for item in &items {
+        println!("{}", list::item_path(&library, item, false).display());
+    }
+    Ok(())
+}
+
+async fn tag(client: &EagleClient, matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let tags: Vec<&String> = matches.get_many::<String>("tags").unwrap().collect();
+    let remove = matches.get_flag("remove");
+    let (items, _) = selected_items(client, matches).await?;
+    if items.is_empty() {
+        eprintln!("Nothing is selected in Eagle");
+        return Ok(());
+    }
+
+    let mut changed = 0;
+    for item in &items {
+        let new_tags: Vec<String> = match remove {
+            true => item.tags.iter().filter(|tag| !tags.contains(tag)).cloned().collect(),
+            false => item.tags.iter().chain(tags.iter().copied().filter(|tag| !item.tags.contains(tag))).cloned().collect(),
+        };
+        if new_tags == item.tags {
+            continue;
+        }
+        let params = UpdateItemParams {
+            id: item.id.clone(),
+            tags: Some(new_tags),
+            ..Default::default()
+        };
+        client.item().update(&params).await?;
+        changed += 1;
+    }
+    let action = if remove { "Untagged" } else { "Tagged" };
+    println!("{} {} of {} selected items", action, changed, items.len());
+    Ok(())
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match matches.subcommand() {
+        Some(("list", list_matches)) => {
+            list(client, list_matches).await?;
+        },
+        Some(("tag", tag_matches)) => {
+            tag(client, tag_matches).await?;
+        },
+        Some(("export", export_matches)) => {
+            export::execute(client, export_matches).await?;
+        },
+        _ => {
+            println!("No subcommand was used");
+        },
+    }
+    Ok(())
+}
@@ -0,0 +1,286 @@
+use crate::cli::datetime;
+use crate::cli::handlers::{self, HandlerError, HandlerResult};
+use crate::lib::client::EagleClient;
+use clap::{Arg, ArgMatches, Command};
+use hyper::header::{HeaderValue, AUTHORIZATION, CONTENT_TYPE, ORIGIN};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use ring::digest::{digest, SHA256};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+pub fn build() -> Command {
+    Command::new("serve")
+        .about("Serve a small REST API in front of Eagle for dashboards and scripts")
+        .long_about(
+            "Serve a small REST API in front of Eagle for dashboards and scripts.\n\n\
+             GET  /health\n\
+             GET  /items?keyword=&tags=&ext=&folders=&orderBy=&limit=&offset=\n\
+             GET  /items/{id}\n\
+             PATCH /items/{id}        {name, tags, folders, annotation, url, star}\n\
+             GET  /search?q=&where=   text and `item list --where` search over every item\n\
+             POST /import             {url | path, bookmark, name, tags, folder, annotation, website}\n\
+             GET  /folders\n\n\
+             Responses are {\"data\": ...} or {\"error\": \"...\"}.\n\n\
+             Browser pages can only call the API from an origin given with --allow-origin. \
+             --token is required with --eagle-token, and when listening beyond this machine.",
+        )
+        .arg(
+            Arg::new("listen")
+                .long("listen")
+                .value_name("ADDR")
+                .help("Address to listen on")
+                .num_args(1)
+                .default_value("127.0.0.1:8787")
+                .value_parser(clap::value_parser!(SocketAddr)),
+        )
+        .arg(
+            Arg::new("token")
+                .long("token")
+                .value_name("TOKEN")
+                .help("Require clients to send Authorization: Bearer TOKEN. Required with --eagle-token or a non-loopback --listen")
+                .env("EAGLE_EYE_SERVE_TOKEN")
+                .hide_env_values(true)
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("eagle_token")
+                .long("eagle-token")
+                .value_name("TOKEN")
+                .help("Eagle API token added to every request sent to Eagle, so clients never see it")
+                .env("EAGLE_API_TOKEN")
+                .hide_env_values(true)
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("allow_origin")
+                .long("allow-origin")
+                .value_name("ORIGIN")
+                .help("Let browser pages from ORIGIN call the API, e.g. http://localhost:3000, or * for any page. Without it, requests from browsers are refused")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("cache_ttl")
+                .long("cache-ttl")
+                .value_name("DURATION")
+                .help("How long GET responses are reused, e.g. 5s; 0s turns caching off. Writes clear the cache")
+                .num_args(1)
+                .default_value("5s")
+                .value_parser(|value: &str| {
+                    datetime::parse_duration_millis(value)
+                        .map(|millis| Duration::from_millis(millis.max(0) as u64))
+                        .ok_or_else(|| format!("invalid duration: {} (use 5s, 1m, ...)", value))
+                }),
+        )
+}
+
+/// State shared by every connection
+struct Proxy {
+    client: EagleClient,
+    token: Option<String>,
+    /// Origin browser pages may call from; `None` refuses every request sent by a browser page
+    allow_origin: Option<HeaderValue>,
+    cache_ttl: Duration,
+    cache: Mutex<HashMap<String, (Instant, Value)>>,
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let address = *matches.get_one::<SocketAddr>("listen").unwrap();
+    let token = matches.get_one::<String>("token").cloned();
+    if token.is_none() {
+        if matches.get_one::<String>("eagle_token").is_some() {
+            return Err("--eagle-token needs --token, or anything that can reach the server would use it".into());
+        }
+        if !address.ip().is_loopback() {
+            return Err(format!("Listening on {} needs --token, since other machines can reach it", address).into());
+        }
+    }
+    let mut client = client.clone();
+    if let Some(token) = matches.get_one::<String>("eagle_token") {
+        client = client.with_token(token);
+    }
+    let proxy = Arc::new(Proxy {
+        client,
+        token,
+        allow_origin: matches
+            .get_one::<String>("allow_origin")
+            .map(|origin| HeaderValue::from_str(origin))
+            .transpose()?,
+        cache_ttl: *matches.get_one::<Duration>("cache_ttl").unwrap(),
+        cache: Mutex::new(HashMap::new()),
+    });
+
+    let make_service = make_service_fn(move |_| {
+        let proxy = proxy.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let proxy = proxy.clone();
+                async move { Ok::<_, Infallible>(proxy.respond(request).await) }
+            }))
+        }
+    });
+    let server = Server::try_bind(&address)?.serve(make_service);
+    eprintln!("Serving on http://{}, press Ctrl-C to stop", server.local_addr());
+    server
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+    Ok(())
+}
+
+impl Proxy {
+    async fn respond(&self, request: Request<Body>) -> Response<Body> {
+        let (status, body) = match self.handle(request).await {
+            Ok(data) => (StatusCode::OK, json!({ "data": data })),
+            Err(HandlerError(status, message)) => (status, json!({ "error": message })),
+        };
+        let mut response = Response::new(Body::from(body.to_string()));
+        *response.status_mut() = status;
+        response.headers_mut().insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        self.add_cors(&mut response);
+        response
+    }
+
+    fn add_cors(&self, response: &mut Response<Body>) {
+        let Some(allow_origin) = &self.allow_origin else {
+            return;
+        };
+        let headers = response.headers_mut();
+        headers.insert("access-control-allow-origin", allow_origin.clone());
+        headers.insert("access-control-allow-methods", HeaderValue::from_static("GET, POST, PATCH, OPTIONS"));
+        headers.insert("access-control-allow-headers", HeaderValue::from_static("authorization, content-type"));
+    }
+
+    /// Whether a browser page from `origin` may call the API
+    fn allows(&self, origin: &HeaderValue) -> bool {
+        self.allow_origin
+            .as_ref()
+            .is_some_and(|allowed| allowed == "*" || allowed == origin)
+    }
+
+    async fn handle(&self, request: Request<Body>) -> HandlerResult {
+        // Browsers send simple requests without a preflight, so CORS alone wouldn't stop a page
+        // from writing through the API
+        if let Some(origin) = request.headers().get(ORIGIN) {
+            if !self.allows(origin) {
+                let origin = origin.to_str().unwrap_or_default();
+                return Err(HandlerError(StatusCode::FORBIDDEN, format!("Origin {} is not allowed, see --allow-origin", origin)));
+            }
+        }
+        // Preflight requests carry no credentials
+        if request.method() == Method::OPTIONS {
+            return Ok(Value::Null);
+        }
+        if let Some(token) = &self.token {
+            let sent = request.headers().get(AUTHORIZATION).and_then(|value| value.to_str().ok());
+            if !sent.is_some_and(|sent| same_secret(sent, &format!("Bearer {}", token))) {
+                return Err(HandlerError(StatusCode::UNAUTHORIZED, "Missing or wrong bearer token".to_string()));
+            }
+        }
+
+        let method = request.method().clone();
+        let path = request.uri().path().trim_end_matches('/').to_string();
+        let query = parse_query(request.uri().query().unwrap_or_default());
+        let segments: Vec<&str> = path.split('/').skip(1).collect();
+
+        if method == Method::GET {
+            let key = request.uri().to_string();
+            if let Some(data) = self.cached(&key) {
+                return Ok(data);
+            }
+            let data = match segments.as_slice() {
+                ["health"] => json!({ "status": "ok" }),
+                ["items"] => handlers::list_items(&self.client, &query).await?,
+                ["items", id] => handlers::item_info(&self.client, &json!({ "id": id })).await?,
+                ["search"] => handlers::search(&self.client, &query).await?,
+                ["folders"] => handlers::folders(&self.client).await?,
+                _ => return Err(not_found(&path)),
+            };
+            self.store(key, &data);
+            return Ok(data);
+        }
+
+        let body = hyper::body::to_bytes(request.into_body())
+            .await
+            .map_err(|e| HandlerError::bad_request(e.to_string()))?;
+        let mut body: Value = match body.is_empty() {
+            true => json!({}),
+            false => serde_json::from_slice(&body).map_err(|e| HandlerError::bad_request(format!("Invalid JSON: {}", e)))?,
+        };
+        let data = match (&method, segments.as_slice()) {
+            (&Method::PATCH, ["items", id]) => {
+                body["id"] = json!(id);
+                handlers::update_item(&self.client, &body).await?
+            }
+            (&Method::POST, ["import"]) => handlers::import(&self.client, &body).await?,
+            _ => return Err(not_found(&path)),
+        };
+        self.cache.lock().unwrap().clear();
+        Ok(data)
+    }
+
+    fn cached(&self, key: &str) -> Option<Value> {
+        let cache = self.cache.lock().unwrap();
+        let (stored, data) = cache.get(key)?;
+        (stored.elapsed() < self.cache_ttl).then(|| data.clone())
+    }
+
+    fn store(&self, key: String, data: &Value) {
+        if !self.cache_ttl.is_zero() {
+            self.cache.lock().unwrap().insert(key, (Instant::now(), data.clone()));
+        }
+    }
+}
+
+fn not_found(path: &str) -> HandlerError {
+    HandlerError(StatusCode::NOT_FOUND, format!("No route for {}", path))
+}
+
+/// Query parameters as a JSON object of strings, the form the handlers take
+fn parse_query(query: &str) -> Value {
+    let params: serde_json::Map<String, Value> = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(name), Value::String(decode(value)))
+        })
+        .collect();
+    Value::Object(params)
+}
+
+/// Percent-decode a query component, with `+` as a space
+fn decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = value.get(i + 1..i + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], escaped) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+            (b'+', _) => decoded.push(b' '),
+            (byte, _) => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Compare a sent secret with the expected one in time that doesn't depend on where they differ:
+/// both are hashed, and every byte of the hashes is looked at
+fn same_secret(sent: &str, expected: &str) -> bool {
+    let (sent, expected) = (digest(&SHA256, sent.as_bytes()), digest(&SHA256, expected.as_bytes()));
+    sent.as_ref().iter().zip(expected.as_ref()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
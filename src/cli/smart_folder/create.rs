@@ -0,0 +1,327 @@
+use crate::cli::folder::resolve_folder;
+use crate::cli::item::list::filter::split_list;
+use crate::cli::library::select::same_path;
+use crate::cli::units::parse_size;
+use crate::lib::client::EagleClient;
+use crate::lib::library::{generate_id, LibraryDir};
+use crate::lib::types::{Child, Conditions, Rules, SmartFolders};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use serde_json::{json, Value};
+use std::path::Path;
+
+use super::{find, flatten};
+
+pub fn command() -> Command {
+    Command::new("create")
+        .about("Save item filters as a smart folder")
+        .long_about(
+            "Save item filters as a smart folder.\n\n\
+             Every filter becomes a rule and an item has to match them all. Eagle has no API for \
+             smart folders, so this writes the library's metadata.json. Eagle writes that file from \
+             memory while the library is open, which would drop the new folder, so quit Eagle first \
+             and name the library with --library-path.",
+        )
+        .arg(Arg::new("name").value_name("NAME").help("Name of the smart folder").required(true))
+        .arg(
+            Arg::new("library_path")
+                .long("library-path")
+                .value_name("DIR")
+                .help("Library folder to write, e.g. ~/Pictures/Main.library [default: the one Eagle has open]")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("force")
+                .long("force")
+                .help("Write metadata.json even though Eagle has the library open and may overwrite it")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("parent")
+                .long("parent")
+                .value_name("SMART-FOLDER")
+                .help("Create it inside this smart folder (id, name, or path), which adds the parent's rules")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("keyword")
+                .short('k')
+                .long("keyword")
+                .value_name("KEYWORD")
+                .help("Only items whose name contains KEYWORD")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("ext")
+                .short('e')
+                .long("ext")
+                .value_name("EXTENSION")
+                .help("Only items with one of these extensions. Comma separated. It works like OR")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("not_ext")
+                .long("not-ext")
+                .value_name("EXTENSION")
+                .help("Exclude items with any of these extensions. Comma separated")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("tags")
+                .short('t')
+                .long("tags")
+                .value_name("TAG")
+                .help("Only items with any of these tags. Comma separated. It works like OR")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("tags_all")
+                .long("tags-all")
+                .value_name("TAG")
+                .help("Only items with every one of these tags. Comma separated. It works like AND")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("not_tags")
+                .long("not-tags")
+                .value_name("TAG")
+                .help("Exclude items with any of these tags. Comma separated")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("folders")
+                .short('f')
+                .long("folders")
+                .value_name("FOLDER")
+                .help("Only items in any of these folders, by id, path, or name. Comma separated")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("url")
+                .long("url")
+                .value_name("TEXT")
+                .help("Only items whose URL contains TEXT")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("min_star")
+                .long("min-star")
+                .value_name("STARS")
+                .help("Only items rated at least this many stars")
+                .num_args(1)
+                .value_parser(clap::value_parser!(u8).range(1..=5)),
+        )
+        .arg(
+            Arg::new("min_size")
+                .long("min-size")
+                .value_name("SIZE")
+                .help("Only items at least this large, e.g. 500KB or 10MB")
+                .num_args(1)
+                .value_parser(parse_size),
+        )
+        .arg(
+            Arg::new("max_size")
+                .long("max-size")
+                .value_name("SIZE")
+                .help("Only items at most this large, e.g. 500KB or 10MB")
+                .num_args(1)
+                .value_parser(parse_size),
+        )
+        .arg(
+            Arg::new("min_width")
+                .long("min-width")
+                .value_name("PIXELS")
+                .help("Only items at least this wide")
+                .num_args(1)
+                .value_parser(clap::value_parser!(u64)),
+        )
+        .arg(
+            Arg::new("max_width")
+                .long("max-width")
+                .value_name("PIXELS")
+                .help("Only items at most this wide")
+                .num_args(1)
+                .value_parser(clap::value_parser!(u64)),
+        )
+        .arg(
+            Arg::new("min_height")
+                .long("min-height")
+                .value_name("PIXELS")
+                .help("Only items at least this tall")
+                .num_args(1)
+                .value_parser(clap::value_parser!(u64)),
+        )
+        .arg(
+            Arg::new("max_height")
+                .long("max-height")
+                .value_name("PIXELS")
+                .help("Only items at most this tall")
+                .num_args(1)
+                .value_parser(clap::value_parser!(u64)),
+        )
+}
+
+fn rule(property: &str, method: &str, value: Value) -> Rules {
+    Rules {
+        property: property.to_string(),
+        method: method.to_string(),
+        value,
+    }
+}
+
+/// The filters of `matches` as rules in Eagle's smart folder schema
+fn rules(matches: &ArgMatches, folder_ids: Vec<String>) -> Vec<Rules> {
+    let lowercase = |values: Vec<String>| -> Vec<String> { values.into_iter().map(|value| value.to_lowercase()).collect() };
+    let mut rules = Vec::new();
+    if let Some(keyword) = matches.get_one::<String>("keyword") {
+        rules.push(rule("name", "contain", json!(keyword)));
+    }
+    let exts = lowercase(split_list(matches, "ext"));
+    if !exts.is_empty() {
+        rules.push(rule("type", "union", json!(exts)));
+    }
+    let not_exts = lowercase(split_list(matches, "not_ext"));
+    if !not_exts.is_empty() {
+        rules.push(rule("type", "uncontain", json!(not_exts)));
+    }
+    for (id, method) in [("tags", "union"), ("tags_all", "intersection"), ("not_tags", "uncontain")] {
+        let tags = split_list(matches, id);
+        if !tags.is_empty() {
+            rules.push(rule("tags", method, json!(tags)));
+        }
+    }
+    if !folder_ids.is_empty() {
+        rules.push(rule("folders", "union", json!(folder_ids)));
+    }
+    if let Some(url) = matches.get_one::<String>("url") {
+        rules.push(rule("url", "contain", json!(url)));
+    }
+    if let Some(star) = matches.get_one::<u8>("min_star") {
+        rules.push(rule("rating", ">=", json!(star)));
+    }
+    for (id, property, method) in [
+        ("min_size", "fileSize", ">="),
+        ("max_size", "fileSize", "<="),
+        ("min_width", "width", ">="),
+        ("max_width", "width", "<="),
+        ("min_height", "height", ">="),
+        ("max_height", "height", "<="),
+    ] {
+        if let Some(value) = matches.get_one::<u64>(id) {
+            rules.push(rule(property, method, json!(value)));
+        }
+    }
+    rules
+}
+
+/// The `children` list of the smart folder with this id, searching nested ones too
+fn children_of<'a>(folders: &'a mut Value, id: &str) -> Option<&'a mut Value> {
+    let list = folders.as_array_mut()?;
+    for folder in list {
+        if folder["id"] == id {
+            if !folder["children"].is_array() {
+                folder["children"] = json!([]);
+            }
+            return Some(&mut folder["children"]);
+        }
+        if let Some(children) = children_of(&mut folder["children"], id) {
+            return Some(children);
+        }
+    }
+    None
+}
+
+/// The library to write, refusing the one Eagle has open unless `--force` is given
+async fn target_library(client: &EagleClient, matches: &ArgMatches) -> Result<LibraryDir, Box<dyn std::error::Error>> {
+    // Eagle not answering means it can't overwrite the file
+    let open = client.library().info().await.ok().map(|info| info.data.library.path);
+    let path = match (matches.get_one::<String>("library_path"), &open) {
+        (Some(path), _) => path.clone(),
+        (None, Some(open)) => open.clone(),
+        (None, None) => return Err("Eagle isn't running; give the library folder with --library-path".into()),
+    };
+    let library = LibraryDir::new(&path);
+    if !library.metadata_path().is_file() {
+        return Err(format!("{} is not an Eagle library (no metadata.json)", path).into());
+    }
+    let is_open = open.is_some_and(|open| same_path(Path::new(&open), library.root()));
+    if is_open && !matches.get_flag("force") {
+        return Err(format!(
+            "Eagle has {} open and writes its metadata.json from memory, which would drop the new smart \
+             folder. Quit Eagle and run this again with --library-path {}, or pass --force",
+            path, path
+        )
+        .into());
+    }
+    if is_open {
+        eprintln!("Warning: Eagle has {} open and may write over the new smart folder", path);
+    }
+    Ok(library)
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let name = matches.get_one::<String>("name").unwrap();
+    let library = target_library(client, matches).await?;
+    let mut metadata = library.read_metadata()?;
+    let smart_folders: Vec<SmartFolders> = match metadata["smartFolders"].is_array() {
+        true => serde_json::from_value(metadata["smartFolders"].clone())?,
+        false => Vec::new(),
+    };
+    let entries = flatten(&smart_folders, "", &[]);
+    let parent = matches
+        .get_one::<String>("parent")
+        .map(|parent| find(&entries, parent))
+        .transpose()?;
+    let folder_values = split_list(matches, "folders");
+    let folder_ids = match folder_values.is_empty() {
+        true => Vec::new(),
+        false => {
+            let folders: Vec<Child> = serde_json::from_value(metadata["folders"].clone())?;
+            folder_values
+                .iter()
+                .map(|value| resolve_folder(&folders, value))
+                .collect::<Result<Vec<_>, _>>()?
+        }
+    };
+    let rules = rules(matches, folder_ids);
+    if rules.is_empty() {
+        return Err("Give at least one filter, e.g. --ext png or --tags logo".into());
+    }
+
+    let smart_folder = SmartFolders {
+        id: generate_id(),
+        icon: None,
+        name: name.clone(),
+        description: None,
+        modification_time: chrono::Utc::now().timestamp_millis() as u64,
+        conditions: vec![Conditions {
+            match_: "AND".to_string(),
+            rules,
+            boolean: Some("TRUE".to_string()),
+        }],
+        children: Vec::new(),
+    };
+
+    if !metadata["smartFolders"].is_array() {
+        metadata["smartFolders"] = json!([]);
+    }
+    let list = match parent {
+        Some(parent) => children_of(&mut metadata["smartFolders"], &parent.folder.id)
+            .ok_or_else(|| format!("Smart folder {} is missing from metadata.json", parent.path))?,
+        None => &mut metadata["smartFolders"],
+    };
+    let Some(list) = list.as_array_mut() else {
+        return Err("Invalid smartFolders in metadata.json".into());
+    };
+    if list.iter().any(|folder| folder["name"] == *name) {
+        return Err(format!("A smart folder named {} already exists there", name).into());
+    }
+    list.push(serde_json::to_value(&smart_folder)?);
+    library.write_metadata(&metadata)?;
+
+    println!("{}", smart_folder.id);
+    eprintln!("Created smart folder {}; Eagle shows it the next time it opens the library.", name);
+    Ok(())
+}
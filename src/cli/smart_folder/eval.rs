@@ -0,0 +1,148 @@
+use crate::lib::types::{Conditions, ItemListData, Rules};
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// Why a rule can't be evaluated outside Eagle
+pub type Unsupported = String;
+
+/// Whether `item` is in a smart folder with these conditions: every condition has to hold,
+/// each matching all (`AND`) or any (`OR`) of its rules, inverted when its `boolean` is `FALSE`.
+///
+/// Rules on properties only Eagle knows (colors, shapes, ...) are reported in `unsupported`
+/// and left out, so the result may hold more items than Eagle shows.
+pub fn matches(conditions: &[&Conditions], item: &ItemListData, unsupported: &mut HashSet<Unsupported>) -> bool {
+    conditions.iter().all(|condition| {
+        let mut results = condition.rules.iter().filter_map(|rule| match rule_matches(rule, item) {
+            Ok(matched) => Some(matched),
+            Err(reason) => {
+                unsupported.insert(reason);
+                None
+            }
+        });
+        let matched = match condition.match_.eq_ignore_ascii_case("OR") {
+            true => {
+                let results: Vec<bool> = results.collect();
+                results.is_empty() || results.contains(&true)
+            }
+            false => results.all(|matched| matched),
+        };
+        match condition.boolean.as_deref() {
+            Some(boolean) if boolean.eq_ignore_ascii_case("FALSE") => !matched,
+            _ => matched,
+        }
+    })
+}
+
+/// Values of a rule: a list as is, anything else as a list of one
+fn values(value: &Value) -> Vec<&Value> {
+    match value {
+        Value::Array(values) => values.iter().collect(),
+        Value::Null => Vec::new(),
+        value => vec![value],
+    }
+}
+
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.to_lowercase(),
+        value => value.to_string(),
+    }
+}
+
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(text) => text.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn rule_matches(rule: &Rules, item: &ItemListData) -> Result<bool, Unsupported> {
+    let method = rule.method.as_str();
+    match rule.property.as_str() {
+        "name" => text_matches(method, &item.name, &rule.value),
+        "annotation" => text_matches(method, &item.annotation, &rule.value),
+        "url" => text_matches(method, &item.url, &rule.value),
+        "type" | "ext" => {
+            let exts: HashSet<String> = values(&rule.value).into_iter().map(text).collect();
+            let ext = item.ext.to_lowercase();
+            match method {
+                "equal" | "is" | "union" | "contain" => Ok(exts.contains(&ext)),
+                "unequal" | "isNot" | "uncontain" => Ok(!exts.contains(&ext)),
+                _ => Err(format!("{} {}", rule.property, method)),
+            }
+        }
+        "tags" => set_matches(method, &item.tags, &rule.value),
+        "folders" => set_matches(method, item.folders.as_deref().unwrap_or_default(), &rule.value),
+        "rating" | "star" => number_matches(method, item.star.unwrap_or(0) as f64, &rule.value),
+        "width" => number_matches(method, item.width.unwrap_or(0) as f64, &rule.value),
+        "height" => number_matches(method, item.height.unwrap_or(0) as f64, &rule.value),
+        "fileSize" | "size" => number_matches(method, item.size as f64, &rule.value),
+        "mtime" | "modificationTime" | "createTime" | "btime" => time_matches(method, item.modification_time as i64, &rule.value),
+        property => Err(property.to_string()),
+    }
+    .map_err(|_| format!("{} {} {}", rule.property, rule.method, rule.value))
+}
+
+fn text_matches(method: &str, field: &str, value: &Value) -> Result<bool, Unsupported> {
+    let field = field.to_lowercase();
+    let value = values(value).first().map(|value| text(value)).unwrap_or_default();
+    Ok(match method {
+        "contain" => field.contains(&value),
+        "uncontain" => !field.contains(&value),
+        "equal" | "is" => field == value,
+        "unequal" | "isNot" => field != value,
+        "startWith" => field.starts_with(&value),
+        "endWith" => field.ends_with(&value),
+        "empty" => field.is_empty(),
+        "not-empty" | "notEmpty" => !field.is_empty(),
+        method => return Err(method.to_string()),
+    })
+}
+
+fn set_matches(method: &str, field: &[String], value: &Value) -> Result<bool, Unsupported> {
+    let field: HashSet<String> = field.iter().map(|value| value.to_lowercase()).collect();
+    let wanted: HashSet<String> = values(value).into_iter().map(text).collect();
+    Ok(match method {
+        "union" | "contain" | "equal" | "is" => !field.is_disjoint(&wanted),
+        "intersection" => wanted.is_subset(&field),
+        "uncontain" | "unequal" | "isNot" | "exclude" => field.is_disjoint(&wanted),
+        "identity" => field == wanted,
+        "empty" => field.is_empty(),
+        "not-empty" | "notEmpty" => !field.is_empty(),
+        method => return Err(method.to_string()),
+    })
+}
+
+fn number_matches(method: &str, field: f64, value: &Value) -> Result<bool, Unsupported> {
+    let bounds: Vec<f64> = values(value).into_iter().filter_map(number).collect();
+    let (Some(&first), last) = (bounds.first(), bounds.last().copied()) else {
+        return Err(format!("{} without a number", method));
+    };
+    Ok(match method {
+        "equal" | "is" => field == first,
+        "unequal" | "isNot" => field != first,
+        ">" => field > first,
+        ">=" => field >= first,
+        "<" => field < first,
+        "<=" => field <= first,
+        "between" => (first..=last.unwrap_or(first)).contains(&field),
+        method => return Err(method.to_string()),
+    })
+}
+
+const DAY_MILLIS: f64 = 86_400_000.0;
+
+/// Times are epoch milliseconds; `within` takes a number of days
+fn time_matches(method: &str, field: i64, value: &Value) -> Result<bool, Unsupported> {
+    let field = field as f64;
+    match method {
+        "within" => {
+            let days = values(value).first().and_then(|value| number(value)).ok_or_else(|| "within without days".to_string())?;
+            Ok(field >= chrono::Utc::now().timestamp_millis() as f64 - days * DAY_MILLIS)
+        }
+        "before" => number_matches("<", field, value),
+        "after" => number_matches(">", field, value),
+        method => number_matches(method, field, value),
+    }
+}
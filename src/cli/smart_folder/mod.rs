@@ -0,0 +1,157 @@
+use crate::cli::item::list::{self, item_path, item_rows};
+use crate::cli::output;
+use crate::cli::pager;
+use crate::lib::client::EagleClient;
+use crate::lib::library::LibraryDir;
+use crate::lib::types::{Conditions, SmartFolders};
+use clap::{Arg, ArgMatches, Command};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+
+pub mod create;
+pub mod eval;
+
+pub fn build() -> Command {
+    Command::new("smart-folder")
+        .about("List, create, and run smart folders")
+        .arg_required_else_help(true)
+        .subcommand(create::command())
+        .subcommand(
+            Command::new("list")
+                .about("List smart folders with their rules")
+                .args(output::args()),
+        )
+        .subcommand(
+            Command::new("run")
+                .about("List the items of a smart folder, evaluating its rules over the library")
+                .long_about(
+                    "List the items of a smart folder, evaluating its rules over the library.\n\n\
+                     Rules on names, annotations, URLs, extensions, tags, folders, ratings, sizes, \
+                     dimensions, and dates are evaluated; others, such as colors, are skipped with a \
+                     warning, so the result may hold more items than Eagle shows.",
+                )
+                .arg(
+                    Arg::new("smart_folder")
+                        .value_name("ID")
+                        .help("Smart folder id, name, or path like Parent/Child")
+                        .required(true),
+                )
+                .args(output::args()),
+        )
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Creating works on metadata.json, mostly while Eagle is closed
+    if let Some(("create", create_matches)) = matches.subcommand() {
+        return create::execute(client, create_matches).await;
+    }
+    let data = client.library().info().await?.data;
+    let smart_folders = flatten(&data.smart_folders, "", &[]);
+
+    match matches.subcommand() {
+        Some(("list", list_matches)) => {
+            let rows: Vec<Value> = smart_folders
+                .iter()
+                .map(|entry| {
+                    json!({
+                        "id": entry.folder.id,
+                        "name": entry.folder.name,
+                        "path": entry.path,
+                        "rules": describe(&entry.folder.conditions),
+                    })
+                })
+                .collect();
+            output::output(&Value::Array(rows), list_matches)?;
+        }
+        Some(("run", run_matches)) => {
+            let value = run_matches.get_one::<String>("smart_folder").unwrap();
+            let entry = find(&smart_folders, value)?;
+            let library = LibraryDir::new(&data.library.path);
+            let mut unsupported = HashSet::new();
+            let items: Vec<_> = list::all_items(client)
+                .await?
+                .into_iter()
+                .filter(|item| eval::matches(&entry.conditions, item, &mut unsupported))
+                .collect();
+            for rule in &unsupported {
+                eprintln!("Warning: skipped a rule eagle-eye can't evaluate: {}", rule);
+            }
+            if output::is_explicit(run_matches) {
+                return output::output(&Value::Array(item_rows(&items, &library, false)), run_matches);
+            }
+            let paths: String = items
+                .iter()
+                .map(|item| format!("{}\n", item_path(&library, item, false).display()))
+                .collect();
+            pager::print(&paths, run_matches)?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// A smart folder with its path and the conditions inherited from its parents
+pub struct Entry<'a> {
+    pub folder: &'a SmartFolders,
+    pub path: String,
+    pub conditions: Vec<&'a Conditions>,
+}
+
+/// Every smart folder, parents before their children
+pub fn flatten<'a>(folders: &'a [SmartFolders], parent: &str, inherited: &[&'a Conditions]) -> Vec<Entry<'a>> {
+    let mut entries = Vec::new();
+    for folder in folders {
+        let path = match parent {
+            "" => folder.name.clone(),
+            parent => format!("{}/{}", parent, folder.name),
+        };
+        let conditions: Vec<&Conditions> = inherited.iter().copied().chain(&folder.conditions).collect();
+        let children = flatten(&folder.children, &path, &conditions);
+        entries.push(Entry { folder, path, conditions });
+        entries.extend(children);
+    }
+    entries
+}
+
+/// The smart folder with this id, else with this path or unique name
+fn find<'a, 'b>(entries: &'b [Entry<'a>], value: &str) -> Result<&'b Entry<'a>, Box<dyn std::error::Error>> {
+    if let Some(entry) = entries.iter().find(|entry| entry.folder.id == value || entry.path == value) {
+        return Ok(entry);
+    }
+    let named: Vec<&Entry> = entries.iter().filter(|entry| entry.folder.name == value).collect();
+    match named.as_slice() {
+        [entry] => Ok(entry),
+        [] => Err(format!("No smart folder matches {} (see `smart-folder list`)", value).into()),
+        _ => Err(format!("Several smart folders are named {}; use the path or id", value).into()),
+    }
+}
+
+/// Rules as a line of text, e.g. `type equal png AND tags union [logo]`
+fn describe(conditions: &[Conditions]) -> String {
+    let conditions: Vec<String> = conditions
+        .iter()
+        .map(|condition| {
+            let rules: Vec<String> = condition
+                .rules
+                .iter()
+                .map(|rule| {
+                    let value = match &rule.value {
+                        Value::String(value) => value.clone(),
+                        value => value.to_string(),
+                    };
+                    format!("{} {} {}", rule.property, rule.method, value)
+                })
+                .collect();
+            let rules = rules.join(&format!(" {} ", condition.match_.to_uppercase()));
+            match condition.boolean.as_deref() {
+                Some(boolean) if boolean.eq_ignore_ascii_case("FALSE") => format!("NOT ({})", rules),
+                _ if conditions.len() > 1 => format!("({})", rules),
+                _ => rules,
+            }
+        })
+        .collect();
+    conditions.join(" AND ")
+}
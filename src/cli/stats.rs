@@ -0,0 +1,232 @@
+use crate::cli::output;
+use clap::parser::ValueSource;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+
+// Nothing is recorded until `stats enable` creates the marker file, and nothing ever
+// leaves the machine: records are appended to a local JSON lines file, which
+// `stats dump` prints for anyone who chooses to share it.
+const ENABLED_FILE_NAME: &str = "stats.enabled";
+const RECORDS_FILE_NAME: &str = "stats.jsonl";
+
+/// One invocation of eagle-eye
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Record {
+    /// Start time in epoch milliseconds
+    pub time: i64,
+    /// Subcommand path, e.g. `item list`
+    pub command: String,
+    /// Arguments given on the command line (names only, never values)
+    pub flags: Vec<String>,
+    pub duration_ms: u64,
+    pub success: bool,
+}
+
+/// Directory for eagle-eye's local data: `$EAGLE_EYE_DATA_DIR`, else `$XDG_DATA_HOME/eagle-eye`,
+/// else `~/.local/share/eagle-eye`.
+pub fn data_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("EAGLE_EYE_DATA_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+    if let Some(dir) = std::env::var_os("XDG_DATA_HOME").filter(|dir| !dir.is_empty()) {
+        return Some(PathBuf::from(dir).join("eagle-eye"));
+    }
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share/eagle-eye"))
+}
+
+pub fn is_enabled() -> bool {
+    data_dir().is_some_and(|dir| dir.join(ENABLED_FILE_NAME).exists())
+}
+
+/// Append a record for this invocation when stats are enabled.
+pub fn record(matches: &ArgMatches, duration: Duration, success: bool) -> std::io::Result<()> {
+    let Some(dir) = data_dir() else {
+        return Ok(());
+    };
+    if !dir.join(ENABLED_FILE_NAME).exists() {
+        return Ok(());
+    }
+
+    let mut command = Vec::new();
+    let mut flags = Vec::new();
+    let mut current = matches;
+    loop {
+        flags.extend(
+            current
+                .ids()
+                .filter(|id| current.value_source(id.as_str()) == Some(ValueSource::CommandLine))
+                .map(|id| id.to_string()),
+        );
+        match current.subcommand() {
+            Some((name, sub_matches)) => {
+                command.push(name);
+                current = sub_matches;
+            }
+            None => break,
+        }
+    }
+
+    let now = chrono::Utc::now().timestamp_millis();
+    let record = Record {
+        time: now - duration.as_millis() as i64,
+        command: command.join(" "),
+        flags,
+        duration_ms: duration.as_millis() as u64,
+        success,
+    };
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join(RECORDS_FILE_NAME))?;
+    writeln!(file, "{}", serde_json::to_string(&record)?)
+}
+
+fn read_records() -> Result<Vec<Record>, Box<dyn std::error::Error>> {
+    let path = data_dir().ok_or("Can't locate the data directory")?.join(RECORDS_FILE_NAME);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    // Skip lines a crashed run may have left half-written
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+pub fn build() -> Command {
+    Command::new("stats")
+        .about("Opt-in local usage statistics")
+        .subcommand(Command::new("enable").about("Start recording which commands are run and how long they take"))
+        .subcommand(Command::new("disable").about("Stop recording (keeps what was recorded)"))
+        .subcommand(Command::new("status").about("Show whether stats are recorded and where").args(output::args()))
+        .subcommand(
+            Command::new("commands")
+                .about("Summarize usage per command")
+                .arg(
+                    Arg::new("flags")
+                        .long("flags")
+                        .help("Also list how often each flag was used")
+                        .action(ArgAction::SetTrue),
+                )
+                .args(output::args()),
+        )
+        .subcommand(
+            Command::new("dump")
+                .about("Print the raw records as JSON lines, or in the format asked for")
+                .args(output::args()),
+        )
+        .subcommand(Command::new("clear").about("Delete all recorded stats"))
+}
+
+pub async fn execute(matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = data_dir().ok_or("Can't locate the data directory, set EAGLE_EYE_DATA_DIR")?;
+
+    match matches.subcommand() {
+        Some(("enable", _)) => {
+            fs::create_dir_all(&dir)?;
+            fs::write(dir.join(ENABLED_FILE_NAME), "")?;
+            println!("Recording usage stats to {}", dir.join(RECORDS_FILE_NAME).display());
+        }
+        Some(("disable", _)) => {
+            let enabled_file = dir.join(ENABLED_FILE_NAME);
+            if enabled_file.exists() {
+                fs::remove_file(enabled_file)?;
+            }
+            println!("Usage stats disabled");
+        }
+        Some(("status", status_matches)) => {
+            let records = read_records()?.len();
+            if output::is_explicit(status_matches) {
+                let status = json!({ "enabled": is_enabled(), "records": records, "dir": dir });
+                return output::output(&status, status_matches);
+            }
+            let state = if is_enabled() { "enabled" } else { "disabled" };
+            println!("Usage stats {} ({} records in {})", state, records, dir.display());
+        }
+        Some(("commands", commands_matches)) => {
+            let records = read_records()?;
+            if records.is_empty() {
+                eprintln!("No usage stats recorded{}", if is_enabled() { "" } else { " (run `stats enable`)" });
+            }
+            let rows = command_rows(&records, commands_matches.get_flag("flags"));
+            output::output(&Value::Array(rows), commands_matches)?;
+        }
+        Some(("dump", dump_matches)) => {
+            let records = read_records()?;
+            if output::is_explicit(dump_matches) {
+                return output::output(&serde_json::to_value(records)?, dump_matches);
+            }
+            for record in records {
+                println!("{}", serde_json::to_string(&record)?);
+            }
+        }
+        Some(("clear", _)) => {
+            let records_file = dir.join(RECORDS_FILE_NAME);
+            if records_file.exists() {
+                fs::remove_file(records_file)?;
+            }
+            println!("Usage stats cleared");
+        }
+        _ => {
+            println!("No subcommand was used");
+        }
+    }
+    Ok(())
+}
+
+#[derive(Default)]
+struct CommandStats {
+    runs: usize,
+    failures: usize,
+    total_ms: u64,
+    max_ms: u64,
+    flags: BTreeMap<String, usize>,
+}
+
+/// Usage per command, most run first; with `show_flags`, how often each flag was used as
+/// `flag (count)`, most used first
+fn command_rows(records: &[Record], show_flags: bool) -> Vec<Value> {
+    let mut commands: BTreeMap<&str, CommandStats> = BTreeMap::new();
+    for record in records {
+        let stats = commands.entry(record.command.as_str()).or_default();
+        stats.runs += 1;
+        stats.failures += usize::from(!record.success);
+        stats.total_ms += record.duration_ms;
+        stats.max_ms = stats.max_ms.max(record.duration_ms);
+        for flag in &record.flags {
+            *stats.flags.entry(flag.clone()).or_default() += 1;
+        }
+    }
+
+    let mut commands: Vec<(&str, CommandStats)> = commands.into_iter().collect();
+    commands.sort_by_key(|(_, stats)| std::cmp::Reverse(stats.runs));
+
+    commands
+        .into_iter()
+        .map(|(command, stats)| {
+            let mut row = json!({
+                "runs": stats.runs,
+                "failed": stats.failures,
+                "avg_ms": stats.total_ms / stats.runs as u64,
+                "max_ms": stats.max_ms,
+                "command": command,
+            });
+            if show_flags {
+                let mut flags: Vec<(String, usize)> = stats.flags.into_iter().collect();
+                flags.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+                row["flags"] = json!(flags
+                    .into_iter()
+                    .map(|(flag, count)| format!("{} ({})", flag, count))
+                    .collect::<Vec<_>>());
+            }
+            row
+        })
+        .collect()
+}
@@ -0,0 +1,259 @@
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Open a file or URL with the default application of the platform.
+pub fn open(target: &str) -> std::io::Result<()> {
+    if cfg!(target_os = "macos") {
+        run(Command::new("open").arg(target))
+    } else if cfg!(windows) {
+        // The empty argument is the window title `start` expects before the target
+        run(Command::new("cmd").args(["/C", "start", ""]).arg(target))
+    } else {
+        run(Command::new("xdg-open").arg(target))
+    }
+}
+
+/// Show a file selected in Finder / Explorer, or open its directory elsewhere.
+pub fn reveal(path: &Path) -> std::io::Result<()> {
+    if cfg!(target_os = "macos") {
+        run(Command::new("open").arg("-R").arg(path))
+    } else if cfg!(windows) {
+        run(Command::new("explorer").arg(format!("/select,{}", path.display())))
+    } else {
+        let dir = path.parent().unwrap_or(path);
+        run(Command::new("xdg-open").arg(dir))
+    }
+}
+
+/// Open a file with a specific application: an app name on macOS, a command elsewhere.
+pub fn open_with(app: &str, path: &Path) -> std::io::Result<()> {
+    if cfg!(target_os = "macos") {
+        run(Command::new("open").arg("-a").arg(app).arg(path))
+    } else {
+        run(Command::new(app).arg(path))
+    }
+}
+
+/// Start a program in the background, detached from this process's stdio so it outlives it.
+pub fn spawn_detached(program: &Path) -> std::io::Result<()> {
+    Command::new(program)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| std::io::Error::new(e.kind(), format!("Failed to run {}: {}", program.display(), e)))?;
+    Ok(())
+}
+
+/// Ask the application running from `program` to quit: through AppleScript on macOS, otherwise
+/// by signalling the processes named like the executable.
+pub fn quit_app(program: &Path) -> std::io::Result<()> {
+    let name = |path: &Path| path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    if cfg!(target_os = "macos") {
+        // The bundle name, e.g. Eagle for /Applications/Eagle.app/Contents/MacOS/Eagle
+        let app = program
+            .ancestors()
+            .find_map(|dir| name(dir).strip_suffix(".app").map(str::to_string))
+            .unwrap_or_else(|| name(program));
+        let script = format!("quit app \"{}\"", app.replace('\\', "\\\\").replace('"', "\\\""));
+        run(Command::new("osascript").arg("-e").arg(script))
+    } else if cfg!(windows) {
+        run(Command::new("taskkill").args(["/IM", &name(program)]))
+    } else {
+        run(Command::new("pkill").arg("-x").arg(name(program)))
+    }
+}
+
+/// Put text on the system clipboard.
+pub fn copy_text(text: &str) -> std::io::Result<()> {
+    if cfg!(target_os = "macos") {
+        pipe(&mut Command::new("pbcopy"), text.as_bytes())
+    } else if cfg!(windows) {
+        pipe(&mut Command::new("clip"), text.as_bytes())
+    } else if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        pipe(&mut Command::new("wl-copy"), text.as_bytes())
+    } else {
+        pipe(Command::new("xclip").args(["-selection", "clipboard"]), text.as_bytes())
+    }
+}
+
+/// Put the contents of an image file on the system clipboard.
+pub fn copy_image(path: &Path) -> std::io::Result<()> {
+    let ext = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    if cfg!(target_os = "macos") {
+        let class = match ext.as_str() {
+            "png" => "PNGf",
+            "jpg" | "jpeg" => "JPEG",
+            "gif" => "GIFf",
+            "tif" | "tiff" => "TIFF",
+            _ => return Err(std::io::Error::other(format!("Can't copy .{} images to the clipboard", ext))),
+        };
+        let script = format!(
+            "set the clipboard to (read (POSIX file \"{}\") as «class {}»)",
+            path.display().to_string().replace('\\', "\\\\").replace('"', "\\\""),
+            class
+        );
+        return run(Command::new("osascript").arg("-e").arg(script));
+    }
+    if cfg!(windows) {
+        return Err(std::io::Error::other("Copying images to the clipboard is not supported on Windows"));
+    }
+
+    let mime = match ext.as_str() {
+        "jpg" | "jpeg" => "image/jpeg".to_string(),
+        "svg" => "image/svg+xml".to_string(),
+        "tif" => "image/tiff".to_string(),
+        _ => format!("image/{}", ext),
+    };
+    let data = std::fs::read(path)?;
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        pipe(Command::new("wl-copy").args(["--type", &mime]), &data)
+    } else {
+        pipe(Command::new("xclip").args(["-selection", "clipboard", "-t", &mime]), &data)
+    }
+}
+
+/// What the system clipboard holds
+#[derive(Debug, PartialEq)]
+pub enum Clipboard {
+    /// PNG data
+    Image(Vec<u8>),
+    Text(String),
+    Empty,
+}
+
+/// Read the system clipboard, preferring an image over text when it holds both.
+pub fn read_clipboard() -> std::io::Result<Clipboard> {
+    if cfg!(target_os = "macos") {
+        let file = std::env::temp_dir().join(format!("eagle-eye-clipboard-{}.png", std::process::id()));
+        let script = format!(
+            "set f to open for access (POSIX file \"{}\") with write permission\n\
+             try\n  set eof f to 0\n  write (the clipboard as «class PNGf») to f\nend try\nclose access f",
+            file.display()
+        );
+        output(Command::new("osascript").arg("-e").arg(script))?;
+        let image = std::fs::read(&file).unwrap_or_default();
+        let _ = std::fs::remove_file(&file);
+        if !image.is_empty() {
+            return Ok(Clipboard::Image(image));
+        }
+        return Ok(text_clipboard(output(&mut Command::new("pbpaste"))?));
+    }
+    if cfg!(windows) {
+        let file = std::env::temp_dir().join(format!("eagle-eye-clipboard-{}.png", std::process::id()));
+        let script = format!(
+            "Add-Type -AssemblyName System.Windows.Forms; $i = [Windows.Forms.Clipboard]::GetImage(); \
+             if ($i) {{ $i.Save('{}', [Drawing.Imaging.ImageFormat]::Png) }}",
+            file.display()
+        );
+        output(Command::new("powershell").args(["-NoProfile", "-STA", "-Command"]).arg(script))?;
+        let image = std::fs::read(&file).unwrap_or_default();
+        let _ = std::fs::remove_file(&file);
+        if !image.is_empty() {
+            return Ok(Clipboard::Image(image));
+        }
+        return Ok(text_clipboard(output(
+            Command::new("powershell").args(["-NoProfile", "-Command", "Get-Clipboard -Raw"]),
+        )?));
+    }
+
+    let wayland = std::env::var_os("WAYLAND_DISPLAY").is_some();
+    let paste = |args: &[&str]| {
+        let mut command = match wayland {
+            true => Command::new("wl-paste"),
+            false => {
+                let mut command = Command::new("xclip");
+                command.args(["-selection", "clipboard", "-o"]);
+                command
+            }
+        };
+        output(command.args(args))
+    };
+    let types = match wayland {
+        true => paste(&["--list-types"])?,
+        false => paste(&["-t", "TARGETS"])?,
+    };
+    if String::from_utf8_lossy(&types).lines().any(|line| line.trim() == "image/png") {
+        return Ok(Clipboard::Image(paste(&["-t", "image/png"])?));
+    }
+    Ok(text_clipboard(match wayland {
+        true => paste(&["--no-newline"])?,
+        false => paste(&[])?,
+    }))
+}
+
+fn text_clipboard(bytes: Vec<u8>) -> Clipboard {
+    let text = String::from_utf8_lossy(&bytes).trim().to_string();
+    if text.is_empty() {
+        Clipboard::Empty
+    } else {
+        Clipboard::Text(text)
+    }
+}
+
+/// Quote `value` so the platform shell passes it through as a single argument.
+pub fn shell_quote(value: &str) -> String {
+    if cfg!(windows) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        format!("'{}'", value.replace('\'', "'\\''"))
+    }
+}
+
+/// A command running `command_line` through the platform shell (`sh -c` or `cmd /C`).
+pub fn shell(command_line: &str) -> Command {
+    if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.arg("/C").arg(command_line);
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(command_line);
+        command
+    }
+}
+
+/// Run a command with `input` on its stdin
+fn pipe(command: &mut Command, input: &[u8]) -> std::io::Result<()> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    let mut child = command
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| std::io::Error::new(e.kind(), format!("Failed to run {}: {}", program, e)))?;
+    child.stdin.take().unwrap().write_all(input)?;
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(std::io::Error::other(format!("{} exited with {}", program, status)));
+    }
+    Ok(())
+}
+
+/// Run a command and return its stdout; a failing status (e.g. an empty clipboard) gives no output
+fn output(command: &mut Command) -> std::io::Result<Vec<u8>> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    let output = command
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|e| std::io::Error::new(e.kind(), format!("Failed to run {}: {}", program, e)))?;
+    Ok(if output.status.success() { output.stdout } else { Vec::new() })
+}
+
+fn run(command: &mut Command) -> std::io::Result<()> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    let status = command
+        .stdin(Stdio::null())
+        .status()
+        .map_err(|e| std::io::Error::new(e.kind(), format!("Failed to run {}: {}", program, e)))?;
+    // explorer.exe exits with 1 even when it succeeds
+    let ignore_status = cfg!(windows) && program == "explorer";
+    if !status.success() && !ignore_status {
+        return Err(std::io::Error::other(format!("{} exited with {}", program, status)));
+    }
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use super::{Taxonomy, TaxonomyGroup};
+use crate::cli::output;
+use crate::lib::client::EagleClient;
+use crate::lib::library::LibraryDir;
+use crate::lib::types::TagsGroups;
+use clap::{Arg, ArgMatches, Command};
+use std::collections::BTreeSet;
+
+pub fn build() -> Command {
+    Command::new("export")
+        .about("Export all tags and tag groups to a YAML taxonomy file")
+        .arg(
+            Arg::new("out")
+                .short('o')
+                .long("out")
+                .value_name("FILE")
+                .help("Write the taxonomy to a file instead of stdout")
+                .num_args(1),
+        )
+        .args(output::args())
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let data = client.library().info().await?.data;
+    let library = LibraryDir::new(&data.library.path);
+    let library_tags = library.read_tags()?;
+
+    let groups: Vec<TaxonomyGroup> = data
+        .tags_groups
+        .iter()
+        .filter_map(|group| serde_json::from_value::<TagsGroups>(group.clone()).ok())
+        .map(|group| TaxonomyGroup {
+            name: group.name,
+            color: group.color,
+            tags: group.tags,
+        })
+        .collect();
+
+    // The API has no tag list, so gather every tag in use plus the ones Eagle remembers
+    let mut tags: BTreeSet<String> = BTreeSet::new();
+    for item in library.items()? {
+        if !item.is_deleted {
+            tags.extend(item.tags);
+        }
+    }
+    tags.extend(library_tags.history_tags.iter().cloned());
+    tags.extend(library_tags.starred_tags.iter().cloned());
+    for group in &groups {
+        tags.extend(group.tags.iter().cloned());
+    }
+
+    let taxonomy = Taxonomy {
+        tags: tags.into_iter().collect(),
+        starred: library_tags.starred_tags,
+        groups,
+    };
+    // YAML is the taxonomy's file format; a format asked for prints it that way instead
+    if matches.get_one::<String>("out").is_none() && output::is_explicit(matches) {
+        return output::output(&serde_json::to_value(&taxonomy)?, matches);
+    }
+    let yaml = serde_yaml::to_string(&taxonomy)?;
+
+    match matches.get_one::<String>("out") {
+        Some(path) => {
+            std::fs::write(path, yaml)?;
+            eprintln!(
+                "Exported {} tags and {} groups to {}",
+                taxonomy.tags.len(),
+                taxonomy.groups.len(),
+                path
+            );
+        }
+        None => print!("{}", yaml),
+    }
+    Ok(())
+}
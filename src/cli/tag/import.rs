@@ -0,0 +1,151 @@
+use super::Taxonomy;
+use crate::lib::client::EagleClient;
+use crate::lib::library::{generate_id, LibraryDir};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use serde_json::{json, Value};
+
+pub fn build() -> Command {
+    Command::new("import")
+        .about("Apply a YAML taxonomy file to the current library")
+        .arg(
+            Arg::new("file")
+                .value_name("FILE")
+                .help("Taxonomy file written by `tag export`")
+                .required(true),
+        )
+        .arg(
+            Arg::new("starred")
+                .long("starred")
+                .help("Pre-create the taxonomy's starred tags")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("all_tags")
+                .long("all-tags")
+                .help("Pre-create every tag of the taxonomy, including ones not in a group")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("dry_run")
+                .long("dry-run")
+                .help("Show what would change without writing anything")
+                .action(ArgAction::SetTrue),
+        )
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = matches.get_one::<String>("file").unwrap();
+    let taxonomy: Taxonomy = serde_yaml::from_str(&std::fs::read_to_string(path)?)?;
+    let dry_run = matches.get_flag("dry_run");
+
+    let data = client.library().info().await?.data;
+    let library = LibraryDir::new(&data.library.path);
+
+    // Tag groups live in the library metadata.json
+    let mut metadata = library.read_metadata()?;
+    let groups = metadata
+        .as_object_mut()
+        .ok_or("Library metadata.json is not an object")?
+        .entry("tagsGroups")
+        .or_insert_with(|| json!([]))
+        .as_array_mut()
+        .ok_or("Library tagsGroups is not an array")?;
+
+    let mut groups_changed = false;
+    for group in &taxonomy.groups {
+        let existing = groups
+            .iter_mut()
+            .find(|existing| existing.get("name").and_then(Value::as_str) == Some(group.name.as_str()));
+
+        match existing {
+            Some(existing) => {
+                let tags = existing
+                    .as_object_mut()
+                    .unwrap()
+                    .entry("tags")
+                    .or_insert_with(|| json!([]))
+                    .as_array_mut()
+                    .ok_or("Tag group tags is not an array")?;
+                let missing: Vec<&String> = group
+                    .tags
+                    .iter()
+                    .filter(|tag| !tags.iter().any(|existing| existing.as_str() == Some(tag.as_str())))
+                    .collect();
+                if !missing.is_empty() {
+                    println!("Update group {} (+{} tags)", group.name, missing.len());
+                    tags.extend(missing.into_iter().map(|tag| json!(tag)));
+                    groups_changed = true;
+                }
+                if let Some(color) = group.color {
+                    let color = json!(color);
+                    if existing.get("color") != Some(&color) {
+                        println!("Set color of group {} to {}", group.name, color.as_str().unwrap_or_default());
+                        existing["color"] = color;
+                        groups_changed = true;
+                    }
+                }
+            }
+            None => {
+                println!("Create group {} ({} tags)", group.name, group.tags.len());
+                let mut new_group = json!({
+                    "id": generate_id(),
+                    "name": group.name,
+                    "tags": group.tags,
+                });
+                if let Some(color) = group.color {
+                    new_group["color"] = json!(color);
+                }
+                groups.push(new_group);
+                groups_changed = true;
+            }
+        }
+    }
+
+    // Starred tags live in tags.json; starring a tag makes it exist without any item using it
+    let mut library_tags = library.read_tags()?;
+    let mut tags_changed = false;
+    if matches.get_flag("starred") {
+        for tag in &taxonomy.starred {
+            if !library_tags.starred_tags.contains(tag) {
+                println!("Star tag {}", tag);
+                library_tags.starred_tags.push(tag.clone());
+                tags_changed = true;
+            }
+            if !library_tags.history_tags.contains(tag) {
+                library_tags.history_tags.push(tag.clone());
+                tags_changed = true;
+            }
+        }
+    }
+
+    if matches.get_flag("all_tags") {
+        for tag in &taxonomy.tags {
+            if !library_tags.history_tags.contains(tag) {
+                println!("Add tag {}", tag);
+                library_tags.history_tags.push(tag.clone());
+                tags_changed = true;
+            }
+        }
+    }
+
+    if !groups_changed && !tags_changed {
+        println!("Library already matches {}", path);
+        return Ok(());
+    }
+    if dry_run {
+        println!("Dry run: no changes written");
+        return Ok(());
+    }
+
+    if groups_changed {
+        library.write_metadata(&metadata)?;
+    }
+    if tags_changed {
+        library.write_tags(&library_tags)?;
+    }
+    println!("Imported taxonomy into {}. Restart Eagle to load the changes.", data.library.name);
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use crate::lib::client::EagleClient;
+use crate::lib::types::Color;
+use clap::{ArgMatches, Command};
+use serde::{Deserialize, Serialize};
+
+pub mod export;
+pub mod import;
+pub mod rename_prefix;
+pub mod suggest;
+pub mod tree;
+
+/// Separator of namespaced tags like `brand/acme/blue`
+pub const NAMESPACE_SEPARATOR: char = '/';
+
+/// Whether `tag` is `namespace` itself or nested under it; a trailing `/` on `namespace` is ignored.
+pub fn in_namespace(tag: &str, namespace: &str) -> bool {
+    let namespace = namespace.trim_end_matches(NAMESPACE_SEPARATOR);
+    match tag.strip_prefix(namespace) {
+        Some(rest) => rest.is_empty() || rest.starts_with(NAMESPACE_SEPARATOR),
+        None => false,
+    }
+}
+
+/// A shareable tag vocabulary: all tags, the starred ones, and tag groups.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Taxonomy {
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub starred: Vec<String>,
+    #[serde(default)]
+    pub groups: Vec<TaxonomyGroup>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TaxonomyGroup {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<Color>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+pub fn build() -> Command {
+    Command::new("tag")
+        .about("Tag")
+        .subcommand(export::build())
+        .subcommand(import::build())
+        .subcommand(rename_prefix::build())
+        .subcommand(suggest::build())
+        .subcommand(tree::build())
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match matches.subcommand() {
+        Some(("export", export_matches)) => {
+            export::execute(client, export_matches).await?;
+        },
+        Some(("import", import_matches)) => {
+            import::execute(client, import_matches).await?;
+        },
+        Some(("rename-prefix", rename_matches)) => {
+            rename_prefix::execute(client, rename_matches).await?;
+        },
+        Some(("suggest", suggest_matches)) => {
+            suggest::execute(client, suggest_matches).await?;
+        },
+        Some(("tree", tree_matches)) => {
+            tree::execute(client, tree_matches).await?;
+        },
+        _ => {
+            println!("No subcommand was used");
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,148 @@
+use super::{in_namespace, NAMESPACE_SEPARATOR};
+use crate::cli::item::list;
+use crate::cli::progress::Progress;
+use crate::cli::report::{self, Failures};
+use crate::cli::{confirm, output};
+use crate::lib::client::EagleClient;
+use crate::lib::library::LibraryDir;
+use crate::lib::types::UpdateItemParams;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use serde_json::{json, Value};
+
+pub fn build() -> Command {
+    Command::new("rename-prefix")
+        .about("Move a whole branch of namespaced tags, e.g. brand/acme to clients/acme")
+        .long_about(
+            "Move a whole branch of namespaced tags, e.g. brand/acme to clients/acme.\n\n\
+             The tag OLD itself and every tag below it (OLD/...) are renamed on items through \
+             Eagle, and in the library's tag groups and starred tags; restart Eagle to see those.",
+        )
+        .arg(Arg::new("old").value_name("OLD").help("Namespace to rename, e.g. brand").required(true))
+        .arg(Arg::new("new").value_name("NEW").help("New namespace, e.g. clients; empty to lift the branch to the top").required(true))
+        .arg(
+            Arg::new("dry_run")
+                .long("dry-run")
+                .help("Show the old and new tags of each item without changing anything")
+                .action(ArgAction::SetTrue),
+        )
+        .args(output::args())
+        .args(report::args())
+}
+
+/// `tag` with the `old` namespace replaced by `new`, or `None` when it's outside `old`
+fn rename(tag: &str, old: &str, new: &str) -> Option<String> {
+    if !in_namespace(tag, old) {
+        return None;
+    }
+    let rest = tag[old.len()..].trim_start_matches(NAMESPACE_SEPARATOR);
+    Some(match (new, rest) {
+        ("", rest) => rest.to_string(),
+        (new, "") => new.to_string(),
+        (new, rest) => format!("{}{}{}", new, NAMESPACE_SEPARATOR, rest),
+    })
+    .filter(|tag| !tag.is_empty())
+}
+
+/// Tags with the namespace renamed, keeping their order and dropping duplicates it creates
+fn rename_all(tags: &[String], old: &str, new: &str) -> Vec<String> {
+    let mut renamed: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = rename(tag, old, new).unwrap_or_else(|| tag.clone());
+        if !renamed.contains(&tag) {
+            renamed.push(tag);
+        }
+    }
+    renamed
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let old = matches.get_one::<String>("old").unwrap().trim_end_matches(NAMESPACE_SEPARATOR);
+    let new = matches.get_one::<String>("new").unwrap().trim_end_matches(NAMESPACE_SEPARATOR);
+    if old.is_empty() {
+        return Err("The namespace to rename can't be empty".into());
+    }
+    if old == new {
+        return Err("The old and new namespaces are the same".into());
+    }
+
+    let data = client.library().info().await?.data;
+    let library = LibraryDir::new(&data.library.path);
+    let resume = report::resume_targets(matches)?;
+    let changes: Vec<(String, String, Vec<String>, Vec<String>)> = list::all_items(client)
+        .await?
+        .into_iter()
+        .filter(|item| resume.as_ref().is_none_or(|ids| ids.contains(&item.id)))
+        .filter(|item| item.tags.iter().any(|tag| in_namespace(tag, old)))
+        .map(|item| {
+            let tags = rename_all(&item.tags, old, new);
+            (item.id, item.name, item.tags, tags)
+        })
+        .collect();
+
+    if matches.get_flag("dry_run") {
+        let rows: Vec<Value> = changes
+            .iter()
+            .map(|(id, name, old_tags, new_tags)| json!({ "id": id, "name": name, "old": old_tags, "new": new_tags }))
+            .collect();
+        return output::output(&Value::Array(rows), matches);
+    }
+
+    if !changes.is_empty() {
+        confirm::confirm(matches, &format!("Tags of {} items will be renamed", changes.len()))?;
+    }
+
+    let mut renamed = 0;
+    let mut failures = Failures::default();
+    let mut progress = Progress::new(changes.len(), "retagging", matches);
+    for (id, name, _, tags) in &changes {
+        progress.inc();
+        let params = UpdateItemParams {
+            id: id.clone(),
+            tags: Some(tags.clone()),
+            ..Default::default()
+        };
+        match client.item().update(&params).await {
+            Ok(_) => renamed += 1,
+            Err(error) => {
+                progress.finish();
+                eprintln!("Failed to retag {} ({}): {}", id, name, error);
+                failures.add(id, error);
+            }
+        }
+    }
+    progress.finish();
+
+    // Tag groups and starred tags aren't reachable through the API
+    let mut metadata = library.read_metadata()?;
+    let mut groups_changed = false;
+    for group in metadata["tagsGroups"].as_array_mut().into_iter().flatten() {
+        let tags: Vec<String> = serde_json::from_value(group["tags"].clone()).unwrap_or_default();
+        if tags.iter().any(|tag| in_namespace(tag, old)) {
+            group["tags"] = json!(rename_all(&tags, old, new));
+            groups_changed = true;
+        }
+    }
+    let mut library_tags = library.read_tags()?;
+    let tags_changed = library_tags
+        .history_tags
+        .iter()
+        .chain(&library_tags.starred_tags)
+        .any(|tag| in_namespace(tag, old));
+    if groups_changed {
+        library.write_metadata(&metadata)?;
+    }
+    if tags_changed {
+        library_tags.history_tags = rename_all(&library_tags.history_tags, old, new);
+        library_tags.starred_tags = rename_all(&library_tags.starred_tags, old, new);
+        library.write_tags(&library_tags)?;
+    }
+
+    eprintln!("Retagged {} items ({} failed)", renamed, failures.len());
+    if groups_changed || tags_changed {
+        eprintln!("Renamed tags in the library's tag groups and starred tags. Restart Eagle to load the changes.");
+    }
+    report::finish(matches, "tag rename-prefix", renamed, failures)
+}
@@ -0,0 +1,122 @@
+use crate::cli::item::list;
+use crate::cli::output;
+use crate::lib::client::EagleClient;
+use crate::lib::types::UpdateItemParams;
+use clap::{Arg, ArgMatches, Command};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+
+pub fn build() -> Command {
+    Command::new("suggest")
+        .about("Suggest tags for an item from the tags it shares with other items and the items in its folders")
+        .long_about(
+            "Suggest tags for an item from the tags it shares with other items and the items in its folders.\n\n\
+             Every other item votes for the tags the item lacks, with one vote per tag the two share \
+             and one per folder they share. Tags are ranked by votes, and by how many items voted on a tie.",
+        )
+        .arg(Arg::new("id").value_name("ITEM_ID").help("Item to suggest tags for").required(true))
+        .arg(
+            Arg::new("limit")
+                .short('n')
+                .long("limit")
+                .value_name("N")
+                .help("Number of suggestions to show")
+                .num_args(1)
+                .default_value("10")
+                .value_parser(clap::value_parser!(usize)),
+        )
+        .arg(
+            Arg::new("apply_top")
+                .long("apply-top")
+                .value_name("N")
+                .help("Add the N best suggestions to the item")
+                .num_args(1)
+                .value_parser(clap::builder::RangedU64ValueParser::<usize>::new().range(1..)),
+        )
+        .args(output::args())
+}
+
+/// Votes for a candidate tag
+#[derive(Default)]
+struct Score {
+    /// Votes from tags shared with the voting items
+    tags: usize,
+    /// Votes from folders shared with the voting items
+    folders: usize,
+    /// Items that voted
+    items: usize,
+}
+
+impl Score {
+    fn total(&self) -> usize {
+        self.tags + self.folders
+    }
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let id = matches.get_one::<String>("id").unwrap();
+    let items = list::all_items(client).await?;
+    let item = items
+        .iter()
+        .find(|item| &item.id == id)
+        .ok_or_else(|| format!("No item with id {}", id))?;
+    let tags: HashSet<&String> = item.tags.iter().collect();
+    let folders: HashSet<&String> = item.folders.iter().flatten().collect();
+
+    let mut scores: HashMap<&String, Score> = HashMap::new();
+    for other in items.iter().filter(|other| other.id != item.id) {
+        let shared_tags = other.tags.iter().filter(|tag| tags.contains(tag)).count();
+        let shared_folders = other.folders.iter().flatten().filter(|folder| folders.contains(folder)).count();
+        if shared_tags + shared_folders == 0 {
+            continue;
+        }
+        for tag in other.tags.iter().filter(|tag| !tags.contains(tag)) {
+            let score = scores.entry(tag).or_default();
+            score.tags += shared_tags;
+            score.folders += shared_folders;
+            score.items += 1;
+        }
+    }
+    let mut ranked: Vec<(&String, Score)> = scores.into_iter().collect();
+    ranked.sort_by(|(a_tag, a), (b_tag, b)| {
+        b.total()
+            .cmp(&a.total())
+            .then(b.items.cmp(&a.items))
+            .then(a_tag.cmp(b_tag))
+    });
+
+    if let Some(&top) = matches.get_one::<usize>("apply_top") {
+        let added: Vec<String> = ranked.iter().take(top).map(|(tag, _)| (*tag).clone()).collect();
+        if added.is_empty() {
+            eprintln!("No tags to suggest for {}", id);
+            return Ok(());
+        }
+        let params = UpdateItemParams {
+            id: id.clone(),
+            tags: Some(item.tags.iter().chain(&added).cloned().collect()),
+            ..Default::default()
+        };
+        client.item().update(&params).await?;
+        println!("Tagged {} with {}", id, added.join(", "));
+        return Ok(());
+    }
+
+    let limit = *matches.get_one::<usize>("limit").unwrap();
+    let rows: Vec<Value> = ranked
+        .iter()
+        .take(limit)
+        .map(|(tag, score)| {
+            json!({
+                "tag": tag,
+                "score": score.total(),
+                "tagVotes": score.tags,
+                "folderVotes": score.folders,
+                "items": score.items,
+            })
+        })
+        .collect();
+    output::output(&Value::Array(rows), matches)
+}
@@ -0,0 +1,116 @@
+use super::{in_namespace, NAMESPACE_SEPARATOR};
+use crate::cli::item::list;
+use crate::cli::output;
+use crate::cli::pager;
+use crate::cli::theme::Theme;
+use crate::lib::client::EagleClient;
+use crate::lib::library::LibraryDir;
+use clap::{Arg, ArgMatches, Command};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet};
+
+pub fn build() -> Command {
+    Command::new("tree")
+        .about("Show namespaced tags like brand/acme/blue as a tree, with the number of items under each")
+        .arg(
+            Arg::new("prefix")
+                .value_name("PREFIX")
+                .help("Only show this namespace, e.g. brand/"),
+        )
+        .args(output::args())
+}
+
+/// A segment of a tag path, counting the items tagged with it or anything below it
+#[derive(Default)]
+struct Node {
+    items: usize,
+    children: BTreeMap<String, Node>,
+}
+
+impl Node {
+    fn insert(&mut self, tag: &str) -> &mut Node {
+        tag.split(NAMESPACE_SEPARATOR)
+            .filter(|segment| !segment.is_empty())
+            .fold(self, |node, segment| node.children.entry(segment.to_string()).or_default())
+    }
+
+    /// Count an item once in every node on the paths of its tags
+    fn count(&mut self, tags: &[String]) {
+        let mut paths: BTreeSet<Vec<&str>> = BTreeSet::new();
+        for tag in tags {
+            let segments: Vec<&str> = tag.split(NAMESPACE_SEPARATOR).filter(|segment| !segment.is_empty()).collect();
+            for depth in 1..=segments.len() {
+                paths.insert(segments[..depth].to_vec());
+            }
+        }
+        for path in paths {
+            let node = path.iter().fold(&mut *self, |node, segment| node.children.entry(segment.to_string()).or_default());
+            node.items += 1;
+        }
+    }
+
+    fn rows(&self, parent: &str, depth: usize, rows: &mut Vec<Value>) {
+        for (name, node) in &self.children {
+            let tag = match parent {
+                "" => name.clone(),
+                parent => format!("{}{}{}", parent, NAMESPACE_SEPARATOR, name),
+            };
+            rows.push(json!({ "tag": tag, "name": name, "depth": depth, "items": node.items }));
+            node.rows(&tag, depth + 1, rows);
+        }
+    }
+
+    fn render(&self, indent: &str, depth: usize, theme: &Theme, text: &mut String) {
+        let count = self.children.len();
+        for (i, (name, node)) in self.children.iter().enumerate() {
+            let last = i == count - 1;
+            let (corner, vertical_line) = match (depth, last) {
+                (0, _) => ("", ""),
+                (_, true) => ("╰── ", "    "),
+                (_, false) => ("├── ", "│   "),
+            };
+            text.push_str(&format!(
+                "{}{}{} ({})\n",
+                indent,
+                theme.depth(depth, corner),
+                theme.depth(depth, name),
+                node.items
+            ));
+            node.render(&format!("{}{}", indent, vertical_line), depth + 1, theme, text);
+        }
+    }
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let data = client.library().info().await?.data;
+    let library_tags = LibraryDir::new(&data.library.path).read_tags()?;
+    let prefix = matches.get_one::<String>("prefix");
+    let wanted = |tag: &String| prefix.is_none_or(|prefix| in_namespace(tag, prefix));
+
+    let mut root = Node::default();
+    // Tags Eagle remembers show up even when no item uses them any more
+    for tag in library_tags.history_tags.iter().chain(&library_tags.starred_tags).filter(|tag| wanted(tag)) {
+        root.insert(tag);
+    }
+    for item in list::all_items(client).await? {
+        let tags: Vec<String> = item.tags.into_iter().filter(|tag| wanted(tag)).collect();
+        root.count(&tags);
+    }
+
+    if output::is_explicit(matches) {
+        let mut rows = Vec::new();
+        root.rows("", 0, &mut rows);
+        return output::output(&Value::Array(rows), matches);
+    }
+    if root.children.is_empty() {
+        eprintln!("No tags");
+        return Ok(());
+    }
+    let mut text = String::new();
+    root.render("", 0, &Theme::from_matches(matches), &mut text);
+    pager::print(&text, matches)?;
+    Ok(())
+}
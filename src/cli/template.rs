@@ -0,0 +1,218 @@
+use crate::cli::datetime::{self, TimeZone};
+use serde_json::Value;
+use std::str::FromStr;
+
+/// Date format of the `date` filter when none is given
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// An output template such as `{id}\t{name}\t{tags|join:, }`, rendered once per row.
+///
+/// `{a.b.0}` reads a field, following object keys and array indexes, and `{.}` is the whole
+/// row. Filters after `|` transform the value in turn:
+///
+/// - `join[:SEP]` joins a list, with `, ` by default
+/// - `first` and `count` take the first element and the length of a list
+/// - `date[:FORMAT]` formats epoch milliseconds with strftime in the `--tz` zone, `%Y-%m-%d` by default
+/// - `upper` and `lower` change case
+/// - `default:TEXT` replaces a missing or empty value
+///
+/// `\t`, `\n` and `\\` are escapes, and `{{` and `}}` stand for literal braces.
+#[derive(Debug, Clone)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Clone)]
+enum Part {
+    Literal(String),
+    Field { path: Vec<String>, filters: Vec<Filter> },
+}
+
+#[derive(Debug, Clone)]
+enum Filter {
+    Join(String),
+    First,
+    Count,
+    Date(String),
+    Upper,
+    Lower,
+    Default(String),
+}
+
+impl FromStr for Filter {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (name, arg) = match value.split_once(':') {
+            Some((name, arg)) => (name.trim(), Some(arg)),
+            None => (value.trim(), None),
+        };
+        Ok(match (name, arg) {
+            ("join", arg) => Filter::Join(arg.unwrap_or(", ").to_string()),
+            ("first", None) => Filter::First,
+            ("count", None) => Filter::Count,
+            ("date", arg) => {
+                let format = arg.unwrap_or(DEFAULT_DATE_FORMAT);
+                if !datetime::is_valid_format(format) {
+                    return Err(format!("invalid date format: {}", format));
+                }
+                Filter::Date(format.to_string())
+            }
+            ("upper", None) => Filter::Upper,
+            ("lower", None) => Filter::Lower,
+            ("default", Some(arg)) => Filter::Default(arg.to_string()),
+            _ => {
+                return Err(format!(
+                    "unknown filter: {} (expected join[:SEP], first, count, date[:FORMAT], upper, lower, default:TEXT)",
+                    value
+                ))
+            }
+        })
+    }
+}
+
+impl FromStr for Template {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = value.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some('t') => literal.push('\t'),
+                    Some('n') => literal.push('\n'),
+                    Some(other) => literal.push(other),
+                    None => literal.push('\\'),
+                },
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut field = String::new();
+                    let mut closed = false;
+                    for c in chars.by_ref() {
+                        if c == '}' {
+                            closed = true;
+                            break;
+                        }
+                        field.push(c);
+                    }
+                    if !closed {
+                        return Err(format!("unclosed {{ in template: {}", value));
+                    }
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(parse_field(&field)?);
+                }
+                '}' => return Err(format!("unmatched }} in template (write }}}} for a brace): {}", value)),
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+        if parts.is_empty() {
+            return Err("empty template".to_string());
+        }
+        Ok(Template { parts })
+    }
+}
+
+fn parse_field(field: &str) -> Result<Part, String> {
+    let mut pieces = field.split('|');
+    let path = pieces.next().unwrap_or_default().trim();
+    if path.is_empty() {
+        return Err("empty field in template; write {.} for the whole row".to_string());
+    }
+    let path = match path {
+        "." => Vec::new(),
+        path => path.split('.').map(String::from).collect(),
+    };
+    let filters = pieces.map(str::parse).collect::<Result<_, _>>()?;
+    Ok(Part::Field { path, filters })
+}
+
+impl Template {
+    /// One line per row of an array, or a single line for any other value
+    pub fn render_all(&self, value: &Value, tz: &TimeZone) -> String {
+        let rows = match value {
+            Value::Array(rows) => rows.as_slice(),
+            _ => std::slice::from_ref(value),
+        };
+        rows.iter().map(|row| format!("{}\n", self.render(row, tz))).collect()
+    }
+
+    pub fn render(&self, row: &Value, tz: &TimeZone) -> String {
+        let mut out = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Literal(literal) => out.push_str(literal),
+                Part::Field { path, filters } => {
+                    let value = path
+                        .iter()
+                        .try_fold(row, |value, key| match value {
+                            Value::Array(values) => key.parse::<usize>().ok().and_then(|index| values.get(index)),
+                            _ => value.get(key),
+                        })
+                        .cloned()
+                        .unwrap_or(Value::Null);
+                    let value = filters.iter().fold(value, |value, filter| apply(filter, value, tz));
+                    out.push_str(&text(&value));
+                }
+            }
+        }
+        out
+    }
+}
+
+fn apply(filter: &Filter, value: Value, tz: &TimeZone) -> Value {
+    match filter {
+        Filter::Join(separator) => match value {
+            Value::Array(values) => Value::String(values.iter().map(text).collect::<Vec<_>>().join(separator)),
+            value => value,
+        },
+        Filter::First => match value {
+            Value::Array(values) => values.into_iter().next().unwrap_or(Value::Null),
+            value => value,
+        },
+        Filter::Count => Value::from(match &value {
+            Value::Array(values) => values.len(),
+            Value::Object(object) => object.len(),
+            Value::String(string) => string.chars().count(),
+            Value::Null => 0,
+            _ => 1,
+        }),
+        Filter::Date(format) => match value.as_i64() {
+            Some(millis) => Value::String(tz.format_millis_as(millis, format)),
+            None => value,
+        },
+        Filter::Upper => Value::String(text(&value).to_uppercase()),
+        Filter::Lower => Value::String(text(&value).to_lowercase()),
+        Filter::Default(default) => match &value {
+            Value::Null => Value::String(default.clone()),
+            Value::String(string) if string.is_empty() => Value::String(default.clone()),
+            Value::Array(values) if values.is_empty() => Value::String(default.clone()),
+            _ => value,
+        },
+    }
+}
+
+/// A value as template text: strings as they are, lists of plain values joined with `, `
+fn text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(string) => string.clone(),
+        Value::Array(values) if values.iter().all(|value| !value.is_object() && !value.is_array()) => {
+            values.iter().map(text).collect::<Vec<_>>().join(", ")
+        }
+        _ => value.to_string(),
+    }
+}
@@ -0,0 +1,91 @@
+use clap::{Arg, ArgMatches};
+use std::io::IsTerminal;
+
+/// Colors of folder tree levels, cycled from the top
+const DEPTH_COLORS: [&str; 6] = ["31", "32", "33", "34", "35", "36"];
+
+pub fn arg() -> Arg {
+    Arg::new("color_mode")
+        .long("color")
+        .value_name("WHEN")
+        .help("Color human output: auto colors a terminal unless NO_COLOR is set")
+        .num_args(1)
+        .default_value("auto")
+        .value_parser(["auto", "always", "never"])
+        .global(true)
+}
+
+/// Colors for tables and trees printed to a terminal, or no colors at all.
+///
+/// Only human output is colored; JSON, CSV, and the other machine formats never are.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Theme {
+    enabled: bool,
+}
+
+impl Theme {
+    /// Theme selected with `--color`: `auto` colors when stdout is a terminal and neither
+    /// `NO_COLOR` is set nor `TERM` is `dumb`.
+    pub fn from_matches(matches: &ArgMatches) -> Self {
+        let mode = matches.try_get_one::<String>("color_mode").ok().flatten().map(String::as_str);
+        let enabled = match mode {
+            Some("always") => true,
+            Some("never") => false,
+            _ => {
+                std::io::stdout().is_terminal()
+                    && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+                    && std::env::var("TERM").map_or(true, |term| term != "dumb")
+            }
+        };
+        Theme { enabled }
+    }
+
+    fn paint(&self, code: &str, text: &str) -> String {
+        match self.enabled && !text.is_empty() {
+            true => format!("\x1b[{}m{}\x1b[0m", code, text),
+            false => text.to_string(),
+        }
+    }
+
+    pub fn header(&self, text: &str) -> String {
+        self.paint("1", text)
+    }
+
+    /// A folder tree level, each in its own color
+    pub fn depth(&self, depth: usize, text: &str) -> String {
+        self.paint(DEPTH_COLORS[depth % DEPTH_COLORS.len()], text)
+    }
+
+    /// A star rating: yellow when rated, dimmed when not
+    pub fn stars(&self, text: &str) -> String {
+        match text.trim() {
+            "0" => self.paint("2", text),
+            _ => self.paint("33", text),
+        }
+    }
+
+    /// A byte count or size such as `2.1 MB`, colored by magnitude so large files stand out
+    pub fn size(&self, text: &str) -> String {
+        let Ok(bytes) = crate::cli::units::parse_size(text) else {
+            return text.to_string();
+        };
+        match bytes {
+            0..=999_999 => self.paint("32", text),
+            1_000_000..=99_999_999 => self.paint("33", text),
+            _ => self.paint("31", text),
+        }
+    }
+
+    /// A line of a diff, by its `+`, `-`, or `~` marker
+    pub fn diff(&self, marker: char, text: &str) -> String {
+        match marker {
+            '+' => self.paint("32", text),
+            '-' => self.paint("31", text),
+            _ => self.paint("33", text),
+        }
+    }
+
+    pub fn error(&self, text: &str) -> String {
+        self.paint("31", text)
+    }
+}
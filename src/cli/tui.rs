@@ -0,0 +1,515 @@
+use crate::cli::datetime::{self, TimeZone};
+use crate::cli::folder::folder_paths;
+use crate::cli::item::list::{self, filter::ItemFilter};
+use crate::cli::{system, units};
+use crate::lib::client::EagleClient;
+use crate::lib::library::LibraryDir;
+use crate::lib::types::{Child, ItemListData, UpdateItemParams};
+use clap::{ArgMatches, Command};
+use image::imageops::FilterType;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::HashMap;
+
+const KEYS_HELP: &str = "Tab pane  / search  t tag  T untag  0-5 rate  d trash  o open in Eagle  q quit";
+
+pub fn build() -> Command {
+    Command::new("tui")
+        .about("Browse and triage the library in an interactive terminal UI")
+        .long_about(
+            "Browse and triage the library in an interactive terminal UI.\n\n\
+             The left pane lists folders, the middle one the items in the selected folder, and the \
+             right one a thumbnail and the metadata of the selected item.\n\n\
+             Keys: Tab/Left/Right switch pane, Up/Down or j/k move, / search, t add tags, \
+             T remove tags, 0-5 set the rating, d move to trash, o open in Eagle, q or Esc quit.",
+        )
+        .args(list::query_args())
+        .args(list::filter::args())
+        // The browser works on the whole selection, so always walk every page
+        .mut_arg("all", |arg| arg.default_value("true").hide(true))
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let library_data = client.library().info().await?.data;
+    let library = LibraryDir::new(&library_data.library.path);
+    let item_filter = ItemFilter::from_matches(matches)?;
+    let items = list::fetch_items(client, matches, &item_filter).await?;
+    let folders = client.folder().list().await?.data;
+
+    let mut browser = Browser::new(items, &folders, library, datetime::from_matches(matches));
+    let mut terminal = ratatui::init();
+    let result = browser.run(&mut terminal, client).await;
+    ratatui::restore();
+    result
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Pane {
+    Folders,
+    Items,
+}
+
+enum Mode {
+    Browse,
+    Search,
+    /// Typing comma separated tags to add (or remove) on the selected item
+    EditTags { add: bool, input: String },
+    ConfirmTrash,
+}
+
+/// A row of the folder pane; `id` is `None` for "All items"
+struct FolderRow {
+    id: Option<String>,
+    label: String,
+}
+
+struct Browser {
+    items: Vec<ItemListData>,
+    folders: Vec<FolderRow>,
+    folder_paths: HashMap<String, String>,
+    library: LibraryDir,
+    tz: TimeZone,
+    /// Indices into `items` shown in the item pane
+    visible: Vec<usize>,
+    folder_state: ListState,
+    item_state: ListState,
+    focus: Pane,
+    mode: Mode,
+    query: String,
+    status: String,
+    /// Rendered thumbnail of the selected item, keyed by item id and area
+    preview: Option<(String, Rect, Vec<Line<'static>>)>,
+}
+
+impl Browser {
+    fn new(items: Vec<ItemListData>, folders: &[Child], library: LibraryDir, tz: TimeZone) -> Self {
+        let mut rows = vec![FolderRow { id: None, label: "All items".to_string() }];
+        flatten_folders(folders, 0, &mut rows);
+
+        let mut browser = Browser {
+            items,
+            folders: rows,
+            folder_paths: folder_paths(folders),
+            library,
+            tz,
+            visible: Vec::new(),
+            folder_state: ListState::default().with_selected(Some(0)),
+            item_state: ListState::default(),
+            focus: Pane::Items,
+            mode: Mode::Browse,
+            query: String::new(),
+            status: String::new(),
+            preview: None,
+        };
+        browser.refresh();
+        browser
+    }
+
+    async fn run(&mut self, terminal: &mut DefaultTerminal, client: &EagleClient) -> Result<(), Box<dyn std::error::Error>> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind == KeyEventKind::Release {
+                continue;
+            }
+            if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                return Ok(());
+            }
+
+            match std::mem::replace(&mut self.mode, Mode::Browse) {
+                Mode::Browse => {
+                    if !self.on_browse_key(key, client).await {
+                        return Ok(());
+                    }
+                }
+                Mode::Search => self.on_search_key(key),
+                Mode::EditTags { add, mut input } => match key.code {
+                    KeyCode::Esc => self.status = "Cancelled".to_string(),
+                    KeyCode::Enter => self.edit_tags(client, &input, add).await,
+                    KeyCode::Backspace => {
+                        input.pop();
+                        self.mode = Mode::EditTags { add, input };
+                    }
+                    KeyCode::Char(c) => {
+                        input.push(c);
+                        self.mode = Mode::EditTags { add, input };
+                    }
+                    _ => self.mode = Mode::EditTags { add, input },
+                },
+                Mode::ConfirmTrash => {
+                    if key.code == KeyCode::Char('y') {
+                        self.trash(client).await;
+                    } else {
+                        self.status = "Cancelled".to_string();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Handle a key in browse mode; returns `false` to quit
+    async fn on_browse_key(&mut self, key: KeyEvent, client: &EagleClient) -> bool {
+        self.status.clear();
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Tab | KeyCode::BackTab | KeyCode::Left | KeyCode::Right | KeyCode::Char('h') | KeyCode::Char('l') => {
+                self.focus = match (self.focus, key.code) {
+                    (_, KeyCode::Left | KeyCode::Char('h')) => Pane::Folders,
+                    (_, KeyCode::Right | KeyCode::Char('l')) => Pane::Items,
+                    (Pane::Folders, _) => Pane::Items,
+                    (Pane::Items, _) => Pane::Folders,
+                };
+            }
+            KeyCode::Enter if self.focus == Pane::Folders => self.focus = Pane::Items,
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::PageDown => self.move_selection(10),
+            KeyCode::PageUp => self.move_selection(-10),
+            KeyCode::Home | KeyCode::Char('g') => self.move_selection(isize::MIN),
+            KeyCode::End | KeyCode::Char('G') => self.move_selection(isize::MAX),
+            KeyCode::Char('/') => self.mode = Mode::Search,
+            KeyCode::Char('t') | KeyCode::Char('T') if self.selected_item().is_some() => {
+                self.mode = Mode::EditTags { add: key.code == KeyCode::Char('t'), input: String::new() };
+            }
+            KeyCode::Char(c @ '0'..='5') => self.rate(client, c as u8 - b'0').await,
+            KeyCode::Char('d') | KeyCode::Delete if self.selected_item().is_some() => self.mode = Mode::ConfirmTrash,
+            KeyCode::Char('o') => {
+                if let Some(item) = self.selected_item() {
+                    if let Err(error) = system::open(&format!("eagle://item/{}", item.id)) {
+                        self.status = format!("Could not open item: {}", error);
+                    }
+                }
+            }
+            _ => {}
+        }
+        true
+    }
+
+    fn on_search_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.query.clear();
+                self.refresh();
+                return;
+            }
+            KeyCode::Enter => return,
+            KeyCode::Backspace => {
+                self.query.pop();
+            }
+            KeyCode::Char(c) => self.query.push(c),
+            _ => {}
+        }
+        self.refresh();
+        self.mode = Mode::Search;
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        let (state, len) = match self.focus {
+            Pane::Folders => (&mut self.folder_state, self.folders.len()),
+            Pane::Items => (&mut self.item_state, self.visible.len()),
+        };
+        if len == 0 {
+            return;
+        }
+        let current = state.selected().unwrap_or(0) as isize;
+        let next = current.saturating_add(delta).clamp(0, len as isize - 1) as usize;
+        state.select(Some(next));
+        if self.focus == Pane::Folders {
+            self.refresh();
+        }
+    }
+
+    /// Recompute the visible items after the folder or search query changed
+    fn refresh(&mut self) {
+        let folder = self
+            .folder_state
+            .selected()
+            .and_then(|index| self.folders.get(index))
+            .and_then(|row| row.id.clone());
+        let terms: Vec<String> = self.query.split_whitespace().map(str::to_lowercase).collect();
+
+        let selected_id = self.selected_item().map(|item| item.id.clone());
+        self.visible = self
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| {
+                folder
+                    .as_ref()
+                    .is_none_or(|folder| item.folders.iter().flatten().any(|id| id == folder))
+            })
+            .filter(|(_, item)| {
+                let haystack = format!("{}.{} {} {}", item.name, item.ext, item.tags.join(" "), item.annotation).to_lowercase();
+                terms.iter().all(|term| haystack.contains(term))
+            })
+            .map(|(index, _)| index)
+            .collect();
+
+        // Keep the same item selected when it is still visible
+        let position = selected_id
+            .and_then(|id| self.visible.iter().position(|&index| self.items[index].id == id))
+            .or(if self.visible.is_empty() { None } else { Some(0) });
+        self.item_state.select(position);
+    }
+
+    fn selected_item(&self) -> Option<&ItemListData> {
+        let index = *self.visible.get(self.item_state.selected()?)?;
+        self.items.get(index)
+    }
+
+    fn selected_index(&self) -> Option<usize> {
+        self.visible.get(self.item_state.selected()?).copied()
+    }
+
+    async fn edit_tags(&mut self, client: &EagleClient, input: &str, add: bool) {
+        let Some(index) = self.selected_index() else {
+            return;
+        };
+        let edited: Vec<String> = input
+            .split(',')
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .map(String::from)
+            .collect();
+        let mut tags = self.items[index].tags.clone();
+        if add {
+            tags.extend(edited.iter().filter(|tag| !self.items[index].tags.contains(tag)).cloned());
+        } else {
+            tags.retain(|tag| !edited.contains(tag));
+        }
+
+        let params = UpdateItemParams {
+            id: self.items[index].id.clone(),
+            tags: Some(tags.clone()),
+            ..Default::default()
+        };
+        match client.item().update(&params).await {
+            Ok(_) => {
+                self.status = format!("Tags: {}", tags.join(", "));
+                self.items[index].tags = tags;
+            }
+            Err(error) => self.status = format!("Could not update tags: {}", error),
+        }
+    }
+
+    async fn rate(&mut self, client: &EagleClient, star: u8) {
+        let Some(index) = self.selected_index() else {
+            return;
+        };
+        let params = UpdateItemParams {
+            id: self.items[index].id.clone(),
+            star: Some(star),
+            ..Default::default()
+        };
+        match client.item().update(&params).await {
+            Ok(_) => {
+                self.status = format!("Rated {}", stars(star));
+                self.items[index].star = Some(star);
+            }
+            Err(error) => self.status = format!("Could not update rating: {}", error),
+        }
+    }
+
+    async fn trash(&mut self, client: &EagleClient) {
+        let Some(index) = self.selected_index() else {
+            return;
+        };
+        let id = self.items[index].id.clone();
+        match client.item().move_to_trash(std::slice::from_ref(&id)).await {
+            Ok(_) => {
+                let item = self.items.remove(index);
+                self.status = format!("Moved {}.{} to trash", item.name, item.ext);
+                self.refresh();
+            }
+            Err(error) => self.status = format!("Could not move to trash: {}", error),
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [search_area, main_area, status_area] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(5),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [folders_area, items_area, details_area] = Layout::horizontal([
+            Constraint::Percentage(22),
+            Constraint::Percentage(38),
+            Constraint::Percentage(40),
+        ])
+        .areas(main_area);
+
+        let searching = matches!(self.mode, Mode::Search);
+        let search = Paragraph::new(self.query.as_str()).block(pane_block("Search", searching));
+        frame.render_widget(search, search_area);
+        if searching {
+            frame.set_cursor_position((search_area.x + 1 + self.query.chars().count() as u16, search_area.y + 1));
+        }
+
+        let folders: Vec<ListItem> = self.folders.iter().map(|row| ListItem::new(row.label.as_str())).collect();
+        let folders = List::new(folders)
+            .block(pane_block("Folders", self.focus == Pane::Folders))
+            .highlight_style(highlight(self.focus == Pane::Folders));
+        frame.render_stateful_widget(folders, folders_area, &mut self.folder_state);
+
+        let items: Vec<ListItem> = self
+            .visible
+            .iter()
+            .map(|&index| {
+                let item = &self.items[index];
+                Line::from(vec![
+                    Span::raw(format!("{}.{}", item.name, item.ext)),
+                    Span::styled(format!(" {}", stars(item.star.unwrap_or(0))), Style::new().fg(Color::Yellow)),
+                ])
+                .into()
+            })
+            .collect();
+        let title = format!("Items ({}/{})", self.visible.len(), self.items.len());
+        let items = List::new(items)
+            .block(pane_block(&title, self.focus == Pane::Items))
+            .highlight_style(highlight(self.focus == Pane::Items));
+        frame.render_stateful_widget(items, items_area, &mut self.item_state);
+
+        self.draw_details(frame, details_area);
+
+        let status = match &self.mode {
+            Mode::EditTags { add: true, input } => format!("Add tags (comma separated): {}", input),
+            Mode::EditTags { add: false, input } => format!("Remove tags (comma separated): {}", input),
+            Mode::ConfirmTrash => "Move the selected item to trash? (y/n)".to_string(),
+            _ if !self.status.is_empty() => self.status.clone(),
+            _ => KEYS_HELP.to_string(),
+        };
+        frame.render_widget(Paragraph::new(status).style(Style::new().add_modifier(Modifier::DIM)), status_area);
+    }
+
+    fn draw_details(&mut self, frame: &mut Frame, area: Rect) {
+        let block = pane_block("Details", false);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+        let Some(item) = self.selected_item() else {
+            return;
+        };
+
+        let [thumbnail_area, metadata_area] =
+            Layout::vertical([Constraint::Percentage(50), Constraint::Min(3)]).areas(inner);
+
+        let id = item.id.clone();
+        let cached = matches!(&self.preview, Some((cached_id, cached_area, _)) if *cached_id == id && *cached_area == thumbnail_area);
+        if !cached {
+            let lines = self.thumbnail_lines(item, thumbnail_area);
+            self.preview = Some((id, thumbnail_area, lines));
+        }
+        if let Some((_, _, lines)) = &self.preview {
+            frame.render_widget(Paragraph::new(lines.clone()), thumbnail_area);
+        }
+
+        let Some(item) = self.selected_item() else {
+            return;
+        };
+        let folders: Vec<&str> = item
+            .folders
+            .iter()
+            .flatten()
+            .filter_map(|id| self.folder_paths.get(id).map(String::as_str))
+            .collect();
+        let dimensions = match (item.width, item.height) {
+            (Some(width), Some(height)) => format!("{}x{}", width, height),
+            _ => "-".to_string(),
+        };
+        let path = list::item_path(&self.library, item, false);
+        let rows = [
+            ("Name", format!("{}.{}", item.name, item.ext)),
+            ("ID", item.id.clone()),
+            ("Size", units::format_size(item.size)),
+            ("Dimensions", dimensions),
+            ("Rating", stars(item.star.unwrap_or(0))),
+            ("Tags", item.tags.join(", ")),
+            ("Folders", folders.join(", ")),
+            ("Modified", self.tz.format_millis(item.modification_time as i64)),
+            ("URL", item.url.clone()),
+            ("Note", item.annotation.clone()),
+            ("Path", path.display().to_string()),
+        ];
+        let lines: Vec<Line> = rows
+            .into_iter()
+            .map(|(label, value)| {
+                Line::from(vec![
+                    Span::styled(format!("{:<11}", label), Style::new().add_modifier(Modifier::BOLD)),
+                    Span::raw(value),
+                ])
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), metadata_area);
+    }
+
+    /// The item's thumbnail as half-block cells fitting `area`, or a note when there is none
+    fn thumbnail_lines(&self, item: &ItemListData, area: Rect) -> Vec<Line<'static>> {
+        let image = self
+            .library
+            .item_thumbnail(&item.id, &item.name)
+            .or_else(|| self.library.item_file(&item.id, &item.name, &item.ext))
+            .and_then(|path| image::open(path).ok());
+        let Some(image) = image else {
+            return vec![Line::styled("(no preview)", Style::new().add_modifier(Modifier::DIM))];
+        };
+
+        // Each cell shows two pixels stacked vertically
+        let image = image.resize(area.width.max(1) as u32, area.height.max(1) as u32 * 2, FilterType::Triangle).to_rgb8();
+        let (width, height) = image.dimensions();
+        (0..height)
+            .step_by(2)
+            .map(|y| {
+                let spans: Vec<Span> = (0..width)
+                    .map(|x| {
+                        let [r, g, b] = image.get_pixel(x, y).0;
+                        let mut style = Style::new().fg(Color::Rgb(r, g, b));
+                        if y + 1 < height {
+                            let [r, g, b] = image.get_pixel(x, y + 1).0;
+                            style = style.bg(Color::Rgb(r, g, b));
+                        }
+                        Span::styled("▀", style)
+                    })
+                    .collect();
+                Line::from(spans)
+            })
+            .collect()
+    }
+}
+
+/// Append `folders` depth first, indenting children under their parent
+fn flatten_folders(folders: &[Child], depth: usize, rows: &mut Vec<FolderRow>) {
+    for folder in folders {
+        rows.push(FolderRow {
+            id: Some(folder.id.clone()),
+            label: format!("{}{}", "  ".repeat(depth), folder.name),
+        });
+        flatten_folders(&folder.children, depth + 1, rows);
+    }
+}
+
+fn pane_block(title: &str, focused: bool) -> Block<'static> {
+    let style = if focused { Style::new().fg(Color::Cyan) } else { Style::new() };
+    Block::bordered().title(title.to_string()).border_style(style)
+}
+
+fn highlight(focused: bool) -> Style {
+    if focused {
+        Style::new().add_modifier(Modifier::REVERSED)
+    } else {
+        Style::new().add_modifier(Modifier::BOLD)
+    }
+}
+
+fn stars(star: u8) -> String {
+    let star = star.min(5) as usize;
+    format!("{}{}", "★".repeat(star), "☆".repeat(5 - star))
+}
@@ -0,0 +1,253 @@
+use crate::cli::datetime::{self, TimeZone};
+use crate::cli::folder::resolve_folder;
+use crate::cli::ignore::{self, IgnoreList};
+use crate::cli::item::list::filter::split_list;
+use crate::lib::client::EagleClient;
+use crate::lib::types::AddFromPathParams;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use notify::{EventKind, RecursiveMode, Watcher};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Files browsers and editors write while still working on them
+const PARTIAL_FILES: &[&str] = &["*.part", "*.crdownload", "*.download", "*.tmp", "*.swp", ".*", "~$*"];
+
+pub fn build() -> Command {
+    Command::new("watch")
+        .about("Import files dropped into a directory, like Eagle's auto-import folders")
+        .arg(
+            Arg::new("dir")
+                .value_name("DIR")
+                .help("Directory to watch, including its subdirectories")
+                .required(true)
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("folder")
+                .short('f')
+                .long("folder")
+                .value_name("FOLDER")
+                .help("Folder to import into, by id, path (Brand/Logos) or unique name")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("tags")
+                .short('t')
+                .long("tags")
+                .value_name("TAG")
+                .help("Tags for imported items. Comma separated")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("move")
+                .long("move")
+                .help("Delete files from DIR once imported")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("keep"),
+        )
+        .arg(
+            Arg::new("keep")
+                .long("keep")
+                .help("Leave imported files in DIR (the default)")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("debounce")
+                .long("debounce")
+                .value_name("MS")
+                .help("Wait until a file has not changed for this long before importing it")
+                .num_args(1)
+                .default_value("1000")
+                .value_parser(clap::value_parser!(u64)),
+        )
+        .arg(
+            Arg::new("ignore")
+                .long("ignore")
+                .value_name("GLOB")
+                .help("Skip files whose name or path below DIR matches. Repeatable; partial downloads and dotfiles are always skipped")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("log")
+                .long("log")
+                .value_name("FILE")
+                .help("Append the NDJSON event log to FILE instead of printing it")
+                .num_args(1),
+        )
+        .args(ignore::args())
+}
+
+/// Where events go: one JSON object per line
+struct EventLog {
+    file: Option<std::fs::File>,
+    tz: TimeZone,
+}
+
+impl EventLog {
+    fn emit(&mut self, event: &str, path: &Path, extra: Value) {
+        let mut record = json!({
+            "time": self.tz.format_millis(chrono::Utc::now().timestamp_millis()),
+            "event": event,
+            "path": path,
+        });
+        if let (Some(record), Value::Object(extra)) = (record.as_object_mut(), extra) {
+            record.extend(extra);
+        }
+        let line = record.to_string();
+        match self.file.as_mut() {
+            Some(file) => {
+                if let Err(error) = writeln!(file, "{}", line) {
+                    eprintln!("Failed to write the event log: {}", error);
+                }
+            }
+            None => println!("{}", line),
+        }
+    }
+}
+
+/// A file seen changing, waiting for the debounce period to pass
+struct Settling {
+    changed: Instant,
+    size: Option<u64>,
+}
+
+pub async fn execute(
+    client: &EagleClient,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = PathBuf::from(matches.get_one::<String>("dir").unwrap());
+    if !dir.is_dir() {
+        return Err(format!("Not a directory: {}", dir.display()).into());
+    }
+    let dir = dir.canonicalize()?;
+    let debounce = Duration::from_millis(*matches.get_one::<u64>("debounce").unwrap());
+    let move_files = matches.get_flag("move");
+    let tags = Some(split_list(matches, "tags")).filter(|tags| !tags.is_empty());
+    let folder_id = match matches.get_one::<String>("folder") {
+        Some(folder) => Some(resolve_folder(&client.folder().list().await?.data, folder)?),
+        None => None,
+    };
+    let ignore_list = IgnoreList::from_matches(matches, &dir)?;
+    let globs = glob_set(matches.get_many::<String>("ignore").into_iter().flatten())?;
+
+    let mut log = EventLog {
+        file: match matches.get_one::<String>("log") {
+            Some(path) => Some(OpenOptions::new().create(true).append(true).open(path)?),
+            None => None,
+        },
+        tz: datetime::from_matches(matches),
+    };
+
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        let _ = sender.send(event);
+    })?;
+    watcher.watch(&dir, RecursiveMode::Recursive)?;
+    eprintln!("Watching {}, press Ctrl-C to stop", dir.display());
+
+    let mut pending: HashMap<PathBuf, Settling> = HashMap::new();
+    let tick = (debounce / 4).max(Duration::from_millis(50));
+    loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => break,
+            event = receiver.recv() => {
+                match event {
+                    Some(Ok(event)) if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) => {
+                        for path in event.paths {
+                            let size = std::fs::metadata(&path).ok().map(|metadata| metadata.len());
+                            pending.insert(path, Settling { changed: Instant::now(), size });
+                        }
+                    }
+                    Some(Ok(_)) => {}
+                    Some(Err(error)) => eprintln!("Watch error: {}", error),
+                    None => break,
+                }
+            }
+            _ = tokio::time::sleep(tick) => {}
+        }
+
+        let due: Vec<PathBuf> = pending
+            .iter()
+            .filter(|(_, pending)| pending.changed.elapsed() >= debounce)
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in due {
+            let Ok(metadata) = std::fs::metadata(&path) else {
+                // Removed or renamed away before it settled
+                pending.remove(&path);
+                continue;
+            };
+            if !metadata.is_file() {
+                pending.remove(&path);
+                continue;
+            }
+            // Still being written when the size moved since the last event
+            let entry = pending.get_mut(&path).unwrap();
+            if entry.size != Some(metadata.len()) {
+                entry.size = Some(metadata.len());
+                entry.changed = Instant::now();
+                continue;
+            }
+            pending.remove(&path);
+
+            let relative = path.strip_prefix(&dir).unwrap_or(&path);
+            if is_ignored(relative, &ignore_list, &globs) {
+                log.emit("ignored", &path, json!({}));
+                continue;
+            }
+            import(client, &path, tags.clone(), folder_id.clone(), move_files, &mut log).await;
+        }
+    }
+    Ok(())
+}
+
+async fn import(
+    client: &EagleClient,
+    path: &Path,
+    tags: Option<Vec<String>>,
+    folder_id: Option<String>,
+    move_files: bool,
+    log: &mut EventLog,
+) {
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let params = AddFromPathParams {
+        path: path.to_string_lossy().into_owned(),
+        name: name.clone(),
+        tags,
+        folder_id,
+        ..Default::default()
+    };
+    if let Err(error) = client.item().add_from_path(&params).await {
+        log.emit("failed", path, json!({ "name": name, "error": error.to_string() }));
+        return;
+    }
+    log.emit("imported", path, json!({ "name": name }));
+    if move_files {
+        match std::fs::remove_file(path) {
+            Ok(()) => log.emit("removed", path, json!({})),
+            Err(error) => log.emit("failed", path, json!({ "name": name, "error": error.to_string() })),
+        }
+    }
+}
+
+fn glob_set<'a>(patterns: impl Iterator<Item = &'a String>) -> Result<GlobSet, Box<dyn std::error::Error>> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in PARTIAL_FILES.iter().copied().chain(patterns.map(String::as_str)) {
+        builder.add(Glob::new(pattern)?);
+    }
+    Ok(builder.build()?)
+}
+
+/// Globs match the file name or the path below the watched directory
+fn is_ignored(relative: &Path, ignore_list: &IgnoreList, globs: &GlobSet) -> bool {
+    let name_matches = relative.file_name().is_some_and(|name| globs.is_match(name));
+    name_matches || globs.is_match(relative) || ignore_list.is_ignored(relative, false)
+}
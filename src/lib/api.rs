@@ -1,5 +1,5 @@
 use super::client::EagleClient;
-use serde_json::{json, Value};
+use serde_json::json;
 use hyper::{Body, Method};
 use super::types::*;
 use std::error::Error;
@@ -30,7 +30,6 @@ impl<'a> ApplicationRequest<'a> {
 
 pub struct FolderRequest<'a> {
     client: &'a EagleClient,
-    data: Option<Value>,
 }
 
 impl<'a> FolderRequest<'a> {
@@ -39,7 +38,6 @@ impl<'a> FolderRequest<'a> {
     pub fn new(client: &'a EagleClient) -> Self {
         FolderRequest {
             client,
-            data: None,
         }
     }
 
@@ -48,17 +46,56 @@ impl<'a> FolderRequest<'a> {
         self.client.execute_request(uri, Method::GET, Body::empty()).await
     }
 
+    /// Create a folder, inside `parent` when given
+    pub async fn create(&self, name: &str, parent: Option<&str>) -> Result<CreateFolderResult, Box<dyn Error>> {
+        let mut data = json!({
+            "folderName": name,
+        });
+        if let Some(parent) = parent {
+            data["parent"] = json!(parent);
+        }
+        let uri = self.client.endpoint(Self::RESOURCE, "create", None)?;
+        let result: CreateFolderResult =
+            self.client.execute_request(uri, Method::POST, Body::from(serde_json::to_string(&data)?)).await?;
+        self.client.record_changed([result.data.id.clone()]);
+        Ok(result)
+    }
+
     pub async fn rename(
         &self,
-        folder_id: u64,
-        new_name: String,
+        folder_id: &str,
+        new_name: &str,
     ) -> Result<RenameFolderResult, Box<dyn Error>> {
         let data = json!({
-            "folder_id": folder_id,
-            "new_name": new_name,
+            "folderId": folder_id,
+            "newName": new_name,
         });
         let uri = self.client.endpoint(Self::RESOURCE, "rename", None)?;
-        self.client.execute_request(uri, Method::POST, Body::from(serde_json::to_string(&data)?)).await
+        let result = self.client.execute_request(uri, Method::POST, Body::from(serde_json::to_string(&data)?)).await?;
+        self.client.record_changed([folder_id.to_string()]);
+        Ok(result)
+    }
+
+    /// Change the name, description, or color of a folder; `None` leaves that one as it is
+    pub async fn update(
+        &self,
+        folder_id: &str,
+        new_name: Option<&str>,
+        new_description: Option<&str>,
+        new_color: Option<&str>,
+    ) -> Result<UpdateFolderResult, Box<dyn Error>> {
+        let mut data = json!({
+            "folderId": folder_id,
+        });
+        for (key, value) in [("newName", new_name), ("newDescription", new_description), ("newColor", new_color)] {
+            if let Some(value) = value {
+                data[key] = json!(value);
+            }
+        }
+        let uri = self.client.endpoint(Self::RESOURCE, "update", None)?;
+        let result = self.client.execute_request(uri, Method::POST, Body::from(serde_json::to_string(&data)?)).await?;
+        self.client.record_changed([folder_id.to_string()]);
+        Ok(result)
     }
 }
 
@@ -89,13 +126,89 @@ impl<'a> ItemRequest<'a> {
         let uri: Uri = self.client.endpoint(Self::RESOURCE, "thumbnail", Some(query_params.to_query_string()))?;
         self.client.execute_request(uri, Method::GET, Body::empty()).await
     }
+
+    pub async fn update(&self, params: &UpdateItemParams) -> Result<UpdateItemResult, Box<dyn Error>> {
+        let uri = self.client.endpoint(Self::RESOURCE, "update", None)?;
+        let result = self.client.execute_request(uri, Method::POST, Body::from(serde_json::to_string(params)?)).await?;
+        self.client.record_changed([params.id.clone()]);
+        Ok(result)
+    }
+
+    /// Have Eagle extract the item's color palette again
+    pub async fn refresh_palette(&self, item_id: &str) -> Result<RefreshItemPaletteResult, Box<dyn Error>> {
+        let data = json!({
+            "id": item_id,
+        });
+        let uri = self.client.endpoint(Self::RESOURCE, "refreshPalette", None)?;
+        let result = self.client.execute_request(uri, Method::POST, Body::from(serde_json::to_string(&data)?)).await?;
+        self.client.record_changed([item_id.to_string()]);
+        Ok(result)
+    }
+
+    /// Have Eagle render the item's thumbnail again
+    pub async fn refresh_thumbnail(&self, item_id: &str) -> Result<RefreshThumbnailResult, Box<dyn Error>> {
+        let data = json!({
+            "id": item_id,
+        });
+        let uri = self.client.endpoint(Self::RESOURCE, "refreshThumbnail", None)?;
+        let result = self.client.execute_request(uri, Method::POST, Body::from(serde_json::to_string(&data)?)).await?;
+        self.client.record_changed([item_id.to_string()]);
+        Ok(result)
+    }
+
+    pub async fn move_to_trash(&self, item_ids: &[String]) -> Result<MoveItemToTrashResult, Box<dyn Error>> {
+        let data = json!({
+            "itemIds": item_ids,
+        });
+        let uri = self.client.endpoint(Self::RESOURCE, "moveToTrash", None)?;
+        let result = self.client.execute_request(uri, Method::POST, Body::from(serde_json::to_string(&data)?)).await?;
+        self.client.record_changed(item_ids.iter().cloned());
+        Ok(result)
+    }
+
+    pub async fn add_from_url(&self, params: &AddFromUrlParams) -> Result<AddItemFromUrlResult, Box<dyn Error>> {
+        let uri = self.client.endpoint(Self::RESOURCE, "addFromURL", None)?;
+        let result = self.client.execute_request(uri, Method::POST, Body::from(serde_json::to_string(params)?)).await?;
+        self.client.record_changed([params.url.clone()]);
+        Ok(result)
+    }
+
+    pub async fn add_from_urls(
+        &self,
+        items: &[AddFromUrlParams],
+        folder_id: Option<&str>,
+    ) -> Result<AddItemFromUrlsResult, Box<dyn Error>> {
+        let mut data = json!({
+            "items": items,
+        });
+        if let Some(folder_id) = folder_id {
+            data["folderId"] = json!(folder_id);
+        }
+        let uri = self.client.endpoint(Self::RESOURCE, "addFromURLs", None)?;
+        let result = self.client.execute_request(uri, Method::POST, Body::from(serde_json::to_string(&data)?)).await?;
+        self.client.record_changed(items.iter().map(|item| item.url.clone()));
+        Ok(result)
+    }
+
+    pub async fn add_bookmark(&self, params: &AddBookmarkParams) -> Result<AddBookmarkResult, Box<dyn Error>> {
+        let uri = self.client.endpoint(Self::RESOURCE, "addBookmark", None)?;
+        let result = self.client.execute_request(uri, Method::POST, Body::from(serde_json::to_string(params)?)).await?;
+        self.client.record_changed([params.url.clone()]);
+        Ok(result)
+    }
+
+    pub async fn add_from_path(&self, params: &AddFromPathParams) -> Result<AddItemFromPathResult, Box<dyn Error>> {
+        let uri = self.client.endpoint(Self::RESOURCE, "addFromPath", None)?;
+        let result = self.client.execute_request(uri, Method::POST, Body::from(serde_json::to_string(params)?)).await?;
+        self.client.record_changed([params.path.clone()]);
+        Ok(result)
+    }
 }
 
 // Library
 
 pub struct LibraryRequest<'a> {
     client: &'a EagleClient,
-    data: Option<Value>,
 }
 
 impl<'a> LibraryRequest<'a> {
@@ -104,7 +217,6 @@ impl<'a> LibraryRequest<'a> {
     pub fn new(client: &'a EagleClient) -> Self {
         LibraryRequest {
             client,
-            data: None,
         }
     }
 
@@ -123,7 +235,7 @@ impl<'a> LibraryRequest<'a> {
         library_path: &Path,
     ) -> Result<SwitchLibraryResult, Box<dyn Error>> {
         let data = json!({
-            "library_path": library_path,
+            "libraryPath": library_path,
         });
         let uri = self.client.endpoint(Self::RESOURCE, "switch", None)?;
         self.client.execute_request(uri, Method::POST, Body::from(serde_json::to_string(&data)?)).await
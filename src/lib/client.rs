@@ -1,17 +1,33 @@
 use super::api::{ApplicationRequest, FolderRequest, ItemRequest, LibraryRequest};
+use super::compat::Version;
+use super::decode;
+use super::types::GetApplicationInfoResult;
 use hyper::client::HttpConnector;
 use hyper::http::uri::Authority;
 use hyper::StatusCode;
 use hyper::{Body, Client, Request, Uri};
+use percent_encoding::{percent_encode, NON_ALPHANUMERIC};
 use std::error::Error;
+use std::sync::{Arc, Mutex};
+use tokio::sync::OnceCell;
 use serde::Deserialize;
 
 // Error
 
 /// Client for communicating with the Eagle server
+#[derive(Clone)]
 pub struct EagleClient {
     authority: Authority,
     http_client: Client<HttpConnector>,
+    /// Eagle API token, sent as the `token` query parameter
+    token: Option<String>,
+    /// Ids of the items and folders changed, and the paths and URLs added, through this client;
+    /// `None` when they aren't kept
+    changed: Option<Arc<Mutex<Vec<String>>>>,
+    /// Fail on responses with fields the models don't have or lack fields they do
+    strict_decode: bool,
+    /// Version of the Eagle answering, read once; `None` when it couldn't be read
+    version: Arc<OnceCell<Option<Version>>>,
 }
 
 impl EagleClient {
@@ -20,6 +36,63 @@ impl EagleClient {
         EagleClient {
             authority: Authority::from_maybe_shared(format!("{}:{}", host, port)).unwrap(),
             http_client: Client::new(),
+            token: None,
+            changed: Some(Arc::default()),
+            strict_decode: false,
+            version: Arc::default(),
+        }
+    }
+
+    /// Send `token` with every request, for Eagle versions that require an API token
+    pub fn with_token(mut self, token: &str) -> Self {
+        self.token = Some(token.to_string());
+        self
+    }
+
+    /// Check every response against the models in `types.rs`, failing on any drift
+    pub fn with_strict_decode(mut self, strict_decode: bool) -> Self {
+        self.strict_decode = strict_decode;
+        self
+    }
+
+    /// Stop keeping what changes, for commands that run until stopped and would grow the list
+    /// without bound
+    pub fn without_changes(mut self) -> Self {
+        self.changed = None;
+        self
+    }
+
+    /// Note targets of a successful change, for the audit log
+    pub fn record_changed<I: IntoIterator<Item = String>>(&self, targets: I) {
+        if let Some(changed) = &self.changed {
+            changed.lock().unwrap().extend(targets);
+        }
+    }
+
+    /// Everything changed through this client and its clones so far
+    pub fn changed(&self) -> Vec<String> {
+        self.changed.as_ref().map(|changed| changed.lock().unwrap().clone()).unwrap_or_default()
+    }
+
+    /// Version of the Eagle answering, read from `application/info` on first use
+    pub async fn eagle_version(&self) -> Option<Version> {
+        *self.version.get_or_init(|| self.fetch_version()).await
+    }
+
+    async fn fetch_version(&self) -> Option<Version> {
+        // Straight through the HTTP client, as execute_request itself needs the version
+        let uri = self.endpoint("application", "info", None).ok()?;
+        let response = self.http_client.get(uri).await.ok()?;
+        let body = hyper::body::to_bytes(response.into_body()).await.ok()?;
+        let info: GetApplicationInfoResult = serde_json::from_slice(&body).ok()?;
+        Version::parse(&info.data.version)
+    }
+
+    /// `error` with the version of the Eagle answering, read only once a request failed
+    async fn explain(&self, error: String) -> Box<dyn Error> {
+        match self.eagle_version().await {
+            Some(version) => format!("{} (Eagle {})", error, version).into(),
+            None => error.into(),
         }
     }
 
@@ -30,7 +103,9 @@ impl EagleClient {
         query_params: Option<String>,
     ) -> Result<Uri, Box<dyn std::error::Error>> {
 
-    let query_string = query_params.map_or("".to_string(), |params| format!("?{}", params));
+    let token = self.token.as_ref().map(|token| format!("token={}", percent_encode(token.as_bytes(), NON_ALPHANUMERIC)));
+    let query: Vec<String> = query_params.into_iter().chain(token).filter(|part| !part.is_empty()).collect();
+    let query_string = if query.is_empty() { String::new() } else { format!("?{}", query.join("&")) };
 
     let path_and_query = format!("/api/{}/{}{}", resource, action, query_string);
 
@@ -48,60 +123,77 @@ pub async fn execute_request<T: for<'de> Deserialize<'de>>(
     method: hyper::Method,
     body: Body,
 ) -> Result<T, Box<dyn Error>> {
+    let path = uri.path().to_string();
     let request = Request::builder().method(method).uri(uri).body(body)?;
 
     let response = self.http_client.request(request).await?;
     if response.status() != StatusCode::OK {
-        return Err(Box::new(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            "Server returned an error",
-        )));
+        let error = format!("Server returned {} for {}", response.status(), path);
+        return Err(self.explain(error).await);
     }
-    Ok(decode_body(response).await?)
+    // Only the message may live across the next await, as the error itself isn't Send
+    let error = {
+        let decoded = match self.strict_decode {
+            true => decode_body_strict(response, &path).await,
+            false => decode_body(response, &path).await,
+        };
+        match decoded {
+            Ok(decoded) => return Ok(decoded),
+            Err(error) => error.to_string(),
+        }
+    };
+    Err(self.explain(error).await)
 }
 
     /// Get a request builder for the application resource
-    pub fn application(&self) -> ApplicationRequest {
-        ApplicationRequest::new(&self)
+    pub fn application(&self) -> ApplicationRequest<'_> {
+        ApplicationRequest::new(self)
     }
 
     /// Get a request builder for the folder resource
-    pub fn folder(&self) -> FolderRequest {
-        FolderRequest::new(&self)
+    pub fn folder(&self) -> FolderRequest<'_> {
+        FolderRequest::new(self)
     }
 
     /// Get a request builder for the item resource
-    pub fn item(&self) -> ItemRequest {
-        ItemRequest::new(&self)
+    pub fn item(&self) -> ItemRequest<'_> {
+        ItemRequest::new(self)
     }
 
     /// Get a request builder for the library resource
-    pub fn library(&self) -> LibraryRequest {
-        LibraryRequest::new(&self)
+    pub fn library(&self) -> LibraryRequest<'_> {
+        LibraryRequest::new(self)
     }
 }
 
-/// Decode the body of a response into the expected type
+/// Decode the body of a response, failing when its fields and the expected type's differ
+async fn decode_body_strict<T: for<'de> Deserialize<'de>>(
+    res: hyper::Response<Body>,
+    path: &str,
+) -> Result<T, Box<dyn Error>> {
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    let value: serde_json::Value = serde_json::from_slice(&body)?;
+    let (decoded, drift) = decode::check(value).map_err(|e| format!("Response of {} doesn't decode: {}", path, e))?;
+    if !drift.is_empty() {
+        return Err(format!("Response of {} doesn't match the model: {}", path, drift).into());
+    }
+    Ok(decoded)
+}
+
+/// Decode the body of a response into the expected type, skipping the array elements that
+/// don't decode, with a warning naming what they lacked
 async fn decode_body<T: for<'de> Deserialize<'de>>(
-    _res: hyper::Response<Body>,
+    res: hyper::Response<Body>,
+    path: &str,
 ) -> Result<T, Box<dyn Error>> {
-    let body = hyper::body::to_bytes(_res.into_body()).await?;
-    let body_str = String::from_utf8(body.to_vec())?;
-
-    // Deserialize into the expected type
-    match serde_json::from_str(&body_str) {
-        Ok(parsed) => Ok(parsed),
-        Err(e) => {
-            let column = e.column();
-            println!("Failed to parse JSON at column: {}", column);
-
-            // Get 50 characters before and after the error column for context
-            let start = if column > 500 { column - 500 } else { 0 };
-            let end = std::cmp::min(column, body_str.len());
-            let context = &body_str[start..end];
-            println!("Context: {}", context);
-
-            Err(Box::new(e))
-        }
+    let body = hyper::body::to_bytes(res.into_body()).await?;
+    if let Ok(parsed) = serde_json::from_slice(&body) {
+        return Ok(parsed);
+    }
+    let value: serde_json::Value = serde_json::from_slice(&body)?;
+    let (decoded, skipped) = decode::salvage(value).map_err(|e| format!("Response of {} doesn't decode: {}", path, e))?;
+    for skip in skipped {
+        eprintln!("Warning: skipped {} in the response of {}", skip, path);
     }
+    Ok(decoded)
 }
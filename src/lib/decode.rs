@@ -0,0 +1,330 @@
+use serde::de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Fields of a response that don't line up with the model it is decoded into
+#[derive(Debug, Default)]
+pub struct Drift {
+    /// Fields in the response the model doesn't have, with how often they occur
+    pub unknown: BTreeMap<String, usize>,
+    /// Fields of the model the response didn't send, with how often they were absent
+    pub missing: BTreeMap<String, usize>,
+}
+
+impl Drift {
+    pub fn is_empty(&self) -> bool {
+        self.unknown.is_empty() && self.missing.is_empty()
+    }
+}
+
+impl fmt::Display for Drift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let list = |fields: &BTreeMap<String, usize>| -> String {
+            let fields: Vec<String> = fields
+                .iter()
+                .map(|(path, count)| match count {
+                    1 => path.clone(),
+                    count => format!("{} ({}x)", path, count),
+                })
+                .collect();
+            fields.join(", ")
+        };
+        match (self.unknown.is_empty(), self.missing.is_empty()) {
+            (false, false) => write!(f, "unknown fields: {}; missing fields: {}", list(&self.unknown), list(&self.missing)),
+            (false, true) => write!(f, "unknown fields: {}", list(&self.unknown)),
+            (true, false) => write!(f, "missing fields: {}", list(&self.missing)),
+            (true, true) => write!(f, "no drift"),
+        }
+    }
+}
+
+/// Decode `value` into `T` like `serde_json::from_value`, also noting the fields of every
+/// struct on the way that the value has but the model lacks, or the other way around.
+///
+/// Array indexes are left out of the field paths (`data[].name`), so a field missing on many
+/// items is reported once with a count.
+pub fn check<T: for<'de> Deserialize<'de>>(value: Value) -> Result<(T, Drift), serde_json::Error> {
+    let tracker = Tracker::default();
+    match T::deserialize(Tracked::root(value, &tracker)) {
+        Ok(decoded) => Ok((decoded, tracker.drift.into_inner())),
+        Err(error) => Err(tracker.locate(error)),
+    }
+}
+
+/// Decode `value` into `T`, dropping the array elements that don't decode, such as a folder
+/// lacking a field the model requires, instead of failing as a whole.
+///
+/// Returns what was dropped, as `data[3]: missing field `name``, alongside the result.
+pub fn salvage<T: for<'de> Deserialize<'de>>(mut value: Value) -> Result<(T, Vec<String>), serde_json::Error> {
+    let mut skipped = Vec::new();
+    loop {
+        let tracker = Tracker::default();
+        let error = match T::deserialize(Tracked::root(value.clone(), &tracker)) {
+            Ok(decoded) => return Ok((decoded, skipped)),
+            Err(error) => error,
+        };
+        let at = tracker.error_at.borrow().clone().unwrap_or_default();
+        let Some((array, index)) = innermost_element(&value, &at) else {
+            return Err(tracker.locate(error));
+        };
+        if let Some(elements) = value.pointer_mut(&array).and_then(Value::as_array_mut) {
+            elements.remove(index);
+        }
+        skipped.push(format!("{}: {}", display_path(&format!("{}/{}", array, index)), error));
+    }
+}
+
+/// The array and index of the innermost array element at or above the JSON pointer `at`
+fn innermost_element(value: &Value, at: &str) -> Option<(String, usize)> {
+    let segments: Vec<&str> = at.split('/').skip(1).collect();
+    (0..segments.len()).rev().find_map(|i| {
+        let index: usize = segments[i].parse().ok()?;
+        let array = segments[..i].iter().map(|segment| format!("/{}", segment)).collect::<String>();
+        let length = value.pointer(&array)?.as_array()?.len();
+        Some((array, index)).filter(|_| index < length)
+    })
+}
+
+/// A JSON pointer like `/data/3/name` as `data[3].name`
+fn display_path(pointer: &str) -> String {
+    let mut path = String::new();
+    for segment in pointer.split('/').skip(1) {
+        let segment = segment.replace("~1", "/").replace("~0", "~");
+        match segment.parse::<usize>() {
+            Ok(index) => path.push_str(&format!("[{}]", index)),
+            Err(_) if path.is_empty() => path.push_str(&segment),
+            Err(_) => path.push_str(&format!(".{}", segment)),
+        }
+    }
+    path
+}
+
+#[derive(Default)]
+struct Tracker {
+    drift: RefCell<Drift>,
+    /// JSON pointer to the value where decoding first failed
+    error_at: RefCell<Option<String>>,
+}
+
+impl Tracker {
+    fn note<T>(&self, result: Result<T, serde_json::Error>, pointer: &str) -> Result<T, serde_json::Error> {
+        if result.is_err() {
+            self.error_at.borrow_mut().get_or_insert_with(|| pointer.to_string());
+        }
+        result
+    }
+
+    /// `error` with the path of the value it happened at
+    fn locate(&self, error: serde_json::Error) -> serde_json::Error {
+        match self.error_at.borrow().as_deref() {
+            Some(pointer) if !pointer.is_empty() => de::Error::custom(format!("{} at {}", error, display_path(pointer))),
+            _ => error,
+        }
+    }
+}
+
+struct Tracked<'a> {
+    value: Value,
+    /// Where the value is in the aggregated form of `Drift`, like `data[].name`
+    path: String,
+    /// Where the value is exactly, as a JSON pointer like `/data/3/name`
+    pointer: String,
+    tracker: &'a Tracker,
+}
+
+impl<'a> Tracked<'a> {
+    fn root(value: Value, tracker: &'a Tracker) -> Tracked<'a> {
+        Tracked { value, path: String::new(), pointer: String::new(), tracker }
+    }
+
+    fn child(&self, value: Value, key: &str) -> Tracked<'a> {
+        let path = match self.path.as_str() {
+            "" => key.to_string(),
+            path => format!("{}.{}", path, key),
+        };
+        let pointer = format!("{}/{}", self.pointer, key.replace('~', "~0").replace('/', "~1"));
+        Tracked { value, path, pointer, tracker: self.tracker }
+    }
+
+    fn element(&self, value: Value, index: usize) -> Tracked<'a> {
+        Tracked {
+            value,
+            path: format!("{}[]", self.path),
+            pointer: format!("{}/{}", self.pointer, index),
+            tracker: self.tracker,
+        }
+    }
+
+    fn visit_object<'de, V: Visitor<'de>>(self, object: Map<String, Value>, visitor: V) -> Result<V::Value, serde_json::Error> {
+        let (tracker, pointer) = (self.tracker, self.pointer.clone());
+        let parent = Tracked { value: Value::Null, ..self };
+        tracker.note(visitor.visit_map(TrackedMap { parent, entries: object.into_iter(), value: None }), &pointer)
+    }
+
+    fn visit_array<'de, V: Visitor<'de>>(self, array: Vec<Value>, visitor: V) -> Result<V::Value, serde_json::Error> {
+        let (tracker, pointer) = (self.tracker, self.pointer.clone());
+        let parent = Tracked { value: Value::Null, ..self };
+        tracker.note(visitor.visit_seq(TrackedSeq { parent, values: array.into_iter().enumerate() }), &pointer)
+    }
+
+    fn check_fields(&self, object: &Map<String, Value>, fields: &[&str]) {
+        let mut drift = self.tracker.drift.borrow_mut();
+        let path = |field: &str| match self.path.as_str() {
+            "" => field.to_string(),
+            path => format!("{}.{}", path, field),
+        };
+        for key in object.keys().filter(|key| !fields.contains(&key.as_str())) {
+            *drift.unknown.entry(path(key)).or_default() += 1;
+        }
+        for field in fields.iter().filter(|field| !object.contains_key(**field)) {
+            *drift.missing.entry(path(field)).or_default() += 1;
+        }
+    }
+}
+
+struct TrackedMap<'a> {
+    parent: Tracked<'a>,
+    entries: serde_json::map::IntoIter,
+    value: Option<(String, Value)>,
+}
+
+impl<'de, 'a> MapAccess<'de> for TrackedMap<'a> {
+    type Error = serde_json::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error> {
+        let Some((key, value)) = self.entries.next() else {
+            return Ok(None);
+        };
+        let key_value = seed.deserialize(Value::String(key.clone()))?;
+        self.value = Some((key, value));
+        Ok(Some(key_value))
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Self::Error> {
+        let (key, value) = self.value.take().ok_or_else(|| de::Error::custom("value without a key"))?;
+        seed.deserialize(self.parent.child(value, &key))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+struct TrackedSeq<'a> {
+    parent: Tracked<'a>,
+    values: std::iter::Enumerate<std::vec::IntoIter<Value>>,
+}
+
+impl<'de, 'a> SeqAccess<'de> for TrackedSeq<'a> {
+    type Error = serde_json::Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error> {
+        match self.values.next() {
+            Some((index, value)) => seed.deserialize(self.parent.element(value, index)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.values.len())
+    }
+}
+
+/// Hand the rest of `deserialize_*` methods to `serde_json::Value`, which needs no tracking
+macro_rules! forward_to_value {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                self.tracker.note(self.value.$method(visitor), &self.pointer)
+            }
+        )*
+    };
+}
+
+impl<'de, 'a> Deserializer<'de> for Tracked<'a> {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(mut self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.value.take() {
+            Value::Object(object) => self.visit_object(object, visitor),
+            Value::Array(array) => self.visit_array(array, visitor),
+            value => self.tracker.note(value.deserialize_any(visitor), &self.pointer),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.value {
+            Value::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(mut self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.value.take() {
+            Value::Array(array) => self.visit_array(array, visitor),
+            value => self.tracker.note(value.deserialize_seq(visitor), &self.pointer),
+        }
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(mut self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.value.take() {
+            Value::Object(object) => self.visit_object(object, visitor),
+            value => self.tracker.note(value.deserialize_map(visitor), &self.pointer),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        mut self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self.value.take() {
+            Value::Object(object) => {
+                self.check_fields(&object, fields);
+                self.visit_object(object, visitor)
+            }
+            value => self.tracker.note(value.deserialize_struct(name, fields, visitor), &self.pointer),
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error> {
+        self.tracker.note(self.value.deserialize_tuple(len, visitor), &self.pointer)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.tracker.note(self.value.deserialize_tuple_struct(name, len, visitor), &self.pointer)
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(self, name: &'static str, visitor: V) -> Result<V::Value, Self::Error> {
+        self.tracker.note(self.value.deserialize_unit_struct(name, visitor), &self.pointer)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.tracker.note(self.value.deserialize_enum(name, variants, visitor), &self.pointer)
+    }
+
+    forward_to_value! {
+        deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64 deserialize_i128
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_u128
+        deserialize_f32 deserialize_f64 deserialize_char deserialize_str deserialize_string
+        deserialize_bytes deserialize_byte_buf deserialize_unit deserialize_identifier deserialize_ignored_any
+    }
+}
@@ -0,0 +1,184 @@
+use super::types::{ItemListData, LibraryTags};
+use serde_json::Value;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Direct access to an Eagle library folder (`*.library`) on disk.
+///
+/// The HTTP API doesn't expose everything (tag groups, starred tags, the raw
+/// item metadata), so some commands read or write the library files instead.
+/// Eagle keeps its own copy in memory, so writes only show up after a restart.
+#[derive(Debug, Clone)]
+pub struct LibraryDir {
+    root: PathBuf,
+}
+
+impl LibraryDir {
+    pub fn new<P: AsRef<Path>>(root: P) -> Self {
+        LibraryDir {
+            root: root.as_ref().to_path_buf(),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Directory holding one `<id>.info` folder per item
+    pub fn images_dir(&self) -> PathBuf {
+        self.root.join("images")
+    }
+
+    pub fn item_dir(&self, id: &str) -> PathBuf {
+        self.images_dir().join(format!("{}.info", id))
+    }
+
+    /// Locate the original file of an item.
+    ///
+    /// Eagle usually stores it as `<name>.<ext>`, but sanitizes some characters and
+    /// appends `_1`, `_2`, ... on collisions, so when that file is missing the item
+    /// folder is scanned for the file that isn't metadata or a thumbnail.
+    pub fn item_file(&self, id: &str, name: &str, ext: &str) -> Option<PathBuf> {
+        let item_dir = self.item_dir(id);
+        let expected = item_dir.join(format!("{}.{}", name, ext));
+        if expected.is_file() {
+            return Some(expected);
+        }
+
+        let candidates: Vec<PathBuf> = fs::read_dir(&item_dir)
+            .ok()?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_file() && !is_item_support_file(path))
+            .collect();
+        let same_ext = candidates.iter().find(|path| {
+            path.extension()
+                .is_some_and(|candidate| candidate.to_string_lossy().eq_ignore_ascii_case(ext))
+        });
+        match same_ext {
+            Some(path) => Some(path.clone()),
+            None if candidates.len() == 1 => candidates.into_iter().next(),
+            None => None,
+        }
+    }
+
+    /// Locate the thumbnail of an item; not every item has one
+    pub fn item_thumbnail(&self, id: &str, name: &str) -> Option<PathBuf> {
+        let item_dir = self.item_dir(id);
+        let expected = item_dir.join(format!("{}_thumbnail.png", name));
+        if expected.is_file() {
+            return Some(expected);
+        }
+        fs::read_dir(&item_dir)
+            .ok()?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .find(|path| path.to_string_lossy().ends_with("_thumbnail.png"))
+    }
+
+    pub fn metadata_path(&self) -> PathBuf {
+        self.root.join("metadata.json")
+    }
+
+    pub fn tags_path(&self) -> PathBuf {
+        self.root.join("tags.json")
+    }
+
+    /// Read the library-level `metadata.json` (folders, smart folders, tag groups)
+    pub fn read_metadata(&self) -> Result<Value, Box<dyn Error>> {
+        read_json(&self.metadata_path())
+    }
+
+    pub fn write_metadata(&self, metadata: &Value) -> Result<(), Box<dyn Error>> {
+        write_json(&self.metadata_path(), metadata)
+    }
+
+    /// Write a JSON file at the root of the library, such as `metadata.json` or `tags.json`
+    pub fn write_json_file(&self, file_name: &str, value: &Value) -> Result<(), Box<dyn Error>> {
+        write_json(&self.root.join(file_name), value)
+    }
+
+    /// Read `tags.json`; a missing file is treated as an empty tag list
+    pub fn read_tags(&self) -> Result<LibraryTags, Box<dyn Error>> {
+        let path = self.tags_path();
+        if !path.exists() {
+            return Ok(LibraryTags::default());
+        }
+        Ok(serde_json::from_value(read_json(&path)?)?)
+    }
+
+    pub fn write_tags(&self, tags: &LibraryTags) -> Result<(), Box<dyn Error>> {
+        write_json(&self.tags_path(), &serde_json::to_value(tags)?)
+    }
+
+    /// Read the `metadata.json` of a single item
+    pub fn item(&self, id: &str) -> Result<ItemListData, Box<dyn Error>> {
+        Ok(serde_json::from_value(read_json(&self.item_dir(id).join("metadata.json"))?)?)
+    }
+
+    /// Read the raw `metadata.json` of an item, keeping fields `ItemListData` doesn't model
+    pub fn read_item_metadata(&self, id: &str) -> Result<Value, Box<dyn Error>> {
+        read_json(&self.item_dir(id).join("metadata.json"))
+    }
+
+    pub fn write_item_metadata(&self, id: &str, metadata: &Value) -> Result<(), Box<dyn Error>> {
+        write_json(&self.item_dir(id).join("metadata.json"), metadata)
+    }
+
+    /// Delete an item folder with its file, thumbnail, and metadata
+    pub fn remove_item(&self, id: &str) -> Result<(), Box<dyn Error>> {
+        fs::remove_dir_all(self.item_dir(id))
+            .map_err(|e| format!("Failed to remove {}: {}", self.item_dir(id).display(), e))?;
+        Ok(())
+    }
+
+    /// Read the `metadata.json` of every item in the library, skipping unreadable ones
+    pub fn items(&self) -> Result<Vec<ItemListData>, Box<dyn Error>> {
+        let mut items = Vec::new();
+        for entry in fs::read_dir(self.images_dir())? {
+            let path = entry?.path().join("metadata.json");
+            if let Ok(item) = read_json(&path).and_then(|value| Ok(serde_json::from_value(value)?)) {
+                items.push(item);
+            }
+        }
+        Ok(items)
+    }
+}
+
+/// Whether a file in an item folder is Eagle's own (metadata or thumbnail) rather than the item
+fn is_item_support_file(path: &Path) -> bool {
+    let file_name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+    file_name == "metadata.json" || file_name.ends_with("_thumbnail.png")
+}
+
+fn read_json(path: &Path) -> Result<Value, Box<dyn Error>> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    Ok(serde_json::from_str(&contents)?)
+}
+
+fn write_json(path: &Path, value: &Value) -> Result<(), Box<dyn Error>> {
+    // Write next to the target and rename, so a crash never leaves a truncated file
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, serde_json::to_string(value)?)?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// Generate an item/folder/group id in Eagle's style (13 uppercase base36 characters)
+pub fn generate_id() -> String {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+    let mut n = nanos + COUNTER.fetch_add(1, Ordering::Relaxed) as u128;
+
+    const ALPHABET: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    let mut id = Vec::with_capacity(13);
+    while id.len() < 13 {
+        id.push(ALPHABET[(n % 36) as usize]);
+        n /= 36;
+    }
+    id.reverse();
+    String::from_utf8(id).unwrap()
+}
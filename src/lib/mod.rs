@@ -1,3 +1,4 @@
 pub mod client;
 pub mod api;
 pub mod types;
+pub mod library;
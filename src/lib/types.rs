@@ -1,4 +1,5 @@
 use serde_json::Value;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt;
@@ -24,7 +25,8 @@ pub enum Status {
     Error,
 }
 
-#[derive(Debug, PartialEq, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
 pub enum Color {
     Red,
     Orange,
@@ -42,7 +44,7 @@ pub struct GetApplicationInfoResult {
     pub data: ApplicationData,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
 pub struct ApplicationData {
     pub version: String,
     pub prerelease_version: Option<String>,
@@ -53,17 +55,19 @@ pub struct ApplicationData {
     pub platform: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
 pub struct Child {
     pub id: String,
     pub name: String,
     pub images: Option<Vec<Value>>,
     pub folders: Option<Vec<Value>>,
-    #[serde(rename = "modificationTime")]
+    #[serde(rename = "modificationTime", default)]
     pub modification_time: u64,
     pub editable: Option<bool>,
     // pub imagesMappings: Option<Vec<Value>>,
+    #[serde(default)]
     pub tags: Vec<String>,
+    #[serde(default)]
     pub children: Vec<Child>,
     #[serde(rename = "isExpand")]
     pub is_expand: Option<bool>,
@@ -86,7 +90,7 @@ pub struct Child {
     pub parent: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
 pub struct Styles {
     pub depth: u64,
     pub first: bool,
@@ -100,19 +104,23 @@ pub struct CreateFolderResult {
     pub data: CreateFolderData,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct CreateFolderData {
     pub id: String,
     pub name: String,
+    #[serde(default)]
     pub images: Vec<Value>,
+    #[serde(default)]
     pub folders: Vec<Value>,
-    #[serde(rename = "modificationTime")]
+    #[serde(rename = "modificationTime", default)]
     pub modification_time: u64,
-    #[serde(rename = "imageMappings")]
+    #[serde(rename = "imagesMappings", default)]
     pub image_mappings: Value,
+    #[serde(default)]
     pub tags: Vec<String>,
+    #[serde(default)]
     pub children: Vec<Child>,
-    #[serde(rename = "isExpand")]
+    #[serde(rename = "isExpand", default)]
     pub is_expand: bool,
 }
 
@@ -123,30 +131,38 @@ pub struct RenameFolderResult {
     pub data: RenameFolderData,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct RenameFolderData {
     pub id: String,
     pub name: String,
+    #[serde(default)]
     pub images: Vec<Value>,
+    #[serde(default)]
     pub folders: Vec<Value>,
-    #[serde(rename = "modificationTime")]
+    #[serde(rename = "modificationTime", default)]
     pub modification_time: u64,
-    #[serde(rename = "imageMappings")]
+    #[serde(rename = "imagesMappings", default)]
     pub image_mappings: Value,
+    #[serde(default)]
     pub tags: Vec<String>,
+    #[serde(default)]
     pub children: Vec<Child>,
-    #[serde(rename = "isExpand")]
+    #[serde(rename = "isExpand", default)]
     pub is_expand: bool,
+    #[serde(default)]
     pub size: u64,
+    #[serde(default)]
     pub vstype: String,
-    pub styles: Styles,
-    #[serde(rename = "isVisible")]
+    pub styles: Option<Styles>,
+    #[serde(rename = "isVisible", default)]
     pub is_visible: bool,
     #[serde(rename = "$$hashKey")]
-    pub hash_key_: String,
-    #[serde(rename = "newFolderName")]
+    pub hash_key_: Option<String>,
+    #[serde(rename = "newFolderName", default)]
     pub new_folder_name: String,
+    #[serde(default)]
     pub editable: bool,
+    #[serde(default)]
     pub pinyin: String,
 }
 
@@ -157,30 +173,38 @@ pub struct UpdateFolderResult {
     pub data: UpdateFolderData,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct UpdateFolderData {
     pub id: String,
     pub name: String,
+    #[serde(default)]
     pub images: Vec<Value>,
+    #[serde(default)]
     pub folders: Vec<Value>,
-    #[serde(rename = "modificationTime")]
+    #[serde(rename = "modificationTime", default)]
     pub modification_time: u64,
-    #[serde(rename = "imagesMappings")]
+    #[serde(rename = "imagesMappings", default)]
     pub images_mappings: Value,
+    #[serde(default)]
     pub tags: Vec<String>,
+    #[serde(default)]
     pub children: Vec<Child>,
-    #[serde(rename = "isExpand")]
+    #[serde(rename = "isExpand", default)]
     pub is_expand: bool,
+    #[serde(default)]
     pub size: u64,
+    #[serde(default)]
     pub vstype: String,
-    pub styles: Styles,
-    #[serde(rename = "isVisible")]
+    pub styles: Option<Styles>,
+    #[serde(rename = "isVisible", default)]
     pub is_visible: bool,
     #[serde(rename = "$$hashKey")]
-    pub hash_key_: String,
-    #[serde(rename = "newFolderName")]
+    pub hash_key_: Option<String>,
+    #[serde(rename = "newFolderName", default)]
     pub new_folder_name: String,
+    #[serde(default)]
     pub editable: bool,
+    #[serde(default)]
     pub pinyin: String,
 }
 
@@ -199,17 +223,20 @@ pub struct GetFolderListResult {
 pub struct FolderListData {
     pub id: String,
     pub name: String,
+    #[serde(default)]
     pub description: String,
     pub children: Option<Vec<Child>>,
-    #[serde(rename = "modificationTime")]
+    #[serde(rename = "modificationTime", default)]
     pub modification_time: u64,
+    #[serde(default)]
     pub tags: Vec<String>,
-    #[serde(rename = "imageCount")]
+    #[serde(rename = "imageCount", default)]
     pub image_count: u64,
     #[serde(rename = "descendantImageCount")]
     pub descendant_image_count: Option<u64>,
+    #[serde(default)]
     pub pinyin: String,
-    #[serde(rename = "extendTags")]
+    #[serde(rename = "extendTags", default)]
     pub extend_tags: Vec<String>,
 }
 
@@ -219,31 +246,37 @@ pub struct GetRecentFolderListResult {
     pub data: Vec<RecentFolderListData>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct RecentFolderListData {
     pub id: String,
     pub name: String,
+    #[serde(default)]
     pub description: String,
+    #[serde(default)]
     pub children: Vec<Child>,
-    #[serde(rename = "modificationTime")]
+    #[serde(rename = "modificationTime", default)]
     pub modification_time: u64,
+    #[serde(default)]
     pub tags: Vec<String>,
+    #[serde(default)]
     pub password: String,
-    #[serde(rename = "passwordTips")]
+    #[serde(rename = "passwordTips", default)]
     pub password_tips: String,
+    #[serde(default)]
     pub images: Vec<Value>,
-    #[serde(rename = "isExpand")]
+    #[serde(rename = "isExpand", default)]
     pub is_expand: bool,
-    #[serde(rename = "newFolderName")]
+    #[serde(rename = "newFolderName", default)]
     pub new_folder_name: String,
-    #[serde(rename = "imagesMappings")]
+    #[serde(rename = "imagesMappings", default)]
     pub images_mappings: Value,
-    #[serde(rename = "imageCount")]
+    #[serde(rename = "imageCount", default)]
     pub image_count: u64,
     #[serde(rename = "descendantImageCount")]
     pub descendant_image_count: Option<u64>,
+    #[serde(default)]
     pub pinyin: String,
-    #[serde(rename = "extendTags")]
+    #[serde(rename = "extendTags", default)]
     pub extend_tags: Vec<String>,
 }
 
@@ -303,8 +336,8 @@ impl QueryParams for GetItemInfoParams {
 
         let query_params: Vec<String> = fields
             .iter()
-            .filter_map(|&(param_name, param)| {
-                Some(format!("{}={}", param_name, percent_encode(param.as_bytes(), NON_ALPHANUMERIC).to_string()))
+            .map(|&(param_name, param)| {
+                format!("{}={}", param_name, percent_encode(param.as_bytes(), NON_ALPHANUMERIC))
             })
             .collect();
 
@@ -318,7 +351,7 @@ pub struct GetItemInfoResult {
     pub data: ItemInfoData,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
 pub struct ItemInfoData {
     pub id: String,
     pub name: String,
@@ -339,9 +372,27 @@ pub struct ItemInfoData {
     #[serde(rename = "lastModified")]
     pub last_modified: u64,
     pub palettes: Vec<Palettes>,
-}
-
-#[derive(Debug, Deserialize)]
+    /// Length of a video or audio item, in seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<f64>,
+    /// Bits per second of a video or audio item
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bitrate: Option<u64>,
+    /// Frames per second of a video item
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fps: Option<f64>,
+    /// Family of a font item, e.g. `Inter`
+    #[serde(rename = "fontFamily", skip_serializing_if = "Option::is_none")]
+    pub font_family: Option<String>,
+    /// Style of a font item within its family, e.g. `Bold Italic`
+    #[serde(rename = "fontStyle", skip_serializing_if = "Option::is_none")]
+    pub font_style: Option<String>,
+    /// Number of glyphs in a font item
+    #[serde(rename = "glyphCount", skip_serializing_if = "Option::is_none")]
+    pub glyph_count: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
 pub struct Palettes {
     pub color: Vec<u64>,
     // pub ratio: u64, // or f64
@@ -363,8 +414,8 @@ impl QueryParams for GetItemThumbnailParams {
 
         let query_params: Vec<String> = fields
             .iter()
-            .filter_map(|&(param_name, param)| {
-                Some(format!("{}={}", param_name, percent_encode(param.as_bytes(), NON_ALPHANUMERIC).to_string()))
+            .map(|&(param_name, param)| {
+                format!("{}={}", param_name, percent_encode(param.as_bytes(), NON_ALPHANUMERIC))
             })
             .collect();
 
@@ -381,7 +432,8 @@ pub struct GetItemThumbnailResult {
 pub type ItemThumbnailData = String;
 
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, JsonSchema)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum Order {
     MANUAL,
     CREATEDATE,
@@ -415,10 +467,39 @@ impl fmt::Display for Order {
     }
 }
 
+impl Order {
+    /// Every order as the API spells it, `-` marking the reversed ones
+    pub const VALUES: [&'static str; 11] = [
+        "MANUAL", "CREATEDATE", "-CREATEDATE", "BTIME", "MTIME", "FILESIZE", "-FILESIZE", "NAME", "-NAME",
+        "RESOLUTION", "-RESOLUTION",
+    ];
+}
+
+impl std::str::FromStr for Order {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_uppercase().as_str() {
+            "MANUAL" => Ok(Order::MANUAL),
+            "CREATEDATE" => Ok(Order::CREATEDATE),
+            "-CREATEDATE" => Ok(Order::CREATEDATEDESC),
+            "BTIME" => Ok(Order::BTIME),
+            "MTIME" => Ok(Order::MTIME),
+            "FILESIZE" => Ok(Order::FILESIZE),
+            "-FILESIZE" => Ok(Order::FILESIZEREVERSE),
+            "NAME" => Ok(Order::NAME),
+            "-NAME" => Ok(Order::NAMEREVERSE),
+            "RESOLUTION" => Ok(Order::RESOLUTION),
+            "-RESOLUTION" => Ok(Order::RESOLUTIONREVERSE),
+            _ => Err(format!("unknown order: {} (use one of {})", value, Order::VALUES.join(", "))),
+        }
+    }
+}
+
 
 
 /// Represents the parameters for the `/api/item/list` request.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Default, Clone, Serialize)]
 pub struct GetItemListParams {
     /// The number of items to be displayed. The default number is 200.
     pub limit: Option<usize>,
@@ -455,7 +536,7 @@ impl QueryParams for GetItemListParams {
         let fields: [(&str, Option<String>); 7] = [
             ("limit", self.limit.as_ref().map(|value| value.to_string())),
             ("offset", self.offset.as_ref().map(|value| value.to_string())),
-            ("order_by", self.order_by.as_ref().map(|value| value.to_string())),
+            ("orderBy", self.order_by.as_ref().map(|value| value.to_string())),
             ("keyword", self.keyword.as_ref().map(|value| value.to_string())),
             ("ext", self.ext.as_ref().map(|value| value.to_string())),
             ("tags", self.tags.as_ref().map(|value| value.to_string())),
@@ -466,7 +547,7 @@ impl QueryParams for GetItemListParams {
         let query_params: Vec<String> = fields
             .iter()
             .filter_map(|(param_name, param)| {
-                param.as_ref().map(|value| format!("{}={}", param_name, percent_encode(value.as_bytes(), NON_ALPHANUMERIC).to_string()))
+                param.as_ref().map(|value| format!("{}={}", param_name, percent_encode(value.as_bytes(), NON_ALPHANUMERIC)))
             })
             .collect();
 
@@ -480,7 +561,7 @@ pub struct GetItemListResult {
     pub data: Vec<ItemListData>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
 pub struct ItemListData {
     pub id: String,
     pub name: String,
@@ -499,6 +580,29 @@ pub struct ItemListData {
     #[serde(rename = "lastModified")]
     pub last_modified: Option<u64>,
     pub palettes: Option<Vec<Palettes>>,
+    /// Rating from 1 to 5; missing or 0 when unrated
+    pub star: Option<u8>,
+    /// Set when Eagle couldn't make a thumbnail for the item
+    #[serde(rename = "noThumbnail", skip_serializing_if = "Option::is_none")]
+    pub no_thumbnail: Option<bool>,
+    /// Length of a video or audio item, in seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<f64>,
+    /// Bits per second of a video or audio item
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bitrate: Option<u64>,
+    /// Frames per second of a video item
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fps: Option<f64>,
+    /// Family of a font item, e.g. `Inter`
+    #[serde(rename = "fontFamily", skip_serializing_if = "Option::is_none")]
+    pub font_family: Option<String>,
+    /// Style of a font item within its family, e.g. `Bold Italic`
+    #[serde(rename = "fontStyle", skip_serializing_if = "Option::is_none")]
+    pub font_style: Option<String>,
+    /// Number of glyphs in a font item
+    #[serde(rename = "glyphCount", skip_serializing_if = "Option::is_none")]
+    pub glyph_count: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -517,12 +621,83 @@ pub struct RefreshThumbnailResult {
     pub status: Status,
 }
 
+/// Represents the body of the `/api/item/update` request; fields left as `None` are unchanged.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct UpdateItemParams {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub folders: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub annotation: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub star: Option<u8>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct UpdateItemResult {
     pub status: Status,
     pub data: ItemInfoData,
 }
 
+/// Represents the body of the `/api/item/addFromURL` request (and one entry of `addFromURLs`).
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct AddFromUrlParams {
+    pub url: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub website: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub star: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub annotation: Option<String>,
+    /// Creation date in epoch milliseconds
+    #[serde(rename = "modificationTime", skip_serializing_if = "Option::is_none")]
+    pub modification_time: Option<u64>,
+    #[serde(rename = "folderId", skip_serializing_if = "Option::is_none")]
+    pub folder_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub headers: Option<OutgoingHttpHeaders>,
+}
+
+/// Represents the body of the `/api/item/addBookmark` request.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct AddBookmarkParams {
+    pub url: String,
+    pub name: String,
+    /// Thumbnail as a base64 data URL
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base64: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    #[serde(rename = "modificationTime", skip_serializing_if = "Option::is_none")]
+    pub modification_time: Option<u64>,
+    #[serde(rename = "folderId", skip_serializing_if = "Option::is_none")]
+    pub folder_id: Option<String>,
+}
+
+/// Represents the body of the `/api/item/addFromPath` request.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct AddFromPathParams {
+    pub path: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub website: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub annotation: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    #[serde(rename = "folderId", skip_serializing_if = "Option::is_none")]
+    pub folder_id: Option<String>,
+}
+
 /// Get Library Info
 #[derive(Debug, Deserialize)]
 pub struct GetLibraryInfoResult {
@@ -530,7 +705,7 @@ pub struct GetLibraryInfoResult {
     pub data: LibraryInfoData,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
 pub struct LibraryInfoData {
     pub folders: Vec<Folder>,
     #[serde(rename = "smartFolders")]
@@ -546,13 +721,13 @@ pub struct LibraryInfoData {
     pub library: LibraryData,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
 pub struct LibraryData {
     pub path: String,
     pub name: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
         // folders: {
         //     id: string;
         //     name: string;
@@ -602,7 +777,7 @@ pub struct Folder {
     pub sort_increase: Option<bool>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
 pub struct SmartFolders {
     pub id: String,
     pub icon: Option<String>,
@@ -611,16 +786,23 @@ pub struct SmartFolders {
     #[serde(rename = "modificationTime")]
     pub modification_time: u64,
     pub conditions: Vec<Conditions>,
+    /// Nested smart folders, which also apply the conditions of their parents
+    #[serde(default)]
+    pub children: Vec<SmartFolders>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
 pub struct Conditions {
+    /// `AND` when every rule has to match, `OR` when one is enough
     #[serde(rename = "match")]
     pub match_: String,
     pub rules: Vec<Rules>,
+    /// `FALSE` to select the items the rules don't match
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub boolean: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
 pub struct Rules {
     pub method: String,
     pub property: String,
@@ -634,12 +816,24 @@ pub struct QuickAccess {
     pub id: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct TagsGroups {
     pub id: String,
     pub name: String,
     pub tags: Vec<String>,
-    pub color: Color,
+    pub color: Option<Color>,
+}
+
+/// Contents of the library's `tags.json`
+#[derive(Debug, Default, Deserialize, Serialize, JsonSchema)]
+pub struct LibraryTags {
+    #[serde(rename = "historyTags", default)]
+    pub history_tags: Vec<String>,
+    #[serde(rename = "starredTags", default)]
+    pub starred_tags: Vec<String>,
+    // Keep fields we don't model so rewriting the file doesn't drop them
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, Value>,
 }
 
 #[derive(Debug, Deserialize)]
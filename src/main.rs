@@ -1,12 +1,21 @@
-mod lib {
+pub mod lib {
     pub mod client;
     pub mod api;
     pub mod types;
+    pub mod library;
+    pub mod decode;
+    pub mod compat;
 }
 pub mod cli;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    cli::execute().await?;
+    if let Err(error) = cli::execute().await {
+        if let Some(partial) = error.downcast_ref::<cli::exit::Partial>() {
+            eprintln!("Error: {}", partial);
+            std::process::exit(cli::exit::PARTIAL);
+        }
+        return Err(error);
+    }
     Ok(())
 }